@@ -20,7 +20,5 @@ pub(crate) async fn get_project(
 
     let res = bitwarden_api_api::apis::projects_api::projects_id_get(&config.api, input.id).await?;
 
-    let enc = client.internal.get_encryption_settings()?;
-
-    ProjectResponse::process_response(res, &enc)
+    ProjectResponse::process_response(res, client.internal.get_key_store())
 }
@@ -1,5 +1,6 @@
 use bitwarden_api_api::models::ProjectResponseModelListResponseModel;
-use bitwarden_core::client::{encryption_settings::EncryptionSettings, Client};
+use bitwarden_core::{client::Client, key_management::KeyIds};
+use bitwarden_crypto::KeyStore;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -24,9 +25,7 @@ pub(crate) async fn list_projects(
     )
     .await?;
 
-    let enc = client.internal.get_encryption_settings()?;
-
-    ProjectsResponse::process_response(res, &enc)
+    ProjectsResponse::process_response(res, client.internal.get_key_store())
 }
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -38,14 +37,14 @@ pub struct ProjectsResponse {
 impl ProjectsResponse {
     pub(crate) fn process_response(
         response: ProjectResponseModelListResponseModel,
-        enc: &EncryptionSettings,
+        key_store: &KeyStore<KeyIds>,
     ) -> Result<Self, SecretsManagerError> {
         let data = response.data.unwrap_or_default();
 
         Ok(ProjectsResponse {
             data: data
                 .into_iter()
-                .map(|r| ProjectResponse::process_response(r, enc))
+                .map(|r| ProjectResponse::process_response(r, key_store))
                 .collect::<Result<_, _>>()?,
         })
     }
@@ -19,7 +19,5 @@ pub(crate) async fn get_secret(
     let config = client.internal.get_api_configurations().await;
     let res = bitwarden_api_api::apis::secrets_api::secrets_id_get(&config.api, input.id).await?;
 
-    let enc = client.internal.get_encryption_settings()?;
-
-    SecretResponse::process_response(res, &enc)
+    SecretResponse::process_response(res, client.internal.get_key_store())
 }
@@ -2,10 +2,11 @@ use bitwarden_api_api::models::{
     SecretWithProjectsListResponseModel, SecretsWithProjectsInnerSecret,
 };
 use bitwarden_core::{
-    client::{encryption_settings::EncryptionSettings, Client},
+    client::Client,
+    key_management::{KeyIds, SymmetricKeyId},
     require,
 };
-use bitwarden_crypto::{EncString, KeyDecryptable};
+use bitwarden_crypto::{Decryptable, EncString, KeyStore};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -30,9 +31,7 @@ pub(crate) async fn list_secrets(
     )
     .await?;
 
-    let enc = client.internal.get_encryption_settings()?;
-
-    SecretIdentifiersResponse::process_response(res, &enc)
+    SecretIdentifiersResponse::process_response(res, client.internal.get_key_store())
 }
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -53,9 +52,7 @@ pub(crate) async fn list_secrets_by_project(
     )
     .await?;
 
-    let enc = client.internal.get_encryption_settings()?;
-
-    SecretIdentifiersResponse::process_response(res, &enc)
+    SecretIdentifiersResponse::process_response(res, client.internal.get_key_store())
 }
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -67,14 +64,14 @@ pub struct SecretIdentifiersResponse {
 impl SecretIdentifiersResponse {
     pub(crate) fn process_response(
         response: SecretWithProjectsListResponseModel,
-        enc: &EncryptionSettings,
+        key_store: &KeyStore<KeyIds>,
     ) -> Result<SecretIdentifiersResponse, SecretsManagerError> {
         Ok(SecretIdentifiersResponse {
             data: response
                 .secrets
                 .unwrap_or_default()
                 .into_iter()
-                .map(|r| SecretIdentifierResponse::process_response(r, enc))
+                .map(|r| SecretIdentifierResponse::process_response(r, key_store))
                 .collect::<Result<_, _>>()?,
         })
     }
@@ -92,14 +89,15 @@ pub struct SecretIdentifierResponse {
 impl SecretIdentifierResponse {
     pub(crate) fn process_response(
         response: SecretsWithProjectsInnerSecret,
-        enc: &EncryptionSettings,
+        key_store: &KeyStore<KeyIds>,
     ) -> Result<SecretIdentifierResponse, SecretsManagerError> {
         let organization_id = require!(response.organization_id);
-        let enc_key = enc.get_key(&Some(organization_id))?;
+        let enc_key = SymmetricKeyId::Organization(organization_id);
+        let mut ctx = key_store.context();
 
         let key = require!(response.key)
             .parse::<EncString>()?
-            .decrypt_with_key(enc_key)?;
+            .decrypt(&mut ctx, enc_key)?;
 
         Ok(SecretIdentifierResponse {
             id: require!(response.id),
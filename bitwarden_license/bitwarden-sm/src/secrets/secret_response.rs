@@ -1,8 +1,11 @@
 use bitwarden_api_api::models::{
     BaseSecretResponseModel, BaseSecretResponseModelListResponseModel, SecretResponseModel,
 };
-use bitwarden_core::{client::encryption_settings::EncryptionSettings, require};
-use bitwarden_crypto::{EncString, KeyDecryptable};
+use bitwarden_core::{
+    key_management::{KeyIds, SymmetricKeyId},
+    require,
+};
+use bitwarden_crypto::{Decryptable, EncString, KeyStore};
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -28,7 +31,7 @@ pub struct SecretResponse {
 impl SecretResponse {
     pub(crate) fn process_response(
         response: SecretResponseModel,
-        enc: &EncryptionSettings,
+        key_store: &KeyStore<KeyIds>,
     ) -> Result<SecretResponse, SecretsManagerError> {
         let base = BaseSecretResponseModel {
             object: response.object,
@@ -41,24 +44,25 @@ impl SecretResponse {
             revision_date: response.revision_date,
             projects: response.projects,
         };
-        Self::process_base_response(base, enc)
+        Self::process_base_response(base, key_store)
     }
     pub(crate) fn process_base_response(
         response: BaseSecretResponseModel,
-        enc: &EncryptionSettings,
+        key_store: &KeyStore<KeyIds>,
     ) -> Result<SecretResponse, SecretsManagerError> {
-        let org_id = response.organization_id;
-        let enc_key = enc.get_key(&org_id)?;
+        let organization_id = require!(response.organization_id);
+        let enc_key = SymmetricKeyId::Organization(organization_id);
+        let mut ctx = key_store.context();
 
         let key = require!(response.key)
             .parse::<EncString>()?
-            .decrypt_with_key(enc_key)?;
+            .decrypt(&mut ctx, enc_key)?;
         let value = require!(response.value)
             .parse::<EncString>()?
-            .decrypt_with_key(enc_key)?;
+            .decrypt(&mut ctx, enc_key)?;
         let note = require!(response.note)
             .parse::<EncString>()?
-            .decrypt_with_key(enc_key)?;
+            .decrypt(&mut ctx, enc_key)?;
 
         let project = response
             .projects
@@ -67,7 +71,7 @@ impl SecretResponse {
 
         Ok(SecretResponse {
             id: require!(response.id),
-            organization_id: require!(org_id),
+            organization_id,
             project_id: project,
             key,
             value,
@@ -88,14 +92,14 @@ pub struct SecretsResponse {
 impl SecretsResponse {
     pub(crate) fn process_response(
         response: BaseSecretResponseModelListResponseModel,
-        enc: &EncryptionSettings,
+        key_store: &KeyStore<KeyIds>,
     ) -> Result<SecretsResponse, SecretsManagerError> {
         Ok(SecretsResponse {
             data: response
                 .data
                 .unwrap_or_default()
                 .into_iter()
-                .map(|r| SecretResponse::process_base_response(r, enc))
+                .map(|r| SecretResponse::process_base_response(r, key_store))
                 .collect::<Result<_, _>>()?,
         })
     }
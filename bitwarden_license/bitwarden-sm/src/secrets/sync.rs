@@ -1,5 +1,6 @@
 use bitwarden_api_api::models::SecretsSyncResponseModel;
-use bitwarden_core::{client::encryption_settings::EncryptionSettings, require, Client};
+use bitwarden_core::{key_management::KeyIds, require, Client};
+use bitwarden_crypto::KeyStore;
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -30,9 +31,7 @@ pub(crate) async fn sync_secrets(
     )
     .await?;
 
-    let enc = client.internal.get_encryption_settings()?;
-
-    SecretsSyncResponse::process_response(res, &enc)
+    SecretsSyncResponse::process_response(res, client.internal.get_key_store())
 }
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -45,7 +44,7 @@ pub struct SecretsSyncResponse {
 impl SecretsSyncResponse {
     pub(crate) fn process_response(
         response: SecretsSyncResponseModel,
-        enc: &EncryptionSettings,
+        key_store: &KeyStore<KeyIds>,
     ) -> Result<SecretsSyncResponse, SecretsManagerError> {
         let has_changes = require!(response.has_changes);
 
@@ -54,7 +53,7 @@ impl SecretsSyncResponse {
                 .data
                 .unwrap_or_default()
                 .into_iter()
-                .map(|r| SecretResponse::process_base_response(r, enc))
+                .map(|r| SecretResponse::process_base_response(r, key_store))
                 .collect::<Result<_, _>>()?;
             return Ok(SecretsSyncResponse {
                 has_changes,
@@ -1,3 +1,3 @@
 mod policy;
 
-pub use policy::Policy;
+pub use policy::{Policy, PolicyType};
@@ -6,21 +6,20 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use uuid::Uuid;
 
-use crate::{
-    error::{Error, Result},
-    require,
-};
+use crate::{require, MissingFieldError};
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Policy {
-    id: Uuid,
-    organization_id: Uuid,
-    r#type: PolicyType,
-    data: Option<HashMap<String, serde_json::Value>>,
-    enabled: bool,
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub r#type: PolicyType,
+    /// Policy specific configuration, like the password requirements of
+    /// [PolicyType::MasterPassword]
+    pub data: Option<HashMap<String, serde_json::Value>>,
+    pub enabled: bool,
 }
 
-#[derive(Serialize_repr, Deserialize_repr, Debug, JsonSchema)]
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, Debug, JsonSchema, PartialEq)]
 #[repr(u8)]
 pub enum PolicyType {
     TwoFactorAuthentication = 0, // Requires users to have 2fa enabled
@@ -42,9 +41,9 @@ pub enum PolicyType {
 }
 
 impl TryFrom<PolicyResponseModel> for Policy {
-    type Error = Error;
+    type Error = MissingFieldError;
 
-    fn try_from(policy: PolicyResponseModel) -> Result<Self> {
+    fn try_from(policy: PolicyResponseModel) -> Result<Self, Self::Error> {
         Ok(Self {
             id: require!(policy.id),
             organization_id: require!(policy.organization_id),
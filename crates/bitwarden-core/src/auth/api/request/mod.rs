@@ -11,6 +11,11 @@ mod password_token_request;
 #[cfg(feature = "internal")]
 pub(crate) use password_token_request::*;
 
+#[cfg(feature = "internal")]
+mod sso_token_request;
+#[cfg(feature = "internal")]
+pub(crate) use sso_token_request::*;
+
 mod renew_token_request;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 pub(crate) use renew_token_request::*;
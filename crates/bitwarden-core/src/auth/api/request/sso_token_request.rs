@@ -0,0 +1,73 @@
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{
+        api::response::IdentityTokenResponse,
+        login::{TwoFactorProvider, TwoFactorRequest},
+    },
+    client::ApiConfigurations,
+    error::Result,
+    DeviceType,
+};
+
+/// Exchanges the authorization code received from an SSO login for tokens, proving possession of
+/// the PKCE code verifier.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SsoTokenRequest {
+    scope: String,
+    client_id: String,
+    #[serde(rename = "deviceType")]
+    device_type: u8,
+    #[serde(rename = "deviceIdentifier")]
+    device_identifier: String,
+    #[serde(rename = "deviceName")]
+    device_name: String,
+    grant_type: String,
+    code: String,
+    code_verifier: String,
+    redirect_uri: String,
+
+    #[serde(rename = "twoFactorToken")]
+    two_factor_token: Option<String>,
+    #[serde(rename = "twoFactorProvider")]
+    two_factor_provider: Option<TwoFactorProvider>,
+    #[serde(rename = "twoFactorRemember")]
+    two_factor_remember: Option<bool>,
+}
+
+impl SsoTokenRequest {
+    pub fn new(
+        code: &str,
+        code_verifier: &str,
+        redirect_uri: &str,
+        device_type: DeviceType,
+        device_identifier: &str,
+        two_factor: &Option<TwoFactorRequest>,
+    ) -> Self {
+        let tf = two_factor.as_ref();
+        let obj = Self {
+            scope: "api offline_access".to_string(),
+            client_id: "web".to_string(),
+            device_type: device_type as u8,
+            device_identifier: device_identifier.to_string(),
+            device_name: "chrome".to_string(),
+            grant_type: "authorization_code".to_string(),
+            code: code.to_string(),
+            code_verifier: code_verifier.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            two_factor_token: tf.map(|t| t.token.to_owned()),
+            two_factor_provider: tf.map(|t| t.provider.clone()),
+            two_factor_remember: tf.map(|t| t.remember),
+        };
+        debug!("initializing {:?}", obj);
+        obj
+    }
+
+    pub(crate) async fn send(
+        &self,
+        configurations: &ApiConfigurations,
+    ) -> Result<IdentityTokenResponse> {
+        super::send_identity_connect_request(configurations, None, &self).await
+    }
+}
@@ -4,6 +4,8 @@ use bitwarden_api_identity::models::KdfType;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::auth::api::response::UserDecryptionOptionsResponse;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct IdentityTokenSuccessResponse {
     pub access_token: String,
@@ -15,8 +17,8 @@ pub struct IdentityTokenSuccessResponse {
     pub(crate) private_key: Option<String>,
     #[serde(alias = "Key")]
     pub(crate) key: Option<String>,
-    #[serde(rename = "twoFactorToken")]
-    two_factor_token: Option<String>,
+    #[serde(rename = "twoFactorToken", alias = "TwoFactorToken")]
+    pub(crate) two_factor_token: Option<String>,
     #[serde(alias = "Kdf")]
     kdf: KdfType,
     #[serde(
@@ -34,6 +36,8 @@ pub struct IdentityTokenSuccessResponse {
     api_use_key_connector: Option<bool>,
     #[serde(rename = "keyConnectorUrl", alias = "KeyConnectorUrl")]
     key_connector_url: Option<String>,
+    #[serde(rename = "userDecryptionOptions", alias = "UserDecryptionOptions")]
+    pub(crate) user_decryption_options: Option<UserDecryptionOptionsResponse>,
 
     /// Stores unknown api response fields
     extra: Option<HashMap<String, Value>>,
@@ -61,6 +65,7 @@ mod test {
                 force_password_reset: Default::default(),
                 api_use_key_connector: Default::default(),
                 key_connector_url: Default::default(),
+                user_decryption_options: Default::default(),
                 extra: Default::default(),
             }
         }
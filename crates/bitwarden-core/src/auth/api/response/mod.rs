@@ -7,6 +7,7 @@ mod identity_token_response;
 mod identity_two_factor_response;
 pub(crate) mod two_factor_provider_data;
 mod two_factor_providers;
+mod user_decryption_options_response;
 
 pub(crate) use identity_captcha_response::*;
 pub(crate) use identity_payload_response::*;
@@ -16,3 +17,4 @@ pub(crate) use identity_token_fail_response::*;
 pub(crate) use identity_token_response::*;
 pub(crate) use identity_two_factor_response::*;
 pub(crate) use two_factor_providers::*;
+pub(crate) use user_decryption_options_response::*;
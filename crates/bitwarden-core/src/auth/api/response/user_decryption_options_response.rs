@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// The ways the user can decrypt their vault, returned by the identity server on login.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct UserDecryptionOptionsResponse {
+    #[serde(rename = "hasMasterPassword", alias = "HasMasterPassword")]
+    pub has_master_password: bool,
+    #[serde(rename = "trustedDeviceOption", alias = "TrustedDeviceOption")]
+    pub trusted_device_option: Option<TrustedDeviceUserDecryptionOption>,
+    #[serde(rename = "keyConnectorOption", alias = "KeyConnectorOption")]
+    pub key_connector_option: Option<KeyConnectorUserDecryptionOption>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct TrustedDeviceUserDecryptionOption {
+    #[serde(rename = "hasAdminApproval", alias = "HasAdminApproval")]
+    pub has_admin_approval: bool,
+    #[serde(rename = "hasLoginApprovingDevice", alias = "HasLoginApprovingDevice")]
+    pub has_login_approving_device: bool,
+    #[serde(
+        rename = "hasManageResetPasswordPermission",
+        alias = "HasManageResetPasswordPermission"
+    )]
+    pub has_manage_reset_password_permission: bool,
+    /// The device private key, encrypted with the device key. Only present for trusted devices.
+    #[serde(rename = "encryptedPrivateKey", alias = "EncryptedPrivateKey")]
+    pub encrypted_private_key: Option<String>,
+    /// The user key, encrypted with the device public key. Only present for trusted devices.
+    #[serde(rename = "encryptedUserKey", alias = "EncryptedUserKey")]
+    pub encrypted_user_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct KeyConnectorUserDecryptionOption {
+    #[serde(rename = "keyConnectorUrl", alias = "KeyConnectorUrl")]
+    pub key_connector_url: String,
+}
@@ -8,11 +8,15 @@ use crate::auth::login::{login_access_token, AccessTokenLoginRequest, AccessToke
 #[cfg(feature = "internal")]
 use crate::auth::{
     auth_request::{approve_auth_request, new_auth_request},
-    key_connector::{make_key_connector_keys, KeyConnectorResponse},
+    key_connector::{
+        make_key_connector_keys, migrate_to_key_connector, unlock_key_connector, KeyConnectorApi,
+        KeyConnectorResponse, UnlockKeyConnectorRequest,
+    },
     login::{
         login_api_key, login_password, send_two_factor_email, ApiKeyLoginRequest,
-        ApiKeyLoginResponse, NewAuthRequestResponse, PasswordLoginRequest, PasswordLoginResponse,
-        TwoFactorEmailRequest,
+        ApiKeyLoginResponse, NewAuthRequestResponse, NewSsoLoginResponse, PasswordLoginRequest,
+        PasswordLoginResponse, PasswordLoginSession, SsoLoginCompleteRequest, SsoLoginRequest,
+        SsoLoginResponse, TwoFactorEmailRequest,
     },
     password::{
         password_strength, satisfies_policy, validate_password, validate_password_user_key,
@@ -23,6 +27,8 @@ use crate::auth::{
     tde::{make_register_tde_keys, RegisterTdeKeyResponse},
     AuthRequestResponse, RegisterKeyResponse, RegisterRequest,
 };
+#[cfg(feature = "internal")]
+use crate::mobile::crypto::DeriveKeyConnectorRequest;
 use crate::{auth::renew::renew_token, error::Result, Client};
 
 pub struct AuthClient<'a> {
@@ -44,7 +50,7 @@ impl AuthClient<'_> {
 }
 
 #[cfg(feature = "internal")]
-impl AuthClient<'_> {
+impl<'a> AuthClient<'a> {
     pub fn password_strength(
         &self,
         password: String,
@@ -86,6 +92,24 @@ impl AuthClient<'_> {
         make_key_connector_keys(&mut rng)
     }
 
+    /// Unlock the vault with the master key stored in Key Connector.
+    pub async fn unlock_key_connector(
+        &self,
+        api: &dyn KeyConnectorApi,
+        request: UnlockKeyConnectorRequest,
+    ) -> Result<()> {
+        unlock_key_connector(self.client, api, request).await
+    }
+
+    /// Move a master password user onto Key Connector, removing their master password.
+    pub async fn migrate_to_key_connector(
+        &self,
+        api: &dyn KeyConnectorApi,
+        request: DeriveKeyConnectorRequest,
+    ) -> Result<()> {
+        migrate_to_key_connector(self.client, api, request).await
+    }
+
     pub async fn register(&self, input: &RegisterRequest) -> Result<()> {
         register(self.client, input).await
     }
@@ -104,6 +128,16 @@ impl AuthClient<'_> {
         login_password(self.client, input).await
     }
 
+    /// Start a password login that keeps the credentials to continue with a second factor.
+    pub fn password_login_session(
+        &self,
+        email: String,
+        password: String,
+        kdf: Kdf,
+    ) -> PasswordLoginSession<'a> {
+        PasswordLoginSession::new(self.client, email, password, kdf)
+    }
+
     pub async fn login_api_key(&self, input: &ApiKeyLoginRequest) -> Result<ApiKeyLoginResponse> {
         login_api_key(self.client, input).await
     }
@@ -158,15 +192,36 @@ impl AuthClient<'_> {
 
         complete_auth_request(self.client, auth_req).await
     }
+
+    /// Start logging in with the SSO of an organization. Open the returned URL in a browser, and
+    /// pass the parameters it redirects to to [AuthClient::login_sso_complete].
+    pub async fn login_sso(&self, input: &SsoLoginRequest) -> Result<NewSsoLoginResponse> {
+        use crate::auth::login::send_sso_login;
+
+        send_sso_login(self.client, input).await
+    }
+
+    pub async fn login_sso_complete(
+        &self,
+        sso: &NewSsoLoginResponse,
+        input: &SsoLoginCompleteRequest,
+    ) -> Result<SsoLoginResponse> {
+        use crate::auth::login::complete_sso_login;
+
+        complete_sso_login(self.client, sso, input).await
+    }
 }
 
 #[cfg(feature = "internal")]
 fn trust_device(client: &Client) -> Result<TrustDeviceResponse> {
-    let enc = client.internal.get_encryption_settings()?;
+    use crate::key_management::SymmetricKeyId;
 
-    let user_key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let user_key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
-    Ok(DeviceKey::trust_device(user_key)?)
+    Ok(DeviceKey::trust_device(&user_key)?)
 }
 
 impl<'a> Client {
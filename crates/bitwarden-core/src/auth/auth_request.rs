@@ -8,7 +8,7 @@ use bitwarden_crypto::{EncString, KeyDecryptable, SymmetricCryptoKey};
 
 #[cfg(feature = "internal")]
 use crate::client::encryption_settings::EncryptionSettingsError;
-use crate::{error::Error, Client};
+use crate::{error::Error, key_management::SymmetricKeyId, Client};
 
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct AuthRequestResponse {
@@ -83,13 +83,12 @@ pub(crate) fn approve_auth_request(
 ) -> Result<AsymmetricEncString, Error> {
     let public_key = AsymmetricPublicCryptoKey::from_der(&STANDARD.decode(public_key)?)?;
 
-    let enc = client.internal.get_encryption_settings()?;
-    let key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
-    Ok(AsymmetricEncString::encrypt_rsa2048_oaep_sha1(
-        &key.to_vec(),
-        &public_key,
-    )?)
+    Ok(AsymmetricEncString::encrypt(&key.to_vec(), &public_key)?)
 }
 
 #[test]
@@ -113,6 +112,22 @@ fn test_auth_request() {
     assert_eq!(&decrypted.to_vec(), secret);
 }
 
+#[test]
+fn test_auth_request_x25519() {
+    let private_key = AsymmetricCryptoKey::generate_x25519(&mut rand::thread_rng());
+    let public_key =
+        AsymmetricPublicCryptoKey::from_der(&private_key.to_public_der().unwrap()).unwrap();
+
+    let secret = SymmetricCryptoKey::generate(rand::thread_rng()).to_vec();
+    let encrypted = AsymmetricEncString::encrypt(&secret, &public_key).unwrap();
+
+    let decrypted =
+        auth_request_decrypt_user_key(STANDARD.encode(private_key.to_der().unwrap()), encrypted)
+            .unwrap();
+
+    assert_eq!(decrypted.to_vec(), secret);
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU32;
@@ -235,21 +250,22 @@ mod tests {
 
         // We can validate that the vault is unlocked correctly by confirming the user key is the
         // same
-        assert_eq!(
-            existing_device
-                .internal
-                .get_encryption_settings()
-                .unwrap()
-                .get_key(&None)
-                .unwrap()
-                .to_base64(),
-            new_device
-                .internal
-                .get_encryption_settings()
-                .unwrap()
-                .get_key(&None)
-                .unwrap()
-                .to_base64()
-        );
+        #[allow(deprecated)]
+        let existing_key = existing_device
+            .internal
+            .get_key_store()
+            .context()
+            .dangerous_get_symmetric_key(SymmetricKeyId::User)
+            .unwrap()
+            .to_base64();
+        #[allow(deprecated)]
+        let new_key = new_device
+            .internal
+            .get_key_store()
+            .context()
+            .dangerous_get_symmetric_key(SymmetricKeyId::User)
+            .unwrap()
+            .to_base64();
+        assert_eq!(existing_key, new_key);
     }
 }
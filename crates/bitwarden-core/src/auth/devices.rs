@@ -0,0 +1,484 @@
+use bitwarden_api_api::{
+    apis::devices_api::{
+        devices_get, devices_id_deactivate_post, devices_identifier_keys_put,
+        devices_identifier_retrieve_keys_post, devices_update_trust_post,
+    },
+    models::{
+        DeviceKeysRequestModel, DeviceKeysUpdateRequestModel, DeviceResponseModel,
+        OtherDeviceKeysUpdateRequestModel, ProtectedDeviceResponseModel,
+        SecretVerificationRequestModel, UpdateDevicesTrustRequestModel,
+    },
+};
+use bitwarden_crypto::{
+    AsymmetricEncString, AsymmetricPublicCryptoKey, Decryptable, EncString, HashPurpose,
+    KeyEncryptable, MasterKey, SymmetricCryptoKey, TrustDeviceResponse,
+};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    client::{LoginMethod, UserLoginMethod},
+    error::{Error, NotAuthenticatedError, Result},
+    key_management::SymmetricKeyId,
+    require, Client, DeviceType,
+};
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DeviceView {
+    pub id: Uuid,
+    pub name: String,
+    /// The identifier the device logs in with
+    pub identifier: String,
+    pub r#type: DeviceType,
+    pub creation_date: DateTime<Utc>,
+    /// Whether the device can decrypt the vault with its device key
+    pub is_trusted: bool,
+}
+
+impl TryFrom<DeviceResponseModel> for DeviceView {
+    type Error = Error;
+
+    fn try_from(device: DeviceResponseModel) -> Result<Self> {
+        Ok(Self {
+            id: require!(device.id),
+            name: require!(device.name),
+            identifier: require!(device.identifier),
+            r#type: require!(device.r#type).into(),
+            creation_date: require!(device.creation_date).parse()?,
+            is_trusted: device.is_trusted.unwrap_or(false),
+        })
+    }
+}
+
+impl From<bitwarden_api_api::models::DeviceType> for DeviceType {
+    fn from(device_type: bitwarden_api_api::models::DeviceType) -> Self {
+        use bitwarden_api_api::models::DeviceType as ApiDeviceType;
+
+        match device_type {
+            ApiDeviceType::Android => DeviceType::Android,
+            ApiDeviceType::iOS => DeviceType::iOS,
+            ApiDeviceType::ChromeExtension => DeviceType::ChromeExtension,
+            ApiDeviceType::FirefoxExtension => DeviceType::FirefoxExtension,
+            ApiDeviceType::OperaExtension => DeviceType::OperaExtension,
+            ApiDeviceType::EdgeExtension => DeviceType::EdgeExtension,
+            ApiDeviceType::WindowsDesktop => DeviceType::WindowsDesktop,
+            ApiDeviceType::MacOsDesktop => DeviceType::MacOsDesktop,
+            ApiDeviceType::LinuxDesktop => DeviceType::LinuxDesktop,
+            ApiDeviceType::ChromeBrowser => DeviceType::ChromeBrowser,
+            ApiDeviceType::FirefoxBrowser => DeviceType::FirefoxBrowser,
+            ApiDeviceType::OperaBrowser => DeviceType::OperaBrowser,
+            ApiDeviceType::EdgeBrowser => DeviceType::EdgeBrowser,
+            ApiDeviceType::IEBrowser => DeviceType::IEBrowser,
+            ApiDeviceType::UnknownBrowser => DeviceType::UnknownBrowser,
+            ApiDeviceType::AndroidAmazon => DeviceType::AndroidAmazon,
+            ApiDeviceType::UWP => DeviceType::UWP,
+            ApiDeviceType::SafariBrowser => DeviceType::SafariBrowser,
+            ApiDeviceType::VivaldiBrowser => DeviceType::VivaldiBrowser,
+            ApiDeviceType::VivaldiExtension => DeviceType::VivaldiExtension,
+            ApiDeviceType::SafariExtension => DeviceType::SafariExtension,
+            ApiDeviceType::SDK => DeviceType::SDK,
+            ApiDeviceType::Server => DeviceType::Server,
+            ApiDeviceType::WindowsCLI => DeviceType::WindowsCLI,
+            ApiDeviceType::MacOsCLI => DeviceType::MacOsCLI,
+            ApiDeviceType::LinuxCLI => DeviceType::LinuxCLI,
+        }
+    }
+}
+
+pub(crate) async fn list_devices(client: &Client) -> Result<Vec<DeviceView>> {
+    let config = client.internal.get_api_configurations().await;
+    let response = devices_get(&config.api).await?;
+
+    response
+        .data
+        .unwrap_or_default()
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect()
+}
+
+/// Store the keys of a newly trusted device on the server.
+pub(crate) async fn register_trusted_device(
+    client: &Client,
+    device_identifier: &str,
+    keys: &TrustDeviceResponse,
+) -> Result<()> {
+    let config = client.internal.get_api_configurations().await;
+    devices_identifier_keys_put(
+        &config.api,
+        device_identifier,
+        Some(DeviceKeysRequestModel {
+            encrypted_user_key: keys.protected_user_key.to_string(),
+            encrypted_public_key: keys.protected_device_public_key.to_string(),
+            encrypted_private_key: keys.protected_device_private_key.to_string(),
+        }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Remove the trust of the device, for example when its device key was lost.
+pub(crate) async fn untrust_device(client: &Client, device_identifier: &str) -> Result<()> {
+    let config = client.internal.get_api_configurations().await;
+
+    // The server identifies the device by its header, which the generated API doesn't send
+    let mut request = config
+        .api
+        .client
+        .post(format!("{}/devices/lost-trust", config.api.base_path))
+        .header("Device-Identifier", device_identifier);
+    if let Some(token) = &config.api.oauth_access_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::ResponseContent {
+            status,
+            message: response.text().await?,
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn deactivate_device(client: &Client, id: Uuid) -> Result<()> {
+    let config = client.internal.get_api_configurations().await;
+    devices_id_deactivate_post(&config.api, &id.to_string()).await?;
+
+    Ok(())
+}
+
+/// The device keys of the trusted devices as they were before [rotate_device_keys], used to
+/// restore them with [restore_device_keys] when the user key rotation fails.
+#[derive(Debug)]
+pub struct DeviceKeysBackup(UpdateDevicesTrustRequestModel);
+
+/// Re-encrypt the keys of this and every other trusted device for a rotated user key, so they
+/// stay trusted. `new_user_key` is the new user key protected by the master key, and the client
+/// still needs to be unlocked with the current one.
+///
+/// Returns the previous device keys, which need to be restored if the rotation isn't completed.
+pub(crate) async fn rotate_device_keys(
+    client: &Client,
+    device_identifier: &str,
+    password: &str,
+    new_user_key: EncString,
+) -> Result<DeviceKeysBackup> {
+    let master_key = match client
+        .internal
+        .get_login_method()
+        .ok_or(NotAuthenticatedError)?
+        .as_ref()
+    {
+        LoginMethod::User(
+            UserLoginMethod::Username { email, kdf, .. }
+            | UserLoginMethod::ApiKey { email, kdf, .. },
+        ) => MasterKey::derive(password, email, kdf)?,
+        #[cfg(feature = "secrets")]
+        LoginMethod::ServiceAccount(_) => return Err(NotAuthenticatedError)?,
+    };
+    let master_password_hash =
+        master_key.derive_master_key_hash(password.as_bytes(), HashPurpose::ServerAuthorization)?;
+    let new_user_key = master_key.decrypt_user_key(new_user_key)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let verification = SecretVerificationRequestModel {
+        master_password_hash: Some(master_password_hash.clone()),
+        otp: None,
+        auth_request_access_code: None,
+        secret: None,
+    };
+
+    let device = devices_identifier_retrieve_keys_post(
+        &config.api,
+        device_identifier,
+        Some(verification.clone()),
+    )
+    .await?;
+    let current_device = previous_device_keys(&device)?;
+    let (encrypted_public_key, encrypted_user_key) = rekey_device(client, device, &new_user_key)?;
+
+    let mut previous_other_devices = Vec::new();
+    let mut other_devices = Vec::new();
+    for other in list_devices(client).await? {
+        if !other.is_trusted || other.identifier == device_identifier {
+            continue;
+        }
+
+        let device = devices_identifier_retrieve_keys_post(
+            &config.api,
+            &other.identifier,
+            Some(verification.clone()),
+        )
+        .await?;
+        let previous = previous_device_keys(&device)?;
+        previous_other_devices.push(OtherDeviceKeysUpdateRequestModel {
+            encrypted_public_key: previous.encrypted_public_key,
+            encrypted_user_key: previous.encrypted_user_key,
+            device_id: other.id,
+        });
+        let (encrypted_public_key, encrypted_user_key) =
+            rekey_device(client, device, &new_user_key)?;
+        other_devices.push(OtherDeviceKeysUpdateRequestModel {
+            encrypted_public_key,
+            encrypted_user_key,
+            device_id: other.id,
+        });
+    }
+
+    let backup = DeviceKeysBackup(UpdateDevicesTrustRequestModel {
+        master_password_hash: Some(master_password_hash.clone()),
+        otp: None,
+        auth_request_access_code: None,
+        secret: None,
+        current_device: Box::new(current_device),
+        other_devices: Some(previous_other_devices),
+    });
+
+    devices_update_trust_post(
+        &config.api,
+        Some(UpdateDevicesTrustRequestModel {
+            master_password_hash: Some(master_password_hash),
+            otp: None,
+            auth_request_access_code: None,
+            secret: None,
+            current_device: Box::new(DeviceKeysUpdateRequestModel {
+                encrypted_public_key,
+                encrypted_user_key,
+            }),
+            other_devices: Some(other_devices),
+        }),
+    )
+    .await?;
+
+    Ok(backup)
+}
+
+/// Put back the device keys replaced by [rotate_device_keys].
+pub(crate) async fn restore_device_keys(client: &Client, backup: DeviceKeysBackup) -> Result<()> {
+    let config = client.internal.get_api_configurations().await;
+    devices_update_trust_post(&config.api, Some(backup.0)).await?;
+
+    Ok(())
+}
+
+fn previous_device_keys(
+    device: &ProtectedDeviceResponseModel,
+) -> Result<DeviceKeysUpdateRequestModel> {
+    Ok(DeviceKeysUpdateRequestModel {
+        encrypted_public_key: require!(device.encrypted_public_key.clone()),
+        encrypted_user_key: require!(device.encrypted_user_key.clone()),
+    })
+}
+
+/// Protect the public key of a trusted device with the new user key, and the new user key with
+/// the public key of the device. Returns the encrypted public key and user key, in that order.
+fn rekey_device(
+    client: &Client,
+    device: ProtectedDeviceResponseModel,
+    new_user_key: &SymmetricCryptoKey,
+) -> Result<(String, String)> {
+    let encrypted_public_key: EncString = require!(device.encrypted_public_key).parse()?;
+    let public_key: Vec<u8> = {
+        let key_store = client.internal.get_key_store();
+        let mut ctx = key_store.context();
+        encrypted_public_key.decrypt(&mut ctx, SymmetricKeyId::User)?
+    };
+    let device_public_key = AsymmetricPublicCryptoKey::from_der(&public_key)?;
+
+    Ok((
+        public_key
+            .as_slice()
+            .encrypt_with_key(new_user_key)?
+            .to_string(),
+        AsymmetricEncString::encrypt(&new_user_key.to_vec(), &device_public_key)?.to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_crypto::{DeviceKey, Kdf};
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+
+    const DEVICE_IDENTIFIER: &str = "b86dd6ab-4265-4ddf-a7f1-eb28d5677f33";
+    const OTHER_DEVICE_IDENTIFIER: &str = "3f5a1c2e-8d4b-4a6f-9e7c-1b2d3e4f5a6b";
+    const OTHER_DEVICE_ID: &str = "a1b2c3d4-e5f6-4a7b-8c9d-b2a500d2a5b7";
+
+    #[tokio::test]
+    async fn test_list_devices() {
+        let (_server, client) = crate::util::start_mock(vec![Mock::given(matchers::method("GET"))
+            .and(matchers::path("/api/devices"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [{
+                    "object": "device",
+                    "id": "c9e6a7e4-1b06-4b2a-9d85-b2a500d2a5b6",
+                    "name": "firefox",
+                    "type": 10,
+                    "identifier": DEVICE_IDENTIFIER,
+                    "creationDate": "2024-05-01T12:00:00.000Z",
+                    "isTrusted": true,
+                }],
+            })))])
+        .await;
+
+        let devices = list_devices(&client).await.unwrap();
+
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].identifier, DEVICE_IDENTIFIER);
+        assert!(matches!(devices[0].r#type, DeviceType::FirefoxBrowser));
+        assert!(devices[0].is_trusted);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_device_keys() {
+        let (server, client) = crate::util::start_mock(vec![]).await;
+
+        let email = "test@bitwarden.com";
+        let password = "asdfasdfasdf";
+        let kdf = Kdf::PBKDF2 {
+            iterations: 5000.try_into().unwrap(),
+        };
+        let master_key = MasterKey::derive(password, email, &kdf).unwrap();
+        let (user_key, protected_user_key) = master_key.make_user_key().unwrap();
+        let key_pair = user_key.make_key_pair().unwrap();
+        let (new_user_key, protected_new_user_key) = master_key.make_user_key().unwrap();
+
+        client
+            .internal
+            .set_login_method(LoginMethod::User(UserLoginMethod::Username {
+                client_id: "web".to_owned(),
+                email: email.to_owned(),
+                kdf,
+            }));
+        client
+            .internal
+            .initialize_user_crypto_master_key(master_key, protected_user_key, key_pair.private)
+            .unwrap();
+
+        let device = client.auth().trust_device().unwrap();
+        let other_device = client.auth().trust_device().unwrap();
+
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/api/devices"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [
+                    {
+                        "object": "device",
+                        "id": "c9e6a7e4-1b06-4b2a-9d85-b2a500d2a5b6",
+                        "name": "firefox",
+                        "type": 10,
+                        "identifier": DEVICE_IDENTIFIER,
+                        "creationDate": "2024-05-01T12:00:00.000Z",
+                        "isTrusted": true,
+                    },
+                    {
+                        "object": "device",
+                        "id": OTHER_DEVICE_ID,
+                        "name": "android",
+                        "type": 0,
+                        "identifier": OTHER_DEVICE_IDENTIFIER,
+                        "creationDate": "2024-05-02T12:00:00.000Z",
+                        "isTrusted": true,
+                    },
+                    {
+                        "object": "device",
+                        "id": "5d7c2c1a-4ee5-4d5b-b7a4-b2a500d2a5b8",
+                        "name": "chrome",
+                        "type": 9,
+                        "identifier": "0f1d2e3c-4b5a-6978-8796-a5b4c3d2e1f0",
+                        "creationDate": "2024-05-03T12:00:00.000Z",
+                        "isTrusted": false,
+                    },
+                ],
+            })))
+            .mount(&server)
+            .await;
+        for (identifier, keys) in [
+            (DEVICE_IDENTIFIER, &device),
+            (OTHER_DEVICE_IDENTIFIER, &other_device),
+        ] {
+            Mock::given(matchers::method("POST"))
+                .and(matchers::path(format!(
+                    "/api/devices/{identifier}/retrieve-keys"
+                )))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "object": "protectedDevice",
+                    "identifier": identifier,
+                    "encryptedUserKey": keys.protected_user_key.to_string(),
+                    "encryptedPublicKey": keys.protected_device_public_key.to_string(),
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/api/devices/update-trust"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let backup =
+            rotate_device_keys(&client, DEVICE_IDENTIFIER, password, protected_new_user_key)
+                .await
+                .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = requests.last().unwrap().body_json().unwrap();
+        let protected_user_key: AsymmetricEncString = body["currentDevice"]["encryptedUserKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        // The device key still decrypts the user key, which is now the new one
+        let device_key = DeviceKey::try_from(device.device_key).unwrap();
+        let decrypted = device_key
+            .decrypt_user_key(device.protected_device_private_key, protected_user_key)
+            .unwrap();
+        assert_eq!(decrypted.to_base64(), new_user_key.0.to_base64());
+
+        // The other trusted device is re-keyed as well, the untrusted one isn't
+        let other_devices = body["otherDevices"].as_array().unwrap();
+        assert_eq!(other_devices.len(), 1);
+        assert_eq!(other_devices[0]["deviceId"], OTHER_DEVICE_ID);
+        let protected_user_key: AsymmetricEncString = other_devices[0]["encryptedUserKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let device_key = DeviceKey::try_from(other_device.device_key).unwrap();
+        let decrypted = device_key
+            .decrypt_user_key(
+                other_device.protected_device_private_key,
+                protected_user_key,
+            )
+            .unwrap();
+        assert_eq!(decrypted.to_base64(), new_user_key.0.to_base64());
+
+        // Restoring puts back the device keys for the current user key
+        restore_device_keys(&client, backup).await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = requests.last().unwrap().body_json().unwrap();
+        assert_eq!(
+            body["currentDevice"]["encryptedUserKey"],
+            device.protected_user_key.to_string()
+        );
+        assert_eq!(
+            body["otherDevices"][0]["encryptedUserKey"],
+            other_device.protected_user_key.to_string()
+        );
+        assert_eq!(body["otherDevices"][0]["deviceId"], OTHER_DEVICE_ID);
+    }
+}
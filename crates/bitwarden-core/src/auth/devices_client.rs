@@ -0,0 +1,73 @@
+use bitwarden_crypto::{EncString, TrustDeviceResponse};
+use uuid::Uuid;
+
+use crate::{
+    auth::devices::{
+        deactivate_device, list_devices, register_trusted_device, restore_device_keys,
+        rotate_device_keys, untrust_device, DeviceKeysBackup, DeviceView,
+    },
+    error::Result,
+    Client,
+};
+
+pub struct DevicesClient<'a> {
+    pub(crate) client: &'a crate::Client,
+}
+
+impl DevicesClient<'_> {
+    /// List the devices the user has logged in with.
+    pub async fn list(&self) -> Result<Vec<DeviceView>> {
+        list_devices(self.client).await
+    }
+
+    /// Trust this device, so it can decrypt the vault with its device key. The returned device key
+    /// needs to be stored on the device.
+    pub async fn trust(&self, device_identifier: &str) -> Result<TrustDeviceResponse> {
+        let keys = self.client.auth().trust_device()?;
+        register_trusted_device(self.client, device_identifier, &keys).await?;
+
+        Ok(keys)
+    }
+
+    /// Store the keys of a device trusted with [crate::auth::auth_client::AuthClient::trust_device]
+    /// on the server.
+    pub async fn register_trusted(
+        &self,
+        device_identifier: &str,
+        keys: &TrustDeviceResponse,
+    ) -> Result<()> {
+        register_trusted_device(self.client, device_identifier, keys).await
+    }
+
+    pub async fn untrust(&self, device_identifier: &str) -> Result<()> {
+        untrust_device(self.client, device_identifier).await
+    }
+
+    /// Deactivate a device, logging it out and removing its trust.
+    pub async fn deactivate(&self, id: Uuid) -> Result<()> {
+        deactivate_device(self.client, id).await
+    }
+
+    /// Re-encrypt the keys of this and the other trusted devices for a rotated user key.
+    /// `new_user_key` is the new user key protected by the master key. Returns the previous
+    /// device keys, pass them to [DevicesClient::restore_keys] if the rotation fails.
+    pub async fn rotate_keys(
+        &self,
+        device_identifier: &str,
+        password: &str,
+        new_user_key: EncString,
+    ) -> Result<DeviceKeysBackup> {
+        rotate_device_keys(self.client, device_identifier, password, new_user_key).await
+    }
+
+    /// Restore the device keys replaced by [DevicesClient::rotate_keys].
+    pub async fn restore_keys(&self, backup: DeviceKeysBackup) -> Result<()> {
+        restore_device_keys(self.client, backup).await
+    }
+}
+
+impl<'a> Client {
+    pub fn devices(&'a self) -> DevicesClient<'a> {
+        DevicesClient { client: self }
+    }
+}
@@ -1,4 +1,21 @@
-use bitwarden_crypto::{CryptoError, MasterKey, RsaKeyPair};
+use bitwarden_api_api::{
+    apis::accounts_api::{
+        accounts_convert_to_key_connector_post, accounts_set_key_connector_key_post,
+    },
+    models::{KdfType, KeysRequestModel, SetKeyConnectorKeyRequestModel},
+};
+use bitwarden_crypto::{CryptoError, Kdf, MasterKey, RsaKeyPair};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, NotAuthenticatedError, Result},
+    mobile::crypto::{
+        derive_key_connector, DeriveKeyConnectorRequest, InitUserCryptoMethod,
+        InitUserCryptoRequest,
+    },
+    Client,
+};
 
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct KeyConnectorResponse {
@@ -21,12 +38,207 @@ pub(super) fn make_key_connector_keys(
     })
 }
 
+/// The `user-keys` endpoints of a Key Connector, which stores the master keys of the users of an
+/// organization. [HttpKeyConnectorApi] talks to a Key Connector over HTTP, but clients can provide
+/// their own implementation, for example to use the HTTP stack of the platform.
+#[async_trait::async_trait]
+pub trait KeyConnectorApi: Send + Sync {
+    /// Fetch the base64 encoded master key stored for the user.
+    async fn get_user_key(&self, access_token: &str) -> Result<String>;
+
+    /// Store the base64 encoded master key of the user.
+    async fn post_user_key(&self, access_token: &str, key: String) -> Result<()>;
+}
+
+pub struct HttpKeyConnectorApi {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl HttpKeyConnectorApi {
+    pub fn new(client: reqwest::Client, url: String) -> Self {
+        Self { client, url }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyConnectorUserKey {
+    key: String,
+}
+
+#[async_trait::async_trait]
+impl KeyConnectorApi for HttpKeyConnectorApi {
+    async fn get_user_key(&self, access_token: &str) -> Result<String> {
+        let response = self
+            .client
+            .get(format!("{}/user-keys", self.url))
+            .bearer_auth(access_token)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::ResponseContent {
+                status,
+                message: text,
+            });
+        }
+
+        Ok(serde_json::from_str::<KeyConnectorUserKey>(&text)?.key)
+    }
+
+    async fn post_user_key(&self, access_token: &str, key: String) -> Result<()> {
+        let response = self
+            .client
+            .post(format!("{}/user-keys", self.url))
+            .bearer_auth(access_token)
+            .json(&KeyConnectorUserKey { key })
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::ResponseContent {
+                status,
+                message: response.text().await?,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct UnlockKeyConnectorRequest {
+    pub email: String,
+    /// The user's encrypted symmetric crypto key
+    pub user_key: String,
+    /// The user's encrypted private key
+    pub private_key: String,
+}
+
+async fn access_token(client: &Client) -> Result<String> {
+    let config = client.internal.get_api_configurations().await;
+    Ok(config
+        .api
+        .oauth_access_token
+        .clone()
+        .ok_or(NotAuthenticatedError)?)
+}
+
+/// Unlock the vault of a Key Connector user with the master key stored in Key Connector.
+pub(crate) async fn unlock_key_connector(
+    client: &Client,
+    api: &dyn KeyConnectorApi,
+    request: UnlockKeyConnectorRequest,
+) -> Result<()> {
+    let master_key = api.get_user_key(&access_token(client).await?).await?;
+
+    client
+        .crypto()
+        .initialize_user_crypto(InitUserCryptoRequest {
+            kdf_params: Kdf::default(),
+            email: request.email,
+            private_key: request.private_key,
+            method: InitUserCryptoMethod::KeyConnector {
+                master_key,
+                user_key: request.user_key,
+            },
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Create the keys of a new Key Connector user and unlock the vault with them. The master key is
+/// stored in Key Connector, and the protected user key and key pair on the server.
+pub(crate) async fn register_key_connector_user(
+    client: &Client,
+    api: &dyn KeyConnectorApi,
+    email: String,
+    organization_identifier: String,
+) -> Result<()> {
+    let keys = make_key_connector_keys(rand::thread_rng())?;
+    api.post_user_key(&access_token(client).await?, keys.master_key.clone())
+        .await?;
+
+    let Kdf::PBKDF2 { iterations } = Kdf::default() else {
+        unreachable!("The default KDF is PBKDF2")
+    };
+    let config = client.internal.get_api_configurations().await;
+    accounts_set_key_connector_key_post(
+        &config.api,
+        Some(SetKeyConnectorKeyRequestModel::new(
+            keys.encrypted_user_key.clone(),
+            KeysRequestModel {
+                public_key: Some(keys.keys.public),
+                encrypted_private_key: keys.keys.private.to_string(),
+            },
+            KdfType::PBKDF2_SHA256,
+            iterations.get() as i32,
+            organization_identifier,
+        )),
+    )
+    .await?;
+
+    client
+        .crypto()
+        .initialize_user_crypto(InitUserCryptoRequest {
+            kdf_params: Kdf::default(),
+            email,
+            private_key: keys.keys.private.to_string(),
+            method: InitUserCryptoMethod::KeyConnector {
+                master_key: keys.master_key,
+                user_key: keys.encrypted_user_key,
+            },
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Move a master password user onto Key Connector. The master key derived from the password is
+/// stored in Key Connector, after which the server removes the master password of the user.
+pub(crate) async fn migrate_to_key_connector(
+    client: &Client,
+    api: &dyn KeyConnectorApi,
+    request: DeriveKeyConnectorRequest,
+) -> Result<()> {
+    let master_key = derive_key_connector(request)?;
+    api.post_user_key(&access_token(client).await?, master_key)
+        .await?;
+
+    let config = client.internal.get_api_configurations().await;
+    accounts_convert_to_key_connector_post(&config.api).await?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
+    use wiremock::{matchers, Mock, ResponseTemplate};
 
     use super::*;
+    use crate::key_management::SymmetricKeyId;
+
+    const ACCESS_TOKEN: &str = "access-token";
+
+    async fn start_mock(mocks: Vec<Mock>) -> (wiremock::MockServer, Client, HttpKeyConnectorApi) {
+        let (server, client) = crate::util::start_mock(mocks).await;
+        client
+            .internal
+            .set_tokens(ACCESS_TOKEN.to_owned(), None, 3600);
+        let api = HttpKeyConnectorApi::new(
+            reqwest::Client::new(),
+            format!("{}/key-connector", server.uri()),
+        );
+        (server, client, api)
+    }
 
     #[test]
     fn test_make_key_connector_keys() {
@@ -39,4 +251,77 @@ mod tests {
             "PgDvL4lfQNZ/W7joHwmloSyEDsPOmn87GBvhiO9xGh4="
         );
     }
+
+    #[tokio::test]
+    async fn test_unlock_key_connector() {
+        let keys = make_key_connector_keys(ChaCha8Rng::from_seed([0u8; 32])).unwrap();
+        let (_server, client, api) = start_mock(vec![Mock::given(matchers::method("GET"))
+            .and(matchers::path("/key-connector/user-keys"))
+            .and(matchers::bearer_token(ACCESS_TOKEN))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "key": keys.master_key })),
+            )])
+        .await;
+
+        unlock_key_connector(
+            &client,
+            &api,
+            UnlockKeyConnectorRequest {
+                email: "test@bitwarden.com".to_owned(),
+                user_key: keys.encrypted_user_key,
+                private_key: keys.keys.private.to_string(),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(client
+            .internal
+            .get_key_store()
+            .context()
+            .has_symmetric_key(SymmetricKeyId::User));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_to_key_connector() {
+        let kdf = Kdf::PBKDF2 {
+            iterations: 5000.try_into().unwrap(),
+        };
+        let master_key = MasterKey::derive("asdfasdfasdf", "test@bitwarden.com", &kdf).unwrap();
+        let (_, user_key) = master_key.make_user_key().unwrap();
+
+        let (_server, client, api) = start_mock(vec![
+            Mock::given(matchers::method("POST"))
+                .and(matchers::path("/key-connector/user-keys"))
+                .and(matchers::bearer_token(ACCESS_TOKEN))
+                .and(matchers::body_json(
+                    serde_json::json!({ "key": master_key.to_base64() }),
+                ))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1),
+            Mock::given(matchers::method("POST"))
+                .and(matchers::path("/api/accounts/convert-to-key-connector"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1),
+        ])
+        .await;
+
+        let request = |password: &str| DeriveKeyConnectorRequest {
+            user_key_encrypted: user_key.clone(),
+            password: password.to_owned(),
+            kdf: kdf.clone(),
+            email: "test@bitwarden.com".to_owned(),
+        };
+
+        // A wrong password is rejected before anything is sent to Key Connector
+        assert!(
+            migrate_to_key_connector(&client, &api, request("wrong password"))
+                .await
+                .is_err()
+        );
+        migrate_to_key_connector(&client, &api, request("asdfasdfasdf"))
+            .await
+            .unwrap();
+    }
 }
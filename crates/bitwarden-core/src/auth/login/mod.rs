@@ -25,6 +25,10 @@ mod two_factor;
 pub(crate) use two_factor::send_two_factor_email;
 #[cfg(feature = "internal")]
 pub use two_factor::{TwoFactorEmailRequest, TwoFactorProvider, TwoFactorRequest};
+#[cfg(feature = "internal")]
+mod two_factor_session;
+#[cfg(feature = "internal")]
+pub use two_factor_session::{PasswordLoginSession, WebAuthnAssertion};
 
 #[cfg(feature = "internal")]
 mod api_key;
@@ -40,6 +44,15 @@ pub use auth_request::NewAuthRequestResponse;
 #[cfg(feature = "internal")]
 pub(crate) use auth_request::{complete_auth_request, send_new_auth_request};
 
+#[cfg(feature = "internal")]
+mod sso;
+#[cfg(feature = "internal")]
+pub(crate) use sso::{complete_sso_login, send_sso_login};
+#[cfg(feature = "internal")]
+pub use sso::{
+    NewSsoLoginResponse, SsoDecryption, SsoLoginCompleteRequest, SsoLoginRequest, SsoLoginResponse,
+};
+
 #[cfg(feature = "secrets")]
 mod access_token;
 #[cfg(feature = "secrets")]
@@ -110,6 +110,9 @@ pub struct PasswordLoginResponse {
     /// The information required to present the user with a captcha challenge. Only present when
     /// authentication fails due to requiring validation of a captcha challenge.
     pub captcha: Option<CaptchaResponse>,
+    /// Token that skips the second factor on later logins from this device, when sent with the
+    /// `Remember` two-factor provider. Only present when the user asked to be remembered.
+    pub two_factor_remember_token: Option<String>,
 }
 
 impl PasswordLoginResponse {
@@ -123,6 +126,7 @@ impl PasswordLoginResponse {
                 force_password_reset: success.force_password_reset,
                 two_factor: None,
                 captcha: None,
+                two_factor_remember_token: success.two_factor_token,
             }),
             IdentityTokenResponse::Payload(_) => Ok(PasswordLoginResponse {
                 authenticated: true,
@@ -130,6 +134,7 @@ impl PasswordLoginResponse {
                 force_password_reset: false,
                 two_factor: None,
                 captcha: None,
+                two_factor_remember_token: None,
             }),
             IdentityTokenResponse::TwoFactorRequired(two_factor) => Ok(PasswordLoginResponse {
                 authenticated: false,
@@ -137,6 +142,7 @@ impl PasswordLoginResponse {
                 force_password_reset: false,
                 two_factor: Some(two_factor.two_factor_providers.into()),
                 captcha: two_factor.captcha_token.map(Into::into),
+                two_factor_remember_token: None,
             }),
             IdentityTokenResponse::CaptchaRequired(captcha) => Ok(PasswordLoginResponse {
                 authenticated: false,
@@ -144,6 +150,7 @@ impl PasswordLoginResponse {
                 force_password_reset: false,
                 two_factor: None,
                 captcha: Some(captcha.site_key.into()),
+                two_factor_remember_token: None,
             }),
             IdentityTokenResponse::Refreshed(_) => {
                 unreachable!("Got a `refresh_token` answer to a login request")
@@ -0,0 +1,515 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bitwarden_api_api::{
+    apis::{
+        accounts_api::accounts_keys_post,
+        organization_users_api::organizations_org_id_users_user_id_reset_password_enrollment_put,
+        organizations_api::organizations_identifier_auto_enroll_status_get,
+    },
+    models::{KeysRequestModel, OrganizationUserResetPasswordEnrollmentRequestModel},
+};
+use bitwarden_crypto::{generate_random_alphanumeric, Kdf, TrustDeviceResponse};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        api::{
+            request::SsoTokenRequest,
+            response::{
+                IdentityTokenResponse, IdentityTokenSuccessResponse, UserDecryptionOptionsResponse,
+            },
+        },
+        key_connector::{
+            register_key_connector_user, unlock_key_connector, HttpKeyConnectorApi,
+            UnlockKeyConnectorRequest,
+        },
+        login::{
+            response::{captcha_response::CaptchaResponse, two_factor::TwoFactorProviders},
+            TwoFactorRequest,
+        },
+        tde::make_register_tde_keys,
+        JWTToken,
+    },
+    client::{ApiConfigurations, LoginMethod, UserLoginMethod},
+    error::{Error, Result},
+    mobile::crypto::{InitUserCryptoMethod, InitUserCryptoRequest},
+    platform::{get_public_key, PublicKeyOwner},
+    require, Client,
+};
+
+/// Login to Bitwarden with the single sign-on of an organization
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SsoLoginRequest {
+    /// The SSO identifier of the organization
+    pub organization_identifier: String,
+    /// Where the identity provider sends the user back to after they have logged in
+    pub redirect_uri: String,
+    pub device_identifier: String,
+}
+
+/// A pending SSO login. Keeps the PKCE code verifier until the authorization code is received.
+pub struct NewSsoLoginResponse {
+    /// The URL to open in a browser to log in with the identity provider of the organization
+    pub authorize_url: String,
+    organization_identifier: String,
+    redirect_uri: String,
+    device_identifier: String,
+    state: String,
+    code_verifier: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SsoLoginCompleteRequest {
+    /// The `code` query parameter the identity provider redirected to
+    pub code: String,
+    /// The `state` query parameter the identity provider redirected to
+    pub state: String,
+    /// The device key, if this device was trusted before
+    pub device_key: Option<String>,
+    /// Trust this device when registering a new user with trusted device encryption
+    pub remember_device: bool,
+    // Two-factor authentication
+    pub two_factor: Option<TwoFactorRequest>,
+}
+
+#[derive(Debug)]
+pub struct SsoLoginResponse {
+    pub authenticated: bool,
+    /// Whether or not the user is required to update their master password
+    pub force_password_reset: bool,
+    /// The available two factor authentication options. Present only when authentication fails due
+    /// to requiring a second authentication factor.
+    pub two_factor: Option<TwoFactorProviders>,
+    /// The information required to present the user with a captcha challenge. Only present when
+    /// authentication fails due to requiring validation of a captcha challenge.
+    pub captcha: Option<CaptchaResponse>,
+    /// How the vault was decrypted. Only present when authenticated.
+    pub decryption: Option<SsoDecryption>,
+}
+
+#[derive(Debug)]
+pub enum SsoDecryption {
+    /// The vault was unlocked with the device key of this trusted device.
+    TrustedDevice,
+    /// A new user was registered with trusted device encryption and the vault unlocked. The device
+    /// key is present when the device was remembered, and still needs to be registered with the
+    /// server.
+    TrustedDeviceRegistered {
+        device_key: Option<Box<TrustDeviceResponse>>,
+    },
+    /// The vault was unlocked with the master key stored in Key Connector.
+    KeyConnector,
+    /// The vault is still locked. It needs to be unlocked with the master password, or the device
+    /// needs to be approved by another device or an administrator.
+    Locked {
+        has_master_password: bool,
+        has_admin_approval: bool,
+        has_login_approving_device: bool,
+    },
+}
+
+pub(crate) async fn send_sso_login(
+    client: &Client,
+    input: &SsoLoginRequest,
+) -> Result<NewSsoLoginResponse> {
+    info!("sso logging in");
+
+    let config = client.internal.get_api_configurations().await;
+    let sso_token = sso_pre_validate(&config, &input.organization_identifier).await?;
+
+    let state = generate_random_alphanumeric(64);
+    let code_verifier = generate_random_alphanumeric(64);
+
+    let authorize_url = reqwest::Url::parse_with_params(
+        &format!("{}/connect/authorize", config.identity.base_path),
+        &[
+            ("client_id", "web"),
+            ("redirect_uri", &input.redirect_uri),
+            ("response_type", "code"),
+            ("scope", "api offline_access"),
+            ("state", &state),
+            ("code_challenge", &code_challenge(&code_verifier)),
+            ("code_challenge_method", "S256"),
+            ("response_mode", "query"),
+            ("domain_hint", &input.organization_identifier),
+            ("ssoToken", &sso_token),
+        ],
+    )
+    .map_err(|_| "Invalid identity URL")?;
+
+    Ok(NewSsoLoginResponse {
+        authorize_url: authorize_url.to_string(),
+        organization_identifier: input.organization_identifier.clone(),
+        redirect_uri: input.redirect_uri.clone(),
+        device_identifier: input.device_identifier.clone(),
+        state,
+        code_verifier,
+    })
+}
+
+pub(crate) async fn complete_sso_login(
+    client: &Client,
+    sso: &NewSsoLoginResponse,
+    input: &SsoLoginCompleteRequest,
+) -> Result<SsoLoginResponse> {
+    if input.state != sso.state {
+        return Err("SSO state does not match the login request".into());
+    }
+
+    let config = client.internal.get_api_configurations().await;
+    let response = SsoTokenRequest::new(
+        &input.code,
+        &sso.code_verifier,
+        &sso.redirect_uri,
+        config.device_type,
+        &sso.device_identifier,
+        &input.two_factor,
+    )
+    .send(&config)
+    .await?;
+
+    let r = match response {
+        IdentityTokenResponse::Authenticated(r) => r,
+        IdentityTokenResponse::TwoFactorRequired(two_factor) => {
+            return Ok(SsoLoginResponse {
+                authenticated: false,
+                force_password_reset: false,
+                two_factor: Some(two_factor.two_factor_providers.into()),
+                captcha: two_factor.captcha_token.map(Into::into),
+                decryption: None,
+            })
+        }
+        IdentityTokenResponse::CaptchaRequired(captcha) => {
+            return Ok(SsoLoginResponse {
+                authenticated: false,
+                force_password_reset: false,
+                two_factor: None,
+                captcha: Some(captcha.site_key.into()),
+                decryption: None,
+            })
+        }
+        _ => return Err("Failed to authenticate".into()),
+    };
+
+    client.internal.set_tokens(
+        r.access_token.clone(),
+        r.refresh_token.clone(),
+        r.expires_in,
+    );
+
+    let token: JWTToken = r.access_token.parse()?;
+    let email = require!(token.email);
+    let user_id: Uuid = token.sub.parse().map_err(|_| "Invalid user id")?;
+
+    client
+        .internal
+        .set_login_method(LoginMethod::User(UserLoginMethod::Username {
+            client_id: "web".to_owned(),
+            email: email.clone(),
+            kdf: Kdf::default(),
+        }));
+
+    let options = require!(r.user_decryption_options.as_ref());
+    let decryption = decrypt_user_key(client, sso, input, &r, options, email, user_id).await?;
+
+    Ok(SsoLoginResponse {
+        authenticated: true,
+        force_password_reset: r.force_password_reset,
+        two_factor: None,
+        captcha: None,
+        decryption: Some(decryption),
+    })
+}
+
+/// Unlock the vault using Key Connector or trusted device encryption, registering the keys of new
+/// users on the way.
+async fn decrypt_user_key(
+    client: &Client,
+    sso: &NewSsoLoginResponse,
+    input: &SsoLoginCompleteRequest,
+    r: &IdentityTokenSuccessResponse,
+    options: &UserDecryptionOptionsResponse,
+    email: String,
+    user_id: Uuid,
+) -> Result<SsoDecryption> {
+    if let Some(key_connector) = &options.key_connector_option {
+        let api = HttpKeyConnectorApi::new(
+            client.internal.get_http_client().clone(),
+            key_connector.key_connector_url.clone(),
+        );
+
+        match (&r.key, &r.private_key) {
+            (Some(user_key), Some(private_key)) => {
+                let request = UnlockKeyConnectorRequest {
+                    email,
+                    user_key: user_key.clone(),
+                    private_key: private_key.clone(),
+                };
+                unlock_key_connector(client, &api, request).await?
+            }
+            _ => {
+                let organization_identifier = sso.organization_identifier.clone();
+                register_key_connector_user(client, &api, email, organization_identifier).await?
+            }
+        }
+
+        return Ok(SsoDecryption::KeyConnector);
+    }
+
+    let Some(trusted_device) = &options.trusted_device_option else {
+        return Ok(SsoDecryption::Locked {
+            has_master_password: options.has_master_password,
+            has_admin_approval: false,
+            has_login_approving_device: false,
+        });
+    };
+
+    let Some(private_key) = &r.private_key else {
+        return register_tde_user(
+            client,
+            &sso.organization_identifier,
+            email,
+            user_id,
+            input.remember_device,
+        )
+        .await;
+    };
+
+    match (
+        &input.device_key,
+        &trusted_device.encrypted_private_key,
+        &trusted_device.encrypted_user_key,
+    ) {
+        (Some(device_key), Some(protected_device_private_key), Some(device_protected_user_key)) => {
+            client
+                .crypto()
+                .initialize_user_crypto(InitUserCryptoRequest {
+                    kdf_params: Kdf::default(),
+                    email,
+                    private_key: private_key.clone(),
+                    method: InitUserCryptoMethod::DeviceKey {
+                        device_key: device_key.clone(),
+                        protected_device_private_key: protected_device_private_key.parse()?,
+                        device_protected_user_key: device_protected_user_key.parse()?,
+                    },
+                })
+                .await?;
+
+            Ok(SsoDecryption::TrustedDevice)
+        }
+        _ => Ok(SsoDecryption::Locked {
+            has_master_password: options.has_master_password,
+            has_admin_approval: trusted_device.has_admin_approval,
+            has_login_approving_device: trusted_device.has_login_approving_device,
+        }),
+    }
+}
+
+/// Create the keys of a new trusted device encryption user, enrolling them into admin password
+/// reset of the organization.
+async fn register_tde_user(
+    client: &Client,
+    organization_identifier: &str,
+    email: String,
+    user_id: Uuid,
+    remember_device: bool,
+) -> Result<SsoDecryption> {
+    let config = client.internal.get_api_configurations().await;
+
+    let organization_id = require!(
+        organizations_identifier_auto_enroll_status_get(&config.api, organization_identifier)
+            .await?
+            .id
+    );
+    let org_public_key = get_public_key(client, PublicKeyOwner::Organization(organization_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .public_key;
+
+    let keys = make_register_tde_keys(client, email, org_public_key, remember_device)?;
+
+    accounts_keys_post(
+        &config.api,
+        Some(KeysRequestModel {
+            public_key: Some(keys.public_key),
+            encrypted_private_key: keys.private_key.to_string(),
+        }),
+    )
+    .await?;
+    organizations_org_id_users_user_id_reset_password_enrollment_put(
+        &config.api,
+        organization_id,
+        user_id,
+        Some(OrganizationUserResetPasswordEnrollmentRequestModel {
+            reset_password_key: Some(keys.admin_reset.to_string()),
+            master_password_hash: None,
+        }),
+    )
+    .await?;
+
+    Ok(SsoDecryption::TrustedDeviceRegistered {
+        device_key: keys.device_key.map(Box::new),
+    })
+}
+
+#[derive(Deserialize)]
+struct SsoPreValidateResponse {
+    token: String,
+}
+
+/// Check that the organization uses SSO, and get the token the identity server needs to start
+/// the login with its identity provider.
+async fn sso_pre_validate(
+    config: &ApiConfigurations,
+    organization_identifier: &str,
+) -> Result<String> {
+    let response = config
+        .identity
+        .client
+        .get(format!("{}/sso/PreValidate", config.identity.base_path))
+        .query(&[("domainHint", organization_identifier)])
+        .send()
+        .await?;
+
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        return Err(Error::ResponseContent {
+            status,
+            message: text,
+        });
+    }
+
+    Ok(serde_json::from_str::<SsoPreValidateResponse>(&text)?.token)
+}
+
+/// The PKCE code challenge of `code_verifier`, using the `S256` method.
+fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::{auth::key_connector::make_key_connector_keys, key_management::SymmetricKeyId};
+
+    #[test]
+    fn test_code_challenge() {
+        // Test vector from RFC 7636, Appendix B
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_login_sso_key_connector() {
+        let keys = make_key_connector_keys(ChaCha8Rng::from_seed([0u8; 32])).unwrap();
+        let access_token = format!(
+            "eyJhbGciOiJub25lIn0.{}.",
+            URL_SAFE_NO_PAD.encode(
+                serde_json::json!({
+                    "exp": 1700000000,
+                    "sub": "060000fb-0922-4dd3-b170-6e15cb5df8c8",
+                    "email": "test@bitwarden.com",
+                    "scope": ["api", "offline_access"],
+                })
+                .to_string()
+            )
+        );
+
+        let (server, client) = crate::util::start_mock(vec![]).await;
+        let mocks = [
+            Mock::given(matchers::path("/identity/sso/PreValidate"))
+                .and(matchers::query_param("domainHint", "my-org"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({ "token": "sso-token" })),
+                ),
+            Mock::given(matchers::path("/identity/connect/token"))
+                .and(matchers::body_string_contains(
+                    "grant_type=authorization_code",
+                ))
+                .and(matchers::body_string_contains("code=auth-code"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "access_token": access_token,
+                    "expires_in": 3600,
+                    "refresh_token": "refresh-token",
+                    "token_type": "Bearer",
+                    "Key": keys.encrypted_user_key,
+                    "PrivateKey": keys.keys.private.to_string(),
+                    "Kdf": 0,
+                    "KdfIterations": 600000,
+                    "ResetMasterPassword": false,
+                    "ForcePasswordReset": false,
+                    "UserDecryptionOptions": {
+                        "HasMasterPassword": false,
+                        "Object": "userDecryptionOptions",
+                        "KeyConnectorOption": {
+                            "KeyConnectorUrl": format!("{}/key-connector", server.uri()),
+                        },
+                    },
+                })))
+                .expect(1),
+            Mock::given(matchers::method("GET"))
+                .and(matchers::path("/key-connector/user-keys"))
+                .and(matchers::bearer_token(&access_token))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({ "key": keys.master_key })),
+                ),
+        ];
+        for mock in mocks {
+            server.register(mock).await;
+        }
+
+        let sso = send_sso_login(
+            &client,
+            &SsoLoginRequest {
+                organization_identifier: "my-org".to_owned(),
+                redirect_uri: "https://localhost/sso-connector.html".to_owned(),
+                device_identifier: "b86dd6ab-4265-4ddf-a7f1-eb28d5677f33".to_owned(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let url = reqwest::Url::parse(&sso.authorize_url).unwrap();
+        let params: std::collections::HashMap<_, _> = url.query_pairs().collect();
+        assert_eq!(params["code_challenge"], code_challenge(&sso.code_verifier));
+        assert_eq!(params["state"], sso.state);
+        assert_eq!(params["ssoToken"], "sso-token");
+
+        let mut input = SsoLoginCompleteRequest {
+            code: "auth-code".to_owned(),
+            state: "forged-state".to_owned(),
+            device_key: None,
+            remember_device: false,
+            two_factor: None,
+        };
+        assert!(complete_sso_login(&client, &sso, &input).await.is_err());
+
+        input.state = sso.state.clone();
+        let response = complete_sso_login(&client, &sso, &input).await.unwrap();
+
+        assert!(response.authenticated);
+        assert!(matches!(
+            response.decryption,
+            Some(SsoDecryption::KeyConnector)
+        ));
+        assert!(client
+            .internal
+            .get_key_store()
+            .context()
+            .has_symmetric_key(SymmetricKeyId::User));
+    }
+}
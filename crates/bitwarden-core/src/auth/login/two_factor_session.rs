@@ -0,0 +1,319 @@
+use bitwarden_crypto::Kdf;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::login::{
+        login_password, response::two_factor::TwoFactorProviders, send_two_factor_email,
+        PasswordLoginRequest, PasswordLoginResponse, TwoFactorEmailRequest, TwoFactorProvider,
+        TwoFactorRequest,
+    },
+    error::{Error, Result},
+    Client,
+};
+
+/// A signed assertion from a WebAuthn authenticator, with all binary fields base64url encoded.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebAuthnAssertion {
+    pub id: String,
+    pub raw_id: String,
+    pub authenticator_data: String,
+    pub client_data_json: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
+
+/// A password login that can be continued with a second factor.
+///
+/// The session keeps the credentials between the attempts, so the caller only has to provide the
+/// two-factor token. Every submit takes a `remember` flag, and when set the response contains a
+/// remember token that can be passed to [PasswordLoginSession::login] on the next login to skip
+/// the second factor.
+pub struct PasswordLoginSession<'a> {
+    client: &'a Client,
+    email: String,
+    password: String,
+    kdf: Kdf,
+    /// The providers the server offered on the last attempt
+    providers: Vec<TwoFactorProvider>,
+}
+
+impl<'a> PasswordLoginSession<'a> {
+    pub(crate) fn new(client: &'a Client, email: String, password: String, kdf: Kdf) -> Self {
+        Self {
+            client,
+            email,
+            password,
+            kdf,
+            providers: Vec::new(),
+        }
+    }
+
+    /// Start the login. A remember token from an earlier login skips the second factor, unless the
+    /// server no longer accepts it.
+    pub async fn login(&mut self, remember_token: Option<String>) -> Result<PasswordLoginResponse> {
+        let two_factor = remember_token.map(|token| TwoFactorRequest {
+            token,
+            provider: TwoFactorProvider::Remember,
+            remember: false,
+        });
+        self.send(two_factor).await
+    }
+
+    /// The two-factor providers the user can continue the login with.
+    pub fn two_factor_providers(&self) -> &[TwoFactorProvider] {
+        &self.providers
+    }
+
+    pub async fn submit_totp(
+        &mut self,
+        code: &str,
+        remember: bool,
+    ) -> Result<PasswordLoginResponse> {
+        self.submit(TwoFactorProvider::Authenticator, code.to_owned(), remember)
+            .await
+    }
+
+    /// Send the two-factor code to the user's email, to be submitted with
+    /// [PasswordLoginSession::submit_email_code].
+    pub async fn send_email_code(&self) -> Result<()> {
+        self.require_provider(&TwoFactorProvider::Email)?;
+
+        send_two_factor_email(
+            self.client,
+            &TwoFactorEmailRequest {
+                password: self.password.clone(),
+                email: self.email.clone(),
+            },
+        )
+        .await
+    }
+
+    pub async fn submit_email_code(
+        &mut self,
+        code: &str,
+        remember: bool,
+    ) -> Result<PasswordLoginResponse> {
+        self.submit(TwoFactorProvider::Email, code.to_owned(), remember)
+            .await
+    }
+
+    pub async fn submit_yubikey_otp(
+        &mut self,
+        otp: &str,
+        remember: bool,
+    ) -> Result<PasswordLoginResponse> {
+        self.submit(TwoFactorProvider::Yubikey, otp.to_owned(), remember)
+            .await
+    }
+
+    pub async fn submit_webauthn_assertion(
+        &mut self,
+        assertion: &WebAuthnAssertion,
+        remember: bool,
+    ) -> Result<PasswordLoginResponse> {
+        let token = serde_json::json!({
+            "id": assertion.id,
+            "rawId": assertion.raw_id,
+            "type": "public-key",
+            "extensions": {},
+            "response": {
+                "authenticatorData": assertion.authenticator_data,
+                "clientDataJSON": assertion.client_data_json,
+                "signature": assertion.signature,
+                "userHandle": assertion.user_handle,
+            },
+        })
+        .to_string();
+
+        self.submit(TwoFactorProvider::WebAuthn, token, remember)
+            .await
+    }
+
+    /// Submit the code and state Duo redirected back with. Uses the organization's Duo when the
+    /// user doesn't have their own.
+    pub async fn submit_duo_code(
+        &mut self,
+        code: &str,
+        state: &str,
+        remember: bool,
+    ) -> Result<PasswordLoginResponse> {
+        let provider = if self.providers.contains(&TwoFactorProvider::Duo) {
+            TwoFactorProvider::Duo
+        } else {
+            TwoFactorProvider::OrganizationDuo
+        };
+
+        self.submit(provider, format!("{code}|{state}"), remember)
+            .await
+    }
+
+    async fn submit(
+        &mut self,
+        provider: TwoFactorProvider,
+        token: String,
+        remember: bool,
+    ) -> Result<PasswordLoginResponse> {
+        self.require_provider(&provider)?;
+
+        self.send(Some(TwoFactorRequest {
+            token,
+            provider,
+            remember,
+        }))
+        .await
+    }
+
+    async fn send(
+        &mut self,
+        two_factor: Option<TwoFactorRequest>,
+    ) -> Result<PasswordLoginResponse> {
+        let response = login_password(
+            self.client,
+            &PasswordLoginRequest {
+                email: self.email.clone(),
+                password: self.password.clone(),
+                two_factor,
+                kdf: self.kdf.clone(),
+            },
+        )
+        .await?;
+
+        self.providers = response
+            .two_factor
+            .as_ref()
+            .map(available_providers)
+            .unwrap_or_default();
+
+        Ok(response)
+    }
+
+    fn require_provider(&self, provider: &TwoFactorProvider) -> Result<()> {
+        if !self.providers.contains(provider) {
+            return Err(Error::from(format!(
+                "Two-factor provider {provider:?} is not available for this login"
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn available_providers(providers: &TwoFactorProviders) -> Vec<TwoFactorProvider> {
+    [
+        (
+            providers.authenticator.is_some(),
+            TwoFactorProvider::Authenticator,
+        ),
+        (providers.email.is_some(), TwoFactorProvider::Email),
+        (providers.duo.is_some(), TwoFactorProvider::Duo),
+        (providers.yubi_key.is_some(), TwoFactorProvider::Yubikey),
+        (providers.remember.is_some(), TwoFactorProvider::Remember),
+        (
+            providers.organization_duo.is_some(),
+            TwoFactorProvider::OrganizationDuo,
+        ),
+        (providers.web_authn.is_some(), TwoFactorProvider::WebAuthn),
+    ]
+    .into_iter()
+    .filter_map(|(available, provider)| available.then_some(provider))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_crypto::MasterKey;
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+
+    const EMAIL: &str = "test@bitwarden.com";
+    const PASSWORD: &str = "asdfasdfasdf";
+
+    fn kdf() -> Kdf {
+        Kdf::PBKDF2 {
+            iterations: 5000.try_into().unwrap(),
+        }
+    }
+
+    fn two_factor_required() -> ResponseTemplate {
+        ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "error": "invalid_grant",
+            "error_description": "Two factor required.",
+            "TwoFactorProviders2": {
+                "0": null,
+                "1": { "Email": "t***@bitwarden.com" },
+            },
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_submit_totp_remember() {
+        let master_key = MasterKey::derive(PASSWORD, EMAIL, &kdf()).unwrap();
+        let (user_key, protected_user_key) = master_key.make_user_key().unwrap();
+        let key_pair = user_key.make_key_pair().unwrap();
+
+        let (_server, client) = crate::util::start_mock(vec![
+            Mock::given(matchers::path("/identity/connect/token"))
+                .and(matchers::body_string_contains("twoFactorToken=123456"))
+                .and(matchers::body_string_contains("twoFactorProvider=0"))
+                .and(matchers::body_string_contains("twoFactorRemember=true"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "access_token": "access-token",
+                    "expires_in": 3600,
+                    "refresh_token": "refresh-token",
+                    "token_type": "Bearer",
+                    "Key": protected_user_key.to_string(),
+                    "PrivateKey": key_pair.private.to_string(),
+                    "TwoFactorToken": "remember-token",
+                    "Kdf": 0,
+                    "KdfIterations": 5000,
+                    "ResetMasterPassword": false,
+                    "ForcePasswordReset": false,
+                })))
+                .expect(1),
+            Mock::given(matchers::path("/identity/connect/token"))
+                .respond_with(two_factor_required())
+                .expect(1),
+        ])
+        .await;
+
+        let mut session =
+            client
+                .auth()
+                .password_login_session(EMAIL.to_owned(), PASSWORD.to_owned(), kdf());
+
+        let response = session.login(None).await.unwrap();
+        assert!(!response.authenticated);
+        assert_eq!(
+            session.two_factor_providers(),
+            [TwoFactorProvider::Authenticator, TwoFactorProvider::Email]
+        );
+
+        let response = session.submit_totp("123456", true).await.unwrap();
+        assert!(response.authenticated);
+        assert_eq!(
+            response.two_factor_remember_token.as_deref(),
+            Some("remember-token")
+        );
+        assert!(session.two_factor_providers().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_submit_unavailable_provider() {
+        let (_server, client) =
+            crate::util::start_mock(vec![Mock::given(matchers::path("/identity/connect/token"))
+                .respond_with(two_factor_required())
+                .expect(1)])
+            .await;
+
+        let mut session =
+            client
+                .auth()
+                .password_login_session(EMAIL.to_owned(), PASSWORD.to_owned(), kdf());
+        session.login(None).await.unwrap();
+
+        let result = session.submit_yubikey_otp("cccccc", false).await;
+        assert!(result.is_err());
+    }
+}
@@ -26,6 +26,13 @@ mod register;
 #[cfg(feature = "internal")]
 pub use register::{RegisterKeyResponse, RegisterRequest};
 
+#[cfg(feature = "internal")]
+mod devices;
+#[cfg(feature = "internal")]
+pub mod devices_client;
+#[cfg(feature = "internal")]
+pub use devices::{DeviceKeysBackup, DeviceView};
+
 #[cfg(feature = "internal")]
 mod tde;
 #[cfg(feature = "internal")]
@@ -33,7 +40,9 @@ pub use tde::RegisterTdeKeyResponse;
 #[cfg(feature = "internal")]
 mod key_connector;
 #[cfg(feature = "internal")]
-pub use key_connector::KeyConnectorResponse;
+pub use key_connector::{
+    HttpKeyConnectorApi, KeyConnectorApi, KeyConnectorResponse, UnlockKeyConnectorRequest,
+};
 
 #[cfg(feature = "internal")]
 use crate::error::Result;
@@ -4,6 +4,7 @@ use crate::{
     auth::determine_password_hash,
     client::{LoginMethod, UserLoginMethod},
     error::{NotAuthenticatedError, Result},
+    key_management::SymmetricKeyId,
     Client,
 };
 
@@ -59,9 +60,10 @@ pub(crate) fn validate_password_user_key(
                     .decrypt_user_key(encrypted_user_key.parse()?)
                     .map_err(|_| "wrong password")?;
 
-                let enc = client.internal.get_encryption_settings()?;
-
-                let existing_key = enc.get_key(&None)?;
+                let key_store = client.internal.get_key_store();
+                let ctx = key_store.context();
+                #[allow(deprecated)]
+                let existing_key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
                 if user_key.to_vec() != existing_key.to_vec() {
                     return Err("wrong user key".into());
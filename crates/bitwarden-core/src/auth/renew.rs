@@ -4,6 +4,7 @@ use chrono::Utc;
 use crate::{
     auth::api::request::AccessTokenRequest,
     client::ServiceAccountLoginMethod,
+    key_management::SymmetricKeyId,
     secrets_manager::state::{self, ClientState},
 };
 use crate::{
@@ -60,8 +61,8 @@ pub(crate) async fn renew_token(client: &InternalClient) -> Result<()> {
             LoginMethod::ServiceAccount(s) => match s {
                 ServiceAccountLoginMethod::AccessToken {
                     access_token,
+                    organization_id,
                     state_file,
-                    ..
                 } => {
                     let result = AccessTokenRequest::new(
                         access_token.access_token_id,
@@ -70,10 +71,16 @@ pub(crate) async fn renew_token(client: &InternalClient) -> Result<()> {
                     .send(&config)
                     .await?;
 
-                    if let (IdentityTokenResponse::Payload(r), Some(state_file), Ok(enc_settings)) =
-                        (&result, state_file, client.get_encryption_settings())
+                    if let (IdentityTokenResponse::Payload(r), Some(state_file)) =
+                        (&result, state_file)
                     {
-                        if let Ok(enc_key) = enc_settings.get_key(&None) {
+                        let ctx = client.get_key_store().context();
+                        #[allow(deprecated)]
+                        let enc_key = ctx.dangerous_get_symmetric_key(
+                            SymmetricKeyId::Organization(*organization_id),
+                        );
+
+                        if let Ok(enc_key) = enc_key {
                             let state =
                                 ClientState::new(r.access_token.clone(), enc_key.to_base64());
                             _ = state::set(state_file, access_token, state);
@@ -1,14 +1,15 @@
 use std::sync::{Arc, RwLock};
 
+use bitwarden_crypto::KeyStore;
 use reqwest::header::{self, HeaderValue};
 
 use super::internal::InternalClient;
-#[cfg(feature = "internal")]
-use crate::client::flags::Flags;
 use crate::client::{
     client_settings::ClientSettings,
     internal::{ApiConfigurations, Tokens},
 };
+#[cfg(feature = "internal")]
+use crate::{client::flags::Flags, platform::PublicKeyStore};
 
 /// The main struct to interact with the Bitwarden SDK.
 #[derive(Debug)]
@@ -80,7 +81,9 @@ impl Client {
                     device_type: settings.device_type,
                 })),
                 external_client,
-                encryption_settings: RwLock::new(None),
+                key_store: KeyStore::default(),
+                #[cfg(feature = "internal")]
+                public_keys: PublicKeyStore::default(),
             },
         }
     }
@@ -1,14 +1,16 @@
-use std::collections::HashMap;
-
-use bitwarden_crypto::{AsymmetricCryptoKey, CryptoError, KeyContainer, SymmetricCryptoKey};
 #[cfg(feature = "internal")]
-use bitwarden_crypto::{AsymmetricEncString, EncString, MasterKey};
+use bitwarden_crypto::{AsymmetricCryptoKey, AsymmetricEncString, EncString, MasterKey};
+#[cfg(any(feature = "internal", feature = "secrets"))]
+use bitwarden_crypto::{KeyStore, SymmetricCryptoKey};
 use bitwarden_error::bitwarden_error;
 use thiserror::Error;
+#[cfg(any(feature = "internal", feature = "secrets"))]
 use uuid::Uuid;
 
 #[cfg(feature = "internal")]
-use crate::error::Result;
+use crate::key_management::AsymmetricKeyId;
+#[cfg(any(feature = "internal", feature = "secrets"))]
+use crate::key_management::{KeyIds, SymmetricKeyId};
 use crate::VaultLocked;
 
 #[bitwarden_error(flat)]
@@ -30,18 +32,7 @@ pub enum EncryptionSettingsError {
     MissingPrivateKey,
 }
 
-#[derive(Clone)]
-pub struct EncryptionSettings {
-    user_key: SymmetricCryptoKey,
-    pub(crate) private_key: Option<AsymmetricCryptoKey>,
-    org_keys: HashMap<Uuid, SymmetricCryptoKey>,
-}
-
-impl std::fmt::Debug for EncryptionSettings {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("EncryptionSettings").finish()
-    }
-}
+pub struct EncryptionSettings {}
 
 impl EncryptionSettings {
     /// Initialize the encryption settings with the master key and the encrypted user keys
@@ -50,10 +41,11 @@ impl EncryptionSettings {
         master_key: MasterKey,
         user_key: EncString,
         private_key: EncString,
-    ) -> Result<Self, EncryptionSettingsError> {
+        store: &KeyStore<KeyIds>,
+    ) -> Result<(), EncryptionSettingsError> {
         // Decrypt the user key
         let user_key = master_key.decrypt_user_key(user_key)?;
-        Self::new_decrypted_key(user_key, private_key)
+        Self::new_decrypted_key(user_key, private_key, store)
     }
 
     /// Initialize the encryption settings with the decrypted user key and the encrypted user
@@ -64,7 +56,8 @@ impl EncryptionSettings {
     pub(crate) fn new_decrypted_key(
         user_key: SymmetricCryptoKey,
         private_key: EncString,
-    ) -> Result<Self, EncryptionSettingsError> {
+        store: &KeyStore<KeyIds>,
+    ) -> Result<(), EncryptionSettingsError> {
         use bitwarden_crypto::KeyDecryptable;
         use log::warn;
 
@@ -85,76 +78,75 @@ impl EncryptionSettings {
             // )
         };
 
-        Ok(EncryptionSettings {
-            user_key,
-            private_key,
-            org_keys: HashMap::new(),
-        })
+        // Clear the store first to remove the keys of a previous session
+        store.clear();
+
+        let mut ctx = store.context_mut();
+
+        #[allow(deprecated)]
+        {
+            ctx.set_symmetric_key(SymmetricKeyId::User, user_key)?;
+            if let Some(private_key) = private_key {
+                ctx.set_asymmetric_key(AsymmetricKeyId::UserPrivateKey, private_key)?;
+            }
+        }
+
+        Ok(())
     }
 
-    /// Initialize the encryption settings with only a single decrypted key.
+    /// Initialize the encryption settings with only a single decrypted organization key.
     /// This is used only for logging in Secrets Manager with an access token
     #[cfg(feature = "secrets")]
-    pub(crate) fn new_single_key(key: SymmetricCryptoKey) -> Self {
-        EncryptionSettings {
-            user_key: key,
-            private_key: None,
-            org_keys: HashMap::new(),
-        }
+    pub(crate) fn new_single_org_key(
+        organization_id: Uuid,
+        key: SymmetricCryptoKey,
+        store: &KeyStore<KeyIds>,
+    ) -> Result<(), EncryptionSettingsError> {
+        store.clear();
+
+        let mut ctx = store.context_mut();
+
+        #[allow(deprecated)]
+        ctx.set_symmetric_key(SymmetricKeyId::Organization(organization_id), key)?;
+
+        Ok(())
     }
 
+    /// Decrypt the organization keys with the user's private key and replace the organization
+    /// keys in the `store` with them.
     #[cfg(feature = "internal")]
     pub(crate) fn set_org_keys(
-        &mut self,
         org_enc_keys: Vec<(Uuid, AsymmetricEncString)>,
-    ) -> Result<&Self, EncryptionSettingsError> {
-        use bitwarden_crypto::KeyDecryptable;
+        store: &KeyStore<KeyIds>,
+    ) -> Result<(), EncryptionSettingsError> {
+        let mut ctx = store.context_mut();
+
+        if !ctx.has_symmetric_key(SymmetricKeyId::User) {
+            return Err(VaultLocked.into());
+        }
 
         // Make sure we only keep the keys given in the arguments and not any of the previous
         // ones, which might be from organizations that the user is no longer a part of anymore
-        self.org_keys.clear();
+        ctx.retain_symmetric_keys(|key_ref| !matches!(key_ref, SymmetricKeyId::Organization(_)));
 
         // FIXME: [PM-11690] - Early abort to handle private key being corrupt
         if org_enc_keys.is_empty() {
-            return Ok(self);
+            return Ok(());
         }
 
-        let private_key = self
-            .private_key
-            .as_ref()
-            .ok_or(EncryptionSettingsError::MissingPrivateKey)?;
+        if !ctx.has_asymmetric_key(AsymmetricKeyId::UserPrivateKey) {
+            return Err(EncryptionSettingsError::MissingPrivateKey);
+        }
 
         // Decrypt the org keys with the private key
         for (org_id, org_enc_key) in org_enc_keys {
-            let mut dec: Vec<u8> = org_enc_key.decrypt_with_key(private_key)?;
-
-            let org_key = SymmetricCryptoKey::try_from(dec.as_mut_slice())?;
-
-            self.org_keys.insert(org_id, org_key);
+            ctx.decrypt_symmetric_key_with_asymmetric_key(
+                AsymmetricKeyId::UserPrivateKey,
+                SymmetricKeyId::Organization(org_id),
+                &org_enc_key,
+            )?;
         }
 
-        Ok(self)
-    }
-
-    pub fn get_key(&self, org_id: &Option<Uuid>) -> Result<&SymmetricCryptoKey, CryptoError> {
-        // If we don't have a private key set (to decode multiple org keys), we just use the main
-        // user key
-        if self.private_key.is_none() {
-            return Ok(&self.user_key);
-        }
-
-        match org_id {
-            Some(org_id) => self
-                .org_keys
-                .get(org_id)
-                .ok_or(CryptoError::MissingKey(*org_id)),
-            None => Ok(&self.user_key),
-        }
-    }
-}
-
-impl KeyContainer for EncryptionSettings {
-    fn get_key(&self, org_id: &Option<Uuid>) -> Result<&SymmetricCryptoKey, CryptoError> {
-        EncryptionSettings::get_key(self, org_id)
+        Ok(())
     }
 }
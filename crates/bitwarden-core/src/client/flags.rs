@@ -2,6 +2,13 @@
 pub struct Flags {
     #[serde(default, rename = "enableCipherKeyEncryption")]
     pub enable_cipher_key_encryption: bool,
+    /// Encrypt new data using AES-256-GCM instead of AES-256-CBC with HMAC-SHA256.
+    #[serde(default, rename = "enableAeadEncryption")]
+    pub enable_aead_encryption: bool,
+    /// Encrypt new attachment and send files using the chunked streaming format instead of a
+    /// single [EncString](bitwarden_crypto::EncString).
+    #[serde(default, rename = "enableStreamEncryption")]
+    pub enable_stream_encryption: bool,
 }
 
 impl Flags {
@@ -23,14 +30,20 @@ mod tests {
         let map = std::collections::HashMap::new();
         let flags = Flags::load_from_map(map);
         assert!(!flags.enable_cipher_key_encryption);
+        assert!(!flags.enable_aead_encryption);
+        assert!(!flags.enable_stream_encryption);
     }
 
     #[test]
     fn test_load_valid_map() {
         let mut map = std::collections::HashMap::new();
         map.insert("enableCipherKeyEncryption".into(), true);
+        map.insert("enableAeadEncryption".into(), true);
+        map.insert("enableStreamEncryption".into(), true);
         let flags = Flags::load_from_map(map);
         assert!(flags.enable_cipher_key_encryption);
+        assert!(flags.enable_aead_encryption);
+        assert!(flags.enable_stream_encryption);
     }
 
     #[test]
@@ -1,5 +1,6 @@
 use std::sync::{Arc, RwLock};
 
+use bitwarden_crypto::KeyStore;
 #[cfg(any(feature = "internal", feature = "secrets"))]
 use bitwarden_crypto::SymmetricCryptoKey;
 #[cfg(feature = "internal")]
@@ -9,17 +10,20 @@ use uuid::Uuid;
 
 #[cfg(feature = "secrets")]
 use super::login_method::ServiceAccountLoginMethod;
+#[cfg(any(feature = "internal", feature = "secrets"))]
+use crate::client::encryption_settings::{EncryptionSettings, EncryptionSettingsError};
 use crate::{
-    auth::renew::renew_token,
-    client::{encryption_settings::EncryptionSettings, login_method::LoginMethod},
-    error::{Result, VaultLocked},
-    DeviceType,
+    auth::renew::renew_token, client::login_method::LoginMethod, error::Result,
+    key_management::KeyIds, DeviceType,
 };
 #[cfg(feature = "internal")]
 use crate::{
-    client::encryption_settings::EncryptionSettingsError,
-    client::{flags::Flags, login_method::UserLoginMethod},
+    client::{
+        flags::Flags,
+        login_method::{UserLoginMethod, UserSession},
+    },
     error::NotAuthenticatedError,
+    platform::PublicKeyStore,
 };
 
 #[derive(Debug, Clone)]
@@ -58,13 +62,28 @@ pub struct InternalClient {
     #[allow(unused)]
     pub(crate) external_client: reqwest::Client,
 
-    pub(super) encryption_settings: RwLock<Option<Arc<EncryptionSettings>>>,
+    /// Holds the user's keys, use [InternalClient::get_key_store] to encrypt and decrypt without
+    /// accessing the key material.
+    pub(super) key_store: KeyStore<KeyIds>,
+
+    /// Public keys of other users and organizations, trusted on first use.
+    #[cfg(feature = "internal")]
+    pub(crate) public_keys: PublicKeyStore,
 }
 
 impl InternalClient {
     #[cfg(feature = "internal")]
     pub fn load_flags(&self, flags: std::collections::HashMap<String, bool>) {
-        *self.flags.write().expect("RwLock is not poisoned") = Flags::load_from_map(flags);
+        let flags = Flags::load_from_map(flags);
+
+        self.key_store
+            .set_symmetric_encryption_type(if flags.enable_aead_encryption {
+                bitwarden_crypto::SymmetricEncryptionType::Aes256Gcm_B64
+            } else {
+                bitwarden_crypto::SymmetricEncryptionType::AesCbc256_HmacSha256_B64
+            });
+
+        *self.flags.write().expect("RwLock is not poisoned") = flags;
     }
 
     #[cfg(feature = "internal")]
@@ -137,6 +156,65 @@ impl InternalClient {
         is_token_set || is_login_method_set
     }
 
+    /// Get the current user session, so it can be persisted and restored later using
+    /// [InternalClient::set_user_session].
+    #[cfg(feature = "internal")]
+    pub fn get_user_session(&self) -> Result<UserSession, NotAuthenticatedError> {
+        let tokens = self.tokens.read().expect("RwLock is not poisoned").clone();
+        let access_token = tokens.access_token.ok_or(NotAuthenticatedError)?;
+
+        match self
+            .login_method
+            .read()
+            .expect("RwLock is not poisoned")
+            .as_deref()
+        {
+            Some(LoginMethod::User(
+                UserLoginMethod::Username {
+                    client_id,
+                    email,
+                    kdf,
+                }
+                | UserLoginMethod::ApiKey {
+                    client_id,
+                    email,
+                    kdf,
+                    ..
+                },
+            )) => Ok(UserSession {
+                client_id: client_id.to_owned(),
+                email: email.to_owned(),
+                kdf: kdf.to_owned(),
+                access_token,
+                refresh_token: tokens.refresh_token,
+                expires_on: tokens.expires_on,
+            }),
+            _ => Err(NotAuthenticatedError),
+        }
+    }
+
+    /// Restore a user session previously obtained from [InternalClient::get_user_session]. The
+    /// access token will be renewed using the refresh token once it expires.
+    #[cfg(feature = "internal")]
+    pub fn set_user_session(&self, session: UserSession) {
+        let expires_in = session
+            .expires_on
+            .map(|e| e - Utc::now().timestamp())
+            .unwrap_or_default()
+            .max(0);
+
+        self.set_tokens(
+            session.access_token,
+            session.refresh_token,
+            expires_in as u64,
+        );
+        self.set_login_method(LoginMethod::User(UserLoginMethod::Username {
+            client_id: session.client_id,
+            email: session.email,
+            kdf: session.kdf,
+        }));
+    }
+
     #[cfg(feature = "internal")]
     pub fn get_kdf(&self) -> Result<Kdf, NotAuthenticatedError> {
         match self
@@ -167,12 +245,8 @@ impl InternalClient {
         &self.external_client
     }
 
-    pub fn get_encryption_settings(&self) -> Result<Arc<EncryptionSettings>, VaultLocked> {
-        self.encryption_settings
-            .read()
-            .expect("RwLock is not poisoned")
-            .clone()
-            .ok_or(VaultLocked)
+    pub fn get_key_store(&self) -> &KeyStore<KeyIds> {
+        &self.key_store
     }
 
     #[cfg(feature = "internal")]
@@ -182,16 +256,7 @@ impl InternalClient {
         user_key: EncString,
         private_key: EncString,
     ) -> Result<(), EncryptionSettingsError> {
-        *self
-            .encryption_settings
-            .write()
-            .expect("RwLock is not poisoned") = Some(Arc::new(EncryptionSettings::new(
-            master_key,
-            user_key,
-            private_key,
-        )?));
-
-        Ok(())
+        EncryptionSettings::new(master_key, user_key, private_key, &self.key_store)
     }
 
     #[cfg(feature = "internal")]
@@ -200,14 +265,7 @@ impl InternalClient {
         user_key: SymmetricCryptoKey,
         private_key: EncString,
     ) -> Result<(), EncryptionSettingsError> {
-        *self
-            .encryption_settings
-            .write()
-            .expect("RwLock is not poisoned") = Some(Arc::new(
-            EncryptionSettings::new_decrypted_key(user_key, private_key)?,
-        ));
-
-        Ok(())
+        EncryptionSettings::new_decrypted_key(user_key, private_key, &self.key_store)
     }
 
     #[cfg(feature = "internal")]
@@ -222,31 +280,19 @@ impl InternalClient {
     }
 
     #[cfg(feature = "secrets")]
-    pub(crate) fn initialize_crypto_single_key(&self, key: SymmetricCryptoKey) {
-        *self
-            .encryption_settings
-            .write()
-            .expect("RwLock is not poisoned") =
-            Some(Arc::new(EncryptionSettings::new_single_key(key)));
+    pub(crate) fn initialize_crypto_single_org_key(
+        &self,
+        organization_id: Uuid,
+        key: SymmetricCryptoKey,
+    ) -> Result<(), EncryptionSettingsError> {
+        EncryptionSettings::new_single_org_key(organization_id, key, &self.key_store)
     }
 
     #[cfg(feature = "internal")]
     pub fn initialize_org_crypto(
         &self,
         org_keys: Vec<(Uuid, AsymmetricEncString)>,
-    ) -> Result<Arc<EncryptionSettings>, EncryptionSettingsError> {
-        let mut guard = self
-            .encryption_settings
-            .write()
-            .expect("RwLock is not poisoned");
-
-        let Some(enc) = guard.as_mut() else {
-            return Err(VaultLocked.into());
-        };
-
-        let inner = Arc::make_mut(enc);
-        inner.set_org_keys(org_keys)?;
-
-        Ok(enc.clone())
+    ) -> Result<(), EncryptionSettingsError> {
+        EncryptionSettings::set_org_keys(org_keys, &self.key_store)
     }
 }
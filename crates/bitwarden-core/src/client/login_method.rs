@@ -2,6 +2,8 @@
 use std::path::PathBuf;
 
 use bitwarden_crypto::Kdf;
+#[cfg(feature = "internal")]
+use serde::{Deserialize, Serialize};
 #[cfg(feature = "secrets")]
 use uuid::Uuid;
 
@@ -35,6 +37,21 @@ pub(crate) enum UserLoginMethod {
     },
 }
 
+/// Authentication state of a logged in user, which can be persisted and used to restore the login
+/// in a new client without authenticating again.
+#[cfg(feature = "internal")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserSession {
+    pub client_id: String,
+    pub email: String,
+    pub kdf: Kdf,
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// Unix timestamp of when the access token expires
+    pub expires_on: Option<i64>,
+}
+
 #[cfg(feature = "secrets")]
 #[derive(Debug)]
 pub(crate) enum ServiceAccountLoginMethod {
@@ -9,6 +9,8 @@ pub use internal::ApiConfigurations;
 pub mod login_method;
 #[cfg(feature = "secrets")]
 pub(crate) use login_method::ServiceAccountLoginMethod;
+#[cfg(feature = "internal")]
+pub use login_method::UserSession;
 pub(crate) use login_method::{LoginMethod, UserLoginMethod};
 #[cfg(feature = "internal")]
 mod flags;
@@ -8,12 +8,21 @@ use crate::{
         initialize_org_crypto, initialize_user_crypto, InitOrgCryptoRequest, InitUserCryptoMethod,
         InitUserCryptoRequest,
     },
-    Client,
+    Client, ClientSettings,
 };
 
 impl Client {
     pub async fn init_test_account(account: TestAccount) -> Self {
-        let client = Client::new(None);
+        Self::init_test_account_with_settings(account, None).await
+    }
+
+    /// Initialize a test account using custom settings, for example to point the client to a mock
+    /// server.
+    pub async fn init_test_account_with_settings(
+        account: TestAccount,
+        settings: Option<ClientSettings>,
+    ) -> Self {
+        let client = Client::new(settings);
 
         client.internal.load_flags(HashMap::from([(
             "enableCipherKeyEncryption".to_owned(),
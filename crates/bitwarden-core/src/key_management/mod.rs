@@ -0,0 +1,27 @@
+//! Key identifiers for the [KeyStore](bitwarden_crypto::KeyStore) owned by the client.
+//!
+//! Code which encrypts or decrypts data with the keys of the client refers to them through these
+//! identifiers, using the store returned by
+//! [InternalClient::get_key_store](crate::client::internal::InternalClient::get_key_store), instead
+//! of handling the key material directly.
+
+use bitwarden_crypto::key_ids;
+
+key_ids! {
+    #[symmetric]
+    pub enum SymmetricKeyId {
+        User,
+        Organization(uuid::Uuid),
+        #[local]
+        Local(&'static str),
+    }
+
+    #[asymmetric]
+    pub enum AsymmetricKeyId {
+        UserPrivateKey,
+        #[local]
+        Local(&'static str),
+    }
+
+    pub KeyIds => SymmetricKeyId, AsymmetricKeyId;
+}
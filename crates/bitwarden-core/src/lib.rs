@@ -8,6 +8,7 @@ pub mod admin_console;
 pub mod auth;
 pub mod client;
 mod error;
+pub mod key_management;
 pub use error::{ApiError, Error, MissingFieldError, NotAuthenticatedError, VaultLocked};
 #[cfg(feature = "internal")]
 pub mod mobile;
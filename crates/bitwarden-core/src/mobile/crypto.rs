@@ -1,18 +1,21 @@
 use std::collections::HashMap;
 
 use base64::{engine::general_purpose::STANDARD, Engine};
+use bitwarden_api_api::models::{KdfRequestModel, KdfType};
 use bitwarden_crypto::{
-    AsymmetricCryptoKey, AsymmetricEncString, EncString, Kdf, KeyDecryptable, KeyEncryptable,
-    MasterKey, SymmetricCryptoKey, UserKey,
+    AsymmetricCryptoKey, AsymmetricEncString, AsymmetricPublicCryptoKey, EncString, Encryptable,
+    Kdf, KeyDecryptable, KeyEncryptable, KeyStoreContext, MasterKey, SymmetricCryptoKey, UserKey,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 #[cfg(feature = "wasm")]
 use {tsify_next::Tsify, wasm_bindgen::prelude::*};
 
 use crate::{
     client::{encryption_settings::EncryptionSettingsError, LoginMethod, UserLoginMethod},
     error::{NotAuthenticatedError, Result},
+    key_management::{AsymmetricKeyId, KeyIds, SymmetricKeyId},
     Client,
 };
 
@@ -207,8 +210,10 @@ pub async fn initialize_org_crypto(
 }
 
 pub async fn get_user_encryption_key(client: &Client) -> Result<String> {
-    let enc = client.internal.get_encryption_settings()?;
-    let user_key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let user_key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
     Ok(user_key.to_base64())
 }
@@ -224,8 +229,10 @@ pub struct UpdatePasswordResponse {
 }
 
 pub fn update_password(client: &Client, new_password: String) -> Result<UpdatePasswordResponse> {
-    let enc = client.internal.get_encryption_settings()?;
-    let user_key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let user_key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
     let login_method = client
         .internal
@@ -242,7 +249,7 @@ pub fn update_password(client: &Client, new_password: String) -> Result<UpdatePa
         LoginMethod::ServiceAccount(_) => return Err(NotAuthenticatedError)?,
     };
 
-    let new_key = new_master_key.encrypt_user_key(user_key)?;
+    let new_key = new_master_key.encrypt_user_key(&user_key)?;
 
     let password_hash = new_master_key.derive_master_key_hash(
         new_password.as_bytes(),
@@ -255,6 +262,192 @@ pub fn update_password(client: &Client, new_password: String) -> Result<UpdatePa
     })
 }
 
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct UpdateKdfResponse {
+    /// Hash of the password, derived with the current KDF
+    pub master_password_hash: String,
+    /// Hash of the password, derived with the new KDF
+    pub new_master_password_hash: String,
+    /// User key, encrypted with the master key derived with the new KDF
+    pub new_key: EncString,
+    pub new_kdf: Kdf,
+}
+
+/// Re-derive the master key with `new_kdf`, and protect the user key with it.
+pub fn make_kdf_update(
+    client: &Client,
+    password: String,
+    new_kdf: Kdf,
+) -> Result<UpdateKdfResponse> {
+    new_kdf.validate()?;
+
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let user_key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
+
+    let login_method = client
+        .internal
+        .get_login_method()
+        .ok_or(NotAuthenticatedError)?;
+
+    let (master_key, new_master_key) = match login_method.as_ref() {
+        LoginMethod::User(
+            UserLoginMethod::Username { email, kdf, .. }
+            | UserLoginMethod::ApiKey { email, kdf, .. },
+        ) => (
+            MasterKey::derive(&password, email, kdf)?,
+            MasterKey::derive(&password, email, &new_kdf)?,
+        ),
+        #[cfg(feature = "secrets")]
+        LoginMethod::ServiceAccount(_) => return Err(NotAuthenticatedError)?,
+    };
+
+    let master_password_hash = master_key.derive_master_key_hash(
+        password.as_bytes(),
+        bitwarden_crypto::HashPurpose::ServerAuthorization,
+    )?;
+    let new_master_password_hash = new_master_key.derive_master_key_hash(
+        password.as_bytes(),
+        bitwarden_crypto::HashPurpose::ServerAuthorization,
+    )?;
+    let new_key = new_master_key.encrypt_user_key(&user_key)?;
+
+    Ok(UpdateKdfResponse {
+        master_password_hash,
+        new_master_password_hash,
+        new_key,
+        new_kdf,
+    })
+}
+
+impl From<UpdateKdfResponse> for KdfRequestModel {
+    fn from(response: UpdateKdfResponse) -> Self {
+        let (kdf, kdf_iterations, kdf_memory, kdf_parallelism) = match response.new_kdf {
+            Kdf::PBKDF2 { iterations } => (KdfType::PBKDF2_SHA256, iterations, None, None),
+            Kdf::Argon2id {
+                iterations,
+                memory,
+                parallelism,
+            } => (
+                KdfType::Argon2id,
+                iterations,
+                Some(memory.get() as i32),
+                Some(parallelism.get() as i32),
+            ),
+        };
+
+        KdfRequestModel {
+            master_password_hash: Some(response.master_password_hash),
+            kdf_memory,
+            kdf_parallelism,
+            ..KdfRequestModel::new(
+                response.new_master_password_hash,
+                response.new_key.to_string(),
+                kdf,
+                kdf_iterations.get() as i32,
+            )
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UserKeyRotationRequest {
+    /// The user's current master password, used to authorize the rotation and to protect the new
+    /// user key
+    pub password: String,
+    /// Base64 encoded public keys of the emergency access grantees, by emergency access id
+    pub emergency_access_public_keys: HashMap<Uuid, String>,
+    /// Base64 encoded public keys of the organizations the user is enrolled in admin password
+    /// reset for, by organization id
+    pub reset_password_public_keys: HashMap<Uuid, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UserKeyRotationResponse {
+    /// Hash of the current password
+    pub master_password_hash: String,
+    /// New user key, encrypted with the master key
+    pub user_key: EncString,
+    /// Private key, encrypted with the new user key
+    pub private_key: EncString,
+    /// New user key, encrypted with the public key of each emergency access grantee
+    pub emergency_access_keys: HashMap<Uuid, AsymmetricEncString>,
+    /// New user key, encrypted with the public key of each organization for admin password reset
+    pub reset_password_keys: HashMap<Uuid, AsymmetricEncString>,
+}
+
+/// Generate a new user key into `new_user_key`, and protect it with the same keys that protect
+/// the current user key. The private key is re-encrypted with the new user key.
+///
+/// The data encrypted with the current user key has to be re-encrypted by the caller, using the
+/// same `ctx`, before the local `new_user_key` is dropped.
+pub fn make_user_key_rotation(
+    client: &Client,
+    ctx: &mut KeyStoreContext<KeyIds>,
+    new_user_key: SymmetricKeyId,
+    request: &UserKeyRotationRequest,
+) -> Result<UserKeyRotationResponse> {
+    let login_method = client
+        .internal
+        .get_login_method()
+        .ok_or(NotAuthenticatedError)?;
+
+    let master_key = match login_method.as_ref() {
+        LoginMethod::User(
+            UserLoginMethod::Username { email, kdf, .. }
+            | UserLoginMethod::ApiKey { email, kdf, .. },
+        ) => MasterKey::derive(&request.password, email, kdf)?,
+        #[cfg(feature = "secrets")]
+        LoginMethod::ServiceAccount(_) => return Err(NotAuthenticatedError)?,
+    };
+    let master_password_hash = master_key.derive_master_key_hash(
+        request.password.as_bytes(),
+        bitwarden_crypto::HashPurpose::ServerAuthorization,
+    )?;
+
+    ctx.generate_symmetric_key(new_user_key)?;
+
+    #[allow(deprecated)]
+    let user_key = ctx.dangerous_get_symmetric_key(new_user_key)?;
+    let encrypt_user_key = |public_key: &String| -> Result<AsymmetricEncString> {
+        let public_key = AsymmetricPublicCryptoKey::from_der(&STANDARD.decode(public_key)?)?;
+        Ok(AsymmetricEncString::encrypt(
+            &user_key.to_vec(),
+            &public_key,
+        )?)
+    };
+    let emergency_access_keys = request
+        .emergency_access_public_keys
+        .iter()
+        .map(|(id, public_key)| Ok((*id, encrypt_user_key(public_key)?)))
+        .collect::<Result<_>>()?;
+    let reset_password_keys = request
+        .reset_password_public_keys
+        .iter()
+        .map(|(id, public_key)| Ok((*id, encrypt_user_key(public_key)?)))
+        .collect::<Result<_>>()?;
+    let user_key = master_key.encrypt_user_key(&user_key)?;
+
+    #[allow(deprecated)]
+    let private_key = ctx
+        .dangerous_get_asymmetric_key(AsymmetricKeyId::UserPrivateKey)?
+        .to_der()?;
+    let private_key = private_key.encrypt(ctx, new_user_key)?;
+
+    Ok(UserKeyRotationResponse {
+        master_password_hash,
+        user_key,
+        private_key,
+        emergency_access_keys,
+        reset_password_keys,
+    })
+}
+
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
@@ -266,33 +459,37 @@ pub struct DerivePinKeyResponse {
 }
 
 pub fn derive_pin_key(client: &Client, pin: String) -> Result<DerivePinKeyResponse> {
-    let enc = client.internal.get_encryption_settings()?;
-    let user_key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let user_key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
     let login_method = client
         .internal
         .get_login_method()
         .ok_or(NotAuthenticatedError)?;
 
-    let pin_protected_user_key = derive_pin_protected_user_key(&pin, &login_method, user_key)?;
+    let pin_protected_user_key = derive_pin_protected_user_key(&pin, &login_method, &user_key)?;
 
     Ok(DerivePinKeyResponse {
         pin_protected_user_key,
-        encrypted_pin: pin.encrypt_with_key(user_key)?,
+        encrypted_pin: pin.encrypt_with_key(&*user_key)?,
     })
 }
 
 pub fn derive_pin_user_key(client: &Client, encrypted_pin: EncString) -> Result<EncString> {
-    let enc = client.internal.get_encryption_settings()?;
-    let user_key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let user_key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
-    let pin: String = encrypted_pin.decrypt_with_key(user_key)?;
+    let pin: String = encrypted_pin.decrypt_with_key(&*user_key)?;
     let login_method = client
         .internal
         .get_login_method()
         .ok_or(NotAuthenticatedError)?;
 
-    derive_pin_protected_user_key(&pin, &login_method, user_key)
+    derive_pin_protected_user_key(&pin, &login_method, &user_key)
 }
 
 fn derive_pin_protected_user_key(
@@ -322,13 +519,12 @@ pub(super) fn enroll_admin_password_reset(
     use bitwarden_crypto::AsymmetricPublicCryptoKey;
 
     let public_key = AsymmetricPublicCryptoKey::from_der(&STANDARD.decode(public_key)?)?;
-    let enc = client.internal.get_encryption_settings()?;
-    let key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
+    let ctx = key_store.context();
+    #[allow(deprecated)]
+    let key = ctx.dangerous_get_symmetric_key(SymmetricKeyId::User)?;
 
-    Ok(AsymmetricEncString::encrypt_rsa2048_oaep_sha1(
-        &key.to_vec(),
-        &public_key,
-    )?)
+    Ok(AsymmetricEncString::encrypt(&key.to_vec(), &public_key)?)
 }
 
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
@@ -342,7 +538,7 @@ pub struct DeriveKeyConnectorRequest {
 }
 
 /// Derive the master key for migrating to the key connector
-pub(super) fn derive_key_connector(request: DeriveKeyConnectorRequest) -> Result<String> {
+pub(crate) fn derive_key_connector(request: DeriveKeyConnectorRequest) -> Result<String> {
     let master_key = MasterKey::derive(&request.password, &request.email, &request.kdf)?;
     master_key
         .decrypt_user_key(request.user_key_encrypted)
@@ -463,6 +659,17 @@ mod tests {
     use super::*;
     use crate::Client;
 
+    #[allow(deprecated)]
+    fn user_key_base64(client: &Client) -> String {
+        client
+            .internal
+            .get_key_store()
+            .context()
+            .dangerous_get_symmetric_key(SymmetricKeyId::User)
+            .unwrap()
+            .to_base64()
+    }
+
     #[tokio::test]
     async fn test_update_password() {
         let client = Client::new(None);
@@ -520,22 +727,159 @@ mod tests {
 
         assert_eq!(new_hash, new_password_response.password_hash);
 
+        assert_eq!(user_key_base64(&client), user_key_base64(&client2));
+    }
+
+    #[tokio::test]
+    async fn test_make_kdf_update() {
+        let client = Client::new(None);
+
+        let priv_key = "2.kmLY8NJVuiKBFJtNd/ZFpA==|qOodlRXER+9ogCe3yOibRHmUcSNvjSKhdDuztLlucs10jLiNoVVVAc+9KfNErLSpx5wmUF1hBOJM8zwVPjgQTrmnNf/wuDpwiaCxNYb/0v4FygPy7ccAHK94xP1lfqq7U9+tv+/yiZSwgcT+xF0wFpoxQeNdNRFzPTuD9o4134n8bzacD9DV/WjcrXfRjbBCzzuUGj1e78+A7BWN7/5IWLz87KWk8G7O/W4+8PtEzlwkru6Wd1xO19GYU18oArCWCNoegSmcGn7w7NDEXlwD403oY8Oa7ylnbqGE28PVJx+HLPNIdSC6YKXeIOMnVs7Mctd/wXC93zGxAWD6ooTCzHSPVV50zKJmWIG2cVVUS7j35H3rGDtUHLI+ASXMEux9REZB8CdVOZMzp2wYeiOpggebJy6MKOZqPT1R3X0fqF2dHtRFPXrNsVr1Qt6bS9qTyO4ag1/BCvXF3P1uJEsI812BFAne3cYHy5bIOxuozPfipJrTb5WH35bxhElqwT3y/o/6JWOGg3HLDun31YmiZ2HScAsUAcEkA4hhoTNnqy4O2s3yVbCcR7jF7NLsbQc0MDTbnjxTdI4VnqUIn8s2c9hIJy/j80pmO9Bjxp+LQ9a2hUkfHgFhgHxZUVaeGVth8zG2kkgGdrp5VHhxMVFfvB26Ka6q6qE/UcS2lONSv+4T8niVRJz57qwctj8MNOkA3PTEfe/DP/LKMefke31YfT0xogHsLhDkx+mS8FCc01HReTjKLktk/Jh9mXwC5oKwueWWwlxI935ecn+3I2kAuOfMsgPLkoEBlwgiREC1pM7VVX1x8WmzIQVQTHd4iwnX96QewYckGRfNYWz/zwvWnjWlfcg8kRSe+68EHOGeRtC5r27fWLqRc0HNcjwpgHkI/b6czerCe8+07TWql4keJxJxhBYj3iOH7r9ZS8ck51XnOb8tGL1isimAJXodYGzakwktqHAD7MZhS+P02O+6jrg7d+yPC2ZCuS/3TOplYOCHQIhnZtR87PXTUwr83zfOwAwCyv6KP84JUQ45+DItrXLap7nOVZKQ5QxYIlbThAO6eima6Zu5XHfqGPMNWv0bLf5+vAjIa5np5DJrSwz9no/hj6CUh0iyI+SJq4RGI60lKtypMvF6MR3nHLEHOycRUQbZIyTHWl4QQLdHzuwN9lv10ouTEvNr6sFflAX2yb6w3hlCo7oBytH3rJekjb3IIOzBpeTPIejxzVlh0N9OT5MZdh4sNKYHUoWJ8mnfjdM+L4j5Q2Kgk/XiGDgEebkUxiEOQUdVpePF5uSCE+TPav/9FIRGXGiFn6NJMaU7aBsDTFBLloffFLYDpd8/bTwoSvifkj7buwLYM+h/qcnfdy5FWau1cKav+Blq/ZC0qBpo658RTC8ZtseAFDgXoQZuksM10hpP9bzD04Bx30xTGX81QbaSTNwSEEVrOtIhbDrj9OI43KH4O6zLzK+t30QxAv5zjk10RZ4+5SAdYndIlld9Y62opCfPDzRy3ubdve4ZEchpIKWTQvIxq3T5ogOhGaWBVYnkMtM2GVqvWV//46gET5SH/MdcwhACUcZ9kCpMnWH9CyyUwYvTT3UlNyV+DlS27LMPvaw7tx7qa+GfNCoCBd8S4esZpQYK/WReiS8=|pc7qpD42wxyXemdNPuwxbh8iIaryrBPu8f/DGwYdHTw=";
+
+        let kdf = Kdf::PBKDF2 {
+            iterations: 100_000.try_into().unwrap(),
+        };
+
+        initialize_user_crypto(
+            & client,
+            InitUserCryptoRequest {
+                kdf_params: kdf.clone(),
+                email: "test@bitwarden.com".into(),
+                private_key: priv_key.to_owned(),
+                method: InitUserCryptoMethod::Password {
+                    password: "asdfasdfasdf".into(),
+                    user_key: "2.u2HDQ/nH2J7f5tYHctZx6Q==|NnUKODz8TPycWJA5svexe1wJIz2VexvLbZh2RDfhj5VI3wP8ZkR0Vicvdv7oJRyLI1GyaZDBCf9CTBunRTYUk39DbZl42Rb+Xmzds02EQhc=|rwuo5wgqvTJf3rgwOUfabUyzqhguMYb3sGBjOYqjevc=".into(),
+                },
+            },
+        )
+        .await
+        .unwrap();
+
+        let insufficient_kdf = Kdf::Argon2id {
+            iterations: 1.try_into().unwrap(),
+            memory: 64.try_into().unwrap(),
+            parallelism: 4.try_into().unwrap(),
+        };
+        assert!(matches!(
+            make_kdf_update(&client, "asdfasdfasdf".into(), insufficient_kdf),
+            Err(crate::Error::Crypto(
+                bitwarden_crypto::CryptoError::InsufficientKdfParameters
+            ))
+        ));
+
+        let new_kdf = Kdf::Argon2id {
+            iterations: 3.try_into().unwrap(),
+            memory: 16.try_into().unwrap(),
+            parallelism: 1.try_into().unwrap(),
+        };
+        let response = make_kdf_update(&client, "asdfasdfasdf".into(), new_kdf.clone()).unwrap();
+
+        let master_key = MasterKey::derive("asdfasdfasdf", "test@bitwarden.com", &kdf).unwrap();
         assert_eq!(
-            client
-                .internal
-                .get_encryption_settings()
-                .unwrap()
-                .get_key(&None)
-                .unwrap()
-                .to_base64(),
-            client2
-                .internal
-                .get_encryption_settings()
+            response.master_password_hash,
+            master_key
+                .derive_master_key_hash(
+                    b"asdfasdfasdf",
+                    bitwarden_crypto::HashPurpose::ServerAuthorization
+                )
                 .unwrap()
-                .get_key(&None)
+        );
+
+        // The user key is unchanged, but protected by the master key derived with the new KDF
+        let new_master_key =
+            MasterKey::derive("asdfasdfasdf", "test@bitwarden.com", &new_kdf).unwrap();
+        assert_eq!(
+            response.new_master_password_hash,
+            new_master_key
+                .derive_master_key_hash(
+                    b"asdfasdfasdf",
+                    bitwarden_crypto::HashPurpose::ServerAuthorization
+                )
                 .unwrap()
-                .to_base64()
         );
+        let user_key = new_master_key
+            .decrypt_user_key(response.new_key.clone())
+            .unwrap();
+        assert_eq!(user_key.to_base64(), user_key_base64(&client));
+
+        let request: KdfRequestModel = response.into();
+        assert_eq!(request.kdf, KdfType::Argon2id);
+        assert_eq!(request.kdf_iterations, 3);
+        assert_eq!(request.kdf_memory, Some(16));
+        assert_eq!(request.kdf_parallelism, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_make_user_key_rotation() {
+        let client = Client::new(None);
+
+        let priv_key = "2.kmLY8NJVuiKBFJtNd/ZFpA==|qOodlRXER+9ogCe3yOibRHmUcSNvjSKhdDuztLlucs10jLiNoVVVAc+9KfNErLSpx5wmUF1hBOJM8zwVPjgQTrmnNf/wuDpwiaCxNYb/0v4FygPy7ccAHK94xP1lfqq7U9+tv+/yiZSwgcT+xF0wFpoxQeNdNRFzPTuD9o4134n8bzacD9DV/WjcrXfRjbBCzzuUGj1e78+A7BWN7/5IWLz87KWk8G7O/W4+8PtEzlwkru6Wd1xO19GYU18oArCWCNoegSmcGn7w7NDEXlwD403oY8Oa7ylnbqGE28PVJx+HLPNIdSC6YKXeIOMnVs7Mctd/wXC93zGxAWD6ooTCzHSPVV50zKJmWIG2cVVUS7j35H3rGDtUHLI+ASXMEux9REZB8CdVOZMzp2wYeiOpggebJy6MKOZqPT1R3X0fqF2dHtRFPXrNsVr1Qt6bS9qTyO4ag1/BCvXF3P1uJEsI812BFAne3cYHy5bIOxuozPfipJrTb5WH35bxhElqwT3y/o/6JWOGg3HLDun31YmiZ2HScAsUAcEkA4hhoTNnqy4O2s3yVbCcR7jF7NLsbQc0MDTbnjxTdI4VnqUIn8s2c9hIJy/j80pmO9Bjxp+LQ9a2hUkfHgFhgHxZUVaeGVth8zG2kkgGdrp5VHhxMVFfvB26Ka6q6qE/UcS2lONSv+4T8niVRJz57qwctj8MNOkA3PTEfe/DP/LKMefke31YfT0xogHsLhDkx+mS8FCc01HReTjKLktk/Jh9mXwC5oKwueWWwlxI935ecn+3I2kAuOfMsgPLkoEBlwgiREC1pM7VVX1x8WmzIQVQTHd4iwnX96QewYckGRfNYWz/zwvWnjWlfcg8kRSe+68EHOGeRtC5r27fWLqRc0HNcjwpgHkI/b6czerCe8+07TWql4keJxJxhBYj3iOH7r9ZS8ck51XnOb8tGL1isimAJXodYGzakwktqHAD7MZhS+P02O+6jrg7d+yPC2ZCuS/3TOplYOCHQIhnZtR87PXTUwr83zfOwAwCyv6KP84JUQ45+DItrXLap7nOVZKQ5QxYIlbThAO6eima6Zu5XHfqGPMNWv0bLf5+vAjIa5np5DJrSwz9no/hj6CUh0iyI+SJq4RGI60lKtypMvF6MR3nHLEHOycRUQbZIyTHWl4QQLdHzuwN9lv10ouTEvNr6sFflAX2yb6w3hlCo7oBytH3rJekjb3IIOzBpeTPIejxzVlh0N9OT5MZdh4sNKYHUoWJ8mnfjdM+L4j5Q2Kgk/XiGDgEebkUxiEOQUdVpePF5uSCE+TPav/9FIRGXGiFn6NJMaU7aBsDTFBLloffFLYDpd8/bTwoSvifkj7buwLYM+h/qcnfdy5FWau1cKav+Blq/ZC0qBpo658RTC8ZtseAFDgXoQZuksM10hpP9bzD04Bx30xTGX81QbaSTNwSEEVrOtIhbDrj9OI43KH4O6zLzK+t30QxAv5zjk10RZ4+5SAdYndIlld9Y62opCfPDzRy3ubdve4ZEchpIKWTQvIxq3T5ogOhGaWBVYnkMtM2GVqvWV//46gET5SH/MdcwhACUcZ9kCpMnWH9CyyUwYvTT3UlNyV+DlS27LMPvaw7tx7qa+GfNCoCBd8S4esZpQYK/WReiS8=|pc7qpD42wxyXemdNPuwxbh8iIaryrBPu8f/DGwYdHTw=";
+        let kdf = Kdf::PBKDF2 {
+            iterations: 100_000.try_into().unwrap(),
+        };
+
+        initialize_user_crypto(
+            &client,
+            InitUserCryptoRequest {
+                kdf_params: kdf.clone(),
+                email: "test@bitwarden.com".into(),
+                private_key: priv_key.to_owned(),
+                method: InitUserCryptoMethod::Password {
+                    password: "asdfasdfasdf".into(),
+                    user_key: "2.u2HDQ/nH2J7f5tYHctZx6Q==|NnUKODz8TPycWJA5svexe1wJIz2VexvLbZh2RDfhj5VI3wP8ZkR0Vicvdv7oJRyLI1GyaZDBCf9CTBunRTYUk39DbZl42Rb+Xmzds02EQhc=|rwuo5wgqvTJf3rgwOUfabUyzqhguMYb3sGBjOYqjevc=".into(),
+                },
+            },
+        )
+        .await
+        .unwrap();
+
+        let org_id = Uuid::new_v4();
+        let org_key = AsymmetricCryptoKey::generate_x25519(&mut rand::thread_rng());
+        let request = UserKeyRotationRequest {
+            password: "asdfasdfasdf".into(),
+            emergency_access_public_keys: HashMap::new(),
+            reset_password_public_keys: HashMap::from([(
+                org_id,
+                STANDARD.encode(org_key.to_public_der().unwrap()),
+            )]),
+        };
+
+        const NEW_USER_KEY: SymmetricKeyId = SymmetricKeyId::Local("new_user_key");
+        let key_store = client.internal.get_key_store();
+        let mut ctx = key_store.context();
+        let response = make_user_key_rotation(&client, &mut ctx, NEW_USER_KEY, &request).unwrap();
+
+        #[allow(deprecated)]
+        let new_user_key = ctx.dangerous_get_symmetric_key(NEW_USER_KEY).unwrap();
+        #[allow(deprecated)]
+        let old_user_key = ctx
+            .dangerous_get_symmetric_key(SymmetricKeyId::User)
+            .unwrap()
+            .to_base64();
+        assert_ne!(new_user_key.to_base64(), old_user_key);
+
+        // The new user key is protected by the same master password
+        let master_key = MasterKey::derive("asdfasdfasdf", "test@bitwarden.com", &kdf).unwrap();
+        let decrypted = master_key.decrypt_user_key(response.user_key).unwrap();
+        assert_eq!(decrypted.to_base64(), new_user_key.to_base64());
+
+        // The private key is unchanged, but protected by the new user key
+        let private_key: Vec<u8> = response
+            .private_key
+            .decrypt_with_key(&*new_user_key)
+            .unwrap();
+        #[allow(deprecated)]
+        let expected_private_key = ctx
+            .dangerous_get_asymmetric_key(AsymmetricKeyId::UserPrivateKey)
+            .unwrap()
+            .to_der()
+            .unwrap();
+        assert_eq!(private_key, expected_private_key);
+
+        let reset_password_key: Vec<u8> = response.reset_password_keys[&org_id]
+            .decrypt_with_key(&org_key)
+            .unwrap();
+        assert_eq!(reset_password_key, new_user_key.to_vec());
     }
 
     #[tokio::test]
@@ -582,22 +926,7 @@ mod tests {
         .await
         .unwrap();
 
-        assert_eq!(
-            client
-                .internal
-                .get_encryption_settings()
-                .unwrap()
-                .get_key(&None)
-                .unwrap()
-                .to_base64(),
-            client2
-                .internal
-                .get_encryption_settings()
-                .unwrap()
-                .get_key(&None)
-                .unwrap()
-                .to_base64()
-        );
+        assert_eq!(user_key_base64(&client), user_key_base64(&client2));
 
         // Verify we can derive the pin protected user key from the encrypted pin
         let pin_protected_user_key = derive_pin_user_key(&client, pin_key.encrypted_pin).unwrap();
@@ -621,22 +950,7 @@ mod tests {
         .await
         .unwrap();
 
-        assert_eq!(
-            client
-                .internal
-                .get_encryption_settings()
-                .unwrap()
-                .get_key(&None)
-                .unwrap()
-                .to_base64(),
-            client3
-                .internal
-                .get_encryption_settings()
-                .unwrap()
-                .get_key(&None)
-                .unwrap()
-                .to_base64()
-        );
+        assert_eq!(user_key_base64(&client), user_key_base64(&client3));
     }
 
     #[test]
@@ -671,8 +985,12 @@ mod tests {
             AsymmetricCryptoKey::from_der(&STANDARD.decode(private_key).unwrap()).unwrap();
         let decrypted: Vec<u8> = encrypted.decrypt_with_key(&private_key).unwrap();
 
-        let enc = client.internal.get_encryption_settings().unwrap();
-        let expected = enc.get_key(&None).unwrap();
+        let key_store = client.internal.get_key_store();
+        let ctx = key_store.context();
+        #[allow(deprecated)]
+        let expected = ctx
+            .dangerous_get_symmetric_key(SymmetricKeyId::User)
+            .unwrap();
         assert_eq!(&decrypted, &expected.to_vec());
     }
 
@@ -1,5 +1,5 @@
 #[cfg(feature = "internal")]
-use bitwarden_crypto::{AsymmetricEncString, EncString};
+use bitwarden_crypto::{AsymmetricEncString, EncString, Kdf};
 
 use super::crypto::{
     derive_key_connector, make_key_pair, verify_asymmetric_keys, DeriveKeyConnectorRequest,
@@ -11,8 +11,9 @@ use crate::{
     error::Result,
     mobile::crypto::{
         derive_pin_key, derive_pin_user_key, enroll_admin_password_reset, get_user_encryption_key,
-        initialize_org_crypto, initialize_user_crypto, update_password, DerivePinKeyResponse,
-        InitOrgCryptoRequest, InitUserCryptoRequest, UpdatePasswordResponse,
+        initialize_org_crypto, initialize_user_crypto, make_kdf_update, update_password,
+        DerivePinKeyResponse, InitOrgCryptoRequest, InitUserCryptoRequest, UpdateKdfResponse,
+        UpdatePasswordResponse,
     },
 };
 
@@ -43,6 +44,12 @@ impl CryptoClient<'_> {
         update_password(self.client, new_password)
     }
 
+    /// Change the KDF used to derive the master key. The response can be converted into the
+    /// request for the KDF endpoint.
+    pub fn make_kdf_update(&self, password: String, kdf: Kdf) -> Result<UpdateKdfResponse> {
+        make_kdf_update(self.client, password, kdf)
+    }
+
     pub fn derive_pin_key(&self, pin: String) -> Result<DerivePinKeyResponse> {
         derive_pin_key(self.client, pin)
     }
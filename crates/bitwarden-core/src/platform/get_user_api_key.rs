@@ -1,17 +1,15 @@
 use std::sync::Arc;
 
-use bitwarden_api_api::{
-    apis::accounts_api::accounts_api_key_post,
-    models::{ApiKeyResponseModel, SecretVerificationRequestModel},
-};
-use bitwarden_crypto::{HashPurpose, MasterKey};
+use bitwarden_api_api::{apis::accounts_api::accounts_api_key_post, models::ApiKeyResponseModel};
 use log::{debug, info};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use super::SecretVerificationRequest;
+use super::{
+    secret_verification_request::get_secret_verification_request, SecretVerificationRequest,
+};
 use crate::{
-    client::{LoginMethod, UserLoginMethod},
+    client::LoginMethod,
     error::{NotAuthenticatedError, Result},
     require, Client,
 };
@@ -43,31 +41,6 @@ fn get_login_method(client: &Client) -> Result<Arc<LoginMethod>, NotAuthenticate
     }
 }
 
-fn get_secret_verification_request(
-    login_method: &LoginMethod,
-    input: &SecretVerificationRequest,
-) -> Result<SecretVerificationRequestModel> {
-    if let LoginMethod::User(UserLoginMethod::Username { email, kdf, .. }) = login_method {
-        let master_password_hash = input
-            .master_password
-            .as_ref()
-            .map(|p| {
-                let master_key = MasterKey::derive(p, email, kdf)?;
-
-                master_key.derive_master_key_hash(p.as_bytes(), HashPurpose::ServerAuthorization)
-            })
-            .transpose()?;
-        Ok(SecretVerificationRequestModel {
-            master_password_hash,
-            otp: input.otp.as_ref().cloned(),
-            secret: None,
-            auth_request_access_code: None,
-        })
-    } else {
-        Err("Unsupported login method".into())
-    }
-}
-
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct UserApiKeyResponse {
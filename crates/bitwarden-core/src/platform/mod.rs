@@ -1,9 +1,14 @@
 mod generate_fingerprint;
 mod get_user_api_key;
 pub mod platform_client;
+mod public_key_store;
 mod secret_verification_request;
 
 pub use generate_fingerprint::{FingerprintRequest, FingerprintResponse};
 pub(crate) use get_user_api_key::get_user_api_key;
 pub use get_user_api_key::UserApiKeyResponse;
+pub(crate) use public_key_store::get_public_key;
+pub use public_key_store::{
+    PublicKeyError, PublicKeyOwner, PublicKeyResponse, PublicKeyStore, TrustedPublicKey,
+};
 pub use secret_verification_request::SecretVerificationRequest;
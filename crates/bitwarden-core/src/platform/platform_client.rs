@@ -1,6 +1,9 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
 use super::{
     generate_fingerprint::{generate_fingerprint, generate_user_fingerprint},
-    get_user_api_key, FingerprintRequest, FingerprintResponse, SecretVerificationRequest,
+    get_public_key, get_user_api_key, FingerprintRequest, FingerprintResponse, PublicKeyError,
+    PublicKeyOwner, PublicKeyResponse, SecretVerificationRequest, TrustedPublicKey,
     UserApiKeyResponse,
 };
 use crate::{error::Result, Client};
@@ -18,6 +21,48 @@ impl PlatformClient<'_> {
         generate_user_fingerprint(self.client, fingerprint_material)
     }
 
+    /// Fetch the public key of a user or organization, along with its fingerprint. The first key
+    /// seen for an owner is trusted, if the server later returns a different key
+    /// [PublicKeyError::PublicKeyChanged] is returned until the new key is trusted with
+    /// [PlatformClient::trust_public_key].
+    pub async fn get_public_key(
+        &self,
+        owner: PublicKeyOwner,
+    ) -> Result<PublicKeyResponse, PublicKeyError> {
+        get_public_key(self.client, owner).await
+    }
+
+    /// Trust a new public key for `owner`. This should only be done after the user has confirmed
+    /// its fingerprint out of band.
+    pub fn trust_public_key(
+        &self,
+        owner: PublicKeyOwner,
+        public_key: String,
+    ) -> Result<(), PublicKeyError> {
+        let public_key = STANDARD.decode(public_key)?;
+        self.client.internal.public_keys.trust(owner, &public_key);
+        Ok(())
+    }
+
+    /// Forget the trusted public key of `owner`, for example after the owner has legitimately
+    /// replaced its keys. The next key fetched for the owner is trusted on first use.
+    pub fn forget_public_key(&self, owner: PublicKeyOwner) {
+        self.client.internal.public_keys.forget(owner);
+    }
+
+    /// Load previously trusted public keys, as returned by
+    /// [PlatformClient::get_trusted_public_keys].
+    pub fn load_trusted_public_keys(
+        &self,
+        keys: Vec<TrustedPublicKey>,
+    ) -> Result<(), PublicKeyError> {
+        self.client.internal.public_keys.load(keys)
+    }
+
+    pub fn get_trusted_public_keys(&self) -> Vec<TrustedPublicKey> {
+        self.client.internal.public_keys.export()
+    }
+
     pub async fn get_user_api_key(
         &mut self,
         input: SecretVerificationRequest,
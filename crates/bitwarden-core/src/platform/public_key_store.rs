@@ -0,0 +1,222 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bitwarden_api_api::apis::{organizations_api, users_api};
+use bitwarden_crypto::{fingerprint, CryptoError};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{require, ApiError, Client, MissingFieldError};
+
+#[derive(Debug, Error)]
+pub enum PublicKeyError {
+    #[error("The public key of {0:?} has changed since it was trusted")]
+    PublicKeyChanged(PublicKeyOwner),
+
+    #[error(transparent)]
+    Api(#[from] ApiError),
+    #[error(transparent)]
+    MissingField(#[from] MissingFieldError),
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+    #[error(transparent)]
+    InvalidBase64(#[from] base64::DecodeError),
+}
+
+/// The user or organization a public key belongs to.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase", tag = "type", content = "id")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum PublicKeyOwner {
+    User(Uuid),
+    Organization(Uuid),
+}
+
+impl PublicKeyOwner {
+    /// The fingerprint material of the owner, which is its id.
+    fn fingerprint_material(&self) -> String {
+        match self {
+            PublicKeyOwner::User(id) | PublicKeyOwner::Organization(id) => id.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct TrustedPublicKey {
+    pub owner: PublicKeyOwner,
+    /// The public key encoded with base64.
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct PublicKeyResponse {
+    /// The public key encoded with base64.
+    pub public_key: String,
+    /// The fingerprint phrase of the public key, which the user can compare out of band.
+    pub fingerprint: String,
+}
+
+/// Trust on first use store of public keys. The first key seen for each owner is trusted, and
+/// any other key for the same owner is rejected until it is explicitly trusted.
+#[derive(Debug, Default)]
+pub struct PublicKeyStore {
+    keys: RwLock<HashMap<PublicKeyOwner, Vec<u8>>>,
+}
+
+impl PublicKeyStore {
+    /// Check `public_key` against the key trusted for `owner`, trusting it if there is none yet.
+    pub fn verify(&self, owner: PublicKeyOwner, public_key: &[u8]) -> Result<(), PublicKeyError> {
+        let mut keys = self.keys.write().expect("RwLock is not poisoned");
+        match keys.get(&owner) {
+            Some(trusted) if trusted.as_slice() != public_key => {
+                Err(PublicKeyError::PublicKeyChanged(owner))
+            }
+            Some(_) => Ok(()),
+            None => {
+                keys.insert(owner, public_key.to_vec());
+                Ok(())
+            }
+        }
+    }
+
+    /// Trust `public_key` for `owner`, replacing any previously trusted key.
+    pub fn trust(&self, owner: PublicKeyOwner, public_key: &[u8]) {
+        self.keys
+            .write()
+            .expect("RwLock is not poisoned")
+            .insert(owner, public_key.to_vec());
+    }
+
+    /// Forget the key trusted for `owner`, so that the next key seen for it is trusted on first
+    /// use again.
+    pub fn forget(&self, owner: PublicKeyOwner) {
+        self.keys
+            .write()
+            .expect("RwLock is not poisoned")
+            .remove(&owner);
+    }
+
+    /// Replace the trusted keys, usually with the ones returned by [PublicKeyStore::export].
+    pub fn load(&self, keys: Vec<TrustedPublicKey>) -> Result<(), PublicKeyError> {
+        let keys = keys
+            .into_iter()
+            .map(|k| Ok((k.owner, STANDARD.decode(k.public_key)?)))
+            .collect::<Result<_, PublicKeyError>>()?;
+
+        *self.keys.write().expect("RwLock is not poisoned") = keys;
+        Ok(())
+    }
+
+    /// The trusted keys, so they can be persisted and loaded into a new client.
+    pub fn export(&self) -> Vec<TrustedPublicKey> {
+        self.keys
+            .read()
+            .expect("RwLock is not poisoned")
+            .iter()
+            .map(|(owner, public_key)| TrustedPublicKey {
+                owner: *owner,
+                public_key: STANDARD.encode(public_key),
+            })
+            .collect()
+    }
+}
+
+/// Fetch the public key of `owner` from the server and verify it against the trusted keys.
+pub(crate) async fn get_public_key(
+    client: &Client,
+    owner: PublicKeyOwner,
+) -> Result<PublicKeyResponse, PublicKeyError> {
+    let config = client.internal.get_api_configurations().await;
+
+    let public_key = match owner {
+        PublicKeyOwner::User(id) => {
+            users_api::users_id_public_key_get(&config.api, &id.to_string())
+                .await
+                .map_err(ApiError::from)?
+                .public_key
+        }
+        PublicKeyOwner::Organization(id) => {
+            organizations_api::organizations_id_public_key_get(&config.api, &id.to_string())
+                .await
+                .map_err(ApiError::from)?
+                .public_key
+        }
+    };
+    let public_key = require!(public_key);
+    let key = STANDARD.decode(&public_key)?;
+
+    client.internal.public_keys.verify(owner, &key)?;
+
+    Ok(PublicKeyResponse {
+        fingerprint: fingerprint(&owner.fingerprint_material(), &key)?,
+        public_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+
+    const ORGANIZATION_ID: &str = "1bc9ac1e-f5aa-45f2-94bf-b181009709b8";
+
+    fn public_key_response(public_key: &[u8]) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "object": "organizationPublicKey",
+            "publicKey": STANDARD.encode(public_key),
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_get_public_key_trust_on_first_use() {
+        let path = format!("/api/organizations/{ORGANIZATION_ID}/public-key");
+        let (server, client) = crate::util::start_mock(vec![Mock::given(matchers::method("GET"))
+            .and(matchers::path(path.clone()))
+            .respond_with(public_key_response(&[1; 32]))])
+        .await;
+        let owner = PublicKeyOwner::Organization(ORGANIZATION_ID.parse().unwrap());
+
+        let first = get_public_key(&client, owner).await.unwrap();
+        assert_eq!(
+            first.fingerprint,
+            fingerprint(ORGANIZATION_ID, &[1; 32]).unwrap()
+        );
+        // The same key is still trusted
+        get_public_key(&client, owner).await.unwrap();
+
+        server.reset().await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path(path))
+            .respond_with(public_key_response(&[2; 32]))
+            .mount(&server)
+            .await;
+
+        assert!(matches!(
+            get_public_key(&client, owner).await,
+            Err(PublicKeyError::PublicKeyChanged(o)) if o == owner
+        ));
+
+        // Once the user has confirmed the new key it is trusted instead
+        client.internal.public_keys.trust(owner, &[2; 32]);
+        let second = get_public_key(&client, owner).await.unwrap();
+        assert_ne!(first.fingerprint, second.fingerprint);
+
+        // Forgetting the key trusts whichever key is seen next
+        client.internal.public_keys.forget(owner);
+        assert!(client.internal.public_keys.verify(owner, &[3; 32]).is_ok());
+        client.internal.public_keys.trust(owner, &[2; 32]);
+
+        let exported = client.internal.public_keys.export();
+        let store = PublicKeyStore::default();
+        store.load(exported).unwrap();
+        assert!(store.verify(owner, &[2; 32]).is_ok());
+        assert!(store.verify(owner, &[1; 32]).is_err());
+    }
+}
@@ -1,6 +1,14 @@
+use bitwarden_api_api::models::SecretVerificationRequestModel;
+use bitwarden_crypto::{HashPurpose, MasterKey};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    client::{LoginMethod, UserLoginMethod},
+    error::{NotAuthenticatedError, Result},
+    Client,
+};
+
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SecretVerificationRequest {
@@ -12,3 +20,41 @@ pub struct SecretVerificationRequest {
     /// master_password is absent.
     pub otp: Option<String>,
 }
+
+impl SecretVerificationRequest {
+    /// Build the verification the API expects, hashing the master password for the logged in
+    /// user.
+    pub fn to_request_model(&self, client: &Client) -> Result<SecretVerificationRequestModel> {
+        let login_method = client
+            .internal
+            .get_login_method()
+            .ok_or(NotAuthenticatedError)?;
+
+        get_secret_verification_request(&login_method, self)
+    }
+}
+
+pub(super) fn get_secret_verification_request(
+    login_method: &LoginMethod,
+    input: &SecretVerificationRequest,
+) -> Result<SecretVerificationRequestModel> {
+    if let LoginMethod::User(UserLoginMethod::Username { email, kdf, .. }) = login_method {
+        let master_password_hash = input
+            .master_password
+            .as_ref()
+            .map(|p| {
+                let master_key = MasterKey::derive(p, email, kdf)?;
+
+                master_key.derive_master_key_hash(p.as_bytes(), HashPurpose::ServerAuthorization)
+            })
+            .transpose()?;
+        Ok(SecretVerificationRequestModel {
+            master_password_hash,
+            otp: input.otp.as_ref().cloned(),
+            secret: None,
+            auth_request_access_code: None,
+        })
+    } else {
+        Err("Unsupported login method".into())
+    }
+}
@@ -10,6 +10,7 @@ use aes::cipher::{
     typenum::{U16, U32},
     BlockDecryptMut, BlockEncryptMut, KeyIvInit,
 };
+use aes_gcm::{aead::Aead, Aes256Gcm};
 use generic_array::GenericArray;
 use hmac::Mac;
 use subtle::ConstantTimeEq;
@@ -111,6 +112,45 @@ fn encrypt_aes256_internal(
     (iv, data)
 }
 
+/// Decrypt using AES-256 in GCM mode. The authentication tag is expected to be appended to the
+/// end of `data`, and is validated before any plaintext is returned.
+pub(crate) fn decrypt_aes256_gcm(
+    nonce: &[u8; 12],
+    data: &[u8],
+    key: &GenericArray<u8, U32>,
+) -> Result<Vec<u8>> {
+    <Aes256Gcm as aes_gcm::KeyInit>::new(key)
+        .decrypt(nonce.into(), data)
+        .map_err(|_| CryptoError::InvalidMac)
+}
+
+/// Encrypt using AES-256 in GCM mode.
+///
+/// ## Returns
+///
+/// A Aes256Gcm_B64 EncString, with the authentication tag appended to the encrypted data
+pub(crate) fn encrypt_aes256_gcm(
+    data_dec: &[u8],
+    key: &GenericArray<u8, U32>,
+) -> ([u8; 12], Vec<u8>) {
+    let rng = rand::thread_rng();
+    encrypt_aes256_gcm_internal(rng, data_dec, key)
+}
+
+fn encrypt_aes256_gcm_internal(
+    mut rng: impl rand::RngCore,
+    data_dec: &[u8],
+    key: &GenericArray<u8, U32>,
+) -> ([u8; 12], Vec<u8>) {
+    let mut nonce = [0u8; 12];
+    rng.fill_bytes(&mut nonce);
+    let data = <Aes256Gcm as aes_gcm::KeyInit>::new(key)
+        .encrypt(&nonce.into(), data_dec)
+        .expect("data is within the AES-GCM length limit");
+
+    (nonce, data)
+}
+
 /// Decrypt using AES-128 in CBC mode.
 ///
 /// Behaves similar to [decrypt_aes128_hmac], but does not validate the MAC.
@@ -198,6 +238,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encrypt_aes256_gcm_internal() {
+        let key = generate_generic_array(0, 1);
+
+        let rng = rand_chacha::ChaCha8Rng::from_seed([0u8; 32]);
+        let (nonce, data) = encrypt_aes256_gcm_internal(rng, "EncryptMe!".as_bytes(), &key);
+
+        assert_eq!(nonce, [62, 0, 239, 47, 137, 95, 64, 214, 127, 91, 184, 232]);
+        // Ciphertext followed by the 16 byte authentication tag
+        assert_eq!(data.len(), "EncryptMe!".len() + 16);
+
+        let decrypted = decrypt_aes256_gcm(&nonce, &data, &key).unwrap();
+        assert_eq!(String::from_utf8(decrypted).unwrap(), "EncryptMe!");
+    }
+
+    #[test]
+    fn test_decrypt_aes256_gcm_tampered() {
+        let key = generate_generic_array(0, 1);
+
+        let (nonce, mut data) = encrypt_aes256_gcm("EncryptMe!".as_bytes(), &key);
+        data[0] ^= 1;
+
+        let result = decrypt_aes256_gcm(&nonce, &data, &key);
+        assert!(matches!(result, Err(CryptoError::InvalidMac)));
+    }
+
     #[test]
     fn test_generate_mac() {
         let mac_key = generate_vec(16, 0, 16);
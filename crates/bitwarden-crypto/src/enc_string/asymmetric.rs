@@ -5,10 +5,12 @@ pub use internal::AsymmetricEncString;
 use rsa::Oaep;
 use serde::Deserialize;
 
-use super::{from_b64_vec, split_enc_string};
+use super::{from_b64, from_b64_vec, split_enc_string};
 use crate::{
     error::{CryptoError, EncStringParseError, Result},
+    keys::{RawPrivateKey, RawPublicKey},
     rsa::encrypt_rsa2048_oaep_sha1,
+    x25519::{decrypt_x25519, encrypt_x25519},
     AsymmetricCryptoKey, AsymmetricEncryptable, KeyDecryptable,
 };
 // This module is a workaround to avoid deprecated warnings that come from the ZeroizeOnDrop
@@ -38,6 +40,7 @@ mod internal {
     /// ## Variants
     /// - [Rsa2048_OaepSha256_B64](AsymmetricEncString::Rsa2048_OaepSha256_B64)
     /// - [Rsa2048_OaepSha1_B64](AsymmetricEncString::Rsa2048_OaepSha1_B64)
+    /// - [X25519_HkdfSha256_Aes256Gcm_B64](AsymmetricEncString::X25519_HkdfSha256_Aes256Gcm_B64)
     ///
     /// ## Serialization
     ///
@@ -63,6 +66,11 @@ mod internal {
         /// 6
         #[deprecated]
         Rsa2048_OaepSha1_HmacSha256_B64 { data: Vec<u8>, mac: Vec<u8> },
+        /// 9
+        X25519_HkdfSha256_Aes256Gcm_B64 {
+            ephemeral_public_key: [u8; 32],
+            data: Vec<u8>,
+        },
     }
 }
 
@@ -101,6 +109,14 @@ impl FromStr for AsymmetricEncString {
                 let mac: Vec<u8> = from_b64_vec(parts[1])?;
                 Ok(AsymmetricEncString::Rsa2048_OaepSha1_HmacSha256_B64 { data, mac })
             }
+            ("9", 2) => {
+                let ephemeral_public_key = from_b64(parts[0])?;
+                let data = from_b64_vec(parts[1])?;
+                Ok(AsymmetricEncString::X25519_HkdfSha256_Aes256Gcm_B64 {
+                    ephemeral_public_key,
+                    data,
+                })
+            }
 
             (enc_type, parts) => Err(EncStringParseError::InvalidTypeAsymm {
                 enc_type: enc_type.to_string(),
@@ -120,6 +136,10 @@ impl Display for AsymmetricEncString {
             AsymmetricEncString::Rsa2048_OaepSha256_HmacSha256_B64 { data, mac } => vec![data, mac],
             #[allow(deprecated)]
             AsymmetricEncString::Rsa2048_OaepSha1_HmacSha256_B64 { data, mac } => vec![data, mac],
+            AsymmetricEncString::X25519_HkdfSha256_Aes256Gcm_B64 {
+                ephemeral_public_key,
+                data,
+            } => vec![ephemeral_public_key, data],
         };
 
         let encoded_parts: Vec<String> = parts.iter().map(|part| STANDARD.encode(part)).collect();
@@ -149,12 +169,36 @@ impl serde::Serialize for AsymmetricEncString {
 }
 
 impl AsymmetricEncString {
+    /// Encrypt using the preferred variant for the type of `key`. That is
+    /// [AsymmetricEncString::Rsa2048_OaepSha1_B64] for RSA keys and
+    /// [AsymmetricEncString::X25519_HkdfSha256_Aes256Gcm_B64] for X25519 keys.
+    pub fn encrypt(
+        data_dec: &[u8],
+        key: &dyn AsymmetricEncryptable,
+    ) -> Result<AsymmetricEncString> {
+        match key.to_public_key().inner {
+            RawPublicKey::Rsa(key) => Ok(AsymmetricEncString::Rsa2048_OaepSha1_B64 {
+                data: encrypt_rsa2048_oaep_sha1(&key, data_dec)?,
+            }),
+            RawPublicKey::X25519(key) => {
+                let (ephemeral_public_key, data) = encrypt_x25519(&key, data_dec)?;
+                Ok(AsymmetricEncString::X25519_HkdfSha256_Aes256Gcm_B64 {
+                    ephemeral_public_key,
+                    data,
+                })
+            }
+        }
+    }
+
     /// Encrypt and produce a [AsymmetricEncString::Rsa2048_OaepSha1_B64] variant.
     pub fn encrypt_rsa2048_oaep_sha1(
         data_dec: &[u8],
         key: &dyn AsymmetricEncryptable,
     ) -> Result<AsymmetricEncString> {
-        let enc = encrypt_rsa2048_oaep_sha1(key.to_public_key(), data_dec)?;
+        let RawPublicKey::Rsa(key) = key.to_public_key().inner else {
+            return Err(CryptoError::InvalidKey);
+        };
+        let enc = encrypt_rsa2048_oaep_sha1(&key, data_dec)?;
         Ok(AsymmetricEncString::Rsa2048_OaepSha1_B64 { data: enc })
     }
 
@@ -167,6 +211,7 @@ impl AsymmetricEncString {
             AsymmetricEncString::Rsa2048_OaepSha256_HmacSha256_B64 { .. } => 5,
             #[allow(deprecated)]
             AsymmetricEncString::Rsa2048_OaepSha1_HmacSha256_B64 { .. } => 6,
+            AsymmetricEncString::X25519_HkdfSha256_Aes256Gcm_B64 { .. } => 9,
         }
     }
 }
@@ -174,17 +219,29 @@ impl AsymmetricEncString {
 impl KeyDecryptable<AsymmetricCryptoKey, Vec<u8>> for AsymmetricEncString {
     fn decrypt_with_key(&self, key: &AsymmetricCryptoKey) -> Result<Vec<u8>> {
         use AsymmetricEncString::*;
-        match self {
-            Rsa2048_OaepSha256_B64 { data } => key.key.decrypt(Oaep::new::<sha2::Sha256>(), data),
-            Rsa2048_OaepSha1_B64 { data } => key.key.decrypt(Oaep::new::<sha1::Sha1>(), data),
+        match (self, &key.inner) {
+            (Rsa2048_OaepSha256_B64 { data }, RawPrivateKey::Rsa(key)) => {
+                key.decrypt(Oaep::new::<sha2::Sha256>(), data)
+            }
+            (Rsa2048_OaepSha1_B64 { data }, RawPrivateKey::Rsa(key)) => {
+                key.decrypt(Oaep::new::<sha1::Sha1>(), data)
+            }
             #[allow(deprecated)]
-            Rsa2048_OaepSha256_HmacSha256_B64 { data, .. } => {
-                key.key.decrypt(Oaep::new::<sha2::Sha256>(), data)
+            (Rsa2048_OaepSha256_HmacSha256_B64 { data, .. }, RawPrivateKey::Rsa(key)) => {
+                key.decrypt(Oaep::new::<sha2::Sha256>(), data)
             }
             #[allow(deprecated)]
-            Rsa2048_OaepSha1_HmacSha256_B64 { data, .. } => {
-                key.key.decrypt(Oaep::new::<sha1::Sha1>(), data)
+            (Rsa2048_OaepSha1_HmacSha256_B64 { data, .. }, RawPrivateKey::Rsa(key)) => {
+                key.decrypt(Oaep::new::<sha1::Sha1>(), data)
             }
+            (
+                X25519_HkdfSha256_Aes256Gcm_B64 {
+                    ephemeral_public_key,
+                    data,
+                },
+                RawPrivateKey::X25519(key),
+            ) => return decrypt_x25519(key, ephemeral_public_key, data),
+            _ => return Err(CryptoError::InvalidKey),
         }
         .map_err(|_| CryptoError::KeyDecrypt)
     }
@@ -296,6 +353,25 @@ XKZBokBGnjFnTnKcs7nv/O8=
         assert_eq!(serde_json::to_string(&t).unwrap(), serialized);
     }
 
+    #[test]
+    fn test_enc_string_x25519_roundtrip() {
+        let private_key = AsymmetricCryptoKey::generate_x25519(&mut rand::thread_rng());
+
+        let enc_string = AsymmetricEncString::encrypt(b"EncryptMe!", &private_key).unwrap();
+        assert_eq!(enc_string.enc_type(), 9);
+
+        let parsed: AsymmetricEncString = enc_string.to_string().parse().unwrap();
+        assert_eq!(parsed.to_string(), enc_string.to_string());
+
+        let res: String = parsed.decrypt_with_key(&private_key).unwrap();
+        assert_eq!(res, "EncryptMe!");
+
+        // RSA keys can't decrypt X25519 encrypted data
+        let rsa_key = AsymmetricCryptoKey::from_pem(RSA_PRIVATE_KEY).unwrap();
+        let res: Result<String, _> = parsed.decrypt_with_key(&rsa_key);
+        assert!(res.is_err());
+    }
+
     #[test]
     fn test_from_str_invalid() {
         let enc_str = "7.ABC";
@@ -13,7 +13,7 @@ use std::str::FromStr;
 
 pub use asymmetric::AsymmetricEncString;
 use base64::{engine::general_purpose::STANDARD, Engine};
-pub use symmetric::EncString;
+pub use symmetric::{EncString, SymmetricEncryptionType};
 
 use crate::error::{EncStringParseError, Result};
 
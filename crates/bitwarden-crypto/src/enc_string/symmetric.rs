@@ -35,6 +35,7 @@ export type EncString = string;
 /// - [AesCbc256_B64](EncString::AesCbc256_B64)
 /// - [AesCbc128_HmacSha256_B64](EncString::AesCbc128_HmacSha256_B64)
 /// - [AesCbc256_HmacSha256_B64](EncString::AesCbc256_HmacSha256_B64)
+/// - [Aes256Gcm_B64](EncString::Aes256Gcm_B64)
 ///
 /// ## Serialization
 ///
@@ -47,8 +48,9 @@ export type EncString = string;
 ///
 /// Where:
 /// - `[type]`: is a digit number representing the variant.
-/// - `[iv]`: (optional) is the initialization vector used for encryption.
-/// - `[data]`: is the encrypted data.
+/// - `[iv]`: (optional) is the initialization vector used for encryption, or the nonce for AEAD
+///   variants.
+/// - `[data]`: is the encrypted data. For AEAD variants the authentication tag is appended to it.
 /// - `[mac]`: (optional) is the MAC used to validate the integrity of the data.
 #[derive(Clone, zeroize::ZeroizeOnDrop, PartialEq)]
 #[allow(unused, non_camel_case_types)]
@@ -67,6 +69,20 @@ pub enum EncString {
         mac: [u8; 32],
         data: Vec<u8>,
     },
+    /// 7
+    Aes256Gcm_B64 { nonce: [u8; 12], data: Vec<u8> },
+}
+
+/// The symmetric encryption algorithm used when creating new [EncString]s. Decryption always
+/// supports every [EncString] variant, regardless of this setting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum SymmetricEncryptionType {
+    /// AES-256 in CBC mode with a separate HMAC-SHA256, see [EncString::AesCbc256_HmacSha256_B64]
+    #[default]
+    AesCbc256_HmacSha256_B64,
+    /// AES-256 in GCM mode, see [EncString::Aes256Gcm_B64]
+    Aes256Gcm_B64,
 }
 
 /// To avoid printing sensitive information, [EncString] debug prints to `EncString`.
@@ -100,6 +116,12 @@ impl FromStr for EncString {
                     Ok(EncString::AesCbc256_HmacSha256_B64 { iv, mac, data })
                 }
             }
+            ("7", 2) => {
+                let nonce = from_b64(parts[0])?;
+                let data = from_b64_vec(parts[1])?;
+
+                Ok(EncString::Aes256Gcm_B64 { nonce, data })
+            }
 
             (enc_type, parts) => Err(EncStringParseError::InvalidTypeSymm {
                 enc_type: enc_type.to_string(),
@@ -142,6 +164,13 @@ impl EncString {
                     Ok(EncString::AesCbc256_HmacSha256_B64 { iv, mac, data })
                 }
             }
+            7 => {
+                check_length(buf, 29)?;
+                let nonce = buf[1..13].try_into().expect("Valid length");
+                let data = buf[13..].to_vec();
+
+                Ok(EncString::Aes256Gcm_B64 { nonce, data })
+            }
             _ => Err(EncStringParseError::InvalidTypeSymm {
                 enc_type: enc_type.to_string(),
                 parts: 1,
@@ -168,6 +197,12 @@ impl EncString {
                 buf.extend_from_slice(mac);
                 buf.extend_from_slice(data);
             }
+            EncString::Aes256Gcm_B64 { nonce, data } => {
+                buf = Vec::with_capacity(1 + 12 + data.len());
+                buf.push(self.enc_type());
+                buf.extend_from_slice(nonce);
+                buf.extend_from_slice(data);
+            }
         }
 
         Ok(buf)
@@ -180,6 +215,7 @@ impl Display for EncString {
             EncString::AesCbc256_B64 { iv, data } => vec![iv, data],
             EncString::AesCbc128_HmacSha256_B64 { iv, mac, data } => vec![iv, data, mac],
             EncString::AesCbc256_HmacSha256_B64 { iv, mac, data } => vec![iv, data, mac],
+            EncString::Aes256Gcm_B64 { nonce, data } => vec![nonce, data],
         };
 
         let encoded_parts: Vec<String> = parts.iter().map(|part| STANDARD.encode(part)).collect();
@@ -218,12 +254,18 @@ impl EncString {
         Ok(EncString::AesCbc256_HmacSha256_B64 { iv, mac, data })
     }
 
+    pub(crate) fn encrypt_aes256_gcm(data_dec: &[u8], key: &GenericArray<u8, U32>) -> EncString {
+        let (nonce, data) = crate::aes::encrypt_aes256_gcm(data_dec, key);
+        EncString::Aes256Gcm_B64 { nonce, data }
+    }
+
     /// The numerical representation of the encryption type of the [EncString].
     const fn enc_type(&self) -> u8 {
         match self {
             EncString::AesCbc256_B64 { .. } => 0,
             EncString::AesCbc128_HmacSha256_B64 { .. } => 1,
             EncString::AesCbc256_HmacSha256_B64 { .. } => 2,
+            EncString::Aes256Gcm_B64 { .. } => 7,
         }
     }
 }
@@ -266,6 +308,10 @@ impl KeyDecryptable<SymmetricCryptoKey, Vec<u8>> for EncString {
                     crate::aes::decrypt_aes256_hmac(iv, mac, data.clone(), mac_key, &key.key)?;
                 Ok(dec)
             }
+            EncString::Aes256Gcm_B64 { nonce, data } => {
+                let dec = crate::aes::decrypt_aes256_gcm(nonce, data, &key.key)?;
+                Ok(dec)
+            }
         }
     }
 }
@@ -461,6 +507,37 @@ mod tests {
         assert_eq!(dec_str, "EncryptMe!");
     }
 
+    #[test]
+    fn test_aes256_gcm_roundtrip() {
+        let key = derive_symmetric_key("test");
+
+        let cipher = EncString::encrypt_aes256_gcm(b"encrypted_test_string", &key.key);
+        assert_eq!(cipher.enc_type(), 7);
+
+        let parsed: EncString = cipher.to_string().parse().unwrap();
+        assert_eq!(parsed, cipher);
+        assert_eq!(
+            EncString::from_buffer(&cipher.to_buffer().unwrap()).unwrap(),
+            cipher
+        );
+
+        let decrypted_str: String = parsed.decrypt_with_key(&key).unwrap();
+        assert_eq!(decrypted_str, "encrypted_test_string");
+    }
+
+    #[test]
+    fn test_decrypt_aes256_gcm() {
+        let key = "hvBMMb1t79YssFZkpetYsM3deyVuQv4r88Uj9gvYe0+G8EwxvW3v1iywVmSl61iwzd17JW5C/ivzxSP2C9h7Tw==".to_string();
+        let key = SymmetricCryptoKey::try_from(key).unwrap();
+
+        let enc_str = "7.AAECAwQFBgcICQoL|03HZeQlQEHZCaXWzWo5iIdIeQF/MNJ/W3Gs=";
+        let enc_string: EncString = enc_str.parse().unwrap();
+        assert_eq!(enc_string.enc_type(), 7);
+
+        let dec_str: String = enc_string.decrypt_with_key(&key).unwrap();
+        assert_eq!(dec_str, "EncryptMe!");
+    }
+
     #[test]
     fn test_from_str_invalid() {
         let enc_str = "7.ABC";
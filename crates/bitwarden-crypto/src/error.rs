@@ -9,12 +9,16 @@ use crate::fingerprint::FingerprintError;
 pub enum CryptoError {
     #[error("The provided key is not the expected type")]
     InvalidKey,
+    #[error("The signature is not valid for the data")]
+    InvalidSignature,
     #[error("The cipher's MAC doesn't match the expected value")]
     InvalidMac,
     #[error("The key provided expects mac protected encstrings, but the mac is missing")]
     MacNotProvided,
     #[error("Error while decrypting EncString")]
     KeyDecrypt,
+    #[error("The encrypted stream is malformed or truncated")]
+    InvalidStream,
     #[error("The cipher key has an invalid length")]
     InvalidKeyLen,
     #[error("The value is not a valid UTF8 String")]
@@ -27,9 +31,13 @@ pub enum CryptoError {
     MissingKeyId(String),
     #[error("Crypto store is read-only")]
     ReadOnlyKeyStore,
+    #[error("Failed to allocate protected memory for the key store")]
+    KeyStoreAllocation,
 
     #[error("Insufficient KDF parameters")]
     InsufficientKdfParameters,
+    #[error("Excessive KDF parameters")]
+    ExcessiveKdfParameters,
 
     #[error("EncString error, {0}")]
     EncString(#[from] EncStringParseError),
@@ -1,56 +1,111 @@
 use std::pin::Pin;
 
 use rsa::{pkcs8::DecodePublicKey, RsaPrivateKey, RsaPublicKey};
+use zeroize::Zeroizing;
 
 use super::key_encryptable::CryptoKey;
 use crate::error::{CryptoError, Result};
 
+/// PKCS#8 `PrivateKeyInfo` prefix of an X25519 private key, followed by the 32 byte key
+/// ([RFC 8410](https://datatracker.ietf.org/doc/html/rfc8410#section-7)).
+const X25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x04, 0x22, 0x04, 0x20,
+];
+/// `SubjectPublicKeyInfo` prefix of an X25519 public key, followed by the 32 byte key
+/// ([RFC 8410](https://datatracker.ietf.org/doc/html/rfc8410#section-4)).
+const X25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x03, 0x21, 0x00,
+];
+
+/// Parses the 32 byte key that follows `prefix`, or returns [None] if `der` isn't a key of that
+/// form.
+fn strip_x25519_prefix(der: &[u8], prefix: &[u8]) -> Option<[u8; 32]> {
+    der.strip_prefix(prefix)?.try_into().ok()
+}
+
 /// Trait to allow both [`AsymmetricCryptoKey`] and [`AsymmetricPublicCryptoKey`] to be used to
 /// encrypt [AsymmetricEncString](crate::AsymmetricEncString).
 pub trait AsymmetricEncryptable {
-    fn to_public_key(&self) -> &RsaPublicKey;
+    fn to_public_key(&self) -> AsymmetricPublicCryptoKey;
+}
+
+#[derive(Clone)]
+pub(crate) enum RawPublicKey {
+    Rsa(RsaPublicKey),
+    X25519(x25519_dalek::PublicKey),
 }
 
 /// An asymmetric public encryption key. Can only encrypt
 /// [AsymmetricEncString](crate::AsymmetricEncString), usually accompanied by a
 /// [AsymmetricCryptoKey]
+#[derive(Clone)]
 pub struct AsymmetricPublicCryptoKey {
-    key: RsaPublicKey,
+    pub(crate) inner: RawPublicKey,
 }
 
 impl AsymmetricPublicCryptoKey {
-    /// Build a public key from the SubjectPublicKeyInfo DER.
+    /// Build a public key from the SubjectPublicKeyInfo DER. Both RSA and X25519 keys are
+    /// supported.
     pub fn from_der(der: &[u8]) -> Result<Self> {
-        Ok(Self {
-            key: rsa::RsaPublicKey::from_public_key_der(der)
-                .map_err(|_| CryptoError::InvalidKey)?,
-        })
+        let inner = match strip_x25519_prefix(der, &X25519_SPKI_PREFIX) {
+            Some(key) => RawPublicKey::X25519(x25519_dalek::PublicKey::from(key)),
+            None => RawPublicKey::Rsa(
+                RsaPublicKey::from_public_key_der(der).map_err(|_| CryptoError::InvalidKey)?,
+            ),
+        };
+        Ok(Self { inner })
+    }
+
+    /// Encode the public key as SubjectPublicKeyInfo DER.
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        match &self.inner {
+            RawPublicKey::Rsa(key) => {
+                use rsa::pkcs8::EncodePublicKey;
+                Ok(key
+                    .to_public_key_der()
+                    .map_err(|_| CryptoError::InvalidKey)?
+                    .as_bytes()
+                    .to_owned())
+            }
+            RawPublicKey::X25519(key) => Ok([&X25519_SPKI_PREFIX[..], key.as_bytes()].concat()),
+        }
     }
 }
 
 impl AsymmetricEncryptable for AsymmetricPublicCryptoKey {
-    fn to_public_key(&self) -> &RsaPublicKey {
-        &self.key
+    fn to_public_key(&self) -> AsymmetricPublicCryptoKey {
+        self.clone()
     }
 }
 
-/// An asymmetric encryption key. Contains both the public and private key. Can be used to both
-/// encrypt and decrypt [`AsymmetricEncString`](crate::AsymmetricEncString).
 #[derive(Clone)]
-pub struct AsymmetricCryptoKey {
+pub(crate) enum RawPrivateKey {
     // RsaPrivateKey is not a Copy type so this isn't completely necessary, but
     // to keep the compiler from making stack copies when moving this struct around,
     // we use a Box to keep the values on the heap. We also pin the box to make sure
     // that the contents can't be pulled out of the box and moved
-    pub(crate) key: Pin<Box<RsaPrivateKey>>,
+    Rsa(Pin<Box<RsaPrivateKey>>),
+    // StaticSecret implements Zeroize but doesn't zeroize itself on drop
+    X25519(Pin<Box<Zeroizing<x25519_dalek::StaticSecret>>>),
+}
+
+/// An asymmetric encryption key. Contains both the public and private key. Can be used to both
+/// encrypt and decrypt [`AsymmetricEncString`](crate::AsymmetricEncString).
+///
+/// The key is either an RSA-2048 key, or an X25519 key which produces smaller ciphertexts and
+/// is much faster to generate.
+#[derive(Clone)]
+pub struct AsymmetricCryptoKey {
+    pub(crate) inner: RawPrivateKey,
 }
 
-// Note that RsaPrivateKey already implements ZeroizeOnDrop, so we don't need to do anything
-// We add this assertion to make sure that this is still true in the future
+// Note that RsaPrivateKey and Zeroizing already implement ZeroizeOnDrop, so we don't need to do
+// anything. We add this assertion to make sure that this is still true in the future
 const _: () = {
     fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
     fn assert_all() {
         assert_zeroize_on_drop::<RsaPrivateKey>();
+        assert_zeroize_on_drop::<Zeroizing<x25519_dalek::StaticSecret>>();
     }
 };
 
@@ -62,48 +117,70 @@ impl AsymmetricCryptoKey {
         let bits = 2048;
 
         Self {
-            key: Box::pin(RsaPrivateKey::new(rng, bits).expect("failed to generate a key")),
+            inner: RawPrivateKey::Rsa(Box::pin(
+                RsaPrivateKey::new(rng, bits).expect("failed to generate a key"),
+            )),
+        }
+    }
+
+    /// Generate a random X25519 AsymmetricCryptoKey.
+    pub fn generate_x25519<R: rand::CryptoRng + rand::RngCore>(rng: &mut R) -> Self {
+        Self {
+            inner: RawPrivateKey::X25519(Box::pin(Zeroizing::new(
+                x25519_dalek::StaticSecret::random_from_rng(rng),
+            ))),
         }
     }
 
     pub fn from_pem(pem: &str) -> Result<Self> {
         use rsa::pkcs8::DecodePrivateKey;
         Ok(Self {
-            key: Box::pin(RsaPrivateKey::from_pkcs8_pem(pem).map_err(|_| CryptoError::InvalidKey)?),
+            inner: RawPrivateKey::Rsa(Box::pin(
+                RsaPrivateKey::from_pkcs8_pem(pem).map_err(|_| CryptoError::InvalidKey)?,
+            )),
         })
     }
 
+    /// Build a private key from the PKCS#8 DER. Both RSA and X25519 keys are supported.
     pub fn from_der(der: &[u8]) -> Result<Self> {
         use rsa::pkcs8::DecodePrivateKey;
-        Ok(Self {
-            key: Box::pin(RsaPrivateKey::from_pkcs8_der(der).map_err(|_| CryptoError::InvalidKey)?),
-        })
+        let inner = match strip_x25519_prefix(der, &X25519_PKCS8_PREFIX) {
+            Some(key) => RawPrivateKey::X25519(Box::pin(Zeroizing::new(key.into()))),
+            None => RawPrivateKey::Rsa(Box::pin(
+                RsaPrivateKey::from_pkcs8_der(der).map_err(|_| CryptoError::InvalidKey)?,
+            )),
+        };
+        Ok(Self { inner })
     }
 
     pub fn to_der(&self) -> Result<Vec<u8>> {
-        use rsa::pkcs8::EncodePrivateKey;
-        Ok(self
-            .key
-            .to_pkcs8_der()
-            .map_err(|_| CryptoError::InvalidKey)?
-            .as_bytes()
-            .to_owned())
+        match &self.inner {
+            RawPrivateKey::Rsa(key) => {
+                use rsa::pkcs8::EncodePrivateKey;
+                Ok(key
+                    .to_pkcs8_der()
+                    .map_err(|_| CryptoError::InvalidKey)?
+                    .as_bytes()
+                    .to_owned())
+            }
+            RawPrivateKey::X25519(key) => Ok([&X25519_PKCS8_PREFIX[..], key.as_bytes()].concat()),
+        }
     }
 
     pub fn to_public_der(&self) -> Result<Vec<u8>> {
-        use rsa::pkcs8::EncodePublicKey;
-        Ok(self
-            .to_public_key()
-            .to_public_key_der()
-            .map_err(|_| CryptoError::InvalidKey)?
-            .as_bytes()
-            .to_owned())
+        self.to_public_key().to_der()
     }
 }
 
 impl AsymmetricEncryptable for AsymmetricCryptoKey {
-    fn to_public_key(&self) -> &RsaPublicKey {
-        (*self.key).as_ref()
+    fn to_public_key(&self) -> AsymmetricPublicCryptoKey {
+        let inner = match &self.inner {
+            RawPrivateKey::Rsa(key) => RawPublicKey::Rsa((**key).as_ref().clone()),
+            RawPrivateKey::X25519(key) => {
+                RawPublicKey::X25519(x25519_dalek::PublicKey::from(&***key))
+            }
+        };
+        AsymmetricPublicCryptoKey { inner }
     }
 }
 
@@ -160,7 +237,7 @@ DnqOsltgPomWZ7xVfMkm9niL2OA=
         // Load the two different formats and check they are the same key
         let pem_key = AsymmetricCryptoKey::from_pem(pem_key_str).unwrap();
         let der_key = AsymmetricCryptoKey::from_der(&der_key_vec).unwrap();
-        assert_eq!(pem_key.key, der_key.key);
+        assert_eq!(pem_key.to_der().unwrap(), der_key.to_der().unwrap());
 
         // Check that the keys can be converted back to DER
         assert_eq!(der_key.to_der().unwrap(), der_key_vec);
@@ -223,4 +300,37 @@ DnqOsltgPomWZ7xVfMkm9niL2OA=
 
         assert_eq!(plaintext, decrypted);
     }
+
+    #[test]
+    fn test_x25519_der_roundtrip() {
+        let key = AsymmetricCryptoKey::generate_x25519(&mut rand::thread_rng());
+
+        let der = key.to_der().unwrap();
+        assert_eq!(der.len(), 48);
+        assert_eq!(
+            AsymmetricCryptoKey::from_der(&der)
+                .unwrap()
+                .to_der()
+                .unwrap(),
+            der
+        );
+
+        let public_der = key.to_public_der().unwrap();
+        assert_eq!(public_der.len(), 44);
+        let public_key = AsymmetricPublicCryptoKey::from_der(&public_der).unwrap();
+        assert_eq!(public_key.to_der().unwrap(), public_der);
+    }
+
+    #[test]
+    fn test_x25519_encrypt_public_decrypt_private() {
+        let private_key = AsymmetricCryptoKey::generate_x25519(&mut rand::thread_rng());
+        let public_key =
+            AsymmetricPublicCryptoKey::from_der(&private_key.to_public_der().unwrap()).unwrap();
+
+        let plaintext = "Hello, world!";
+        let encrypted = AsymmetricEncString::encrypt(plaintext.as_bytes(), &public_key).unwrap();
+        let decrypted: String = encrypted.decrypt_with_key(&private_key).unwrap();
+
+        assert_eq!(plaintext, decrypted);
+    }
 }
@@ -0,0 +1,197 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use super::utils::{derive_kdf_key, stretch_kdf_key};
+use crate::{
+    error::EncStringParseError, generate_random_bytes, AsymmetricCryptoKey, CryptoError, EncString,
+    Kdf, KeyDecryptable, KeyEncryptable, Result, SymmetricCryptoKey,
+};
+
+/// The type of key protected by a [PasswordProtectedKeyEnvelope].
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EnvelopeKeyType {
+    Symmetric,
+    Asymmetric,
+}
+
+/// A key protected by a password, along with everything needed to unlock it again except the
+/// password itself. Used to export keys, for example for offline backups.
+///
+/// The envelope is versioned, so the format can change without breaking existing envelopes.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
+#[serde(tag = "version")]
+pub enum PasswordProtectedKeyEnvelope {
+    /// The password is stretched with the KDF and a random salt, and the key is encrypted with
+    /// AES-256-CBC-HMAC-SHA256.
+    #[serde(rename = "1", rename_all = "camelCase")]
+    V1 {
+        kdf: Kdf,
+        /// Random salt for the KDF, encoded with base64
+        salt: String,
+        key_type: EnvelopeKeyType,
+        encrypted_key: EncString,
+    },
+}
+
+impl PasswordProtectedKeyEnvelope {
+    /// Protect a symmetric key with `password`.
+    pub fn seal_symmetric(key: &SymmetricCryptoKey, password: &str, kdf: Kdf) -> Result<Self> {
+        Self::seal(
+            &Zeroizing::new(key.to_vec()),
+            EnvelopeKeyType::Symmetric,
+            password,
+            kdf,
+        )
+    }
+
+    /// Protect an asymmetric private key with `password`.
+    pub fn seal_asymmetric(key: &AsymmetricCryptoKey, password: &str, kdf: Kdf) -> Result<Self> {
+        Self::seal(
+            &Zeroizing::new(key.to_der()?),
+            EnvelopeKeyType::Asymmetric,
+            password,
+            kdf,
+        )
+    }
+
+    pub fn unseal_symmetric(&self, password: &str) -> Result<SymmetricCryptoKey> {
+        let mut key = self.unseal(EnvelopeKeyType::Symmetric, password)?;
+        SymmetricCryptoKey::try_from(key.as_mut_slice())
+    }
+
+    pub fn unseal_asymmetric(&self, password: &str) -> Result<AsymmetricCryptoKey> {
+        let key = self.unseal(EnvelopeKeyType::Asymmetric, password)?;
+        AsymmetricCryptoKey::from_der(&key)
+    }
+
+    pub fn key_type(&self) -> EnvelopeKeyType {
+        match self {
+            PasswordProtectedKeyEnvelope::V1 { key_type, .. } => *key_type,
+        }
+    }
+
+    fn seal(key: &[u8], key_type: EnvelopeKeyType, password: &str, kdf: Kdf) -> Result<Self> {
+        let salt = generate_random_bytes::<[u8; 16]>();
+        let envelope_key =
+            stretch_kdf_key(&derive_kdf_key(password.as_bytes(), salt.as_slice(), &kdf)?)?;
+
+        Ok(PasswordProtectedKeyEnvelope::V1 {
+            kdf,
+            salt: STANDARD.encode(salt.as_slice()),
+            key_type,
+            encrypted_key: key.encrypt_with_key(&envelope_key)?,
+        })
+    }
+
+    fn unseal(&self, expected: EnvelopeKeyType, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+        match self {
+            PasswordProtectedKeyEnvelope::V1 {
+                kdf,
+                salt,
+                key_type,
+                encrypted_key,
+            } => {
+                if *key_type != expected {
+                    return Err(CryptoError::InvalidKey);
+                }
+                // The envelope may come from anywhere, so don't let it choose arbitrary costs
+                kdf.validate_maximums()?;
+
+                let salt = STANDARD
+                    .decode(salt)
+                    .map_err(EncStringParseError::InvalidBase64)?;
+                let envelope_key =
+                    stretch_kdf_key(&derive_kdf_key(password.as_bytes(), &salt, kdf)?)?;
+
+                Ok(Zeroizing::new(
+                    encrypted_key.decrypt_with_key(&envelope_key)?,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_symmetric_key() {
+        let key = SymmetricCryptoKey::generate(rand::thread_rng());
+        let kdf = Kdf::PBKDF2 {
+            iterations: 5000.try_into().unwrap(),
+        };
+
+        let envelope =
+            PasswordProtectedKeyEnvelope::seal_symmetric(&key, "correct horse", kdf).unwrap();
+        assert_eq!(envelope.key_type(), EnvelopeKeyType::Symmetric);
+
+        // The envelope survives a round trip through its serialized form
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.starts_with(r#"{"version":"1","#));
+        let envelope: PasswordProtectedKeyEnvelope = serde_json::from_str(&json).unwrap();
+
+        let unsealed = envelope.unseal_symmetric("correct horse").unwrap();
+        assert_eq!(unsealed.to_base64(), key.to_base64());
+
+        assert!(envelope.unseal_symmetric("battery staple").is_err());
+        assert!(matches!(
+            envelope.unseal_asymmetric("correct horse"),
+            Err(CryptoError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_asymmetric_key() {
+        let key = AsymmetricCryptoKey::generate_x25519(&mut rand::thread_rng());
+        let kdf = Kdf::Argon2id {
+            iterations: 2.try_into().unwrap(),
+            memory: 16.try_into().unwrap(),
+            parallelism: 1.try_into().unwrap(),
+        };
+
+        let envelope =
+            PasswordProtectedKeyEnvelope::seal_asymmetric(&key, "correct horse", kdf).unwrap();
+        let unsealed = envelope.unseal_asymmetric("correct horse").unwrap();
+
+        assert_eq!(unsealed.to_der().unwrap(), key.to_der().unwrap());
+    }
+
+    #[test]
+    fn test_envelope_excessive_kdf() {
+        let key = SymmetricCryptoKey::generate(rand::thread_rng());
+        let envelope = PasswordProtectedKeyEnvelope::seal_symmetric(
+            &key,
+            "correct horse",
+            Kdf::PBKDF2 {
+                iterations: 5000.try_into().unwrap(),
+            },
+        )
+        .unwrap();
+
+        let PasswordProtectedKeyEnvelope::V1 {
+            salt,
+            key_type,
+            encrypted_key,
+            ..
+        } = envelope;
+        let envelope = PasswordProtectedKeyEnvelope::V1 {
+            kdf: Kdf::Argon2id {
+                iterations: 3.try_into().unwrap(),
+                memory: u32::MAX.try_into().unwrap(),
+                parallelism: 4.try_into().unwrap(),
+            },
+            salt,
+            key_type,
+            encrypted_key,
+        };
+
+        assert!(matches!(
+            envelope.unseal_symmetric("correct horse"),
+            Err(CryptoError::ExcessiveKdfParameters)
+        ));
+    }
+}
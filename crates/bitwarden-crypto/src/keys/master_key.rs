@@ -30,6 +30,64 @@ pub enum Kdf {
     },
 }
 
+const PBKDF2_MIN_ITERATIONS: u32 = 5000;
+
+const ARGON2ID_MIN_MEMORY: u32 = 16;
+const ARGON2ID_MIN_ITERATIONS: u32 = 2;
+const ARGON2ID_MIN_PARALLELISM: u32 = 1;
+
+const PBKDF2_MAX_ITERATIONS: u32 = 2_000_000;
+
+const ARGON2ID_MAX_MEMORY: u32 = 1024;
+const ARGON2ID_MAX_ITERATIONS: u32 = 10;
+const ARGON2ID_MAX_PARALLELISM: u32 = 16;
+
+impl Kdf {
+    /// Check that the KDF parameters meet the minimums required for deriving keys with them.
+    pub fn validate(&self) -> Result<()> {
+        let sufficient = match self {
+            Kdf::PBKDF2 { iterations } => iterations.get() >= PBKDF2_MIN_ITERATIONS,
+            Kdf::Argon2id {
+                iterations,
+                memory,
+                parallelism,
+            } => {
+                memory.get() >= ARGON2ID_MIN_MEMORY
+                    && iterations.get() >= ARGON2ID_MIN_ITERATIONS
+                    && parallelism.get() >= ARGON2ID_MIN_PARALLELISM
+            }
+        };
+
+        if !sufficient {
+            return Err(CryptoError::InsufficientKdfParameters);
+        }
+        Ok(())
+    }
+
+    /// Check that the KDF parameters don't exceed the maximums, which should be done before
+    /// deriving keys with parameters from an untrusted source. Excessive parameters would
+    /// otherwise exhaust the memory or hang the process.
+    pub fn validate_maximums(&self) -> Result<()> {
+        let acceptable = match self {
+            Kdf::PBKDF2 { iterations } => iterations.get() <= PBKDF2_MAX_ITERATIONS,
+            Kdf::Argon2id {
+                iterations,
+                memory,
+                parallelism,
+            } => {
+                memory.get() <= ARGON2ID_MAX_MEMORY
+                    && iterations.get() <= ARGON2ID_MAX_ITERATIONS
+                    && parallelism.get() <= ARGON2ID_MAX_PARALLELISM
+            }
+        };
+
+        if !acceptable {
+            return Err(CryptoError::ExcessiveKdfParameters);
+        }
+        Ok(())
+    }
+}
+
 impl Default for Kdf {
     /// Default KDF for new accounts.
     fn default() -> Self {
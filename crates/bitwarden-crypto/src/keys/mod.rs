@@ -15,10 +15,15 @@ mod asymmetric_crypto_key;
 pub use asymmetric_crypto_key::{
     AsymmetricCryptoKey, AsymmetricEncryptable, AsymmetricPublicCryptoKey,
 };
+pub(crate) use asymmetric_crypto_key::{RawPrivateKey, RawPublicKey};
+mod signing_key;
+pub use signing_key::{SigningKey, VerifyingKey, SIGNATURE_LENGTH};
 mod user_key;
 pub use user_key::UserKey;
 mod device_key;
 pub use device_key::{DeviceKey, TrustDeviceResponse};
 mod pin_key;
 pub use pin_key::PinKey;
+mod key_envelope;
+pub use key_envelope::{EnvelopeKeyType, PasswordProtectedKeyEnvelope};
 mod utils;
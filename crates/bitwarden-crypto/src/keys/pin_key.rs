@@ -4,7 +4,7 @@ use crate::{
         key_encryptable::CryptoKey,
         utils::{derive_kdf_key, stretch_kdf_key},
     },
-    EncString, Kdf, KeyEncryptable, Result, SymmetricCryptoKey,
+    EncString, Kdf, KeyDecryptable, KeyEncryptable, Result, SymmetricCryptoKey,
 };
 
 /// Pin Key.
@@ -48,3 +48,11 @@ impl KeyEncryptable<PinKey, EncString> for String {
         self.as_bytes().encrypt_with_key(key)
     }
 }
+
+impl KeyDecryptable<PinKey, String> for EncString {
+    fn decrypt_with_key(&self, key: &PinKey) -> Result<String> {
+        let stretched_key = stretch_kdf_key(&key.0)?;
+
+        self.decrypt_with_key(&stretched_key)
+    }
+}
@@ -0,0 +1,138 @@
+use std::pin::Pin;
+
+use ed25519_dalek::{Signer, SECRET_KEY_LENGTH};
+
+use super::key_encryptable::CryptoKey;
+use crate::error::{CryptoError, Result};
+
+/// The length in bytes of a signature produced by a [SigningKey].
+pub const SIGNATURE_LENGTH: usize = ed25519_dalek::SIGNATURE_LENGTH;
+
+/// An Ed25519 signing key, used to prove a user's identity. The matching [VerifyingKey] can be
+/// shared with others so they can verify signatures made with this key.
+#[derive(Clone)]
+pub struct SigningKey {
+    // We box and pin the key to keep the compiler from making stack copies when moving it around
+    key: Pin<Box<ed25519_dalek::SigningKey>>,
+}
+
+// Note that ed25519_dalek::SigningKey already implements ZeroizeOnDrop, so we don't need to do
+// anything. We add this assertion to make sure that this is still true in the future
+const _: () = {
+    fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+    fn assert_all() {
+        assert_zeroize_on_drop::<ed25519_dalek::SigningKey>();
+    }
+};
+
+impl zeroize::ZeroizeOnDrop for SigningKey {}
+
+impl SigningKey {
+    /// Generate a random Ed25519 signing key.
+    pub fn generate<R: rand::CryptoRng + rand::RngCore>(rng: &mut R) -> Self {
+        Self {
+            key: Box::pin(ed25519_dalek::SigningKey::generate(rng)),
+        }
+    }
+
+    /// Build a signing key from its 32 byte secret seed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes: &[u8; SECRET_KEY_LENGTH] =
+            bytes.try_into().map_err(|_| CryptoError::InvalidKeyLen)?;
+        Ok(Self {
+            key: Box::pin(ed25519_dalek::SigningKey::from_bytes(bytes)),
+        })
+    }
+
+    /// The 32 byte secret seed of the signing key. This should be encrypted before being stored.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.key.to_bytes().to_vec()
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey(self.key.verifying_key())
+    }
+
+    pub fn sign(&self, data: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+        self.key.sign(data).to_bytes()
+    }
+}
+
+impl CryptoKey for SigningKey {}
+
+// We manually implement these to make sure we don't print any sensitive data
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey").finish()
+    }
+}
+
+/// An Ed25519 public key, used to verify signatures made by the matching [SigningKey].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyingKey(ed25519_dalek::VerifyingKey);
+
+impl VerifyingKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes: &[u8; 32] = bytes.try_into().map_err(|_| CryptoError::InvalidKeyLen)?;
+        Ok(Self(
+            ed25519_dalek::VerifyingKey::from_bytes(bytes).map_err(|_| CryptoError::InvalidKey)?,
+        ))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// Verify that `signature` was made over `data` by the matching [SigningKey]. Signatures are
+    /// verified strictly, rejecting malleable signatures and weak public keys.
+    pub fn verify(&self, data: &[u8], signature: &[u8]) -> Result<()> {
+        let signature = ed25519_dalek::Signature::from_slice(signature)
+            .map_err(|_| CryptoError::InvalidSignature)?;
+        self.0
+            .verify_strict(data, &signature)
+            .map_err(|_| CryptoError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SigningKey, VerifyingKey};
+    use crate::{CryptoError, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey};
+
+    #[test]
+    fn test_sign_verify() {
+        let signing_key = SigningKey::generate(&mut rand::thread_rng());
+        let verifying_key = signing_key.verifying_key();
+
+        let signature = signing_key.sign(b"Hello, world!");
+        verifying_key.verify(b"Hello, world!", &signature).unwrap();
+
+        assert!(matches!(
+            verifying_key.verify(b"Goodbye, world!", &signature),
+            Err(CryptoError::InvalidSignature)
+        ));
+
+        let other_key = SigningKey::generate(&mut rand::thread_rng()).verifying_key();
+        assert!(matches!(
+            other_key.verify(b"Hello, world!", &signature),
+            Err(CryptoError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_wrap_signing_key() {
+        let user_key = SymmetricCryptoKey::generate(rand::thread_rng());
+        let signing_key = SigningKey::generate(&mut rand::thread_rng());
+
+        let wrapped = signing_key.to_vec().encrypt_with_key(&user_key).unwrap();
+        let unwrapped: Vec<u8> = wrapped.decrypt_with_key(&user_key).unwrap();
+        let unwrapped = SigningKey::from_bytes(&unwrapped).unwrap();
+
+        assert_eq!(unwrapped.verifying_key(), signing_key.verifying_key());
+
+        let verifying_key =
+            VerifyingKey::from_bytes(&signing_key.verifying_key().to_bytes()).unwrap();
+        let signature = unwrapped.sign(b"Hello, world!");
+        verifying_key.verify(b"Hello, world!", &signature).unwrap();
+    }
+}
@@ -5,39 +5,25 @@ use sha2::Digest;
 
 use crate::{util::hkdf_expand, CryptoError, Kdf, Result, SymmetricCryptoKey};
 
-const PBKDF2_MIN_ITERATIONS: u32 = 5000;
-
-const ARGON2ID_MIN_MEMORY: u32 = 16 * 1024;
-const ARGON2ID_MIN_ITERATIONS: u32 = 2;
-const ARGON2ID_MIN_PARALLELISM: u32 = 1;
-
 /// Derive a generic key from a secret and salt using the provided KDF.
 pub(super) fn derive_kdf_key(secret: &[u8], salt: &[u8], kdf: &Kdf) -> Result<SymmetricCryptoKey> {
-    let mut hash = match kdf {
-        Kdf::PBKDF2 { iterations } => {
-            let iterations = iterations.get();
-            if iterations < PBKDF2_MIN_ITERATIONS {
-                return Err(CryptoError::InsufficientKdfParameters);
-            }
+    kdf.validate()?;
 
-            crate::util::pbkdf2(secret, salt, iterations)
-        }
+    let mut hash = match kdf {
+        Kdf::PBKDF2 { iterations } => crate::util::pbkdf2(secret, salt, iterations.get()),
         Kdf::Argon2id {
             iterations,
             memory,
             parallelism,
         } => {
-            let memory = memory.get() * 1024; // Convert MiB to KiB;
+            // Convert MiB to KiB
+            let memory = memory
+                .get()
+                .checked_mul(1024)
+                .ok_or(CryptoError::ExcessiveKdfParameters)?;
             let iterations = iterations.get();
             let parallelism = parallelism.get();
 
-            if memory < ARGON2ID_MIN_MEMORY
-                || iterations < ARGON2ID_MIN_ITERATIONS
-                || parallelism < ARGON2ID_MIN_PARALLELISM
-            {
-                return Err(CryptoError::InsufficientKdfParameters);
-            }
-
             use argon2::*;
 
             let params = Params::new(memory, iterations, parallelism, Some(32))?;
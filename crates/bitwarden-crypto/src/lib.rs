@@ -66,7 +66,7 @@ static ALLOC: ZeroizingAllocator<std::alloc::System> = ZeroizingAllocator(std::a
 
 mod aes;
 mod enc_string;
-pub use enc_string::{AsymmetricEncString, EncString};
+pub use enc_string::{AsymmetricEncString, EncString, SymmetricEncryptionType};
 mod error;
 pub use error::CryptoError;
 pub(crate) use error::Result;
@@ -82,8 +82,11 @@ mod wordlist;
 pub use wordlist::EFF_LONG_WORD_LIST;
 mod store;
 pub use store::{KeyStore, KeyStoreContext};
+mod stream;
+pub use stream::{DecryptingReader, EncryptingWriter, DEFAULT_CHUNK_SIZE, STREAM_ENC_TYPE};
 mod traits;
 pub use traits::{Decryptable, Encryptable, IdentifyKey, KeyId, KeyIds};
+mod x25519;
 pub use zeroizing_alloc::ZeroAlloc as ZeroizingAllocator;
 
 #[cfg(feature = "uniffi")]
@@ -1,6 +1,8 @@
+use std::borrow::Cow;
+
 use zeroize::ZeroizeOnDrop;
 
-use crate::{store::backend::StoreBackend, KeyId};
+use crate::{store::backend::StoreBackend, KeyId, Result};
 
 /// This is a basic key store backend that stores keys in a HashMap memory.
 /// No protections are provided for the keys stored in this backend, beyond enforcing
@@ -18,12 +20,13 @@ impl<Key: KeyId> BasicBackend<Key> {
 }
 
 impl<Key: KeyId> StoreBackend<Key> for BasicBackend<Key> {
-    fn upsert(&mut self, key_id: Key, key: <Key as KeyId>::KeyValue) {
+    fn upsert(&mut self, key_id: Key, key: <Key as KeyId>::KeyValue) -> Result<()> {
         self.keys.insert(key_id, key);
+        Ok(())
     }
 
-    fn get(&self, key_id: Key) -> Option<&<Key as KeyId>::KeyValue> {
-        self.keys.get(&key_id)
+    fn get(&self, key_id: Key) -> Option<Cow<'_, <Key as KeyId>::KeyValue>> {
+        self.keys.get(&key_id).map(Cow::Borrowed)
     }
 
     fn remove(&mut self, key_id: Key) {
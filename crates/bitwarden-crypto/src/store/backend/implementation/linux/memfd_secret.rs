@@ -0,0 +1,39 @@
+use std::ptr::NonNull;
+
+/// Allocates `len` bytes of memory backed by a `memfd_secret` file descriptor. These pages are
+/// removed from the kernel direct map, so they are inaccessible to other processes (including
+/// through `ptrace` and core dumps) and are never swapped out.
+///
+/// Returns [None] if the kernel doesn't support `memfd_secret`, or it has been disabled.
+pub(super) fn allocate(len: usize) -> Option<NonNull<u8>> {
+    // SAFETY: `memfd_secret` only takes a flags argument and returns a new file descriptor or -1
+    let fd = unsafe { libc::syscall(libc::SYS_memfd_secret, libc::O_CLOEXEC) };
+    if fd < 0 {
+        return None;
+    }
+    let fd = fd as libc::c_int;
+
+    // SAFETY: `fd` is a valid file descriptor owned by this function, and is closed before
+    // returning. The mapping keeps the underlying memory alive after the descriptor is closed.
+    unsafe {
+        if libc::ftruncate(fd, len as libc::off_t) != 0 {
+            libc::close(fd);
+            return None;
+        }
+
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        );
+        libc::close(fd);
+
+        if ptr == libc::MAP_FAILED {
+            return None;
+        }
+        NonNull::new(ptr.cast())
+    }
+}
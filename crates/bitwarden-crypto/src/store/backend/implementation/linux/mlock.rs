@@ -0,0 +1,37 @@
+use std::ptr::NonNull;
+
+/// Allocates `len` bytes of anonymous memory that is locked into RAM with `mlock`, so it's never
+/// written to swap, and marked with `MADV_DONTDUMP` so it's excluded from core dumps.
+///
+/// Returns [None] if the memory couldn't be mapped or protected, for example when the process
+/// has exceeded its `RLIMIT_MEMLOCK` limit.
+pub(super) fn allocate(len: usize) -> Option<NonNull<u8>> {
+    // SAFETY: We request a new private anonymous mapping, which doesn't alias any existing memory.
+    // If any of the protections fail, the mapping is released before returning.
+    unsafe {
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if ptr == libc::MAP_FAILED {
+            return None;
+        }
+
+        if libc::mlock(ptr, len) != 0 {
+            libc::munmap(ptr, len);
+            return None;
+        }
+
+        if libc::madvise(ptr, len, libc::MADV_DONTDUMP) != 0 {
+            libc::munlock(ptr, len);
+            libc::munmap(ptr, len);
+            return None;
+        }
+
+        NonNull::new(ptr.cast())
+    }
+}
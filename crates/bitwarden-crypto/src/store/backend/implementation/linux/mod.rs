@@ -0,0 +1,427 @@
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    mem::{align_of, size_of, MaybeUninit},
+    ptr::NonNull,
+};
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{
+    store::backend::{RawKey, StoreBackend},
+    CryptoError, KeyId, Result,
+};
+
+mod memfd_secret;
+mod mlock;
+
+/// The memory protection used by a [ProtectedRegion], in order of preference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Protection {
+    /// Memory backed by `memfd_secret`, which is removed from the kernel direct map.
+    MemfdSecret,
+    /// Anonymous memory locked with `mlock` and excluded from core dumps.
+    Mlock,
+}
+
+impl Protection {
+    fn allocate(self, len: usize) -> Option<NonNull<u8>> {
+        match self {
+            Protection::MemfdSecret => memfd_secret::allocate(len),
+            Protection::Mlock => mlock::allocate(len),
+        }
+    }
+}
+
+/// A page-aligned memory mapping with the protections described by [Protection] applied.
+/// The memory is zeroized and unmapped on drop.
+struct ProtectedRegion {
+    ptr: NonNull<u8>,
+    len: usize,
+    protection: Protection,
+}
+
+// SAFETY: The region is an exclusively owned memory mapping, it's not tied to any thread.
+unsafe impl Send for ProtectedRegion {}
+// SAFETY: The region only hands out access to its memory through `&self`/`&mut self`.
+unsafe impl Sync for ProtectedRegion {}
+
+impl ProtectedRegion {
+    /// Allocates a new region of at least `min_len` bytes, trying each [Protection] starting from
+    /// `preferred`.
+    fn allocate(min_len: usize, preferred: Protection) -> Option<Self> {
+        let len = round_to_page_size(min_len);
+
+        let candidates: &[Protection] = match preferred {
+            Protection::MemfdSecret => &[Protection::MemfdSecret, Protection::Mlock],
+            Protection::Mlock => &[Protection::Mlock],
+        };
+
+        candidates.iter().find_map(|&protection| {
+            let ptr = protection.allocate(len)?;
+            Some(Self {
+                ptr,
+                len,
+                protection,
+            })
+        })
+    }
+}
+
+impl Drop for ProtectedRegion {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` points to a live mapping of `len` bytes owned by this region.
+        unsafe {
+            zeroize_raw(self.ptr.as_ptr(), self.len);
+
+            let addr = self.ptr.as_ptr().cast();
+            if self.protection == Protection::Mlock {
+                libc::munlock(addr, self.len);
+            }
+            libc::munmap(addr, self.len);
+        }
+    }
+}
+
+/// Zeroizes `len` bytes starting at `ptr`, some of which may be uninitialized padding.
+///
+/// # Safety
+/// `ptr` must be valid for writes of `len` bytes.
+unsafe fn zeroize_raw(ptr: *mut u8, len: usize) {
+    std::slice::from_raw_parts_mut(ptr.cast::<MaybeUninit<u8>>(), len).zeroize();
+}
+
+fn round_to_page_size(len: usize) -> usize {
+    // SAFETY: `sysconf` has no preconditions
+    let page_size = match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096,
+    };
+    len.max(1).div_ceil(page_size) * page_size
+}
+
+/// A key store backend that keeps the raw key bytes in protected memory pages. It uses
+/// `memfd_secret` when the kernel supports it, and falls back to `mlock`ed pages that are
+/// excluded from core dumps otherwise.
+///
+/// Every key is stored in a fixed size slot containing its id, the length of the raw key and the
+/// raw key bytes. The key objects themselves are rebuilt from those bytes when they're retrieved,
+/// so the only long lived copy of the key material is the one inside the protected pages.
+pub(crate) struct LinuxBackend<Key: KeyId> {
+    region: ProtectedRegion,
+    len: usize,
+    _key: PhantomData<Key>,
+}
+
+/// The start of every slot, followed by [RawKey::MAX_LEN] bytes for the raw key.
+#[repr(C)]
+struct SlotHeader<Key> {
+    key_id: Key,
+    len: usize,
+}
+
+impl<Key: KeyId> LinuxBackend<Key>
+where
+    Key::KeyValue: RawKey,
+{
+    /// Creates a new backend, or returns [None] if no protected memory could be allocated.
+    pub fn new() -> Option<Self> {
+        Self::with_protection(Protection::MemfdSecret)
+    }
+
+    pub(crate) fn with_protection(protection: Protection) -> Option<Self> {
+        Some(Self {
+            region: ProtectedRegion::allocate(Self::slot_size(), protection)?,
+            len: 0,
+            _key: PhantomData,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn protection(&self) -> Protection {
+        self.region.protection
+    }
+
+    fn slot_size() -> usize {
+        let align = align_of::<SlotHeader<Key>>();
+        (size_of::<SlotHeader<Key>>() + Key::KeyValue::MAX_LEN).div_ceil(align) * align
+    }
+
+    fn capacity(&self) -> usize {
+        self.region.len / Self::slot_size()
+    }
+
+    fn slot_ptr(&self, index: usize) -> *mut u8 {
+        debug_assert!(index < self.capacity());
+        // SAFETY: `index` is within capacity, so the slot is inside the region
+        unsafe { self.region.ptr.as_ptr().add(index * Self::slot_size()) }
+    }
+
+    fn header(&self, index: usize) -> &SlotHeader<Key> {
+        debug_assert!(index < self.len);
+        // SAFETY: The slot is initialized, and it's aligned since both the region and the slot
+        // size are aligned to the header
+        unsafe { &*self.slot_ptr(index).cast::<SlotHeader<Key>>() }
+    }
+
+    fn raw_key(&self, index: usize) -> &[u8] {
+        let len = self.header(index).len;
+        // SAFETY: The slot is initialized, and its first `len` raw key bytes were written by
+        // `write_slot`
+        unsafe {
+            std::slice::from_raw_parts(self.slot_ptr(index).add(size_of::<SlotHeader<Key>>()), len)
+        }
+    }
+
+    fn position(&self, key_id: Key) -> Option<usize> {
+        (0..self.len).find(|&index| self.header(index).key_id == key_id)
+    }
+
+    /// Writes the key into the slot at `index`. The slot is left untouched if the key can't be
+    /// written.
+    fn write_slot(&mut self, index: usize, key_id: Key, key: &Key::KeyValue) -> Result<()> {
+        let slot = self.slot_ptr(index);
+
+        // SAFETY: The raw key bytes of the slot are inside the region, and are only accessed
+        // through `&mut self` here
+        let raw = unsafe {
+            std::slice::from_raw_parts_mut(
+                slot.add(size_of::<SlotHeader<Key>>()),
+                Key::KeyValue::MAX_LEN,
+            )
+        };
+        let len = key.write_raw(raw)?;
+        raw[len..].zeroize();
+
+        // SAFETY: The slot is inside the region and aligned to the header
+        unsafe {
+            slot.cast::<SlotHeader<Key>>()
+                .write(SlotHeader { key_id, len })
+        };
+        Ok(())
+    }
+
+    /// Moves the slots into a new region with double the capacity. The old region is zeroized
+    /// when it's dropped.
+    fn grow(&mut self) -> Result<()> {
+        let new_region = ProtectedRegion::allocate(self.region.len * 2, self.region.protection)
+            .ok_or(CryptoError::KeyStoreAllocation)?;
+
+        // SAFETY: The new region is larger than the old one and they don't overlap
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                self.region.ptr.as_ptr(),
+                new_region.ptr.as_ptr(),
+                self.len * Self::slot_size(),
+            );
+        }
+        self.region = new_region;
+        Ok(())
+    }
+
+    fn remove_at(&mut self, index: usize) {
+        debug_assert!(index < self.len);
+        let last = self.len - 1;
+
+        // SAFETY: `index` and `last` are both initialized slots. The last slot is moved into the
+        // removed one, and the vacated slot is zeroized.
+        unsafe {
+            if index != last {
+                std::ptr::copy_nonoverlapping(
+                    self.slot_ptr(last),
+                    self.slot_ptr(index),
+                    Self::slot_size(),
+                );
+            }
+            zeroize_raw(self.slot_ptr(last), Self::slot_size());
+        }
+        self.len = last;
+    }
+}
+
+impl<Key: KeyId> StoreBackend<Key> for LinuxBackend<Key>
+where
+    Key::KeyValue: RawKey,
+{
+    fn upsert(&mut self, key_id: Key, key: <Key as KeyId>::KeyValue) -> Result<()> {
+        if let Some(index) = self.position(key_id) {
+            return self.write_slot(index, key_id, &key);
+        }
+
+        if self.len == self.capacity() {
+            self.grow()?;
+        }
+
+        self.write_slot(self.len, key_id, &key)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn get(&self, key_id: Key) -> Option<Cow<'_, <Key as KeyId>::KeyValue>> {
+        let index = self.position(key_id)?;
+        // The bytes were written by `RawKey::write_raw`, so they always contain a valid key
+        Key::KeyValue::read_raw(self.raw_key(index))
+            .ok()
+            .map(Cow::Owned)
+    }
+
+    fn remove(&mut self, key_id: Key) {
+        if let Some(index) = self.position(key_id) {
+            self.remove_at(index);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+
+        // SAFETY: The region is only accessed through `&mut self` here
+        unsafe { zeroize_raw(self.region.ptr.as_ptr(), self.region.len) };
+    }
+
+    fn retain(&mut self, f: fn(Key) -> bool) {
+        let mut index = 0;
+        while index < self.len {
+            if f(self.header(index).key_id) {
+                index += 1;
+            } else {
+                self.remove_at(index);
+            }
+        }
+    }
+}
+
+/// The slots only contain key ids and raw key bytes, and the region is zeroized when it's
+/// dropped.
+impl<Key: KeyId> ZeroizeOnDrop for LinuxBackend<Key> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        traits::tests::{TestAsymmKey, TestSymmKey},
+        AsymmetricCryptoKey, SymmetricCryptoKey,
+    };
+
+    fn backends<Key: KeyId>() -> Vec<LinuxBackend<Key>>
+    where
+        Key::KeyValue: RawKey,
+    {
+        // Not every environment supports `memfd_secret` or allows locking memory, so only test
+        // the protections that are available.
+        [Protection::MemfdSecret, Protection::Mlock]
+            .into_iter()
+            .filter_map(LinuxBackend::with_protection)
+            .collect()
+    }
+
+    fn region_bytes<Key: KeyId>(backend: &LinuxBackend<Key>) -> &[u8] {
+        // SAFETY: The region is a live mapping, which the kernel initializes to zero
+        unsafe { std::slice::from_raw_parts(backend.region.ptr.as_ptr(), backend.region.len) }
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn test_keeps_raw_key_bytes_in_region() {
+        for mut backend in backends::<TestSymmKey>() {
+            let key = SymmetricCryptoKey::generate(rand::thread_rng());
+            let raw = key.to_vec();
+            backend.upsert(TestSymmKey::A(0), key).unwrap();
+            assert!(contains(region_bytes(&backend), &raw));
+
+            backend.remove(TestSymmKey::A(0));
+            assert!(region_bytes(&backend).iter().all(|b| *b == 0));
+        }
+    }
+
+    #[test]
+    fn test_asymmetric_keys() {
+        for mut backend in backends::<TestAsymmKey>() {
+            let key = AsymmetricCryptoKey::generate(&mut rand::thread_rng());
+            backend.upsert(TestAsymmKey::A(0), key.clone()).unwrap();
+            assert_eq!(
+                backend.get(TestAsymmKey::A(0)).unwrap().to_der().unwrap(),
+                key.to_der().unwrap()
+            );
+
+            backend.clear();
+            assert!(backend.get(TestAsymmKey::A(0)).is_none());
+        }
+    }
+
+    #[test]
+    fn test_upsert_get_and_replace() {
+        for mut backend in backends() {
+            let key = SymmetricCryptoKey::generate(rand::thread_rng());
+            backend.upsert(TestSymmKey::A(0), key.clone()).unwrap();
+            assert_eq!(
+                backend.get(TestSymmKey::A(0)).unwrap().to_base64(),
+                key.to_base64()
+            );
+
+            let new_key = SymmetricCryptoKey::generate(rand::thread_rng());
+            backend.upsert(TestSymmKey::A(0), new_key.clone()).unwrap();
+            assert_eq!(backend.len, 1);
+            assert_eq!(
+                backend.get(TestSymmKey::A(0)).unwrap().to_base64(),
+                new_key.to_base64()
+            );
+        }
+    }
+
+    #[test]
+    fn test_grows_past_a_single_page() {
+        for mut backend in backends() {
+            let protection = backend.protection();
+            let initial_capacity = backend.capacity();
+
+            let keys: Vec<_> = (0..=initial_capacity)
+                .map(|i| {
+                    let id = TestSymmKey::B(((i / 256) as u8, (i % 256) as u8));
+                    (id, SymmetricCryptoKey::generate(rand::thread_rng()))
+                })
+                .collect();
+            for (id, key) in &keys {
+                backend.upsert(*id, key.clone()).unwrap();
+            }
+
+            assert!(backend.capacity() > initial_capacity);
+            assert_eq!(backend.protection(), protection);
+            for (id, key) in &keys {
+                assert_eq!(backend.get(*id).unwrap().to_base64(), key.to_base64());
+            }
+        }
+    }
+
+    #[test]
+    fn test_remove_and_retain() {
+        for mut backend in backends() {
+            for i in 0..4 {
+                let key = SymmetricCryptoKey::generate(rand::thread_rng());
+                backend.upsert(TestSymmKey::A(i), key).unwrap();
+            }
+            backend
+                .upsert(
+                    TestSymmKey::B((0, 1)),
+                    SymmetricCryptoKey::generate(rand::thread_rng()),
+                )
+                .unwrap();
+
+            backend.remove(TestSymmKey::A(1));
+            assert!(backend.get(TestSymmKey::A(1)).is_none());
+            assert!(backend.get(TestSymmKey::A(3)).is_some());
+
+            backend.retain(|id| matches!(id, TestSymmKey::A(_)));
+            assert_eq!(backend.len, 3);
+            assert!(backend.get(TestSymmKey::B((0, 1))).is_none());
+
+            backend.clear();
+            assert_eq!(backend.len, 0);
+            assert!(backend.get(TestSymmKey::A(0)).is_none());
+        }
+    }
+}
@@ -1,10 +1,27 @@
-use super::StoreBackend;
+use super::{RawKey, StoreBackend};
 use crate::store::KeyId;
 
 mod basic;
+#[cfg(all(target_os = "linux", not(feature = "no-memory-hardening")))]
+mod linux;
 
 /// Initializes a key store backend with the best available implementation for the current platform
-pub fn create_store<Key: KeyId>() -> Box<dyn StoreBackend<Key>> {
+pub fn create_store<Key: KeyId>() -> Box<dyn StoreBackend<Key>>
+where
+    Key::KeyValue: RawKey,
+{
+    #[cfg(all(target_os = "linux", not(feature = "no-memory-hardening")))]
+    if let Some(backend) = linux::LinuxBackend::<Key>::new() {
+        return Box::new(backend);
+    }
+
+    Box::new(basic::BasicBackend::<Key>::new())
+}
+
+/// Initializes a key store backend for the keys local to a
+/// [KeyStoreContext](crate::KeyStoreContext). A context is created for every encryption and
+/// decryption, so these use the basic backend instead of allocating protected memory every time.
+pub fn create_local_store<Key: KeyId>() -> Box<dyn StoreBackend<Key>> {
     Box::new(basic::BasicBackend::<Key>::new())
 }
 
@@ -18,7 +35,7 @@ mod tests {
         let mut store = create_store::<TestSymmKey>();
 
         let key = SymmetricCryptoKey::generate(rand::thread_rng());
-        store.upsert(TestSymmKey::A(0), key.clone());
+        store.upsert(TestSymmKey::A(0), key.clone()).unwrap();
 
         assert_eq!(
             store.get(TestSymmKey::A(0)).unwrap().to_base64(),
@@ -1,29 +1,34 @@
+use std::borrow::Cow;
+
 use zeroize::ZeroizeOnDrop;
 
-use crate::store::KeyId;
+use crate::{store::KeyId, Result};
 
 mod implementation;
+mod raw_key;
 
-pub use implementation::create_store;
+pub use implementation::{create_local_store, create_store};
+pub(crate) use raw_key::RawKey;
 
 /// This trait represents a platform that can store and return keys. If possible,
 /// it will try to enable as many security protections on the keys as it can.
 /// The keys themselves implement [ZeroizeOnDrop], so the store will only need to make sure
 /// that the keys are dropped when they are no longer needed.
 ///
-/// The default implementation is a basic in-memory store that does not provide any security
-/// guarantees.
-///
-/// We have other implementations in testing using `mlock` and `memfd_secret` for protecting keys in
-/// memory.
+/// On Linux, [create_store] returns a backend that keeps the raw key bytes in `memfd_secret`
+/// pages, falling back to `mlock`ed pages that are excluded from core dumps when `memfd_secret` is
+/// unavailable. On other platforms, or when neither protection can be applied, it returns a basic
+/// in-memory store that does not provide any security guarantees. The short lived context-local
+/// keys always use the basic store, see [create_local_store].
 ///
 /// Other implementations could use secure enclaves, HSMs or OS provided keychains.
 pub trait StoreBackend<Key: KeyId>: ZeroizeOnDrop + Send + Sync {
     /// Inserts a key into the store. If the key already exists, it will be replaced.
-    fn upsert(&mut self, key_id: Key, key: Key::KeyValue);
+    fn upsert(&mut self, key_id: Key, key: Key::KeyValue) -> Result<()>;
 
-    /// Retrieves a key from the store.
-    fn get(&self, key_id: Key) -> Option<&Key::KeyValue>;
+    /// Retrieves a key from the store. Backends that don't keep the key objects themselves
+    /// rebuild the key from the stored bytes, the returned copy is zeroized when dropped.
+    fn get(&self, key_id: Key) -> Option<Cow<'_, Key::KeyValue>>;
 
     #[allow(unused)]
     /// Removes a key from the store.
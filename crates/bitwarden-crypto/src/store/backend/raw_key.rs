@@ -0,0 +1,106 @@
+use generic_array::GenericArray;
+use zeroize::Zeroizing;
+
+use crate::{AsymmetricCryptoKey, CryptoError, Result, SymmetricCryptoKey};
+
+/// A key that can be stored as raw bytes, which allows backends to keep the key material in
+/// memory they control instead of in the heap allocations owned by the key.
+#[cfg_attr(
+    not(all(target_os = "linux", not(feature = "no-memory-hardening"))),
+    allow(dead_code)
+)]
+pub(crate) trait RawKey: Sized {
+    /// The maximum length of the raw bytes of a key.
+    const MAX_LEN: usize;
+
+    /// Copies the raw bytes of the key into `buf`, which is [RawKey::MAX_LEN] bytes long, and
+    /// returns the number of bytes written. `buf` is left untouched on failure.
+    fn write_raw(&self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Rebuilds a key from the bytes written by [RawKey::write_raw].
+    fn read_raw(raw: &[u8]) -> Result<Self>;
+}
+
+impl RawKey for SymmetricCryptoKey {
+    // The encryption key, followed by the MAC key if there is one
+    const MAX_LEN: usize = 64;
+
+    fn write_raw(&self, buf: &mut [u8]) -> Result<usize> {
+        let key_len = self.key.len();
+        buf[..key_len].copy_from_slice(&self.key);
+
+        match &self.mac_key {
+            Some(mac_key) => {
+                buf[key_len..key_len + mac_key.len()].copy_from_slice(mac_key);
+                Ok(key_len + mac_key.len())
+            }
+            None => Ok(key_len),
+        }
+    }
+
+    fn read_raw(raw: &[u8]) -> Result<Self> {
+        let (key, mac_key) = match raw.len() {
+            32 => (raw, None),
+            64 => (&raw[..32], Some(&raw[32..])),
+            _ => return Err(CryptoError::InvalidKeyLen),
+        };
+
+        let mut key_box = Box::pin(GenericArray::default());
+        key_box.copy_from_slice(key);
+        let mac_key_box = mac_key.map(|mac_key| {
+            let mut mac_key_box = Box::pin(GenericArray::default());
+            mac_key_box.copy_from_slice(mac_key);
+            mac_key_box
+        });
+
+        Ok(SymmetricCryptoKey::new(key_box, mac_key_box))
+    }
+}
+
+impl RawKey for AsymmetricCryptoKey {
+    // The PKCS#8 DER of the key, which is large enough for RSA keys of up to 4096 bits
+    const MAX_LEN: usize = 2560;
+
+    fn write_raw(&self, buf: &mut [u8]) -> Result<usize> {
+        let der = Zeroizing::new(self.to_der()?);
+        if der.len() > buf.len() {
+            return Err(CryptoError::InvalidKeyLen);
+        }
+
+        buf[..der.len()].copy_from_slice(&der);
+        Ok(der.len())
+    }
+
+    fn read_raw(raw: &[u8]) -> Result<Self> {
+        AsymmetricCryptoKey::from_der(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip<K: RawKey>(key: &K) -> K {
+        let mut buf = vec![0u8; K::MAX_LEN];
+        let len = key.write_raw(&mut buf).unwrap();
+        K::read_raw(&buf[..len]).unwrap()
+    }
+
+    #[test]
+    fn test_symmetric_roundtrip() {
+        let key = SymmetricCryptoKey::generate(rand::thread_rng());
+        assert_eq!(roundtrip(&key).to_base64(), key.to_base64());
+
+        let key = SymmetricCryptoKey::new(Box::pin(GenericArray::default()), None);
+        assert_eq!(roundtrip(&key).to_base64(), key.to_base64());
+    }
+
+    #[test]
+    fn test_asymmetric_roundtrip() {
+        let key = AsymmetricCryptoKey::generate(&mut rand::thread_rng());
+        assert_eq!(roundtrip(&key).to_der().unwrap(), key.to_der().unwrap());
+
+        let key = AsymmetricCryptoKey::generate_x25519(&mut rand::thread_rng());
+        assert_eq!(roundtrip(&key).to_der().unwrap(), key.to_der().unwrap());
+    }
+}
@@ -1,15 +1,16 @@
 use std::{
+    borrow::Cow,
     cell::Cell,
     sync::{RwLockReadGuard, RwLockWriteGuard},
 };
 
-use rsa::Oaep;
 use zeroize::Zeroizing;
 
 use super::KeyStoreInner;
 use crate::{
     derive_shareable_key, store::backend::StoreBackend, AsymmetricCryptoKey, AsymmetricEncString,
-    CryptoError, EncString, KeyId, KeyIds, Result, SymmetricCryptoKey,
+    CryptoError, DecryptingReader, EncString, EncryptingWriter, KeyDecryptable, KeyId, KeyIds,
+    Result, SymmetricCryptoKey, SymmetricEncryptionType, DEFAULT_CHUNK_SIZE,
 };
 
 /// The context of a crypto operation using [super::KeyStore]
@@ -261,10 +262,7 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
         let encryption_key = self.get_asymmetric_key(encryption_key)?;
         let key_to_encrypt = self.get_asymmetric_key(key_to_encrypt)?;
 
-        AsymmetricEncString::encrypt_rsa2048_oaep_sha1(
-            key_to_encrypt.to_der()?.as_slice(),
-            encryption_key,
-        )
+        AsymmetricEncString::encrypt(key_to_encrypt.to_der()?.as_slice(), &*encryption_key)
     }
 
     /// Returns `true` if the context has a symmetric key with the given identifier
@@ -305,7 +303,7 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
     pub fn dangerous_get_symmetric_key(
         &self,
         key_id: Ids::Symmetric,
-    ) -> Result<&SymmetricCryptoKey> {
+    ) -> Result<Cow<'_, SymmetricCryptoKey>> {
         self.get_symmetric_key(key_id)
     }
 
@@ -313,11 +311,14 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
     pub fn dangerous_get_asymmetric_key(
         &self,
         key_id: Ids::Asymmetric,
-    ) -> Result<&AsymmetricCryptoKey> {
+    ) -> Result<Cow<'_, AsymmetricCryptoKey>> {
         self.get_asymmetric_key(key_id)
     }
 
-    fn get_symmetric_key(&self, key_id: Ids::Symmetric) -> Result<&SymmetricCryptoKey> {
+    /// Retrieves a key from the local or global store. Depending on the store backend, the key is
+    /// either borrowed from the store or rebuilt from the protected key bytes, in which case the
+    /// copy is zeroized when it's dropped.
+    fn get_symmetric_key(&self, key_id: Ids::Symmetric) -> Result<Cow<'_, SymmetricCryptoKey>> {
         if key_id.is_local() {
             self.local_symmetric_keys.get(key_id)
         } else {
@@ -326,7 +327,7 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
         .ok_or_else(|| crate::CryptoError::MissingKeyId(format!("{key_id:?}")))
     }
 
-    fn get_asymmetric_key(&self, key_id: Ids::Asymmetric) -> Result<&AsymmetricCryptoKey> {
+    fn get_asymmetric_key(&self, key_id: Ids::Asymmetric) -> Result<Cow<'_, AsymmetricCryptoKey>> {
         if key_id.is_local() {
             self.local_asymmetric_keys.get(key_id)
         } else {
@@ -342,14 +343,13 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
         key: SymmetricCryptoKey,
     ) -> Result<()> {
         if key_id.is_local() {
-            self.local_symmetric_keys.upsert(key_id, key);
+            self.local_symmetric_keys.upsert(key_id, key)
         } else {
             self.global_keys
                 .get_mut()?
                 .symmetric_keys
-                .upsert(key_id, key);
+                .upsert(key_id, key)
         }
-        Ok(())
     }
 
     #[deprecated(note = "This function should ideally never be used outside this crate")]
@@ -359,14 +359,13 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
         key: AsymmetricCryptoKey,
     ) -> Result<()> {
         if key_id.is_local() {
-            self.local_asymmetric_keys.upsert(key_id, key);
+            self.local_asymmetric_keys.upsert(key_id, key)
         } else {
             self.global_keys
                 .get_mut()?
                 .asymmetric_keys
-                .upsert(key_id, key);
+                .upsert(key_id, key)
         }
-        Ok(())
     }
 
     pub(crate) fn decrypt_data_with_symmetric_key(
@@ -397,6 +396,10 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
                     crate::aes::decrypt_aes256_hmac(iv, mac, data.clone(), mac_key, &key.key)?;
                 Ok(dec)
             }
+            EncString::Aes256Gcm_B64 { nonce, data } => {
+                let dec = crate::aes::decrypt_aes256_gcm(nonce, data, &key.key)?;
+                Ok(dec)
+            }
         }
     }
 
@@ -406,11 +409,40 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
         data: &[u8],
     ) -> Result<EncString> {
         let key = self.get_symmetric_key(key)?;
-        EncString::encrypt_aes256_hmac(
-            data,
-            key.mac_key.as_ref().ok_or(CryptoError::InvalidMac)?,
-            &key.key,
-        )
+        match self.global_keys.get().symmetric_encryption_type {
+            SymmetricEncryptionType::AesCbc256_HmacSha256_B64 => EncString::encrypt_aes256_hmac(
+                data,
+                key.mac_key.as_ref().ok_or(CryptoError::InvalidMac)?,
+                &key.key,
+            ),
+            SymmetricEncryptionType::Aes256Gcm_B64 => {
+                Ok(EncString::encrypt_aes256_gcm(data, &key.key))
+            }
+        }
+    }
+
+    /// Creates a [std::io::Write] adapter that encrypts everything written to it with the provided
+    /// key, using the chunked streaming format. [EncryptingWriter::finish] must be called once all
+    /// the data has been written.
+    pub fn encrypt_stream<W: std::io::Write>(
+        &self,
+        key: Ids::Symmetric,
+        writer: W,
+    ) -> Result<EncryptingWriter<W>> {
+        let key = self.get_symmetric_key(key)?;
+        Ok(EncryptingWriter::new(&key.key, writer, DEFAULT_CHUNK_SIZE))
+    }
+
+    /// Creates a [std::io::Read] adapter that decrypts the data read from `reader` with the
+    /// provided key. Both the streaming format and legacy [EncString::from_buffer] payloads are
+    /// supported. The adapter holds the key as returned by the store, without copying it again.
+    pub fn decrypt_stream<R: std::io::Read>(
+        &self,
+        key: Ids::Symmetric,
+        reader: R,
+    ) -> Result<DecryptingReader<'_, R>> {
+        let key = self.get_symmetric_key(key)?;
+        Ok(DecryptingReader::new(key, reader))
     }
 
     pub(crate) fn decrypt_data_with_asymmetric_key(
@@ -419,21 +451,7 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
         data: &AsymmetricEncString,
     ) -> Result<Vec<u8>> {
         let key = self.get_asymmetric_key(key)?;
-
-        use AsymmetricEncString::*;
-        match data {
-            Rsa2048_OaepSha256_B64 { data } => key.key.decrypt(Oaep::new::<sha2::Sha256>(), data),
-            Rsa2048_OaepSha1_B64 { data } => key.key.decrypt(Oaep::new::<sha1::Sha1>(), data),
-            #[allow(deprecated)]
-            Rsa2048_OaepSha256_HmacSha256_B64 { data, .. } => {
-                key.key.decrypt(Oaep::new::<sha2::Sha256>(), data)
-            }
-            #[allow(deprecated)]
-            Rsa2048_OaepSha1_HmacSha256_B64 { data, .. } => {
-                key.key.decrypt(Oaep::new::<sha1::Sha1>(), data)
-            }
-        }
-        .map_err(|_| CryptoError::KeyDecrypt)
+        data.decrypt_with_key(&*key)
     }
 
     pub(crate) fn encrypt_data_with_asymmetric_key(
@@ -442,7 +460,7 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
         data: &[u8],
     ) -> Result<AsymmetricEncString> {
         let key = self.get_asymmetric_key(key)?;
-        AsymmetricEncString::encrypt_rsa2048_oaep_sha1(data, key)
+        AsymmetricEncString::encrypt(data, &*key)
     }
 }
 
@@ -451,8 +469,8 @@ impl<Ids: KeyIds> KeyStoreContext<'_, Ids> {
 mod tests {
     use crate::{
         store::{tests::DataView, KeyStore},
-        traits::tests::{TestIds, TestSymmKey},
-        Decryptable, Encryptable, SymmetricCryptoKey,
+        traits::tests::{TestAsymmKey, TestIds, TestSymmKey},
+        AsymmetricCryptoKey, AsymmetricEncString, Decryptable, Encryptable, SymmetricCryptoKey,
     };
 
     #[test]
@@ -522,4 +540,40 @@ mod tests {
         // Assert that the decrypted data is the same
         assert_eq!(decrypted1.0, decrypted2.0);
     }
+
+    #[test]
+    fn test_key_encryption_with_x25519_key() {
+        let mut rng = rand::thread_rng();
+        let store: KeyStore<TestIds> = KeyStore::default();
+
+        let mut ctx = store.context();
+
+        let private_key_id = TestAsymmKey::C("x25519");
+        ctx.set_asymmetric_key(
+            private_key_id,
+            AsymmetricCryptoKey::generate_x25519(&mut rng),
+        )
+        .unwrap();
+
+        let key_id = TestSymmKey::C(1);
+        ctx.set_symmetric_key(key_id, SymmetricCryptoKey::generate(&mut rng))
+            .unwrap();
+
+        let wrapped = ctx
+            .encrypt_symmetric_key_with_asymmetric_key(private_key_id, key_id)
+            .unwrap();
+        assert!(matches!(
+            wrapped,
+            AsymmetricEncString::X25519_HkdfSha256_Aes256Gcm_B64 { .. }
+        ));
+
+        let unwrapped_key_id = TestSymmKey::C(2);
+        ctx.decrypt_symmetric_key_with_asymmetric_key(private_key_id, unwrapped_key_id, &wrapped)
+            .unwrap();
+
+        let data = DataView("Hello, World!".to_string(), key_id);
+        let encrypted = data.encrypt(&mut ctx, key_id).unwrap();
+        let decrypted = encrypted.decrypt(&mut ctx, unwrapped_key_id).unwrap();
+        assert_eq!(decrypted.0, "Hello, World!");
+    }
 }
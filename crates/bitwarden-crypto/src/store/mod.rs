@@ -26,12 +26,12 @@ use std::sync::{Arc, RwLock};
 
 use rayon::prelude::*;
 
-use crate::{Decryptable, Encryptable, IdentifyKey, KeyId, KeyIds};
+use crate::{Decryptable, Encryptable, IdentifyKey, KeyId, KeyIds, SymmetricEncryptionType};
 
 mod backend;
 mod context;
 
-use backend::{create_store, StoreBackend};
+use backend::{create_local_store, create_store, StoreBackend};
 use context::GlobalKeys;
 pub use context::KeyStoreContext;
 
@@ -101,6 +101,7 @@ impl<Ids: KeyIds> std::fmt::Debug for KeyStore<Ids> {
 struct KeyStoreInner<Ids: KeyIds> {
     symmetric_keys: Box<dyn StoreBackend<Ids::Symmetric>>,
     asymmetric_keys: Box<dyn StoreBackend<Ids::Asymmetric>>,
+    symmetric_encryption_type: SymmetricEncryptionType,
 }
 
 /// Create a new key store with the best available implementation for the current platform.
@@ -110,6 +111,7 @@ impl<Ids: KeyIds> Default for KeyStore<Ids> {
             inner: Arc::new(RwLock::new(KeyStoreInner {
                 symmetric_keys: create_store(),
                 asymmetric_keys: create_store(),
+                symmetric_encryption_type: SymmetricEncryptionType::default(),
             })),
         }
     }
@@ -124,6 +126,13 @@ impl<Ids: KeyIds> KeyStore<Ids> {
         keys.asymmetric_keys.clear();
     }
 
+    /// Sets the algorithm used when encrypting new data with the symmetric keys in this store.
+    /// Existing data can always be decrypted, regardless of the algorithm it was encrypted with.
+    pub fn set_symmetric_encryption_type(&self, encryption_type: SymmetricEncryptionType) {
+        let mut keys = self.inner.write().expect("RwLock is poisoned");
+        keys.symmetric_encryption_type = encryption_type;
+    }
+
     /// <div class="warning">
     /// This is an advanced API, use with care. If you still need to use it, make sure you read this
     /// documentation to understand how to use it safely. </div>
@@ -162,8 +171,8 @@ impl<Ids: KeyIds> KeyStore<Ids> {
     pub fn context(&'_ self) -> KeyStoreContext<'_, Ids> {
         KeyStoreContext {
             global_keys: GlobalKeys::ReadOnly(self.inner.read().expect("RwLock is poisoned")),
-            local_symmetric_keys: create_store(),
-            local_asymmetric_keys: create_store(),
+            local_symmetric_keys: create_local_store(),
+            local_asymmetric_keys: create_local_store(),
             _phantom: std::marker::PhantomData,
         }
     }
@@ -186,8 +195,8 @@ impl<Ids: KeyIds> KeyStore<Ids> {
     pub fn context_mut(&'_ self) -> KeyStoreContext<'_, Ids> {
         KeyStoreContext {
             global_keys: GlobalKeys::ReadWrite(self.inner.write().expect("RwLock is poisoned")),
-            local_symmetric_keys: create_store(),
-            local_asymmetric_keys: create_store(),
+            local_symmetric_keys: create_local_store(),
+            local_asymmetric_keys: create_local_store(),
             _phantom: std::marker::PhantomData,
         }
     }
@@ -305,7 +314,7 @@ pub(crate) mod tests {
     use crate::{
         store::{KeyStore, KeyStoreContext},
         traits::tests::{TestIds, TestSymmKey},
-        EncString, SymmetricCryptoKey,
+        EncString, SymmetricCryptoKey, SymmetricEncryptionType,
     };
 
     pub struct DataView(pub String, pub TestSymmKey);
@@ -374,4 +383,29 @@ pub(crate) mod tests {
             assert_eq!(orig.1, dec.1);
         }
     }
+
+    #[test]
+    fn test_symmetric_encryption_type() {
+        let store: KeyStore<TestIds> = KeyStore::default();
+        #[allow(deprecated)]
+        store
+            .context_mut()
+            .set_symmetric_key(
+                TestSymmKey::A(0),
+                SymmetricCryptoKey::generate(rand::thread_rng()),
+            )
+            .unwrap();
+        let data = DataView("Hello, World!".to_string(), TestSymmKey::A(0));
+
+        let cbc = store.encrypt(DataView(data.0.clone(), data.1)).unwrap();
+        assert!(matches!(cbc.0, EncString::AesCbc256_HmacSha256_B64 { .. }));
+
+        store.set_symmetric_encryption_type(SymmetricEncryptionType::Aes256Gcm_B64);
+        let gcm = store.encrypt(DataView(data.0.clone(), data.1)).unwrap();
+        assert!(matches!(gcm.0, EncString::Aes256Gcm_B64 { .. }));
+
+        // Both variants remain decryptable regardless of the configured type
+        assert_eq!(store.decrypt(&cbc).unwrap().0, data.0);
+        assert_eq!(store.decrypt(&gcm).unwrap().0, data.0);
+    }
 }
@@ -0,0 +1,412 @@
+//! # Streaming encryption
+//!
+//! Contains a chunked, authenticated encryption format for large payloads like attachments and
+//! file sends, which allows encrypting and decrypting them without holding the whole file in
+//! memory.
+//!
+//! ## Format
+//!
+//! The stream starts with a header, followed by the encrypted chunks:
+//! - `[type]`: a single byte, [STREAM_ENC_TYPE].
+//! - `[chunk_size]`: the size of the plaintext chunks, as a big endian `u32`.
+//! - `[nonce_prefix]`: 7 random bytes.
+//!
+//! Every chunk is encrypted with AES-256-GCM and is followed by its 16 byte authentication tag.
+//! All chunks except the last one contain exactly `chunk_size` bytes of plaintext. The nonce of
+//! each chunk is the nonce prefix, followed by the chunk index as a big endian `u32` and a byte
+//! indicating whether it's the last chunk. The header is used as associated data for every chunk.
+//! This prevents chunks from being reordered, dropped or truncated without detection.
+//!
+//! Payloads that don't start with [STREAM_ENC_TYPE] are decrypted as a single
+//! [EncString::from_buffer] blob, so [DecryptingReader] can be used for legacy payloads as well.
+
+use std::{
+    borrow::Cow,
+    io::{self, Read, Write},
+};
+
+use aes::cipher::typenum::U32;
+use aes_gcm::{aead::AeadInPlace, Aes256Gcm};
+use generic_array::GenericArray;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::{CryptoError, EncString, KeyDecryptable, SymmetricCryptoKey};
+
+/// The type byte used at the start of a streaming payload. It doesn't overlap with any of the
+/// [EncString] types, which allows detecting legacy payloads.
+pub const STREAM_ENC_TYPE: u8 = 8;
+
+/// The default size of the plaintext chunks.
+pub const DEFAULT_CHUNK_SIZE: u32 = 64 * 1024;
+
+/// Chunks larger than this are rejected when decrypting, to avoid allocating unbounded buffers
+/// from an untrusted header.
+const MAX_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
+
+const HEADER_LEN: usize = 12;
+const NONCE_PREFIX_LEN: usize = 7;
+const TAG_LEN: usize = 16;
+
+fn new_cipher(key: &GenericArray<u8, U32>) -> Aes256Gcm {
+    <Aes256Gcm as aes_gcm::KeyInit>::new(key)
+}
+
+fn chunk_nonce(
+    prefix: &[u8; NONCE_PREFIX_LEN],
+    index: u32,
+    last: bool,
+) -> GenericArray<u8, aes_gcm::aead::consts::U12> {
+    let mut nonce = GenericArray::default();
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&index.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+fn invalid_stream() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, CryptoError::InvalidStream)
+}
+
+/// A [Write] adapter that encrypts everything written to it using the streaming format, and
+/// writes the result to the inner writer.
+///
+/// The output starts with a header containing [STREAM_ENC_TYPE], the chunk size and a random nonce
+/// prefix, followed by the chunks encrypted with AES-256-GCM. Each chunk is bound to its position
+/// and to whether it's the last one, so reordered, dropped or truncated chunks fail to decrypt.
+///
+/// [EncryptingWriter::finish] must be called once all the data has been written, otherwise the
+/// stream will be missing its last chunk and will fail to decrypt.
+pub struct EncryptingWriter<W: Write> {
+    inner: W,
+    cipher: Aes256Gcm,
+    header: [u8; HEADER_LEN],
+    header_written: bool,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    chunk_size: usize,
+    index: u32,
+    buffer: Zeroizing<Vec<u8>>,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    pub(crate) fn new(key: &GenericArray<u8, U32>, inner: W, chunk_size: u32) -> Self {
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce_prefix);
+
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = STREAM_ENC_TYPE;
+        header[1..5].copy_from_slice(&chunk_size.to_be_bytes());
+        header[5..].copy_from_slice(&nonce_prefix);
+
+        Self {
+            inner,
+            cipher: new_cipher(key),
+            header,
+            header_written: false,
+            nonce_prefix,
+            chunk_size: chunk_size as usize,
+            index: 0,
+            buffer: Zeroizing::new(Vec::with_capacity(chunk_size as usize + TAG_LEN)),
+        }
+    }
+
+    /// Encrypts and writes the last chunk, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_chunk(&mut self, last: bool) -> io::Result<()> {
+        if !self.header_written {
+            self.inner.write_all(&self.header)?;
+            self.header_written = true;
+        }
+
+        let nonce = chunk_nonce(&self.nonce_prefix, self.index, last);
+        self.cipher
+            .encrypt_in_place(&nonce, &self.header, &mut *self.buffer)
+            .map_err(|_| invalid_stream())?;
+        self.inner.write_all(&self.buffer)?;
+        self.buffer.clear();
+
+        self.index = self.index.checked_add(1).ok_or_else(invalid_stream)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        // The last chunk is only written on `finish`, so a full buffer is only flushed once we
+        // know more data follows it
+        if self.buffer.len() == self.chunk_size {
+            self.write_chunk(false)?;
+        }
+
+        let len = buf.len().min(self.chunk_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+enum DecryptingState {
+    /// The header hasn't been read yet.
+    Start,
+    Stream {
+        cipher: Box<Aes256Gcm>,
+        header: [u8; HEADER_LEN],
+        nonce_prefix: [u8; NONCE_PREFIX_LEN],
+        chunk_size: usize,
+        index: u32,
+        /// The first byte of the next chunk, read while checking for the end of the stream.
+        peeked: Option<u8>,
+    },
+    Done,
+}
+
+/// A [Read] adapter that decrypts a payload read from the inner reader. Both the streaming format
+/// written by [EncryptingWriter] and legacy [EncString::from_buffer] payloads are supported.
+///
+/// The key is the one returned by the [crate::KeyStoreContext] that created the reader, so no
+/// additional copy of it is made.
+pub struct DecryptingReader<'a, R: Read> {
+    inner: R,
+    key: Cow<'a, SymmetricCryptoKey>,
+    state: DecryptingState,
+    buffer: Zeroizing<Vec<u8>>,
+    position: usize,
+}
+
+impl<'a, R: Read> DecryptingReader<'a, R> {
+    pub(crate) fn new(key: Cow<'a, SymmetricCryptoKey>, inner: R) -> Self {
+        Self {
+            inner,
+            key,
+            state: DecryptingState::Start,
+            buffer: Zeroizing::new(Vec::new()),
+            position: 0,
+        }
+    }
+
+    /// Reads the header, or decrypts the whole payload if it's in the legacy format.
+    fn read_header(&mut self) -> io::Result<()> {
+        let mut enc_type = [0u8; 1];
+        if read_full(&mut self.inner, &mut enc_type)? == 0 {
+            return Err(invalid_stream());
+        }
+
+        if enc_type[0] != STREAM_ENC_TYPE {
+            let mut buf = enc_type.to_vec();
+            self.inner.read_to_end(&mut buf)?;
+
+            let enc_string = EncString::from_buffer(&buf).map_err(io_error)?;
+            let decrypted: Vec<u8> = enc_string.decrypt_with_key(&*self.key).map_err(io_error)?;
+            self.buffer = Zeroizing::new(decrypted);
+            self.position = 0;
+            self.state = DecryptingState::Done;
+            return Ok(());
+        }
+
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = STREAM_ENC_TYPE;
+        if read_full(&mut self.inner, &mut header[1..])? != HEADER_LEN - 1 {
+            return Err(invalid_stream());
+        }
+
+        let chunk_size = u32::from_be_bytes(header[1..5].try_into().expect("Valid length"));
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(invalid_stream());
+        }
+
+        self.state = DecryptingState::Stream {
+            cipher: Box::new(new_cipher(&self.key.key)),
+            header,
+            nonce_prefix: header[5..].try_into().expect("Valid length"),
+            chunk_size: chunk_size as usize,
+            index: 0,
+            peeked: None,
+        };
+        Ok(())
+    }
+
+    /// Reads and decrypts the next chunk into the buffer.
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let DecryptingState::Stream {
+            cipher,
+            header,
+            nonce_prefix,
+            chunk_size,
+            index,
+            peeked,
+        } = &mut self.state
+        else {
+            return Ok(());
+        };
+
+        let full_len = *chunk_size + TAG_LEN;
+        let mut chunk = Zeroizing::new(vec![0u8; full_len]);
+        let mut len = 0;
+        if let Some(byte) = peeked.take() {
+            chunk[0] = byte;
+            len = 1;
+        }
+        len += read_full(&mut self.inner, &mut chunk[len..])?;
+
+        // A chunk shorter than the chunk size is always the last one. A full chunk is the last one
+        // only if there's no more data after it.
+        let last = if len < full_len {
+            true
+        } else {
+            let mut next = [0u8; 1];
+            match read_full(&mut self.inner, &mut next)? {
+                0 => true,
+                _ => {
+                    *peeked = Some(next[0]);
+                    false
+                }
+            }
+        };
+
+        if len < TAG_LEN {
+            return Err(invalid_stream());
+        }
+        chunk.truncate(len);
+
+        let nonce = chunk_nonce(nonce_prefix, *index, last);
+        cipher
+            .decrypt_in_place(&nonce, header.as_slice(), &mut *chunk)
+            .map_err(|_| io_error(CryptoError::InvalidMac))?;
+
+        *index = index.checked_add(1).ok_or_else(invalid_stream)?;
+        if last {
+            self.state = DecryptingState::Done;
+        }
+
+        self.buffer = chunk;
+        self.position = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.position < self.buffer.len() {
+                let len = buf.len().min(self.buffer.len() - self.position);
+                buf[..len].copy_from_slice(&self.buffer[self.position..self.position + len]);
+                self.position += len;
+                return Ok(len);
+            }
+
+            match self.state {
+                DecryptingState::Start => self.read_header()?,
+                DecryptingState::Stream { .. } => self.read_chunk()?,
+                DecryptingState::Done => return Ok(0),
+            }
+        }
+    }
+}
+
+fn io_error(e: CryptoError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Reads into `buf` until it's full or the reader reaches EOF, returning the number of bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write};
+
+    use super::*;
+    use crate::KeyEncryptable;
+
+    fn encrypt(key: &SymmetricCryptoKey, data: &[u8], chunk_size: u32) -> Vec<u8> {
+        let mut writer = EncryptingWriter::new(&key.key, Vec::new(), chunk_size);
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(key: &SymmetricCryptoKey, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut reader = DecryptingReader::new(Cow::Borrowed(key), Cursor::new(data));
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted)?;
+        Ok(decrypted)
+    }
+
+    #[test]
+    fn test_roundtrip_chunk_boundaries() {
+        let key = SymmetricCryptoKey::generate(rand::thread_rng());
+
+        for len in [0, 1, 15, 16, 17, 32, 100] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let encrypted = encrypt(&key, &data, 16);
+
+            assert_eq!(encrypted[0], STREAM_ENC_TYPE);
+            assert_eq!(decrypt(&key, &encrypted).unwrap(), data, "length {len}");
+        }
+    }
+
+    #[test]
+    fn test_decrypt_legacy_buffer() {
+        let key = SymmetricCryptoKey::generate(rand::thread_rng());
+        let encrypted = b"Hello".as_slice().encrypt_with_key(&key).unwrap();
+
+        let decrypted = decrypt(&key, &encrypted.to_buffer().unwrap()).unwrap();
+        assert_eq!(decrypted, b"Hello");
+    }
+
+    #[test]
+    fn test_detects_truncation() {
+        let key = SymmetricCryptoKey::generate(rand::thread_rng());
+        let data = vec![7u8; 64];
+        let encrypted = encrypt(&key, &data, 16);
+
+        // Drop the last chunk, which leaves a stream ending in a full non-final chunk
+        let truncated = &encrypted[..encrypted.len() - (16 + TAG_LEN)];
+        assert!(decrypt(&key, truncated).is_err());
+
+        // A writer that is never finished doesn't produce a valid stream either
+        let mut writer = EncryptingWriter::new(&key.key, Vec::new(), 16);
+        writer.write_all(&data).unwrap();
+        let unfinished = std::mem::take(&mut writer.inner);
+        assert!(decrypt(&key, &unfinished).is_err());
+    }
+
+    #[test]
+    fn test_detects_tampering() {
+        let key = SymmetricCryptoKey::generate(rand::thread_rng());
+        let encrypted = encrypt(&key, &[1u8; 40], 16);
+
+        let mut tampered = encrypted.clone();
+        tampered[HEADER_LEN + 3] ^= 1;
+        assert!(decrypt(&key, &tampered).is_err());
+
+        // Changing the chunk size in the header invalidates every chunk
+        let mut tampered = encrypted.clone();
+        tampered[4] = 8;
+        assert!(decrypt(&key, &tampered).is_err());
+
+        let other_key = SymmetricCryptoKey::generate(rand::thread_rng());
+        assert!(decrypt(&other_key, &encrypted).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! # X25519 operations
+//!
+//! Contains the low level operations for encrypting data to an X25519 public key. The scheme is
+//! modeled after HPKE in base mode: an ephemeral key pair is generated for every message, and the
+//! Diffie-Hellman shared secret is expanded with HKDF-SHA256 into an AES-256-GCM key and nonce.
+//!
+//! In most cases you should use [AsymmetricEncString][crate::AsymmetricEncString] instead.
+
+use aes_gcm::{aead::Aead, Aes256Gcm};
+use generic_array::GenericArray;
+use hkdf::Hkdf;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::{
+    aes::decrypt_aes256_gcm,
+    error::{CryptoError, Result},
+};
+
+const HKDF_INFO: &[u8] = b"bitwarden-x25519-hkdf-sha256-aes256gcm";
+
+/// Derive the AES-256-GCM key and nonce for a message. Both public keys are bound into the HKDF
+/// salt, so a ciphertext can't be replayed against a different recipient.
+fn derive_key_and_nonce(
+    shared_secret: &[u8; 32],
+    ephemeral_public_key: &PublicKey,
+    recipient_public_key: &PublicKey,
+) -> (Zeroizing<[u8; 32]>, [u8; 12]) {
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public_key.as_bytes());
+    salt[32..].copy_from_slice(recipient_public_key.as_bytes());
+
+    let mut okm = Zeroizing::new([0u8; 44]);
+    Hkdf::<sha2::Sha256>::new(Some(&salt), shared_secret)
+        .expand(HKDF_INFO, okm.as_mut())
+        .expect("44 bytes is a valid HKDF-SHA256 output length");
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&okm[..32]);
+    let mut nonce = [0u8; 12];
+    nonce.copy_from_slice(&okm[32..]);
+
+    (key, nonce)
+}
+
+/// Encrypt data to an X25519 public key.
+///
+/// ## Returns
+///
+/// The ephemeral public key used for the key agreement, and the encrypted data with the
+/// authentication tag appended.
+pub(crate) fn encrypt_x25519(public_key: &PublicKey, data: &[u8]) -> Result<([u8; 32], Vec<u8>)> {
+    let ephemeral_secret = EphemeralSecret::random_from_rng(rand::thread_rng());
+    let ephemeral_public_key = PublicKey::from(&ephemeral_secret);
+
+    let shared_secret = ephemeral_secret.diffie_hellman(public_key);
+    // Low order public keys result in an all-zero shared secret
+    if !shared_secret.was_contributory() {
+        return Err(CryptoError::InvalidKey);
+    }
+
+    let (key, nonce) =
+        derive_key_and_nonce(shared_secret.as_bytes(), &ephemeral_public_key, public_key);
+    let data = <Aes256Gcm as aes_gcm::KeyInit>::new(GenericArray::from_slice(key.as_ref()))
+        .encrypt(&nonce.into(), data)
+        .expect("data is within the AES-GCM length limit");
+
+    Ok((ephemeral_public_key.to_bytes(), data))
+}
+
+/// Decrypt data that was encrypted to the public key of `private_key` using [encrypt_x25519].
+pub(crate) fn decrypt_x25519(
+    private_key: &StaticSecret,
+    ephemeral_public_key: &[u8; 32],
+    data: &[u8],
+) -> Result<Vec<u8>> {
+    let ephemeral_public_key = PublicKey::from(*ephemeral_public_key);
+    let shared_secret = private_key.diffie_hellman(&ephemeral_public_key);
+    if !shared_secret.was_contributory() {
+        return Err(CryptoError::KeyDecrypt);
+    }
+
+    let (key, nonce) = derive_key_and_nonce(
+        shared_secret.as_bytes(),
+        &ephemeral_public_key,
+        &PublicKey::from(private_key),
+    );
+    decrypt_aes256_gcm(&nonce, data, GenericArray::from_slice(key.as_ref()))
+        .map_err(|_| CryptoError::KeyDecrypt)
+}
+
+#[cfg(test)]
+mod tests {
+    use x25519_dalek::{PublicKey, StaticSecret};
+
+    use super::{decrypt_x25519, encrypt_x25519};
+    use crate::CryptoError;
+
+    #[test]
+    fn test_encrypt_decrypt_x25519() {
+        let private_key = StaticSecret::random_from_rng(rand::thread_rng());
+        let public_key = PublicKey::from(&private_key);
+
+        let (ephemeral_public_key, data) = encrypt_x25519(&public_key, b"Hello, world!").unwrap();
+        let decrypted = decrypt_x25519(&private_key, &ephemeral_public_key, &data).unwrap();
+        assert_eq!(decrypted, b"Hello, world!");
+
+        let other_key = StaticSecret::random_from_rng(rand::thread_rng());
+        assert!(matches!(
+            decrypt_x25519(&other_key, &ephemeral_public_key, &data),
+            Err(CryptoError::KeyDecrypt)
+        ));
+    }
+
+    #[test]
+    fn test_encrypt_x25519_rejects_low_order_key() {
+        let public_key = PublicKey::from([0u8; 32]);
+        assert!(matches!(
+            encrypt_x25519(&public_key, b"Hello, world!"),
+            Err(CryptoError::InvalidKey)
+        ));
+    }
+}
@@ -1,11 +1,15 @@
 use std::collections::HashMap;
 
-use csv::Writer;
+use chrono::Utc;
+use csv::{Reader, Writer};
 use serde::Serializer;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::{Cipher, CipherType, Field, Folder};
+use crate::{
+    Cipher, CipherType, Collection, Field, Folder, ImportingCipher, Login, LoginUri, SecureNote,
+    SecureNoteType,
+};
 
 #[derive(Debug, Error)]
 pub enum CsvError {
@@ -54,6 +58,131 @@ pub(crate) fn export_csv(folders: Vec<Folder>, ciphers: Vec<Cipher>) -> Result<S
     String::from_utf8(wtr.into_inner().map_err(|_| CsvError::Csv)?).map_err(|_| CsvError::Csv)
 }
 
+pub(crate) fn export_organization_csv(
+    collections: Vec<Collection>,
+    ciphers: Vec<Cipher>,
+) -> Result<String, CsvError> {
+    let collections: HashMap<Uuid, String> =
+        collections.into_iter().map(|c| (c.id, c.name)).collect();
+
+    let rows = ciphers
+        .into_iter()
+        .filter(|c| matches!(c.r#type, CipherType::Login(_) | CipherType::SecureNote(_)))
+        .map(|c| {
+            let login = if let CipherType::Login(l) = &c.r#type {
+                Some(l)
+            } else {
+                None
+            };
+
+            OrganizationCsvRow {
+                collections: c
+                    .collection_ids
+                    .iter()
+                    .filter_map(|id| collections.get(id))
+                    .map(|name| name.to_owned())
+                    .collect(),
+                r#type: c.r#type.to_string(),
+                name: c.name.to_owned(),
+                notes: c.notes.to_owned(),
+                fields: c.fields,
+                reprompt: c.reprompt,
+                login_uri: login
+                    .map(|l| l.login_uris.iter().flat_map(|l| l.uri.clone()).collect())
+                    .unwrap_or_default(),
+                login_username: login.and_then(|l| l.username.clone()),
+                login_password: login.and_then(|l| l.password.clone()),
+                login_totp: login.and_then(|l| l.totp.clone()),
+            }
+        });
+
+    let mut wtr = Writer::from_writer(vec![]);
+    for row in rows {
+        wtr.serialize(row).expect("Serialize should be infallible");
+    }
+
+    String::from_utf8(wtr.into_inner().map_err(|_| CsvError::Csv)?).map_err(|_| CsvError::Csv)
+}
+
+/// Parse a Bitwarden CSV export. Organization exports are supported as well, but the collections
+/// are not imported.
+///
+/// Folders are created for every distinct folder name referenced by the items.
+pub(crate) fn import_csv(payload: &str) -> Result<(Vec<Folder>, Vec<ImportingCipher>), CsvError> {
+    let mut rdr = Reader::from_reader(payload.as_bytes());
+
+    let mut folders: Vec<Folder> = vec![];
+    let mut ciphers = vec![];
+
+    for row in rdr.deserialize() {
+        let row: CsvImportRow = row.map_err(|_| CsvError::Csv)?;
+
+        let folder_id = match row.folder {
+            Some(name) => match folders.iter().find(|f| f.name == name) {
+                Some(folder) => Some(folder.id),
+                None => {
+                    let id = Uuid::new_v4();
+                    folders.push(Folder { id, name });
+                    Some(id)
+                }
+            },
+            None => None,
+        };
+
+        let r#type = match row.r#type.as_deref() {
+            Some("note") => CipherType::SecureNote(Box::new(SecureNote {
+                r#type: SecureNoteType::Generic,
+            })),
+            _ => CipherType::Login(Box::new(Login {
+                username: row.login_username,
+                password: row.login_password,
+                login_uris: row
+                    .login_uri
+                    .map(|uris| {
+                        uris.split(',')
+                            .map(|uri| LoginUri {
+                                uri: Some(uri.trim().to_owned()),
+                                r#match: None,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                totp: row.login_totp,
+                fido2_credentials: None,
+            })),
+        };
+
+        ciphers.push(ImportingCipher {
+            folder_id,
+            name: row.name.unwrap_or_else(|| "--".to_owned()),
+            notes: row.notes,
+            r#type,
+            favorite: row.favorite.as_deref() == Some("1"),
+            reprompt: row.reprompt.unwrap_or_default(),
+            fields: row.fields.as_deref().map(parse_fields).unwrap_or_default(),
+            revision_date: Utc::now(),
+            creation_date: Utc::now(),
+            deleted_date: None,
+        });
+    }
+
+    Ok((folders, ciphers))
+}
+
+/// Parse the `name: value` lines of the fields column into text fields.
+fn parse_fields(fields: &str) -> Vec<Field> {
+    fields
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .map(|(name, value)| Field {
+            name: Some(name.to_owned()),
+            value: Some(value.to_owned()),
+            r#type: 0,
+            linked_id: None,
+        })
+        .collect()
+}
+
 /// CSV export format. See <https://bitwarden.com/help/condition-bitwarden-import/#condition-a-csv>
 ///
 /// Be careful when changing this struct to maintain compatibility with old exports.
@@ -75,6 +204,43 @@ struct CsvRow {
     login_totp: Option<String>,
 }
 
+/// CSV export format for organizations, which lists the collections instead of the folder and
+/// favorite status of the item.
+///
+/// Be careful when changing this struct to maintain compatibility with old exports.
+#[derive(serde::Serialize)]
+struct OrganizationCsvRow {
+    #[serde(serialize_with = "vec_serialize")]
+    collections: Vec<String>,
+    r#type: String,
+    name: String,
+    notes: Option<String>,
+    #[serde(serialize_with = "fields_serialize")]
+    fields: Vec<Field>,
+    reprompt: u8,
+    #[serde(serialize_with = "vec_serialize")]
+    login_uri: Vec<String>,
+    login_username: Option<String>,
+    login_password: Option<String>,
+    login_totp: Option<String>,
+}
+
+/// A row of either [CsvRow] or [OrganizationCsvRow], as read when importing.
+#[derive(serde::Deserialize)]
+struct CsvImportRow {
+    folder: Option<String>,
+    favorite: Option<String>,
+    r#type: Option<String>,
+    name: Option<String>,
+    notes: Option<String>,
+    fields: Option<String>,
+    reprompt: Option<u8>,
+    login_uri: Option<String>,
+    login_username: Option<String>,
+    login_password: Option<String>,
+    login_totp: Option<String>,
+}
+
 fn vec_serialize<S>(x: &[String], s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -129,6 +295,8 @@ mod tests {
             Cipher {
                 id: "d55d65d7-c161-40a4-94ca-b0d20184d91a".parse().unwrap(),
                 folder_id: None,
+                organization_id: None,
+                collection_ids: vec![],
                 name: "test@bitwarden.com".to_string(),
                 notes: None,
                 r#type: CipherType::Login(Box::new(Login {
@@ -151,6 +319,8 @@ mod tests {
             Cipher {
                 id: "7dd81bd0-cc72-4f42-96e7-b0fc014e71a3".parse().unwrap(),
                 folder_id: Some("583e7665-0126-4d37-9139-b0d20184dd86".parse().unwrap()),
+                organization_id: None,
+                collection_ids: vec![],
                 name: "Steam Account".to_string(),
                 notes: None,
                 r#type: CipherType::Login(Box::new(Login {
@@ -196,12 +366,68 @@ mod tests {
         assert_eq!(csv, expected);
     }
 
+    #[test]
+    fn test_export_organization_csv() {
+        let collections = vec![
+            Collection {
+                id: "e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5".parse().unwrap(),
+                organization_id: "1bc9ac1e-f5aa-45f2-94bf-b181009709b8".parse().unwrap(),
+                name: "Engineering".to_string(),
+                external_id: None,
+            },
+            Collection {
+                id: "9dd2ba69-1d95-4e26-8f52-b18301109f4d".parse().unwrap(),
+                organization_id: "1bc9ac1e-f5aa-45f2-94bf-b181009709b8".parse().unwrap(),
+                name: "Shared".to_string(),
+                external_id: None,
+            },
+        ];
+        let ciphers = vec![Cipher {
+            id: "d55d65d7-c161-40a4-94ca-b0d20184d91a".parse().unwrap(),
+            folder_id: None,
+            organization_id: Some("1bc9ac1e-f5aa-45f2-94bf-b181009709b8".parse().unwrap()),
+            collection_ids: vec![
+                "e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5".parse().unwrap(),
+                "9dd2ba69-1d95-4e26-8f52-b18301109f4d".parse().unwrap(),
+            ],
+            name: "test@bitwarden.com".to_string(),
+            notes: None,
+            r#type: CipherType::Login(Box::new(Login {
+                username: Some("test@bitwarden.com".to_string()),
+                password: Some("Abc123".to_string()),
+                login_uris: vec![LoginUri {
+                    uri: Some("https://google.com".to_string()),
+                    r#match: None,
+                }],
+                totp: None,
+                fido2_credentials: None,
+            })),
+            favorite: false,
+            reprompt: 0,
+            fields: vec![],
+            revision_date: "2024-01-30T11:28:20.036Z".parse().unwrap(),
+            creation_date: "2024-01-30T11:28:20.036Z".parse().unwrap(),
+            deleted_date: None,
+        }];
+
+        let csv = export_organization_csv(collections, ciphers).unwrap();
+        let expected = [
+            "collections,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp",
+            "\"Engineering,Shared\",login,test@bitwarden.com,,,0,https://google.com,test@bitwarden.com,Abc123,",
+            "",
+        ].join("\n");
+
+        assert_eq!(csv, expected);
+    }
+
     #[test]
     fn test_export_ignore_card() {
         let folders = vec![];
         let ciphers = vec![Cipher {
             id: "d55d65d7-c161-40a4-94ca-b0d20184d91a".parse().unwrap(),
             folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
             name: "My Card".to_string(),
             notes: None,
             r#type: CipherType::Card(Box::new(Card {
@@ -231,6 +457,8 @@ mod tests {
         let ciphers = vec![Cipher {
             id: "d55d65d7-c161-40a4-94ca-b0d20184d91a".parse().unwrap(),
             folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
             name: "My Identity".to_string(),
             notes: None,
             r#type: CipherType::Identity(Box::new(Identity {
@@ -265,4 +493,92 @@ mod tests {
 
         assert_eq!(csv, "");
     }
+
+    #[test]
+    fn test_import_csv() {
+        let csv = [
+            "folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp",
+            ",,login,test@bitwarden.com,,,0,https://google.com,test@bitwarden.com,Abc123,",
+            "Test Folder B,1,login,Steam Account,,\"Test: v\nHidden: asdfer\",0,\"https://steampowered.com,https://store.steampowered.com\",steam,3Pvb8u7EfbV*nJ,steam://ABCD123",
+            "Test Folder B,,note,My note,Very secure!,,1,,,,",
+        ].join("\n");
+
+        let (folders, ciphers) = import_csv(&csv).unwrap();
+
+        assert_eq!(folders.len(), 1);
+        assert_eq!(folders[0].name, "Test Folder B");
+        assert_eq!(ciphers.len(), 3);
+
+        assert_eq!(ciphers[0].folder_id, None);
+        assert!(!ciphers[0].favorite);
+
+        let steam = &ciphers[1];
+        assert_eq!(steam.folder_id, Some(folders[0].id));
+        assert!(steam.favorite);
+        assert_eq!(steam.fields.len(), 2);
+        assert_eq!(steam.fields[1].name.as_deref(), Some("Hidden"));
+        assert_eq!(steam.fields[1].value.as_deref(), Some("asdfer"));
+        let CipherType::Login(login) = &steam.r#type else {
+            panic!("Expected login");
+        };
+        assert_eq!(login.username.as_deref(), Some("steam"));
+        assert_eq!(login.totp.as_deref(), Some("steam://ABCD123"));
+        assert_eq!(login.login_uris.len(), 2);
+
+        let note = &ciphers[2];
+        assert_eq!(note.folder_id, Some(folders[0].id));
+        assert_eq!(note.notes.as_deref(), Some("Very secure!"));
+        assert_eq!(note.reprompt, 1);
+        assert!(matches!(note.r#type, CipherType::SecureNote(_)));
+    }
+
+    #[test]
+    fn test_fields_round_trip() {
+        let ciphers = vec![Cipher {
+            id: "d55d65d7-c161-40a4-94ca-b0d20184d91a".parse().unwrap(),
+            folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
+            name: "Server".to_string(),
+            notes: None,
+            r#type: CipherType::SecureNote(Box::new(SecureNote {
+                r#type: SecureNoteType::Generic,
+            })),
+            favorite: false,
+            reprompt: 0,
+            fields: vec![Field {
+                name: Some("Connection".to_string()),
+                value: Some("host: example.com".to_string()),
+                r#type: 0,
+                linked_id: None,
+            }],
+            revision_date: "2024-01-30T11:28:20.036Z".parse().unwrap(),
+            creation_date: "2024-01-30T11:28:20.036Z".parse().unwrap(),
+            deleted_date: None,
+        }];
+
+        let csv = export_csv(vec![], ciphers).unwrap();
+        let (_, ciphers) = import_csv(&csv).unwrap();
+
+        assert_eq!(ciphers[0].fields.len(), 1);
+        assert_eq!(ciphers[0].fields[0].name.as_deref(), Some("Connection"));
+        assert_eq!(
+            ciphers[0].fields[0].value.as_deref(),
+            Some("host: example.com")
+        );
+    }
+
+    #[test]
+    fn test_import_organization_csv() {
+        let csv = [
+            "collections,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp",
+            "Test Collection,login,test@bitwarden.com,,,0,https://google.com,test@bitwarden.com,Abc123,",
+        ].join("\n");
+
+        let (folders, ciphers) = import_csv(&csv).unwrap();
+
+        assert!(folders.is_empty());
+        assert_eq!(ciphers.len(), 1);
+        assert_eq!(ciphers[0].name, "test@bitwarden.com");
+    }
 }
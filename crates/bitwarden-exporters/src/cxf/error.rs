@@ -7,6 +7,11 @@ pub enum CxfError {
     #[error("JSON error: {0}")]
     Serde(#[from] serde_json::Error),
 
+    #[error(transparent)]
+    SshKeyExport(#[from] bitwarden_ssh::error::SshKeyExportError),
+    #[error(transparent)]
+    SshKeyImport(#[from] bitwarden_ssh::error::SshKeyImportError),
+
     #[error("Internal error: {0}")]
     Internal(Cow<'static, str>),
 }
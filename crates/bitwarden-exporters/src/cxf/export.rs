@@ -1,11 +1,14 @@
 use bitwarden_vault::{Totp, TotpAlgorithm};
 use credential_exchange_types::format::{
-    Account as CxfAccount, Credential, Item, ItemType, NoteCredential, OTPHashAlgorithm,
-    TotpCredential,
+    Account as CxfAccount, Collection, Credential, Item, ItemType, LinkedItem, NoteCredential,
+    OTPHashAlgorithm, SshKeyCredential, TotpCredential,
 };
 use uuid::Uuid;
 
-use crate::{cxf::CxfError, Cipher, CipherType, Login};
+use crate::{
+    cxf::{fields::to_custom_fields, CxfError},
+    Cipher, CipherType, CxfExport, Folder, Login, SkippedItem,
+};
 
 /// Temporary struct to hold metadata related to current account
 ///
@@ -19,10 +22,46 @@ pub struct Account {
 }
 
 /// Builds a Credential Exchange Format (CXF) payload
-pub(crate) fn build_cxf(account: Account, ciphers: Vec<Cipher>) -> Result<String, CxfError> {
-    let items: Vec<Item> = ciphers
+///
+/// Folders are exported as collections linking to the items they contain. Ciphers which can't be
+/// converted, like SSH keys of an unsupported type, are left out and reported as skipped.
+pub(crate) fn build_cxf(
+    account: Account,
+    folders: Vec<Folder>,
+    ciphers: Vec<Cipher>,
+) -> Result<CxfExport, CxfError> {
+    let mut items: Vec<(Option<Uuid>, Item)> = vec![];
+    let mut skipped = vec![];
+    for cipher in ciphers {
+        let folder_id = cipher.folder_id;
+        let name = cipher.name.clone();
+
+        match Item::try_from(cipher) {
+            Ok(item) => items.push((folder_id, item)),
+            Err(e) => skipped.push(SkippedItem {
+                name,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    let collections = folders
         .into_iter()
-        .flat_map(|cipher| cipher.try_into())
+        .map(|folder| Collection {
+            id: folder.id.as_bytes().as_slice().into(),
+            title: folder.name,
+            subtitle: None,
+            items: items
+                .iter()
+                .filter(|(folder_id, _)| *folder_id == Some(folder.id))
+                .map(|(_, item)| LinkedItem {
+                    item: item.id.clone(),
+                    account: None,
+                })
+                .collect(),
+            sub_collections: None,
+            extensions: None,
+        })
         .collect();
 
     let account = CxfAccount {
@@ -31,29 +70,34 @@ pub(crate) fn build_cxf(account: Account, ciphers: Vec<Cipher>) -> Result<String
         email: account.email,
         full_name: account.name,
         icon: None,
-        collections: vec![], // TODO: Add support for folders
-        items,
+        collections,
+        items: items.into_iter().map(|(_, item)| item).collect(),
         extensions: None,
     };
 
-    Ok(serde_json::to_string(&account)?)
+    Ok(CxfExport {
+        payload: serde_json::to_string(&account)?,
+        skipped,
+    })
 }
 
 impl TryFrom<Cipher> for Item {
     type Error = CxfError;
 
     fn try_from(value: Cipher) -> Result<Self, Self::Error> {
-        let mut credentials: Vec<Credential> = value.r#type.clone().into();
+        let mut credentials: Vec<Credential> = value.r#type.clone().try_into()?;
 
         if let Some(note) = value.notes {
             credentials.push(Credential::Note(Box::new(NoteCredential { content: note })));
         }
 
+        credentials.extend(to_custom_fields(value.fields, value.reprompt));
+
         Ok(Self {
             id: value.id.as_bytes().as_slice().into(),
             creation_at: Some(value.creation_date.timestamp() as u64),
             modified_at: Some(value.revision_date.timestamp() as u64),
-            ty: value.r#type.try_into()?,
+            ty: value.r#type.into(),
             title: value.name,
             subtitle: None,
             favorite: Some(value.favorite),
@@ -64,37 +108,33 @@ impl TryFrom<Cipher> for Item {
     }
 }
 
-impl TryFrom<CipherType> for ItemType {
-    type Error = CxfError;
-
-    fn try_from(value: CipherType) -> Result<Self, Self::Error> {
+impl From<CipherType> for ItemType {
+    fn from(value: CipherType) -> Self {
         match value {
-            CipherType::Login(_) => Ok(ItemType::Login),
-            CipherType::Card(_) => Ok(ItemType::Identity),
-            CipherType::Identity(_) => Ok(ItemType::Identity),
-            CipherType::SecureNote(_) => Ok(ItemType::Document),
-            CipherType::SshKey(_) => {
-                // TODO(PM-15448): Add support for SSH Keys
-                Err(CxfError::Internal("Unsupported CipherType: SshKey".into()))
-            }
+            CipherType::Login(_) => ItemType::Login,
+            CipherType::Card(_) => ItemType::Document,
+            CipherType::Identity(_) => ItemType::Identity,
+            CipherType::SecureNote(_) => ItemType::Document,
+            CipherType::SshKey(_) => ItemType::Document,
         }
     }
 }
 
-impl From<CipherType> for Vec<Credential> {
-    fn from(value: CipherType) -> Self {
-        match value {
+impl TryFrom<CipherType> for Vec<Credential> {
+    type Error = CxfError;
+
+    fn try_from(value: CipherType) -> Result<Self, Self::Error> {
+        Ok(match value {
             CipherType::Login(login) => (*login).into(),
-            // TODO(PM-15450): Add support for credit cards.
             CipherType::Card(card) => (*card).into(),
-            // TODO(PM-15451): Add support for identities.
-            CipherType::Identity(_) => vec![],
+            CipherType::Identity(identity) => (*identity).into(),
             // Secure Notes only contains a note field which is handled by `TryFrom<Cipher> for
             // Item`.
             CipherType::SecureNote(_) => vec![],
-            // TODO(PM-15448): Add support for SSH Keys.
-            CipherType::SshKey(_) => vec![],
-        }
+            CipherType::SshKey(ssh_key) => vec![Credential::SshKey(Box::new(
+                SshKeyCredential::try_from(*ssh_key)?,
+            ))],
+        })
     }
 }
 
@@ -172,6 +212,8 @@ mod tests {
         let cipher = Cipher {
             id: "25c8c414-b446-48e9-a1bd-b10700bbd740".parse().unwrap(),
             folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+            organization_id: None,
+            collection_ids: vec![],
 
             name: "Bitwarden".to_string(),
             notes: Some("My note".to_string()),
@@ -253,7 +295,7 @@ mod tests {
         assert_eq!(item.tags, None);
         assert!(item.extensions.is_none());
 
-        assert_eq!(item.credentials.len(), 4);
+        assert_eq!(item.credentials.len(), 5);
 
         let credential = &item.credentials[0];
 
@@ -314,5 +356,186 @@ mod tests {
             }
             _ => panic!("Expected Credential::Passkey"),
         }
+
+        let credential = &item.credentials[4];
+
+        match credential {
+            Credential::CustomFields(custom_fields) => {
+                // The linked field has no value of its own and is left out
+                assert_eq!(custom_fields.fields.len(), 4);
+                assert_eq!(custom_fields.fields[0].label, Some("Text".to_string()));
+                assert_eq!(custom_fields.fields[0].field_type, FieldType::String);
+                assert_eq!(custom_fields.fields[0].value, "A");
+                assert_eq!(
+                    custom_fields.fields[1].field_type,
+                    FieldType::ConcealedString
+                );
+                assert_eq!(custom_fields.fields[2].field_type, FieldType::Boolean);
+                assert_eq!(custom_fields.fields[2].value, "true");
+            }
+            _ => panic!("Expected Credential::CustomFields"),
+        }
+    }
+
+    #[test]
+    fn test_build_cxf_folders() {
+        let folder_id: Uuid = "942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap();
+        let account = Account {
+            id: "e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5".parse().unwrap(),
+            email: "test@bitwarden.com".to_string(),
+            name: None,
+        };
+        let note = |id: &str, folder_id: Option<Uuid>| Cipher {
+            id: id.parse().unwrap(),
+            folder_id,
+            organization_id: None,
+            collection_ids: vec![],
+
+            name: "My secure note".to_string(),
+            notes: Some("Very secure!".to_string()),
+
+            r#type: CipherType::SecureNote(Box::new(crate::SecureNote {
+                r#type: crate::SecureNoteType::Generic,
+            })),
+
+            favorite: false,
+            reprompt: 0,
+
+            fields: vec![],
+
+            revision_date: "2024-01-30T11:25:25.466Z".parse().unwrap(),
+            creation_date: "2024-01-30T11:25:25.466Z".parse().unwrap(),
+            deleted_date: None,
+        };
+
+        let export = build_cxf(
+            account,
+            vec![Folder {
+                id: folder_id,
+                name: "Important".to_string(),
+            }],
+            vec![
+                note("23f0f877-42b1-4820-a850-b10700bc41eb", Some(folder_id)),
+                note("3ed8de45-48ee-4e26-a2dc-b10701276c53", None),
+            ],
+        )
+        .unwrap();
+
+        assert!(export.skipped.is_empty());
+        let account: CxfAccount = serde_json::from_str(&export.payload).unwrap();
+        assert_eq!(account.items.len(), 2);
+        assert_eq!(account.collections.len(), 1);
+
+        let collection = &account.collections[0];
+        assert_eq!(collection.title, "Important");
+        assert_eq!(collection.id.as_ref(), folder_id.as_bytes());
+        assert_eq!(collection.items.len(), 1);
+        assert_eq!(collection.items[0].item, account.items[0].id);
+    }
+
+    #[test]
+    fn test_identity_to_item() {
+        let cipher = Cipher {
+            id: "41cc3bc1-c3d9-4637-876c-b10701273712".parse().unwrap(),
+            folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
+
+            name: "My identity".to_string(),
+            notes: None,
+
+            r#type: CipherType::Identity(Box::new(crate::Identity {
+                title: None,
+                first_name: Some("John".to_string()),
+                middle_name: None,
+                last_name: Some("Doe".to_string()),
+                address1: None,
+                address2: None,
+                address3: None,
+                city: None,
+                state: None,
+                postal_code: None,
+                country: None,
+                company: None,
+                email: None,
+                phone: None,
+                ssn: None,
+                username: None,
+                passport_number: None,
+                license_number: None,
+            })),
+
+            favorite: false,
+            reprompt: 0,
+
+            fields: vec![],
+
+            revision_date: "2024-01-30T17:54:50.706Z".parse().unwrap(),
+            creation_date: "2024-01-30T17:54:50.706Z".parse().unwrap(),
+            deleted_date: None,
+        };
+
+        let item: Item = cipher.try_into().unwrap();
+
+        assert_eq!(item.ty, ItemType::Identity);
+        assert_eq!(item.credentials.len(), 1);
+        assert!(matches!(item.credentials[0], Credential::PersonName(_)));
+    }
+
+    #[test]
+    fn test_card_item_type() {
+        let card = crate::Card {
+            cardholder_name: Some("John Doe".to_string()),
+            exp_month: None,
+            exp_year: None,
+            code: None,
+            brand: None,
+            number: Some("4111111111111111".to_string()),
+        };
+
+        assert_eq!(
+            ItemType::from(CipherType::Card(Box::new(card))),
+            ItemType::Document
+        );
+    }
+
+    #[test]
+    fn test_build_cxf_skips_invalid_ssh_key() {
+        let account = Account {
+            id: "e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5".parse().unwrap(),
+            email: "test@bitwarden.com".to_string(),
+            name: None,
+        };
+        let cipher = Cipher {
+            id: "c8f7e0c4-2b36-4a5e-9c7a-b18301109bc6".parse().unwrap(),
+            folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
+
+            name: "My SSH key".to_string(),
+            notes: None,
+
+            r#type: CipherType::SshKey(Box::new(crate::SshKey {
+                private_key: "not a key".to_string(),
+                public_key: "ssh-ed25519 AAAA".to_string(),
+                fingerprint: "SHA256:AAAA".to_string(),
+            })),
+
+            favorite: false,
+            reprompt: 0,
+
+            fields: vec![],
+
+            revision_date: "2024-01-30T17:54:50.706Z".parse().unwrap(),
+            creation_date: "2024-01-30T17:54:50.706Z".parse().unwrap(),
+            deleted_date: None,
+        };
+
+        let export = build_cxf(account, vec![], vec![cipher]).unwrap();
+
+        let account: CxfAccount = serde_json::from_str(&export.payload).unwrap();
+        assert!(account.items.is_empty());
+        assert_eq!(export.skipped.len(), 1);
+        assert_eq!(export.skipped[0].name, "My SSH key");
     }
 }
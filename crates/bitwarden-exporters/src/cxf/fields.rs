@@ -0,0 +1,107 @@
+//! Custom field conversion
+//!
+//! Handles conversion between the internal custom [Field]s of a cipher and the credential exchange
+//! [CustomFieldsCredential]. The reprompt setting has no matching credential, and is stored as a
+//! labelled field of a separate [CustomFieldsCredential] so that it isn't imported back as a
+//! custom field.
+
+use credential_exchange_types::format::{
+    Credential, CustomFieldsCredential, EditableField, FieldType,
+};
+
+use crate::{cxf::login::random_id, Field};
+
+const BITWARDEN_LABEL: &str = "bitwarden";
+const REPROMPT_LABEL: &str = "reprompt";
+
+/// Convert the custom fields and the reprompt setting of a cipher into [Credential]s.
+pub(super) fn to_custom_fields(fields: Vec<Field>, reprompt: u8) -> Vec<Credential> {
+    let mut credentials = vec![];
+
+    let fields: Vec<EditableField> = fields
+        .into_iter()
+        .filter_map(|field| {
+            let field_type = match field.r#type {
+                0 => FieldType::String,
+                1 => FieldType::ConcealedString,
+                2 => FieldType::Boolean,
+                // Linked fields only refer to another value of the cipher
+                _ => return None,
+            };
+
+            Some(EditableField {
+                id: random_id(),
+                field_type,
+                value: field.value.unwrap_or_default(),
+                label: field.name,
+            })
+        })
+        .collect();
+
+    if !fields.is_empty() {
+        credentials.push(Credential::CustomFields(Box::new(CustomFieldsCredential {
+            id: None,
+            label: None,
+            fields,
+            extensions: None,
+        })));
+    }
+
+    if reprompt != 0 {
+        credentials.push(Credential::CustomFields(Box::new(CustomFieldsCredential {
+            id: None,
+            label: Some(BITWARDEN_LABEL.to_owned()),
+            fields: vec![EditableField {
+                id: random_id(),
+                field_type: FieldType::Number,
+                value: reprompt.to_string(),
+                label: Some(REPROMPT_LABEL.to_owned()),
+            }],
+            extensions: None,
+        })));
+    }
+
+    credentials
+}
+
+/// Convert the [CustomFieldsCredential]s of an item into custom fields and the reprompt setting.
+///
+/// Fields labelled with one of `skip_labels` are left out, as they were already imported into the
+/// cipher itself.
+pub(super) fn from_custom_fields(
+    custom_fields: &[CustomFieldsCredential],
+    skip_labels: &[&str],
+) -> (Vec<Field>, u8) {
+    let (bitwarden, custom_fields): (Vec<_>, Vec<_>) = custom_fields
+        .iter()
+        .partition(|c| c.label.as_deref() == Some(BITWARDEN_LABEL));
+
+    let reprompt = bitwarden
+        .iter()
+        .flat_map(|c| c.fields.iter())
+        .find(|f| f.label.as_deref() == Some(REPROMPT_LABEL))
+        .and_then(|f| f.value.parse().ok())
+        .unwrap_or_default();
+
+    let fields = custom_fields
+        .iter()
+        .flat_map(|c| c.fields.iter())
+        .filter(|f| {
+            !f.label
+                .as_deref()
+                .is_some_and(|label| skip_labels.contains(&label))
+        })
+        .map(|f| Field {
+            name: f.label.clone(),
+            value: Some(f.value.clone()),
+            r#type: match f.field_type {
+                FieldType::ConcealedString => 1,
+                FieldType::Boolean => 2,
+                _ => 0,
+            },
+            linked_id: None,
+        })
+        .collect();
+
+    (fields, reprompt)
+}
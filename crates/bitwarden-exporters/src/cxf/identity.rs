@@ -0,0 +1,336 @@
+//! Identity credential conversion
+//!
+//! Handles conversion between internal [Identity] and the credential exchange
+//! [PersonNameCredential], [AddressCredential], [PassportCredential], [DriversLicenseCredential]
+//! and [IdentityDocumentCredential]. Values without a matching credential, like the email, are
+//! stored as labelled fields of a [CustomFieldsCredential].
+//!
+//! The address lines are joined into a single street address for other importers, and also kept
+//! as separate labelled fields so that they are imported back into the same lines.
+
+use credential_exchange_types::format::{
+    AddressCredential, Credential, CustomFieldsCredential, DriversLicenseCredential, EditableField,
+    FieldType, IdentityDocumentCredential, PassportCredential, PersonNameCredential,
+};
+
+use crate::{cxf::login::random_id, Identity};
+
+const EMAIL_LABEL: &str = "email";
+const COMPANY_LABEL: &str = "company";
+const USERNAME_LABEL: &str = "username";
+const ADDRESS_LABELS: [&str; 3] = ["address1", "address2", "address3"];
+/// Labels of the fields read by [to_identity], which aren't imported as custom fields.
+pub(super) const IDENTITY_FIELD_LABELS: [&str; 6] = [
+    EMAIL_LABEL,
+    COMPANY_LABEL,
+    USERNAME_LABEL,
+    ADDRESS_LABELS[0],
+    ADDRESS_LABELS[1],
+    ADDRESS_LABELS[2],
+];
+
+impl From<Identity> for Vec<Credential> {
+    fn from(value: Identity) -> Self {
+        let full_name = join(
+            [&value.first_name, &value.middle_name, &value.last_name],
+            " ",
+        );
+
+        let mut credentials = vec![];
+
+        if value.title.is_some()
+            || value.first_name.is_some()
+            || value.middle_name.is_some()
+            || value.last_name.is_some()
+        {
+            credentials.push(Credential::PersonName(Box::new(PersonNameCredential {
+                title: value.title,
+                given: value.first_name,
+                given_informal: None,
+                given2: value.middle_name,
+                surname_prefix: None,
+                surname: value.last_name,
+                surname2: None,
+                credentials: None,
+                generation: None,
+            })));
+        }
+
+        let street_address = join([&value.address1, &value.address2, &value.address3], "\n");
+        if street_address.is_some()
+            || value.city.is_some()
+            || value.state.is_some()
+            || value.postal_code.is_some()
+            || value.country.is_some()
+            || value.phone.is_some()
+        {
+            credentials.push(Credential::Address(Box::new(AddressCredential {
+                street_address,
+                postal_code: value.postal_code,
+                city: value.city,
+                territory: value.state,
+                country: value.country,
+                tel: value.phone,
+            })));
+        }
+
+        if let Some(passport_number) = value.passport_number {
+            credentials.push(Credential::Passport(Box::new(PassportCredential {
+                issuing_country: None,
+                passport_type: None,
+                passport_number: Some(passport_number),
+                national_identification_number: None,
+                nationality: None,
+                full_name: full_name.clone(),
+                birth_date: None,
+                birth_place: None,
+                sex: None,
+                issue_date: None,
+                expiry_date: None,
+                issuing_authority: None,
+            })));
+        }
+
+        if let Some(license_number) = value.license_number {
+            credentials.push(Credential::DriversLicense(Box::new(
+                DriversLicenseCredential {
+                    full_name: full_name.clone(),
+                    birth_date: None,
+                    issue_date: None,
+                    expiry_date: None,
+                    issuing_authority: None,
+                    territory: None,
+                    country: None,
+                    license_number: Some(license_number),
+                    license_class: None,
+                },
+            )));
+        }
+
+        if let Some(ssn) = value.ssn {
+            credentials.push(Credential::IdentityDocument(Box::new(
+                IdentityDocumentCredential {
+                    issuing_country: None,
+                    document_number: None,
+                    identification_number: Some(ssn),
+                    nationality: None,
+                    full_name,
+                    birth_date: None,
+                    birth_place: None,
+                    sex: None,
+                    issue_date: None,
+                    expiry_date: None,
+                    issuing_authority: None,
+                },
+            )));
+        }
+
+        let fields: Vec<EditableField> = [
+            (EMAIL_LABEL, FieldType::Email, value.email),
+            (COMPANY_LABEL, FieldType::String, value.company),
+            (USERNAME_LABEL, FieldType::String, value.username),
+            (ADDRESS_LABELS[0], FieldType::String, value.address1),
+            (ADDRESS_LABELS[1], FieldType::String, value.address2),
+            (ADDRESS_LABELS[2], FieldType::String, value.address3),
+        ]
+        .into_iter()
+        .filter_map(|(label, field_type, value)| {
+            value.map(|value| EditableField {
+                id: random_id(),
+                field_type,
+                value,
+                label: Some(label.to_owned()),
+            })
+        })
+        .collect();
+
+        if !fields.is_empty() {
+            credentials.push(Credential::CustomFields(Box::new(CustomFieldsCredential {
+                id: None,
+                label: None,
+                fields,
+                extensions: None,
+            })));
+        }
+
+        credentials
+    }
+}
+
+pub(super) fn to_identity(
+    person_name: Option<&PersonNameCredential>,
+    address: Option<&AddressCredential>,
+    passport: Option<&PassportCredential>,
+    drivers_license: Option<&DriversLicenseCredential>,
+    identity_document: Option<&IdentityDocumentCredential>,
+    custom_fields: &[CustomFieldsCredential],
+) -> Identity {
+    let field = |label: &str| {
+        custom_fields
+            .iter()
+            .flat_map(|c| c.fields.iter())
+            .find(|f| f.label.as_deref() == Some(label))
+            .map(|f| f.value.clone())
+    };
+
+    // Prefer the separate address lines of our own exports, which are exact, over splitting the
+    // street address
+    let [address1, address2, address3] = ADDRESS_LABELS.map(field);
+    let (address1, address2, address3) =
+        if address1.is_some() || address2.is_some() || address3.is_some() {
+            (address1, address2, address3)
+        } else {
+            let mut street_address = address
+                .and_then(|a| a.street_address.as_deref())
+                .map(|s| s.lines().map(|l| l.to_owned()).collect::<Vec<_>>())
+                .unwrap_or_default()
+                .into_iter();
+
+            (
+                street_address.next(),
+                street_address.next(),
+                join_lines(street_address),
+            )
+        };
+
+    Identity {
+        title: person_name.and_then(|p| p.title.clone()),
+        first_name: person_name.and_then(|p| p.given.clone()),
+        middle_name: person_name.and_then(|p| p.given2.clone()),
+        last_name: person_name.and_then(|p| p.surname.clone()),
+        address1,
+        address2,
+        address3,
+        city: address.and_then(|a| a.city.clone()),
+        state: address.and_then(|a| a.territory.clone()),
+        postal_code: address.and_then(|a| a.postal_code.clone()),
+        country: address.and_then(|a| a.country.clone()),
+        company: field(COMPANY_LABEL),
+        email: field(EMAIL_LABEL),
+        phone: address.and_then(|a| a.tel.clone()),
+        ssn: identity_document.and_then(|d| d.identification_number.clone()),
+        username: field(USERNAME_LABEL),
+        passport_number: passport.and_then(|p| p.passport_number.clone()),
+        license_number: drivers_license.and_then(|d| d.license_number.clone()),
+    }
+}
+
+/// Join the present values with the separator, returning `None` if there are none.
+fn join<'a>(
+    values: impl IntoIterator<Item = &'a Option<String>>,
+    separator: &str,
+) -> Option<String> {
+    let values: Vec<&str> = values.into_iter().flatten().map(|v| v.as_str()).collect();
+
+    (!values.is_empty()).then(|| values.join(separator))
+}
+
+/// Any address lines beyond the third are kept together in the last address field.
+fn join_lines(lines: impl Iterator<Item = String>) -> Option<String> {
+    let lines: Vec<String> = lines.collect();
+
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity() -> Identity {
+        Identity {
+            title: Some("Mr".to_string()),
+            first_name: Some("John".to_string()),
+            middle_name: None,
+            last_name: Some("Doe".to_string()),
+            address1: Some("Main Street 1".to_string()),
+            address2: Some("Apartment 2".to_string()),
+            address3: None,
+            city: Some("Springfield".to_string()),
+            state: Some("IL".to_string()),
+            postal_code: Some("12345".to_string()),
+            country: Some("US".to_string()),
+            company: Some("Bitwarden".to_string()),
+            email: Some("john@example.com".to_string()),
+            phone: Some("+1 555 0100".to_string()),
+            ssn: Some("123-45-6789".to_string()),
+            username: Some("JDoe".to_string()),
+            passport_number: Some("P1234567".to_string()),
+            license_number: Some("D1234567".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_identity_to_credentials() {
+        let credentials: Vec<Credential> = identity().into();
+
+        assert_eq!(credentials.len(), 6);
+
+        match &credentials[0] {
+            Credential::PersonName(name) => {
+                assert_eq!(name.title.as_deref(), Some("Mr"));
+                assert_eq!(name.given.as_deref(), Some("John"));
+                assert_eq!(name.given2, None);
+                assert_eq!(name.surname.as_deref(), Some("Doe"));
+            }
+            _ => panic!("Expected Credential::PersonName"),
+        }
+
+        match &credentials[1] {
+            Credential::Address(address) => {
+                assert_eq!(
+                    address.street_address.as_deref(),
+                    Some("Main Street 1\nApartment 2")
+                );
+                assert_eq!(address.territory.as_deref(), Some("IL"));
+                assert_eq!(address.tel.as_deref(), Some("+1 555 0100"));
+            }
+            _ => panic!("Expected Credential::Address"),
+        }
+
+        match &credentials[2] {
+            Credential::Passport(passport) => {
+                assert_eq!(passport.passport_number.as_deref(), Some("P1234567"));
+                assert_eq!(passport.full_name.as_deref(), Some("John Doe"));
+            }
+            _ => panic!("Expected Credential::Passport"),
+        }
+
+        match &credentials[5] {
+            Credential::CustomFields(custom_fields) => {
+                assert_eq!(custom_fields.fields.len(), 5);
+                assert_eq!(custom_fields.fields[0].field_type, FieldType::Email);
+                assert_eq!(custom_fields.fields[0].value, "john@example.com");
+            }
+            _ => panic!("Expected Credential::CustomFields"),
+        }
+    }
+
+    #[test]
+    fn test_identity_address_roundtrip() {
+        let identity = Identity {
+            address1: None,
+            address2: Some("Building 3\nFloor 2".to_string()),
+            address3: Some("Room 4".to_string()),
+            ..identity()
+        };
+
+        let credentials: Vec<Credential> = identity.clone().into();
+        let address = credentials.iter().find_map(|c| match c {
+            Credential::Address(address) => Some(address.as_ref()),
+            _ => None,
+        });
+        let custom_fields: Vec<CustomFieldsCredential> = credentials
+            .iter()
+            .filter_map(|c| match c {
+                Credential::CustomFields(custom_fields) => Some(custom_fields.as_ref().clone()),
+                _ => None,
+            })
+            .collect();
+
+        let imported = to_identity(None, address, None, None, None, &custom_fields);
+
+        assert_eq!(imported.address1, identity.address1);
+        assert_eq!(imported.address2, identity.address2);
+        assert_eq!(imported.address3, identity.address3);
+    }
+}
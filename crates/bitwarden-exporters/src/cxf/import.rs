@@ -1,20 +1,80 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use credential_exchange_types::format::{
-    Account as CxfAccount, BasicAuthCredential, Credential, CreditCardCredential, Item,
-    PasskeyCredential,
+    Account as CxfAccount, AddressCredential, BasicAuthCredential, Collection, Credential,
+    CreditCardCredential, CustomFieldsCredential, DriversLicenseCredential,
+    IdentityDocumentCredential, Item, NoteCredential, PasskeyCredential, PassportCredential,
+    PersonNameCredential, SshKeyCredential,
 };
+use uuid::Uuid;
 
 use crate::{
-    cxf::{login::to_login, CxfError},
-    CipherType, ImportingCipher,
+    cxf::{
+        fields::from_custom_fields,
+        identity::{to_identity, IDENTITY_FIELD_LABELS},
+        login::to_login,
+        CxfError,
+    },
+    importers::ImportResult,
+    CipherType, Folder, ImportingCipher, SecureNote, SecureNoteType,
 };
 
-pub(crate) fn parse_cxf(payload: String) -> Result<Vec<ImportingCipher>, CxfError> {
+/// Parse a Credential Exchange Format (CXF) payload.
+///
+/// Collections are imported as folders, with sub collections using the `Parent/Child` naming of
+/// nested folders. Items which can't be converted are reported as skipped.
+pub(crate) fn parse_cxf(payload: String) -> Result<ImportResult, CxfError> {
     let account: CxfAccount = serde_json::from_str(&payload)?;
 
-    let items: Vec<ImportingCipher> = account.items.into_iter().flat_map(parse_item).collect();
+    let mut result = ImportResult::default();
+    let mut item_folders = HashMap::new();
+    for collection in &account.collections {
+        parse_collection(collection, None, &mut result.folders, &mut item_folders);
+    }
+
+    for item in account.items {
+        let folder_id = item_folders.get(&item.id.to_string()).copied();
+        let title = item.title.clone();
 
-    Ok(items)
+        match parse_item(item, folder_id) {
+            Ok(ciphers) => result.ciphers.extend(ciphers),
+            Err(e) => result.skip(title, e.to_string()),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Convert a collection and its sub collections into folders, keeping track of the folder of each
+/// linked item. Items linked from multiple collections end up in the first one.
+fn parse_collection(
+    collection: &Collection,
+    parent: Option<&str>,
+    folders: &mut Vec<Folder>,
+    item_folders: &mut HashMap<String, Uuid>,
+) {
+    let name = match parent {
+        Some(parent) => format!("{}/{}", parent, collection.title),
+        None => collection.title.clone(),
+    };
+    let id = Uuid::from_slice(collection.id.as_ref()).unwrap_or_else(|_| Uuid::new_v4());
+
+    for linked_item in &collection.items {
+        item_folders
+            .entry(linked_item.item.to_string())
+            .or_insert(id);
+    }
+
+    // Parents come before their children, so they exist when the children are created
+    folders.push(Folder {
+        id,
+        name: name.clone(),
+    });
+
+    for sub_collection in collection.sub_collections.iter().flatten() {
+        parse_collection(sub_collection, Some(&name), folders, item_folders);
+    }
 }
 
 /// Convert a CXF timestamp to a DateTime<Utc>.
@@ -25,13 +85,15 @@ fn convert_date(ts: Option<u64>) -> DateTime<Utc> {
         .unwrap_or(Utc::now())
 }
 
-fn parse_item(value: Item) -> Vec<ImportingCipher> {
+fn parse_item(value: Item, folder_id: Option<Uuid>) -> Result<Vec<ImportingCipher>, CxfError> {
     let grouped = group_credentials_by_type(value.credentials);
 
     let creation_date = convert_date(value.creation_at);
     let revision_date = convert_date(value.modified_at);
 
-    let mut output = vec![];
+    let notes = grouped.note.first().map(|n| n.content.clone());
+
+    let mut types = vec![];
 
     // Login credentials
     if !grouped.basic_auth.is_empty() || !grouped.passkey.is_empty() {
@@ -40,41 +102,65 @@ fn parse_item(value: Item) -> Vec<ImportingCipher> {
 
         let login = to_login(creation_date, basic_auth, passkey);
 
-        output.push(ImportingCipher {
-            folder_id: None, // TODO: Handle folders
-            name: value.title.clone(),
-            notes: None,
-            r#type: CipherType::Login(Box::new(login)),
-            favorite: false,
-            reprompt: 0,
-            fields: vec![],
-            revision_date,
-            creation_date,
-            deleted_date: None,
-        })
+        types.push(CipherType::Login(Box::new(login)));
+    }
+
+    if let Some(credit_card) = grouped.credit_card.first() {
+        types.push(CipherType::Card(Box::new(credit_card.into())));
+    }
+
+    let is_identity = !grouped.person_name.is_empty()
+        || !grouped.address.is_empty()
+        || !grouped.passport.is_empty()
+        || !grouped.drivers_license.is_empty()
+        || !grouped.identity_document.is_empty();
+    if is_identity {
+        let identity = to_identity(
+            grouped.person_name.first(),
+            grouped.address.first(),
+            grouped.passport.first(),
+            grouped.drivers_license.first(),
+            grouped.identity_document.first(),
+            &grouped.custom_fields,
+        );
+
+        types.push(CipherType::Identity(Box::new(identity)));
+    }
+
+    if let Some(ssh_key) = grouped.ssh_key.first() {
+        types.push(CipherType::SshKey(Box::new(ssh_key.try_into()?)));
     }
 
-    if !grouped.credit_card.is_empty() {
-        let credit_card = grouped
-            .credit_card
-            .first()
-            .expect("Credit card is not empty");
+    // The identity fields were already imported into the identity itself
+    let skip_labels: &[&str] = if is_identity {
+        &IDENTITY_FIELD_LABELS
+    } else {
+        &[]
+    };
+    let (fields, reprompt) = from_custom_fields(&grouped.custom_fields, skip_labels);
+
+    // Items with only a note or custom fields are imported as secure notes
+    if types.is_empty() && (notes.is_some() || !fields.is_empty()) {
+        types.push(CipherType::SecureNote(Box::new(SecureNote {
+            r#type: SecureNoteType::Generic,
+        })));
+    }
 
-        output.push(ImportingCipher {
-            folder_id: None, // TODO: Handle folders
+    Ok(types
+        .into_iter()
+        .map(|r#type| ImportingCipher {
+            folder_id,
             name: value.title.clone(),
-            notes: None,
-            r#type: CipherType::Card(Box::new(credit_card.into())),
-            favorite: false,
-            reprompt: 0,
-            fields: vec![],
+            notes: notes.clone(),
+            r#type,
+            favorite: value.favorite.unwrap_or_default(),
+            reprompt,
+            fields: fields.clone(),
             revision_date,
             creation_date,
             deleted_date: None,
         })
-    }
-
-    output
+        .collect())
 }
 
 /// Group credentials by type.
@@ -107,6 +193,38 @@ fn group_credentials_by_type(credentials: Vec<Credential>) -> GroupedCredentials
             Credential::CreditCard(credit_card) => Some(credit_card.as_ref()),
             _ => None,
         }),
+        note: filter_credentials(&credentials, |c| match c {
+            Credential::Note(note) => Some(note.as_ref()),
+            _ => None,
+        }),
+        person_name: filter_credentials(&credentials, |c| match c {
+            Credential::PersonName(person_name) => Some(person_name.as_ref()),
+            _ => None,
+        }),
+        address: filter_credentials(&credentials, |c| match c {
+            Credential::Address(address) => Some(address.as_ref()),
+            _ => None,
+        }),
+        passport: filter_credentials(&credentials, |c| match c {
+            Credential::Passport(passport) => Some(passport.as_ref()),
+            _ => None,
+        }),
+        drivers_license: filter_credentials(&credentials, |c| match c {
+            Credential::DriversLicense(drivers_license) => Some(drivers_license.as_ref()),
+            _ => None,
+        }),
+        identity_document: filter_credentials(&credentials, |c| match c {
+            Credential::IdentityDocument(identity_document) => Some(identity_document.as_ref()),
+            _ => None,
+        }),
+        custom_fields: filter_credentials(&credentials, |c| match c {
+            Credential::CustomFields(custom_fields) => Some(custom_fields.as_ref()),
+            _ => None,
+        }),
+        ssh_key: filter_credentials(&credentials, |c| match c {
+            Credential::SshKey(ssh_key) => Some(ssh_key.as_ref()),
+            _ => None,
+        }),
     }
 }
 
@@ -114,13 +232,24 @@ struct GroupedCredentials {
     basic_auth: Vec<BasicAuthCredential>,
     passkey: Vec<PasskeyCredential>,
     credit_card: Vec<CreditCardCredential>,
+    note: Vec<NoteCredential>,
+    person_name: Vec<PersonNameCredential>,
+    address: Vec<AddressCredential>,
+    passport: Vec<PassportCredential>,
+    drivers_license: Vec<DriversLicenseCredential>,
+    identity_document: Vec<IdentityDocumentCredential>,
+    custom_fields: Vec<CustomFieldsCredential>,
+    ssh_key: Vec<SshKeyCredential>,
 }
 
 #[cfg(test)]
 mod tests {
     use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
     use chrono::Duration;
-    use credential_exchange_types::format::{CreditCardCredential, ItemType};
+    use credential_exchange_types::{
+        format::{EditableField, FieldType, ItemType, LinkedItem},
+        B64Url,
+    };
 
     use super::*;
 
@@ -156,7 +285,7 @@ mod tests {
             extensions: None,
         };
 
-        let ciphers: Vec<ImportingCipher> = parse_item(item);
+        let ciphers: Vec<ImportingCipher> = parse_item(item, None).unwrap();
         assert_eq!(ciphers.len(), 0);
     }
 
@@ -199,7 +328,7 @@ mod tests {
             extensions: None,
         };
 
-        let ciphers: Vec<ImportingCipher> = parse_item(item);
+        let ciphers: Vec<ImportingCipher> = parse_item(item, None).unwrap();
         assert_eq!(ciphers.len(), 1);
         let cipher = ciphers.first().unwrap();
 
@@ -263,7 +392,7 @@ mod tests {
             extensions: None,
         };
 
-        let ciphers: Vec<ImportingCipher> = parse_item(item);
+        let ciphers: Vec<ImportingCipher> = parse_item(item, None).unwrap();
         assert_eq!(ciphers.len(), 1);
         let cipher = ciphers.first().unwrap();
 
@@ -282,4 +411,237 @@ mod tests {
         assert_eq!(card.brand, Some("Mastercard".to_string()));
         assert_eq!(card.number, Some("1234 5678 9012 3456".to_string()));
     }
+
+    #[test]
+    fn test_parse_collections() {
+        let folder_id: Uuid = "942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap();
+        let item_id: B64Url = [0, 1, 2, 3, 4, 5, 6].as_ref().into();
+
+        let account = CxfAccount {
+            id: [0, 1, 2].as_ref().into(),
+            user_name: "".to_string(),
+            email: "test@bitwarden.com".to_string(),
+            full_name: None,
+            icon: None,
+            collections: vec![Collection {
+                id: folder_id.as_bytes().as_slice().into(),
+                title: "Work".to_string(),
+                subtitle: None,
+                items: vec![],
+                sub_collections: Some(vec![Collection {
+                    id: [7, 8, 9].as_ref().into(),
+                    title: "Servers".to_string(),
+                    subtitle: None,
+                    items: vec![LinkedItem {
+                        item: item_id.clone(),
+                        account: None,
+                    }],
+                    sub_collections: None,
+                    extensions: None,
+                }]),
+                extensions: None,
+            }],
+            items: vec![Item {
+                id: item_id,
+                creation_at: Some(1706613834),
+                modified_at: Some(1706623773),
+                ty: ItemType::Document,
+                title: "My note".to_string(),
+                subtitle: None,
+                favorite: Some(true),
+                credentials: vec![Credential::Note(Box::new(NoteCredential {
+                    content: "Very secure!".to_string(),
+                }))],
+                tags: None,
+                extensions: None,
+            }],
+            extensions: None,
+        };
+
+        let ImportResult {
+            folders, ciphers, ..
+        } = parse_cxf(serde_json::to_string(&account).unwrap()).unwrap();
+
+        assert_eq!(folders.len(), 2);
+        assert_eq!(folders[0].name, "Work");
+        assert_eq!(folders[1].name, "Work/Servers");
+        let work = folders.iter().find(|f| f.name == "Work").unwrap();
+        assert_eq!(work.id, folder_id);
+        let servers = folders.iter().find(|f| f.name == "Work/Servers").unwrap();
+
+        assert_eq!(ciphers.len(), 1);
+        let cipher = &ciphers[0];
+        assert_eq!(cipher.folder_id, Some(servers.id));
+        assert_eq!(cipher.notes, Some("Very secure!".to_string()));
+        assert!(cipher.favorite);
+        assert!(matches!(cipher.r#type, CipherType::SecureNote(_)));
+    }
+
+    #[test]
+    fn test_parse_invalid_ssh_key_is_skipped() {
+        let account = CxfAccount {
+            id: [0, 1, 2].as_ref().into(),
+            user_name: "".to_string(),
+            email: "test@bitwarden.com".to_string(),
+            full_name: None,
+            icon: None,
+            collections: vec![],
+            items: vec![Item {
+                id: [0, 1, 2, 3, 4, 5, 6].as_ref().into(),
+                creation_at: Some(1706613834),
+                modified_at: Some(1706623773),
+                ty: ItemType::Document,
+                title: "My SSH key".to_string(),
+                subtitle: None,
+                favorite: None,
+                credentials: vec![Credential::SshKey(Box::new(SshKeyCredential {
+                    key_type: "ssh-ed25519".to_string(),
+                    private_key: [1, 2, 3].as_ref().into(),
+                    key_comment: None,
+                    creation_date: None,
+                    expiry_date: None,
+                    key_generation_source: None,
+                }))],
+                tags: None,
+                extensions: None,
+            }],
+            extensions: None,
+        };
+
+        let result = parse_cxf(serde_json::to_string(&account).unwrap()).unwrap();
+
+        assert!(result.ciphers.is_empty());
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].name, "My SSH key");
+    }
+
+    #[test]
+    fn test_parse_identity() {
+        let item = Item {
+            id: [0, 1, 2, 3, 4, 5, 6].as_ref().into(),
+            creation_at: Some(1706613834),
+            modified_at: Some(1706623773),
+            ty: ItemType::Identity,
+            title: "My identity".to_string(),
+            subtitle: None,
+            favorite: None,
+            credentials: vec![
+                Credential::PersonName(Box::new(PersonNameCredential {
+                    title: Some("Mr".to_string()),
+                    given: Some("John".to_string()),
+                    given_informal: None,
+                    given2: None,
+                    surname_prefix: None,
+                    surname: Some("Doe".to_string()),
+                    surname2: None,
+                    credentials: None,
+                    generation: None,
+                })),
+                Credential::Address(Box::new(AddressCredential {
+                    street_address: Some("Main Street 1\nApartment 2".to_string()),
+                    postal_code: Some("12345".to_string()),
+                    city: Some("Springfield".to_string()),
+                    territory: Some("IL".to_string()),
+                    country: Some("US".to_string()),
+                    tel: Some("+1 555 0100".to_string()),
+                })),
+                Credential::CustomFields(Box::new(CustomFieldsCredential {
+                    id: None,
+                    label: None,
+                    fields: vec![EditableField {
+                        id: [1].as_ref().into(),
+                        field_type: FieldType::Email,
+                        value: "john@example.com".to_string(),
+                        label: Some("email".to_string()),
+                    }],
+                    extensions: None,
+                })),
+            ],
+            tags: None,
+            extensions: None,
+        };
+
+        let ciphers: Vec<ImportingCipher> = parse_item(item, None).unwrap();
+        assert_eq!(ciphers.len(), 1);
+
+        let identity = match &ciphers[0].r#type {
+            CipherType::Identity(identity) => identity,
+            _ => panic!("Expected identity"),
+        };
+
+        assert_eq!(identity.title, Some("Mr".to_string()));
+        assert_eq!(identity.first_name, Some("John".to_string()));
+        assert_eq!(identity.last_name, Some("Doe".to_string()));
+        assert_eq!(identity.address1, Some("Main Street 1".to_string()));
+        assert_eq!(identity.address2, Some("Apartment 2".to_string()));
+        assert_eq!(identity.address3, None);
+        assert_eq!(identity.state, Some("IL".to_string()));
+        assert_eq!(identity.phone, Some("+1 555 0100".to_string()));
+        assert_eq!(identity.email, Some("john@example.com".to_string()));
+        assert_eq!(identity.company, None);
+    }
+
+    #[test]
+    fn test_custom_fields_round_trip() {
+        let field =
+            |name: &str, value: Option<&str>, r#type: u8, linked_id: Option<u32>| crate::Field {
+                name: Some(name.to_string()),
+                value: value.map(|v| v.to_string()),
+                r#type,
+                linked_id,
+            };
+        let cipher = crate::Cipher {
+            id: "25c8c414-b446-48e9-a1bd-b10700bbd740".parse().unwrap(),
+            folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
+
+            name: "Bitwarden".to_string(),
+            notes: None,
+
+            r#type: CipherType::Login(Box::new(crate::Login {
+                username: Some("test@bitwarden.com".to_string()),
+                password: None,
+                login_uris: vec![],
+                totp: None,
+                fido2_credentials: None,
+            })),
+
+            favorite: false,
+            reprompt: 1,
+
+            fields: vec![
+                field("Text", Some("A"), 0, None),
+                field("Hidden", Some("B"), 1, None),
+                field("Boolean", Some("true"), 2, None),
+                field("Linked", None, 3, Some(101)),
+            ],
+
+            revision_date: "2024-01-30T14:09:33.753Z".parse().unwrap(),
+            creation_date: "2024-01-30T11:23:54.416Z".parse().unwrap(),
+            deleted_date: None,
+        };
+
+        let item: Item = cipher.try_into().unwrap();
+        let ciphers: Vec<ImportingCipher> = parse_item(item, None).unwrap();
+        assert_eq!(ciphers.len(), 1);
+
+        let cipher = &ciphers[0];
+        assert_eq!(cipher.reprompt, 1);
+
+        // Linked fields can't be exported
+        let fields: Vec<_> = cipher
+            .fields
+            .iter()
+            .map(|f| (f.name.as_deref(), f.value.as_deref(), f.r#type))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (Some("Text"), Some("A"), 0),
+                (Some("Hidden"), Some("B"), 1),
+                (Some("Boolean"), Some("true"), 2),
+            ]
+        );
+    }
 }
@@ -19,7 +19,7 @@ use crate::{Fido2Credential, Login, LoginUri};
 /// Generate a 32 byte random ID
 ///
 /// TODO: This should be removed shortly.
-fn random_id() -> B64Url {
+pub(super) fn random_id() -> B64Url {
     generate_random_bytes::<[u8; 32]>().as_slice().into()
 }
 
@@ -13,4 +13,7 @@ pub use export::Account;
 mod import;
 pub(crate) use import::parse_cxf;
 mod card;
+mod fields;
+mod identity;
 mod login;
+mod ssh_key;
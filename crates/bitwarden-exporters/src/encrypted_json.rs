@@ -1,12 +1,16 @@
+use std::num::NonZeroU32;
+
 use base64::{engine::general_purpose::STANDARD, Engine};
-use bitwarden_crypto::{generate_random_bytes, Kdf, KeyEncryptable, PinKey};
-use serde::Serialize;
+use bitwarden_crypto::{
+    generate_random_bytes, EncString, Kdf, KeyDecryptable, KeyEncryptable, PinKey,
+};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use uuid::Uuid;
 
 use crate::{
-    json::{self, export_json},
-    Cipher, Folder,
+    json::{self, export_json, export_organization_json, import_json},
+    Cipher, Collection, Folder, ImportingCipher,
 };
 
 #[derive(Error, Debug)]
@@ -19,6 +23,13 @@ pub enum EncryptedJsonError {
 
     #[error("Cryptography error, {0}")]
     Crypto(#[from] bitwarden_crypto::CryptoError),
+
+    #[error("Only password protected exports are supported")]
+    NotPasswordProtected,
+    #[error("Invalid KDF settings")]
+    InvalidKdf,
+    #[error("Invalid password")]
+    InvalidPassword,
 }
 
 pub(crate) fn export_encrypted_json(
@@ -29,6 +40,26 @@ pub(crate) fn export_encrypted_json(
 ) -> Result<String, EncryptedJsonError> {
     let decrypted_export = export_json(folders, ciphers)?;
 
+    encrypt_export(decrypted_export, password, kdf)
+}
+
+pub(crate) fn export_encrypted_organization_json(
+    collections: Vec<Collection>,
+    ciphers: Vec<Cipher>,
+    password: String,
+    kdf: Kdf,
+) -> Result<String, EncryptedJsonError> {
+    let decrypted_export = export_organization_json(collections, ciphers)?;
+
+    encrypt_export(decrypted_export, password, kdf)
+}
+
+/// Protect a JSON export with a key derived from the password.
+fn encrypt_export(
+    decrypted_export: String,
+    password: String,
+    kdf: Kdf,
+) -> Result<String, EncryptedJsonError> {
     let (kdf_type, kdf_iterations, kdf_memory, kdf_parallelism) = match kdf {
         Kdf::PBKDF2 { iterations } => (0, iterations.get(), None, None),
         Kdf::Argon2id {
@@ -64,7 +95,52 @@ pub(crate) fn export_encrypted_json(
     Ok(serde_json::to_string_pretty(&encrypted_export)?)
 }
 
-#[derive(Serialize)]
+/// Decrypt a password protected JSON export and parse its contents.
+pub(crate) fn import_encrypted_json(
+    payload: &str,
+    password: String,
+) -> Result<(Vec<Folder>, Vec<ImportingCipher>), EncryptedJsonError> {
+    let export: EncryptedJsonExport = serde_json::from_str(payload)?;
+
+    if !export.password_protected {
+        return Err(EncryptedJsonError::NotPasswordProtected);
+    }
+
+    let iterations =
+        NonZeroU32::new(export.kdf_iterations).ok_or(EncryptedJsonError::InvalidKdf)?;
+    let kdf = match export.kdf_type {
+        0 => Kdf::PBKDF2 { iterations },
+        1 => Kdf::Argon2id {
+            iterations,
+            memory: export
+                .kdf_memory
+                .and_then(NonZeroU32::new)
+                .ok_or(EncryptedJsonError::InvalidKdf)?,
+            parallelism: export
+                .kdf_parallelism
+                .and_then(NonZeroU32::new)
+                .ok_or(EncryptedJsonError::InvalidKdf)?,
+        },
+        _ => return Err(EncryptedJsonError::InvalidKdf),
+    };
+    // The parameters come from the file, so make sure they can't exhaust the memory or hang
+    kdf.validate_maximums()
+        .map_err(|_| EncryptedJsonError::InvalidKdf)?;
+
+    let key = PinKey::derive(password.as_bytes(), export.salt.as_bytes(), &kdf)?;
+
+    let enc_key_validation: EncString = export.enc_key_validation.parse()?;
+    let _: String = enc_key_validation
+        .decrypt_with_key(&key)
+        .map_err(|_| EncryptedJsonError::InvalidPassword)?;
+
+    let data: EncString = export.data.parse()?;
+    let decrypted_export: String = data.decrypt_with_key(&key)?;
+
+    Ok(import_json(&decrypted_export)?)
+}
+
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct EncryptedJsonExport {
     encrypted: bool,
@@ -81,8 +157,6 @@ pub(crate) struct EncryptedJsonExport {
 
 #[cfg(test)]
 mod tests {
-    use std::num::NonZeroU32;
-
     use super::*;
     use crate::{
         Card, Cipher, CipherType, Field, Identity, Login, LoginUri, SecureNote, SecureNoteType,
@@ -99,6 +173,8 @@ mod tests {
                 Cipher {
                     id: "25c8c414-b446-48e9-a1bd-b10700bbd740".parse().unwrap(),
                     folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "Bitwarden".to_string(),
                     notes: Some("My note".to_string()),
@@ -157,6 +233,8 @@ mod tests {
                 Cipher {
                     id: "23f0f877-42b1-4820-a850-b10700bc41eb".parse().unwrap(),
                     folder_id: None,
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "My secure note".to_string(),
                     notes: Some("Very secure!".to_string()),
@@ -177,6 +255,8 @@ mod tests {
                 Cipher {
                     id: "3ed8de45-48ee-4e26-a2dc-b10701276c53".parse().unwrap(),
                     folder_id: None,
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "My card".to_string(),
                     notes: None,
@@ -202,6 +282,8 @@ mod tests {
                 Cipher {
                     id: "41cc3bc1-c3d9-4637-876c-b10701273712".parse().unwrap(),
                     folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "My identity".to_string(),
                     notes: None,
@@ -244,4 +326,81 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    pub fn test_import() {
+        let kdf = Kdf::PBKDF2 {
+            iterations: NonZeroU32::new(5000).unwrap(),
+        };
+        let export = export_encrypted_json(
+            vec![Folder {
+                id: "942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap(),
+                name: "Important".to_string(),
+            }],
+            vec![Cipher {
+                id: "23f0f877-42b1-4820-a850-b10700bc41eb".parse().unwrap(),
+                folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+                organization_id: None,
+                collection_ids: vec![],
+
+                name: "My secure note".to_string(),
+                notes: Some("Very secure!".to_string()),
+
+                r#type: CipherType::SecureNote(Box::new(SecureNote {
+                    r#type: SecureNoteType::Generic,
+                })),
+
+                favorite: false,
+                reprompt: 0,
+
+                fields: vec![],
+
+                revision_date: "2024-01-30T11:25:25.466Z".parse().unwrap(),
+                creation_date: "2024-01-30T11:25:25.466Z".parse().unwrap(),
+                deleted_date: None,
+            }],
+            "password".to_string(),
+            kdf,
+        )
+        .unwrap();
+
+        let (folders, ciphers) = import_encrypted_json(&export, "password".to_string()).unwrap();
+
+        assert_eq!(folders.len(), 1);
+        assert_eq!(folders[0].name, "Important");
+        assert_eq!(ciphers.len(), 1);
+        assert_eq!(ciphers[0].folder_id, Some(folders[0].id));
+        assert_eq!(ciphers[0].name, "My secure note");
+        assert_eq!(ciphers[0].notes.as_deref(), Some("Very secure!"));
+        assert!(matches!(ciphers[0].r#type, CipherType::SecureNote(_)));
+
+        let result = import_encrypted_json(&export, "wrong".to_string());
+        assert!(matches!(result, Err(EncryptedJsonError::InvalidPassword)));
+    }
+
+    #[test]
+    pub fn test_import_excessive_kdf() {
+        for (kdf_type, kdf_iterations, kdf_memory, kdf_parallelism) in [
+            (0, 4_000_000_000, None, None),
+            (1, 3, Some(u32::MAX), Some(4)),
+            (1, u32::MAX, Some(64), Some(4)),
+            (1, 3, Some(64), Some(u32::MAX)),
+        ] {
+            let export = serde_json::to_string(&EncryptedJsonExport {
+                encrypted: true,
+                password_protected: true,
+                salt: "Yc3NQoV8yW7ETeWg71M5rw==".to_string(),
+                kdf_type,
+                kdf_iterations,
+                kdf_memory,
+                kdf_parallelism,
+                enc_key_validation: "2.aGVsbG8=|aGVsbG8=|aGVsbG8=".to_string(),
+                data: "2.aGVsbG8=|aGVsbG8=|aGVsbG8=".to_string(),
+            })
+            .unwrap();
+
+            let result = import_encrypted_json(&export, "password".to_string());
+            assert!(matches!(result, Err(EncryptedJsonError::InvalidKdf)));
+        }
+    }
 }
@@ -17,6 +17,8 @@ pub enum ExportError {
     Json(#[from] crate::json::JsonError),
     #[error("Encrypted JSON error: {0}")]
     EncryptedJsonError(#[from] crate::encrypted_json::EncryptedJsonError),
+    #[error("Import error: {0}")]
+    Importer(#[from] crate::importers::ImporterError),
 
     #[error(transparent)]
     BitwardenCryptoError(#[from] bitwarden_crypto::CryptoError),
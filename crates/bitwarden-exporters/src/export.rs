@@ -1,13 +1,21 @@
-use bitwarden_core::Client;
-use bitwarden_crypto::{KeyContainer, KeyDecryptable, KeyEncryptable, LocateKey};
-use bitwarden_vault::{Cipher, CipherView, Collection, Folder, FolderView};
+use bitwarden_core::{key_management::KeyIds, Client};
+use bitwarden_crypto::KeyStore;
+use bitwarden_vault::{Cipher, CipherView, Collection, CollectionView, Folder, FolderView};
+use chrono::Utc;
+use uuid::Uuid;
 
 use crate::{
-    csv::export_csv,
+    csv::{export_csv, export_organization_csv, import_csv},
     cxf::{build_cxf, parse_cxf, Account},
-    encrypted_json::export_encrypted_json,
-    json::export_json,
-    ExportError, ExportFormat, ImportingCipher,
+    encrypted_json::{
+        export_encrypted_json, export_encrypted_organization_json, import_encrypted_json,
+    },
+    importers::{
+        import_1pux, import_chrome_csv, import_firefox_csv, import_keepass_xml,
+        import_lastpass_csv, ImportResult,
+    },
+    json::{export_json, export_organization_json, import_json},
+    CxfExport, ExportError, ExportFormat, ImportFormat, ImportedVault, ImportingCipher,
 };
 
 pub(crate) fn export_vault(
@@ -16,15 +24,14 @@ pub(crate) fn export_vault(
     ciphers: Vec<Cipher>,
     format: ExportFormat,
 ) -> Result<String, ExportError> {
-    let enc = client.internal.get_encryption_settings()?;
-    let key = enc.get_key(&None)?;
+    let key_store = client.internal.get_key_store();
 
-    let folders: Vec<FolderView> = folders.decrypt_with_key(key)?;
+    let folders: Vec<FolderView> = key_store.decrypt_list(&folders)?;
     let folders: Vec<crate::Folder> = folders.into_iter().flat_map(|f| f.try_into()).collect();
 
     let ciphers: Vec<crate::Cipher> = ciphers
         .into_iter()
-        .flat_map(|c| crate::Cipher::from_cipher(&enc, c))
+        .flat_map(|c| crate::Cipher::from_cipher(key_store, c))
         .collect();
 
     match format {
@@ -40,30 +47,123 @@ pub(crate) fn export_vault(
 }
 
 pub(crate) fn export_organization_vault(
-    _collections: Vec<Collection>,
-    _ciphers: Vec<Cipher>,
-    _format: ExportFormat,
+    client: &Client,
+    organization_id: Uuid,
+    collections: Vec<Collection>,
+    ciphers: Vec<Cipher>,
+    format: ExportFormat,
 ) -> Result<String, ExportError> {
-    todo!();
+    let key_store = client.internal.get_key_store();
+
+    let collections: Vec<crate::Collection> = collections
+        .into_iter()
+        .filter(|c| c.organization_id == organization_id)
+        .map(|c| -> Result<crate::Collection, ExportError> {
+            let view: CollectionView = key_store.decrypt(&c)?;
+            Ok(view.try_into()?)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ciphers: Vec<crate::Cipher> = ciphers
+        .into_iter()
+        .filter(|c| c.organization_id == Some(organization_id))
+        .map(|c| crate::Cipher::from_cipher(key_store, c))
+        .collect::<Result<_, _>>()?;
+
+    match format {
+        ExportFormat::Csv => Ok(export_organization_csv(collections, ciphers)?),
+        ExportFormat::Json => Ok(export_organization_json(collections, ciphers)?),
+        ExportFormat::EncryptedJson { password } => Ok(export_encrypted_organization_json(
+            collections,
+            ciphers,
+            password,
+            client.internal.get_kdf()?,
+        )?),
+    }
+}
+
+pub(crate) fn import(
+    client: &Client,
+    payload: String,
+    format: ImportFormat,
+) -> Result<ImportedVault, ExportError> {
+    let result: ImportResult = match format {
+        ImportFormat::Csv => import_csv(&payload)?.into(),
+        ImportFormat::Json => import_json(&payload)?.into(),
+        ImportFormat::EncryptedJson { password } => {
+            import_encrypted_json(&payload, password)?.into()
+        }
+        ImportFormat::OnePassword1Pux => import_1pux(&payload)?,
+        ImportFormat::KeePassXml => import_keepass_xml(&payload)?,
+        ImportFormat::LastPassCsv => import_lastpass_csv(&payload)?,
+        ImportFormat::ChromeCsv => import_chrome_csv(&payload)?,
+        ImportFormat::FirefoxCsv => import_firefox_csv(&payload)?,
+    };
+
+    encrypt_imported_vault(client, result)
 }
 
 /// See [crate::ClientExporters::export_cxf] for more documentation.
 pub(crate) fn export_cxf(
     client: &Client,
     account: Account,
+    folders: Vec<Folder>,
     ciphers: Vec<Cipher>,
-) -> Result<String, ExportError> {
-    let enc = client.internal.get_encryption_settings()?;
+) -> Result<CxfExport, ExportError> {
+    let key_store = client.internal.get_key_store();
+
+    let folders: Vec<FolderView> = key_store.decrypt_list(&folders)?;
+    let folders: Vec<crate::Folder> = folders
+        .into_iter()
+        .map(|f| f.try_into())
+        .collect::<Result<_, _>>()?;
 
     let ciphers: Vec<crate::Cipher> = ciphers
         .into_iter()
-        .flat_map(|c| crate::Cipher::from_cipher(&enc, c))
-        .collect();
+        .map(|c| crate::Cipher::from_cipher(key_store, c))
+        .collect::<Result<_, _>>()?;
 
-    Ok(build_cxf(account, ciphers)?)
+    Ok(build_cxf(account, folders, ciphers)?)
 }
 
-fn encrypt_import(enc: &dyn KeyContainer, cipher: ImportingCipher) -> Result<Cipher, ExportError> {
+/// Encrypt the imported folders and ciphers with the user key.
+fn encrypt_imported_vault(
+    client: &Client,
+    ImportResult {
+        folders,
+        ciphers,
+        skipped,
+    }: ImportResult,
+) -> Result<ImportedVault, ExportError> {
+    let key_store = client.internal.get_key_store();
+
+    let folders: Vec<Folder> = folders
+        .into_iter()
+        .map(|f| {
+            key_store.encrypt(FolderView {
+                id: Some(f.id),
+                name: f.name,
+                revision_date: Utc::now(),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    let ciphers: Vec<Cipher> = ciphers
+        .into_iter()
+        .map(|c| encrypt_import(key_store, c))
+        .collect::<Result<_, _>>()?;
+
+    Ok(ImportedVault {
+        folders,
+        ciphers,
+        skipped,
+    })
+}
+
+fn encrypt_import(
+    key_store: &KeyStore<KeyIds>,
+    cipher: ImportingCipher,
+) -> Result<Cipher, ExportError> {
     let mut view: CipherView = cipher.clone().into();
 
     // Get passkey from cipher if cipher is type login
@@ -75,24 +175,17 @@ fn encrypt_import(enc: &dyn KeyContainer, cipher: ImportingCipher) -> Result<Cip
     if let Some(passkey) = passkey {
         let passkeys = passkey.into_iter().map(|p| p.into()).collect();
 
-        view.set_new_fido2_credentials(enc, passkeys)?;
+        view.set_new_fido2_credentials(&mut key_store.context(), passkeys)?;
     }
 
-    let key = view.locate_key(enc, &None)?;
-    let new_cipher = view.encrypt_with_key(key)?;
+    let new_cipher = key_store.encrypt(view)?;
 
     Ok(new_cipher)
 }
 
 /// See [crate::ClientExporters::import_cxf] for more documentation.
-pub(crate) fn import_cxf(client: &Client, payload: String) -> Result<Vec<Cipher>, ExportError> {
-    let enc = client.internal.get_encryption_settings()?;
-
-    let ciphers = parse_cxf(payload)?;
-    let ciphers: Result<Vec<Cipher>, _> = ciphers
-        .into_iter()
-        .map(|c| encrypt_import(&enc, c))
-        .collect();
+pub(crate) fn import_cxf(client: &Client, payload: String) -> Result<ImportedVault, ExportError> {
+    let result = parse_cxf(payload)?;
 
-    ciphers
+    encrypt_imported_vault(client, result)
 }
@@ -1,9 +1,10 @@
 use bitwarden_core::Client;
 use bitwarden_vault::{Cipher, Collection, Folder};
+use uuid::Uuid;
 
 use crate::{
-    export::{export_cxf, export_organization_vault, export_vault, import_cxf},
-    Account, ExportError, ExportFormat,
+    export::{export_cxf, export_organization_vault, export_vault, import, import_cxf},
+    Account, CxfExport, ExportError, ExportFormat, ImportFormat, ImportedVault,
 };
 
 pub struct ExporterClient<'a> {
@@ -24,13 +25,27 @@ impl<'a> ExporterClient<'a> {
         export_vault(self.client, folders, ciphers, format)
     }
 
+    /// Export the collections and ciphers of an organization. Collections and ciphers belonging
+    /// to other organizations or to the user are left out.
     pub fn export_organization_vault(
         &self,
+        organization_id: Uuid,
         collections: Vec<Collection>,
         ciphers: Vec<Cipher>,
         format: ExportFormat,
     ) -> Result<String, ExportError> {
-        export_organization_vault(collections, ciphers, format)
+        export_organization_vault(self.client, organization_id, collections, ciphers, format)
+    }
+
+    /// Import a vault exported by Bitwarden or another password manager, encrypting the items
+    /// with the user key. Entries which can't be imported are listed in
+    /// [ImportedVault::skipped].
+    pub fn import(
+        &self,
+        payload: String,
+        format: ImportFormat,
+    ) -> Result<ImportedVault, ExportError> {
+        import(self.client, payload, format)
     }
 
     /// Credential Exchange Format (CXF)
@@ -39,12 +54,15 @@ impl<'a> ExporterClient<'a> {
     ///
     /// For use with Apple using [ASCredentialExportManager](https://developer.apple.com/documentation/authenticationservices/ascredentialexportmanager).
     /// Ideally the input should be immediately serialized from [ASImportableAccount](https://developer.apple.com/documentation/authenticationservices/asimportableaccount).
+    ///
+    /// Ciphers which can't be exported are listed in [CxfExport::skipped].
     pub fn export_cxf(
         &self,
         account: Account,
+        folders: Vec<Folder>,
         ciphers: Vec<Cipher>,
-    ) -> Result<String, ExportError> {
-        export_cxf(self.client, account, ciphers)
+    ) -> Result<CxfExport, ExportError> {
+        export_cxf(self.client, account, folders, ciphers)
     }
 
     /// Credential Exchange Format (CXF)
@@ -53,7 +71,7 @@ impl<'a> ExporterClient<'a> {
     ///
     /// For use with Apple using [ASCredentialExportManager](https://developer.apple.com/documentation/authenticationservices/ascredentialexportmanager).
     /// Ideally the input should be immediately serialized from [ASImportableAccount](https://developer.apple.com/documentation/authenticationservices/asimportableaccount).
-    pub fn import_cxf(&self, payload: String) -> Result<Vec<Cipher>, ExportError> {
+    pub fn import_cxf(&self, payload: String) -> Result<ImportedVault, ExportError> {
         import_cxf(self.client, payload)
     }
 }
@@ -0,0 +1,203 @@
+//! Password exports of Chromium based browsers and Firefox.
+use chrono::DateTime;
+use csv::Reader;
+
+use super::{name_from_url, new_cipher, non_empty, ImportResult, ImporterError};
+use crate::{CipherType, ImportingCipher, Login, LoginUri};
+
+/// Firefox stores credentials of its own features, like Firefox Accounts, using `chrome://` URLs.
+const FIREFOX_INTERNAL_SCHEME: &str = "chrome://";
+
+/// Parse a password CSV exported by Chrome, Edge, Brave or any other Chromium based browser.
+pub(crate) fn import_chrome_csv(payload: &str) -> Result<ImportResult, ImporterError> {
+    let mut rdr = Reader::from_reader(payload.as_bytes());
+    let mut result = ImportResult::default();
+
+    for (index, row) in rdr.deserialize().enumerate() {
+        let row: ChromeCsvRow = match row {
+            Ok(row) => row,
+            Err(e) => {
+                result.skip(format!("Row {}", index + 1), e.to_string());
+                continue;
+            }
+        };
+
+        let Some(cipher) = login_cipher(row.name, row.url, row.username, row.password) else {
+            continue;
+        };
+
+        result.ciphers.push(ImportingCipher {
+            notes: non_empty(row.note),
+            ..cipher
+        });
+    }
+
+    Ok(result)
+}
+
+/// Parse a password CSV exported by Firefox.
+pub(crate) fn import_firefox_csv(payload: &str) -> Result<ImportResult, ImporterError> {
+    let mut rdr = Reader::from_reader(payload.as_bytes());
+    let mut result = ImportResult::default();
+
+    for (index, row) in rdr.deserialize().enumerate() {
+        let row: FirefoxCsvRow = match row {
+            Ok(row) => row,
+            Err(e) => {
+                result.skip(format!("Row {}", index + 1), e.to_string());
+                continue;
+            }
+        };
+
+        if let Some(url) = row
+            .url
+            .as_deref()
+            .filter(|url| url.starts_with(FIREFOX_INTERNAL_SCHEME))
+        {
+            result.skip(url, "Internal Firefox credential");
+            continue;
+        }
+
+        let Some(cipher) = login_cipher(None, row.url, row.username, row.password) else {
+            continue;
+        };
+
+        let creation_date = row.time_created.and_then(DateTime::from_timestamp_millis);
+        let revision_date = row
+            .time_password_changed
+            .and_then(DateTime::from_timestamp_millis);
+
+        result.ciphers.push(ImportingCipher {
+            creation_date: creation_date.unwrap_or(cipher.creation_date),
+            revision_date: revision_date.unwrap_or(cipher.revision_date),
+            ..cipher
+        });
+    }
+
+    Ok(result)
+}
+
+/// Build a login from a browser entry, returning `None` for rows without any data.
+fn login_cipher(
+    name: Option<String>,
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+) -> Option<ImportingCipher> {
+    let url = non_empty(url);
+    let username = non_empty(username);
+    let password = non_empty(password);
+
+    if url.is_none() && username.is_none() && password.is_none() {
+        return None;
+    }
+
+    let name = non_empty(name)
+        .or_else(|| url.as_deref().and_then(name_from_url))
+        .unwrap_or_else(|| "--".to_owned());
+
+    Some(new_cipher(
+        name,
+        CipherType::Login(Box::new(Login {
+            username,
+            password,
+            login_uris: url
+                .map(|uri| LoginUri {
+                    uri: Some(uri),
+                    r#match: None,
+                })
+                .into_iter()
+                .collect(),
+            totp: None,
+            fido2_credentials: None,
+        })),
+    ))
+}
+
+#[derive(serde::Deserialize)]
+struct ChromeCsvRow {
+    name: Option<String>,
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    /// Only present in exports of recent Chrome versions.
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FirefoxCsvRow {
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    time_created: Option<i64>,
+    time_password_changed: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_chrome_csv() {
+        let payload = r#"name,url,username,password,note
+example.com,https://example.com/login,user@example.com,password123,My note
+,https://www.bitwarden.com/,test,hunter2,
+,,,,
+"#;
+
+        let result = import_chrome_csv(payload).unwrap();
+
+        assert_eq!(result.ciphers.len(), 2);
+        assert!(result.folders.is_empty());
+        assert!(result.skipped.is_empty());
+
+        let cipher = &result.ciphers[0];
+        assert_eq!(cipher.name, "example.com");
+        assert_eq!(cipher.notes.as_deref(), Some("My note"));
+        match &cipher.r#type {
+            CipherType::Login(login) => {
+                assert_eq!(login.username.as_deref(), Some("user@example.com"));
+                assert_eq!(login.password.as_deref(), Some("password123"));
+                assert_eq!(
+                    login.login_uris[0].uri.as_deref(),
+                    Some("https://example.com/login")
+                );
+            }
+            _ => panic!("Expected login"),
+        }
+
+        assert_eq!(result.ciphers[1].name, "bitwarden.com");
+        assert_eq!(result.ciphers[1].notes, None);
+    }
+
+    #[test]
+    fn test_import_chrome_csv_without_notes() {
+        let payload = "name,url,username,password\nexample.com,https://example.com,user,pass\n";
+
+        let result = import_chrome_csv(payload).unwrap();
+
+        assert_eq!(result.ciphers.len(), 1);
+        assert_eq!(result.ciphers[0].notes, None);
+    }
+
+    #[test]
+    fn test_import_firefox_csv() {
+        let payload = r#""url","username","password","httpRealm","formActionOrigin","guid","timeCreated","timeLastUsed","timePasswordChanged"
+"https://accounts.example.com","user","password123",,"https://accounts.example.com","{4a8d3d8c-2f8b-4ad0-a9f9-b8a76c2e1a2a}","1700000000000","1700000500000","1700001000000"
+"chrome://FirefoxAccounts","a1b2c3","{""version"":1}","Firefox Accounts credentials",,"{0b1c2d3e-0000-4000-8000-000000000000}","1700000000000","1700000000000","1700000000000"
+"#;
+
+        let result = import_firefox_csv(payload).unwrap();
+
+        assert_eq!(result.ciphers.len(), 1);
+        let cipher = &result.ciphers[0];
+        assert_eq!(cipher.name, "accounts.example.com");
+        assert_eq!(cipher.creation_date.timestamp_millis(), 1700000000000);
+        assert_eq!(cipher.revision_date.timestamp_millis(), 1700001000000);
+
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].name, "chrome://FirefoxAccounts");
+    }
+}
@@ -0,0 +1,275 @@
+//! KeePass 2.x XML export.
+//!
+//! Only the unencrypted XML export is supported, KDBX databases need to be exported first.
+use chrono::{DateTime, Utc};
+use roxmltree::{Document, Node};
+
+use super::{new_cipher, non_empty, ImportResult, ImporterError};
+use crate::{CipherType, Field, ImportingCipher, Login, LoginUri};
+
+const TITLE_KEY: &str = "Title";
+const USERNAME_KEY: &str = "UserName";
+const PASSWORD_KEY: &str = "Password";
+const URL_KEY: &str = "URL";
+const NOTES_KEY: &str = "Notes";
+const TOTP_KEY: &str = "otp";
+
+/// Parse a KeePass 2.x XML export. Groups below the root group are imported as folders, and
+/// entries in the recycle bin are skipped.
+pub(crate) fn import_keepass_xml(payload: &str) -> Result<ImportResult, ImporterError> {
+    let doc = Document::parse(payload)?;
+    let file = doc.root_element();
+    if !file.has_tag_name("KeePassFile") {
+        return Err(ImporterError::InvalidFormat(
+            "Missing KeePassFile element".to_owned(),
+        ));
+    }
+
+    let recycle_bin = child(file, "Meta")
+        .and_then(|meta| child_text(meta, "RecycleBinUUID"))
+        .filter(|uuid| !uuid.is_empty());
+
+    let root_group = child(file, "Root")
+        .and_then(|root| child(root, "Group"))
+        .ok_or_else(|| ImporterError::InvalidFormat("Missing root group".to_owned()))?;
+
+    let mut result = ImportResult::default();
+    import_group(&mut result, root_group, None, recycle_bin.as_deref());
+
+    Ok(result)
+}
+
+fn import_group(
+    result: &mut ImportResult,
+    group: Node,
+    folder_name: Option<String>,
+    recycle_bin: Option<&str>,
+) {
+    let folder_id = folder_name
+        .as_deref()
+        .and_then(|name| result.folder_id(name));
+
+    for entry in group.children().filter(|n| n.has_tag_name("Entry")) {
+        result.ciphers.push(ImportingCipher {
+            folder_id,
+            ..parse_entry(entry)
+        });
+    }
+
+    for sub_group in group.children().filter(|n| n.has_tag_name("Group")) {
+        let name = child_text(sub_group, "Name").unwrap_or_default();
+
+        if recycle_bin.is_some() && child_text(sub_group, "UUID").as_deref() == recycle_bin {
+            for entry in sub_group.descendants().filter(|n| n.has_tag_name("Entry")) {
+                // Entries in the history of another entry are never imported.
+                if entry.parent().is_some_and(|p| p.has_tag_name("History")) {
+                    continue;
+                }
+                result.skip(parse_entry(entry).name, "In the recycle bin");
+            }
+            continue;
+        }
+
+        let name = match &folder_name {
+            Some(parent) => format!("{parent}/{name}"),
+            None => name,
+        };
+        import_group(result, sub_group, Some(name), recycle_bin);
+    }
+}
+
+fn parse_entry(entry: Node) -> ImportingCipher {
+    let mut title = None;
+    let mut username = None;
+    let mut password = None;
+    let mut url = None;
+    let mut notes = None;
+    let mut totp = None;
+    let mut fields = vec![];
+
+    for string in entry.children().filter(|n| n.has_tag_name("String")) {
+        let Some(key) = child_text(string, "Key") else {
+            continue;
+        };
+        let value_node = child(string, "Value");
+        let value = non_empty(value_node.and_then(|v| v.text()).map(|v| v.to_owned()));
+
+        match key.as_str() {
+            TITLE_KEY => title = value,
+            USERNAME_KEY => username = value,
+            PASSWORD_KEY => password = value,
+            URL_KEY => url = value,
+            NOTES_KEY => notes = value,
+            TOTP_KEY => totp = value,
+            _ => {
+                let protected = value_node
+                    .and_then(|v| v.attribute("ProtectInMemory"))
+                    .is_some_and(|p| p.eq_ignore_ascii_case("true"));
+
+                fields.push(Field {
+                    name: Some(key),
+                    value,
+                    r#type: if protected { 1 } else { 0 },
+                    linked_id: None,
+                });
+            }
+        }
+    }
+
+    let times = child(entry, "Times");
+    let time = |name: &str| {
+        times
+            .and_then(|t| child_text(t, name))
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+            .map(|t| t.with_timezone(&Utc))
+    };
+
+    let cipher = new_cipher(
+        title.unwrap_or_else(|| "--".to_owned()),
+        CipherType::Login(Box::new(Login {
+            username,
+            password,
+            login_uris: url
+                .map(|uri| LoginUri {
+                    uri: Some(uri),
+                    r#match: None,
+                })
+                .into_iter()
+                .collect(),
+            totp,
+            fido2_credentials: None,
+        })),
+    );
+
+    ImportingCipher {
+        notes,
+        fields,
+        creation_date: time("CreationTime").unwrap_or(cipher.creation_date),
+        revision_date: time("LastModificationTime").unwrap_or(cipher.revision_date),
+        ..cipher
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name).map(|n| n.text().unwrap_or_default().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"<?xml version="1.0" encoding="utf-8" standalone="yes"?>
+<KeePassFile>
+	<Meta>
+		<Generator>KeePass</Generator>
+		<RecycleBinEnabled>True</RecycleBinEnabled>
+		<RecycleBinUUID>Ck/3xCpVR0+2u7nxwvGNaw==</RecycleBinUUID>
+	</Meta>
+	<Root>
+		<Group>
+			<UUID>o8Ah0WBZT9mEG9K0y0uTsQ==</UUID>
+			<Name>Database</Name>
+			<Entry>
+				<UUID>AqEo0kImSm2t1JrIvO2G1w==</UUID>
+				<Times>
+					<CreationTime>2024-01-02T10:00:00Z</CreationTime>
+					<LastModificationTime>2024-03-04T12:30:00Z</LastModificationTime>
+				</Times>
+				<String><Key>Notes</Key><Value>Some notes</Value></String>
+				<String><Key>Password</Key><Value ProtectInMemory="True">password123</Value></String>
+				<String><Key>Title</Key><Value>Example</Value></String>
+				<String><Key>URL</Key><Value>https://example.com</Value></String>
+				<String><Key>UserName</Key><Value>user@example.com</Value></String>
+				<String><Key>Recovery Code</Key><Value ProtectInMemory="True">1234-5678</Value></String>
+				<String><Key>Account</Key><Value>42</Value></String>
+				<History>
+					<Entry>
+						<String><Key>Title</Key><Value>Old example</Value></String>
+					</Entry>
+				</History>
+			</Entry>
+			<Group>
+				<UUID>8/8eZP8QRZ6hvLEJqEIRzQ==</UUID>
+				<Name>Work</Name>
+				<Group>
+					<UUID>oo1yFJSiRFW5lJVgeqLq8w==</UUID>
+					<Name>Servers</Name>
+					<Entry>
+						<String><Key>Title</Key><Value>Server</Value></String>
+						<String><Key>UserName</Key><Value>root</Value></String>
+					</Entry>
+				</Group>
+			</Group>
+			<Group>
+				<UUID>Ck/3xCpVR0+2u7nxwvGNaw==</UUID>
+				<Name>Recycle Bin</Name>
+				<Entry>
+					<String><Key>Title</Key><Value>Deleted</Value></String>
+				</Entry>
+			</Group>
+		</Group>
+	</Root>
+</KeePassFile>"#;
+
+    #[test]
+    fn test_import_keepass_xml() {
+        let result = import_keepass_xml(EXPORT).unwrap();
+
+        assert_eq!(result.ciphers.len(), 2);
+        assert_eq!(result.folders.len(), 2);
+        assert_eq!(result.folders[0].name, "Work");
+        assert_eq!(result.folders[1].name, "Work/Servers");
+
+        let cipher = &result.ciphers[0];
+        assert_eq!(cipher.name, "Example");
+        assert_eq!(cipher.folder_id, None);
+        assert_eq!(cipher.notes.as_deref(), Some("Some notes"));
+        assert_eq!(
+            cipher.creation_date.to_rfc3339(),
+            "2024-01-02T10:00:00+00:00"
+        );
+        assert_eq!(
+            cipher.revision_date.to_rfc3339(),
+            "2024-03-04T12:30:00+00:00"
+        );
+        match &cipher.r#type {
+            CipherType::Login(login) => {
+                assert_eq!(login.username.as_deref(), Some("user@example.com"));
+                assert_eq!(login.password.as_deref(), Some("password123"));
+                assert_eq!(
+                    login.login_uris[0].uri.as_deref(),
+                    Some("https://example.com")
+                );
+            }
+            _ => panic!("Expected login"),
+        }
+
+        assert_eq!(cipher.fields.len(), 2);
+        assert_eq!(cipher.fields[0].name.as_deref(), Some("Recovery Code"));
+        assert_eq!(cipher.fields[0].r#type, 1);
+        assert_eq!(cipher.fields[1].value.as_deref(), Some("42"));
+        assert_eq!(cipher.fields[1].r#type, 0);
+
+        assert_eq!(result.ciphers[1].name, "Server");
+        assert_eq!(result.ciphers[1].folder_id, Some(result.folders[1].id));
+
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].name, "Deleted");
+    }
+
+    #[test]
+    fn test_import_keepass_invalid() {
+        assert!(matches!(
+            import_keepass_xml("<Database></Database>"),
+            Err(ImporterError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            import_keepass_xml("not xml"),
+            Err(ImporterError::Xml(_))
+        ));
+    }
+}
@@ -0,0 +1,331 @@
+//! LastPass CSV export.
+//!
+//! Secure notes are identified by the `http://sn` URL, and may contain structured data such as
+//! credit cards or addresses, stored as `Key:Value` lines in the `extra` column.
+use std::collections::HashMap;
+
+use csv::Reader;
+
+use super::{name_from_url, new_cipher, non_empty, ImportResult, ImporterError};
+use crate::{
+    Card, CipherType, Field, Identity, ImportingCipher, Login, LoginUri, SecureNote, SecureNoteType,
+};
+
+const SECURE_NOTE_URL: &str = "http://sn";
+/// Rows with this URL only exist to preserve empty groups.
+const GROUP_URL: &str = "http://group";
+const NOTE_TYPE_KEY: &str = "NoteType";
+const NOTES_KEY: &str = "Notes";
+
+/// Parse a LastPass CSV export. Groups are imported as folders, with nested groups separated by
+/// `/` like Bitwarden folders.
+pub(crate) fn import_lastpass_csv(payload: &str) -> Result<ImportResult, ImporterError> {
+    let mut rdr = Reader::from_reader(payload.as_bytes());
+    let mut result = ImportResult::default();
+
+    for (index, row) in rdr.deserialize().enumerate() {
+        let row: LastPassCsvRow = match row {
+            Ok(row) => row,
+            Err(e) => {
+                result.skip(format!("Row {}", index + 1), e.to_string());
+                continue;
+            }
+        };
+
+        let folder_id = row
+            .grouping
+            .as_deref()
+            .and_then(|g| result.folder_id(&g.replace('\\', "/")));
+
+        let url = non_empty(row.url);
+        if url.as_deref() == Some(GROUP_URL) {
+            continue;
+        }
+
+        let name = non_empty(row.name)
+            .or_else(|| url.as_deref().and_then(name_from_url))
+            .unwrap_or_else(|| "--".to_owned());
+
+        let cipher = if url.as_deref() == Some(SECURE_NOTE_URL) {
+            secure_note(name, non_empty(row.extra))
+        } else {
+            ImportingCipher {
+                notes: non_empty(row.extra),
+                ..new_cipher(
+                    name,
+                    CipherType::Login(Box::new(Login {
+                        username: non_empty(row.username),
+                        password: non_empty(row.password),
+                        login_uris: url
+                            .map(|uri| LoginUri {
+                                uri: Some(uri),
+                                r#match: None,
+                            })
+                            .into_iter()
+                            .collect(),
+                        totp: non_empty(row.totp),
+                        fido2_credentials: None,
+                    })),
+                )
+            }
+        };
+
+        result.ciphers.push(ImportingCipher {
+            folder_id,
+            favorite: row.fav.as_deref() == Some("1"),
+            ..cipher
+        });
+    }
+
+    Ok(result)
+}
+
+/// Convert a secure note, parsing the structured note types which have a Bitwarden equivalent.
+/// Values of other structured notes are kept as custom fields.
+fn secure_note(name: String, extra: Option<String>) -> ImportingCipher {
+    let generic = |name, notes| ImportingCipher {
+        notes,
+        ..new_cipher(
+            name,
+            CipherType::SecureNote(Box::new(SecureNote {
+                r#type: SecureNoteType::Generic,
+            })),
+        )
+    };
+
+    let Some(note) = extra.as_deref().and_then(parse_structured_note) else {
+        return generic(name, extra);
+    };
+
+    let mut values = note.values;
+    let mut take = |key: &str| non_empty(values.remove(key));
+
+    let r#type = match note.note_type.as_str() {
+        "Credit Card" => {
+            let (exp_month, exp_year) = parse_expiration(take("Expiration Date"));
+
+            Some(CipherType::Card(Box::new(Card {
+                cardholder_name: take("Name on Card"),
+                exp_month,
+                exp_year,
+                code: take("Security Code"),
+                brand: take("Type"),
+                number: take("Number"),
+            })))
+        }
+        "Address" => Some(CipherType::Identity(Box::new(Identity {
+            title: take("Title"),
+            first_name: take("First Name"),
+            middle_name: take("Middle Name"),
+            last_name: take("Last Name"),
+            address1: take("Address 1"),
+            address2: take("Address 2"),
+            address3: take("Address 3"),
+            city: take("City / Town"),
+            state: take("State"),
+            postal_code: take("Zip / Postal Code"),
+            country: take("Country"),
+            company: take("Company"),
+            email: take("Email Address"),
+            phone: take("Phone"),
+            ssn: None,
+            username: take("Username"),
+            passport_number: None,
+            license_number: None,
+        }))),
+        _ => None,
+    };
+
+    let fields = note
+        .order
+        .into_iter()
+        .filter_map(|key| {
+            // Empty dates are exported as a lone separator.
+            let value = values.remove(&key).filter(|v| !v.is_empty() && v != ",")?;
+            Some(Field {
+                name: Some(key),
+                value: Some(value),
+                r#type: 0,
+                linked_id: None,
+            })
+        })
+        .collect();
+
+    match r#type {
+        Some(r#type) => ImportingCipher {
+            notes: note.notes,
+            fields,
+            ..new_cipher(name, r#type)
+        },
+        None => ImportingCipher {
+            fields,
+            ..generic(name, note.notes)
+        },
+    }
+}
+
+struct StructuredNote {
+    note_type: String,
+    /// The keys in the order they appear in the note.
+    order: Vec<String>,
+    values: HashMap<String, String>,
+    notes: Option<String>,
+}
+
+/// Parse the `Key:Value` lines of a structured note. The `Notes` value is always last and may span
+/// multiple lines.
+fn parse_structured_note(extra: &str) -> Option<StructuredNote> {
+    let extra = extra.strip_prefix(NOTE_TYPE_KEY)?.strip_prefix(':')?;
+    let (note_type, mut rest) = extra.split_once('\n').unwrap_or((extra, ""));
+
+    let mut order = vec![];
+    let mut values = HashMap::new();
+    let mut notes = None;
+
+    while !rest.is_empty() {
+        if let Some(value) = rest
+            .strip_prefix(NOTES_KEY)
+            .and_then(|r| r.strip_prefix(':'))
+        {
+            notes = non_empty(Some(value.to_owned()));
+            break;
+        }
+
+        let (line, remaining) = rest.split_once('\n').unwrap_or((rest, ""));
+        rest = remaining;
+
+        if let Some((key, value)) = line.split_once(':') {
+            order.push(key.to_owned());
+            values.insert(key.to_owned(), value.to_owned());
+        }
+    }
+
+    Some(StructuredNote {
+        note_type: note_type.to_owned(),
+        order,
+        values,
+        notes,
+    })
+}
+
+/// LastPass stores the expiration date as `Month,Year`, for example `January,2025`.
+fn parse_expiration(value: Option<String>) -> (Option<String>, Option<String>) {
+    const MONTHS: [&str; 12] = [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ];
+
+    let Some((month, year)) = value.as_deref().and_then(|v| v.split_once(',')) else {
+        return (None, None);
+    };
+
+    let month = MONTHS
+        .iter()
+        .position(|m| m.eq_ignore_ascii_case(month.trim()))
+        .map(|i| (i + 1).to_string());
+
+    (month, non_empty(Some(year.trim().to_owned())))
+}
+
+#[derive(serde::Deserialize)]
+struct LastPassCsvRow {
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    #[serde(default)]
+    totp: Option<String>,
+    extra: Option<String>,
+    name: Option<String>,
+    grouping: Option<String>,
+    fav: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_lastpass_csv() {
+        let payload = r#"url,username,password,totp,extra,name,grouping,fav
+https://example.com/login,user@example.com,password123,JBSWY3DPEHPK3PXP,Some notes,Example,Social\Media,1
+http://sn,,,,My secret note,Note,,0
+http://group,,,,,,Empty,0
+"#;
+
+        let result = import_lastpass_csv(payload).unwrap();
+
+        assert_eq!(result.ciphers.len(), 2);
+        assert_eq!(result.folders.len(), 2);
+        assert_eq!(result.folders[0].name, "Social/Media");
+        assert_eq!(result.folders[1].name, "Empty");
+
+        let login = &result.ciphers[0];
+        assert_eq!(login.name, "Example");
+        assert_eq!(login.notes.as_deref(), Some("Some notes"));
+        assert_eq!(login.folder_id, Some(result.folders[0].id));
+        assert!(login.favorite);
+        match &login.r#type {
+            CipherType::Login(login) => {
+                assert_eq!(login.username.as_deref(), Some("user@example.com"));
+                assert_eq!(login.totp.as_deref(), Some("JBSWY3DPEHPK3PXP"));
+            }
+            _ => panic!("Expected login"),
+        }
+
+        let note = &result.ciphers[1];
+        assert!(matches!(note.r#type, CipherType::SecureNote(_)));
+        assert_eq!(note.notes.as_deref(), Some("My secret note"));
+        assert_eq!(note.folder_id, None);
+    }
+
+    #[test]
+    fn test_import_lastpass_credit_card() {
+        let payload = "url,username,password,totp,extra,name,grouping,fav\n\
+            http://sn,,,,\"NoteType:Credit Card\nLanguage:en-US\nName on Card:John Doe\nType:Visa\n\
+            Number:4111111111111111\nSecurity Code:123\nStart Date:,\nExpiration Date:June,2030\n\
+            Notes:Line one\nLine two\",My Card,,0\n";
+
+        let result = import_lastpass_csv(payload).unwrap();
+
+        let cipher = &result.ciphers[0];
+        assert_eq!(cipher.notes.as_deref(), Some("Line one\nLine two"));
+        assert_eq!(cipher.fields.len(), 1);
+        assert_eq!(cipher.fields[0].name.as_deref(), Some("Language"));
+        match &cipher.r#type {
+            CipherType::Card(card) => {
+                assert_eq!(card.cardholder_name.as_deref(), Some("John Doe"));
+                assert_eq!(card.brand.as_deref(), Some("Visa"));
+                assert_eq!(card.number.as_deref(), Some("4111111111111111"));
+                assert_eq!(card.code.as_deref(), Some("123"));
+                assert_eq!(card.exp_month.as_deref(), Some("6"));
+                assert_eq!(card.exp_year.as_deref(), Some("2030"));
+            }
+            _ => panic!("Expected card"),
+        }
+    }
+
+    #[test]
+    fn test_import_lastpass_unsupported_note_type() {
+        let payload = "url,username,password,totp,extra,name,grouping,fav\n\
+            http://sn,,,,\"NoteType:Wi-Fi Password\nSSID:home\nPassword:secret\nNotes:\",Wi-Fi,,0\n";
+
+        let result = import_lastpass_csv(payload).unwrap();
+
+        let cipher = &result.ciphers[0];
+        assert!(matches!(cipher.r#type, CipherType::SecureNote(_)));
+        assert_eq!(cipher.notes, None);
+        assert_eq!(cipher.fields.len(), 2);
+        assert_eq!(cipher.fields[1].name.as_deref(), Some("Password"));
+        assert_eq!(cipher.fields[1].value.as_deref(), Some("secret"));
+    }
+}
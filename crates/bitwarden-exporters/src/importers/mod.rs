@@ -0,0 +1,148 @@
+//! Importers for the export formats of other password managers.
+//!
+//! Each importer parses the export into [ImportingCipher]s and folders. Entries which can't be
+//! represented in Bitwarden are reported as [SkippedItem]s instead of failing the whole import.
+use chrono::Utc;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{CipherType, Folder, ImportingCipher, SkippedItem};
+
+mod browser;
+pub(crate) use browser::{import_chrome_csv, import_firefox_csv};
+mod keepass;
+pub(crate) use keepass::import_keepass_xml;
+mod lastpass;
+pub(crate) use lastpass::import_lastpass_csv;
+mod one_password;
+pub(crate) use one_password::import_1pux;
+
+#[derive(Debug, Error)]
+pub enum ImporterError {
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("XML error: {0}")]
+    Xml(#[from] roxmltree::Error),
+    #[error("Invalid format: {0}")]
+    InvalidFormat(String),
+}
+
+/// The unencrypted result of parsing an export.
+#[derive(Default)]
+pub(crate) struct ImportResult {
+    pub folders: Vec<Folder>,
+    pub ciphers: Vec<ImportingCipher>,
+    pub skipped: Vec<SkippedItem>,
+}
+
+impl ImportResult {
+    /// Return the id of the folder with the given name, creating the folder if it doesn't exist
+    /// yet. Empty names don't create a folder.
+    pub fn folder_id(&mut self, name: &str) -> Option<Uuid> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        if let Some(folder) = self.folders.iter().find(|f| f.name == name) {
+            return Some(folder.id);
+        }
+
+        let id = Uuid::new_v4();
+        self.folders.push(Folder {
+            id,
+            name: name.to_owned(),
+        });
+        Some(id)
+    }
+
+    pub fn skip(&mut self, name: impl Into<String>, reason: impl Into<String>) {
+        self.skipped.push(SkippedItem {
+            name: name.into(),
+            reason: reason.into(),
+        });
+    }
+}
+
+impl From<(Vec<Folder>, Vec<ImportingCipher>)> for ImportResult {
+    fn from((folders, ciphers): (Vec<Folder>, Vec<ImportingCipher>)) -> Self {
+        Self {
+            folders,
+            ciphers,
+            skipped: vec![],
+        }
+    }
+}
+
+/// Create a cipher with the defaults shared by all importers, which is then customized using
+/// struct update syntax.
+fn new_cipher(name: String, r#type: CipherType) -> ImportingCipher {
+    ImportingCipher {
+        folder_id: None,
+        name,
+        notes: None,
+        r#type,
+        favorite: false,
+        reprompt: 0,
+        fields: vec![],
+        revision_date: Utc::now(),
+        creation_date: Utc::now(),
+        deleted_date: None,
+    }
+}
+
+/// Derive an item name from the host of a URL, used when the export doesn't provide one.
+fn name_from_url(url: &str) -> Option<String> {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = without_scheme
+        .split(['/', '?', '#'])
+        .next()?
+        .rsplit('@')
+        .next()?
+        .split(':')
+        .next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    (!host.is_empty()).then(|| host.to_owned())
+}
+
+/// Convert empty strings, which most exports use for missing values, to `None`.
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folder_id() {
+        let mut result = ImportResult::default();
+
+        let social = result.folder_id("Social");
+        assert!(social.is_some());
+        assert_eq!(result.folder_id("Social"), social);
+        assert_ne!(result.folder_id("Work"), social);
+        assert_eq!(result.folder_id(" "), None);
+        assert_eq!(result.folders.len(), 2);
+    }
+
+    #[test]
+    fn test_name_from_url() {
+        assert_eq!(
+            name_from_url("https://www.example.com/login?next=1"),
+            Some("example.com".to_owned())
+        );
+        assert_eq!(
+            name_from_url("http://user@localhost:8080"),
+            Some("localhost".to_owned())
+        );
+        assert_eq!(
+            name_from_url("android://com.example.app"),
+            Some("com.example.app".to_owned())
+        );
+        assert_eq!(name_from_url(""), None);
+    }
+}
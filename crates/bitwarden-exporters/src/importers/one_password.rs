@@ -0,0 +1,622 @@
+//! 1Password 1PUX export.
+//!
+//! A `.1pux` file is a zip archive containing the items in `export.data` along with any file
+//! attachments. Only `export.data` is parsed, the clients are responsible for extracting it.
+//!
+//! <https://support.1password.com/1pux-format/>
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::{new_cipher, non_empty, ImportResult, ImporterError};
+use crate::{
+    Card, CipherType, Field, Identity, ImportingCipher, Login, LoginUri, SecureNote,
+    SecureNoteType, SshKey,
+};
+
+const ARCHIVED_STATE: &str = "archived";
+
+/// Parse the `export.data` file of a 1PUX export. Every vault is imported as a folder.
+pub(crate) fn import_1pux(payload: &str) -> Result<ImportResult, ImporterError> {
+    let export: ExportData = serde_json::from_str(payload)?;
+    let mut result = ImportResult::default();
+
+    for vault in export.accounts.into_iter().flat_map(|a| a.vaults) {
+        let folder_id = vault
+            .attrs
+            .name
+            .as_deref()
+            .and_then(|name| result.folder_id(name));
+
+        for item in vault.items {
+            let name = non_empty(item.overview.title.clone()).unwrap_or_else(|| "--".to_owned());
+
+            match parse_item(item, name.clone()) {
+                Ok(cipher) => result.ciphers.push(ImportingCipher {
+                    folder_id,
+                    ..cipher
+                }),
+                Err(reason) => result.skip(name, reason),
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Convert an item, returning the reason if it can't be imported.
+fn parse_item(item: Item, name: String) -> Result<ImportingCipher, String> {
+    let mut fields = SectionFields(
+        item.details
+            .sections
+            .into_iter()
+            .flat_map(|s| s.fields)
+            .filter_map(|f| Some((f.id, f.title, FieldValue::parse(f.value)?)))
+            .collect(),
+    );
+
+    let r#type = match item.category_uuid.as_str() {
+        // Login and Password
+        "001" | "005" => {
+            let designated = |designation: &str| {
+                item.details
+                    .login_fields
+                    .iter()
+                    .find(|f| f.designation.as_deref() == Some(designation))
+                    .and_then(|f| non_empty(Some(f.value.clone())))
+            };
+
+            let mut login_uris: Vec<LoginUri> = item
+                .overview
+                .urls
+                .iter()
+                .filter_map(|u| non_empty(Some(u.url.clone())))
+                .map(|uri| LoginUri {
+                    uri: Some(uri),
+                    r#match: None,
+                })
+                .collect();
+            if login_uris.is_empty() {
+                login_uris.extend(non_empty(item.overview.url.clone()).map(|uri| LoginUri {
+                    uri: Some(uri),
+                    r#match: None,
+                }));
+            }
+
+            CipherType::Login(Box::new(Login {
+                username: designated("username"),
+                password: designated("password").or(non_empty(item.details.password)),
+                login_uris,
+                totp: fields.take_totp(),
+                fido2_credentials: None,
+            }))
+        }
+        "002" => {
+            let (exp_month, exp_year) = match fields.take("expiry") {
+                Some(FieldValue::MonthYear { month, year }) => {
+                    (Some(month.to_string()), Some(year.to_string()))
+                }
+                _ => (None, None),
+            };
+
+            CipherType::Card(Box::new(Card {
+                cardholder_name: fields.take_text("cardholder"),
+                exp_month,
+                exp_year,
+                code: fields.take_text("cvv"),
+                brand: fields.take_text("type"),
+                number: fields.take_text("ccnum"),
+            }))
+        }
+        "003" => CipherType::SecureNote(Box::new(SecureNote {
+            r#type: SecureNoteType::Generic,
+        })),
+        "004" => {
+            let mut identity = Identity {
+                first_name: fields.take_text("firstname"),
+                middle_name: fields.take_text("initial"),
+                last_name: fields.take_text("lastname"),
+                company: fields.take_text("company"),
+                email: fields.take_text("email"),
+                phone: fields.take_text("defphone"),
+                username: fields.take_text("username"),
+                ..empty_identity()
+            };
+            fields.take_address(&mut identity);
+
+            CipherType::Identity(Box::new(identity))
+        }
+        "006" => return Err("Documents are not supported".to_owned()),
+        // Driver License
+        "103" => {
+            let mut identity = Identity {
+                license_number: fields.take_text("number"),
+                ..split_name(fields.take_text("fullname"))
+            };
+            fields.take_address(&mut identity);
+
+            CipherType::Identity(Box::new(identity))
+        }
+        // Passport
+        "106" => CipherType::Identity(Box::new(Identity {
+            passport_number: fields.take_text("number"),
+            ..split_name(fields.take_text("fullname"))
+        })),
+        // Social Security Number
+        "108" => CipherType::Identity(Box::new(Identity {
+            ssn: fields.take_text("number"),
+            ..split_name(fields.take_text("name"))
+        })),
+        // SSH Key
+        "114" => {
+            let private_key = fields
+                .take_ssh_key()
+                .ok_or_else(|| "Missing private key".to_owned())?;
+            let key =
+                bitwarden_ssh::import::import_key(private_key, None).map_err(|e| e.to_string())?;
+
+            CipherType::SshKey(Box::new(SshKey {
+                private_key: key.private_key,
+                public_key: key.public_key,
+                fingerprint: key.key_fingerprint,
+            }))
+        }
+        // Other categories, like bank accounts or servers, only keep their fields.
+        _ => CipherType::SecureNote(Box::new(SecureNote {
+            r#type: SecureNoteType::Generic,
+        })),
+    };
+
+    let cipher = new_cipher(name, r#type);
+    let updated_at = DateTime::from_timestamp(item.updated_at, 0);
+
+    Ok(ImportingCipher {
+        notes: non_empty(item.details.notes_plain),
+        favorite: item.fav_index > 0,
+        fields: fields.into_fields(),
+        creation_date: DateTime::from_timestamp(item.created_at, 0).unwrap_or(cipher.creation_date),
+        revision_date: updated_at.unwrap_or(cipher.revision_date),
+        // Archived items are hidden from the vault, which is closest to being deleted.
+        deleted_date: (item.state.as_deref() == Some(ARCHIVED_STATE))
+            .then(|| updated_at.unwrap_or_else(Utc::now)),
+        ..cipher
+    })
+}
+
+fn empty_identity() -> Identity {
+    Identity {
+        title: None,
+        first_name: None,
+        middle_name: None,
+        last_name: None,
+        address1: None,
+        address2: None,
+        address3: None,
+        city: None,
+        state: None,
+        postal_code: None,
+        country: None,
+        company: None,
+        email: None,
+        phone: None,
+        ssn: None,
+        username: None,
+        passport_number: None,
+        license_number: None,
+    }
+}
+
+/// Split a full name into the first, middle and last name of an identity.
+fn split_name(full_name: Option<String>) -> Identity {
+    let full_name = full_name.unwrap_or_default();
+    let mut parts: Vec<&str> = full_name.split_whitespace().collect();
+
+    let first_name = (!parts.is_empty()).then(|| parts.remove(0).to_owned());
+    let last_name = parts.pop().map(|p| p.to_owned());
+    let middle_name = (!parts.is_empty()).then(|| parts.join(" "));
+
+    Identity {
+        first_name,
+        middle_name,
+        last_name,
+        ..empty_identity()
+    }
+}
+
+/// The values of the section fields of an item, identified by their id. Fields which aren't
+/// mapped to a property of the cipher are kept as custom fields.
+struct SectionFields(Vec<(String, String, FieldValue)>);
+
+impl SectionFields {
+    fn take(&mut self, id: &str) -> Option<FieldValue> {
+        let index = self.0.iter().position(|(i, _, _)| i == id)?;
+        Some(self.0.remove(index).2)
+    }
+
+    fn take_text(&mut self, id: &str) -> Option<String> {
+        match self.take(id)? {
+            FieldValue::Text(value) | FieldValue::Hidden(value) => non_empty(Some(value)),
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn take_totp(&mut self) -> Option<String> {
+        let index = self
+            .0
+            .iter()
+            .position(|(_, _, v)| matches!(v, FieldValue::Totp(_)))?;
+
+        match self.0.remove(index).2 {
+            FieldValue::Totp(totp) => non_empty(Some(totp)),
+            _ => None,
+        }
+    }
+
+    fn take_ssh_key(&mut self) -> Option<String> {
+        let index = self
+            .0
+            .iter()
+            .position(|(_, _, v)| matches!(v, FieldValue::SshKey(_)))?;
+
+        match self.0.remove(index).2 {
+            FieldValue::SshKey(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    fn take_address(&mut self, identity: &mut Identity) {
+        if let Some(FieldValue::Address(address)) = self.take("address") {
+            identity.address1 = non_empty(address.street);
+            identity.city = non_empty(address.city);
+            identity.state = non_empty(address.state);
+            identity.postal_code = non_empty(address.zip);
+            identity.country = non_empty(address.country);
+        }
+    }
+
+    fn into_fields(self) -> Vec<Field> {
+        self.0
+            .into_iter()
+            .filter_map(|(_, title, value)| {
+                let r#type = match value {
+                    FieldValue::Hidden(_) | FieldValue::SshKey(_) => 1,
+                    _ => 0,
+                };
+                let value = value.to_string();
+
+                (!value.is_empty()).then_some(Field {
+                    name: Some(title),
+                    value: Some(value),
+                    r#type,
+                    linked_id: None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The supported values of a section field. Values like file references are ignored.
+enum FieldValue {
+    Text(String),
+    Hidden(String),
+    Totp(String),
+    MonthYear { month: u32, year: u32 },
+    Address(Address),
+    SshKey(String),
+}
+
+impl FieldValue {
+    /// Field values are objects with a single key describing the type of the value.
+    fn parse(value: Map<String, Value>) -> Option<Self> {
+        let (kind, value) = value.into_iter().next()?;
+
+        Some(match (kind.as_str(), value) {
+            ("concealed", Value::String(v)) => FieldValue::Hidden(v),
+            ("totp", Value::String(v)) => FieldValue::Totp(v),
+            ("email", Value::Object(v)) => {
+                FieldValue::Text(v.get("email_address")?.as_str()?.to_owned())
+            }
+            // Stored as `YYYYMM`
+            ("monthYear", Value::Number(v)) => {
+                let v = v.as_u64()?;
+                FieldValue::MonthYear {
+                    month: (v % 100) as u32,
+                    year: (v / 100) as u32,
+                }
+            }
+            ("date", Value::Number(v)) => FieldValue::Text(
+                DateTime::from_timestamp(v.as_i64()?, 0)?
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            ),
+            ("address", v) => FieldValue::Address(serde_json::from_value(v).ok()?),
+            ("sshKey", Value::Object(v)) => {
+                FieldValue::SshKey(v.get("privateKey")?.as_str()?.to_owned())
+            }
+            ("file" | "reference", _) => return None,
+            (_, Value::String(v)) => FieldValue::Text(v),
+            _ => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Text(v) | FieldValue::Hidden(v) | FieldValue::Totp(v) => f.write_str(v),
+            FieldValue::SshKey(v) => f.write_str(v),
+            FieldValue::MonthYear { month, year } => write!(f, "{month:02}/{year}"),
+            FieldValue::Address(a) => {
+                let parts = [&a.street, &a.city, &a.state, &a.zip, &a.country];
+                let parts: Vec<&str> = parts
+                    .into_iter()
+                    .flatten()
+                    .map(|p| p.as_str())
+                    .filter(|p| !p.is_empty())
+                    .collect();
+                f.write_str(&parts.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportData {
+    accounts: Vec<ExportAccount>,
+}
+
+#[derive(Deserialize)]
+struct ExportAccount {
+    #[serde(default)]
+    vaults: Vec<Vault>,
+}
+
+#[derive(Deserialize)]
+struct Vault {
+    attrs: VaultAttributes,
+    #[serde(default)]
+    items: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct VaultAttributes {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    #[serde(default)]
+    fav_index: u32,
+    created_at: i64,
+    updated_at: i64,
+    state: Option<String>,
+    category_uuid: String,
+    details: ItemDetails,
+    overview: ItemOverview,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemDetails {
+    #[serde(default)]
+    login_fields: Vec<LoginField>,
+    notes_plain: Option<String>,
+    #[serde(default)]
+    sections: Vec<Section>,
+    /// Only used by the Password category.
+    password: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LoginField {
+    value: String,
+    designation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Section {
+    #[serde(default)]
+    fields: Vec<SectionField>,
+}
+
+#[derive(Deserialize)]
+struct SectionField {
+    title: String,
+    id: String,
+    value: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct ItemOverview {
+    title: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    urls: Vec<ItemUrl>,
+}
+
+#[derive(Deserialize)]
+struct ItemUrl {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct Address {
+    street: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    zip: Option<String>,
+    country: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(items: &str) -> String {
+        format!(
+            r#"{{
+                "accounts": [{{
+                    "attrs": {{ "accountName": "John", "name": "John Doe" }},
+                    "vaults": [{{
+                        "attrs": {{ "uuid": "abc", "name": "Personal", "type": "P" }},
+                        "items": [{items}]
+                    }}]
+                }}]
+            }}"#
+        )
+    }
+
+    #[test]
+    fn test_import_login() {
+        let payload = export(
+            r#"{
+                "uuid": "1", "favIndex": 1, "createdAt": 1700000000, "updatedAt": 1700001000,
+                "state": "active", "categoryUuid": "001",
+                "details": {
+                    "loginFields": [
+                        { "value": "user@example.com", "name": "email", "fieldType": "E", "designation": "username" },
+                        { "value": "password123", "name": "password", "fieldType": "P", "designation": "password" }
+                    ],
+                    "notesPlain": "Some notes",
+                    "sections": [{
+                        "title": "", "name": "add more",
+                        "fields": [
+                            { "title": "one-time password", "id": "TOTP_1", "value": { "totp": "otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP" } },
+                            { "title": "PIN", "id": "pin", "value": { "concealed": "1234" } },
+                            { "title": "Recovery email", "id": "recovery", "value": { "email": { "email_address": "other@example.com", "provider": null } } },
+                            { "title": "Attachment", "id": "file", "value": { "file": { "fileName": "a.txt" } } }
+                        ]
+                    }],
+                    "passwordHistory": []
+                },
+                "overview": {
+                    "title": "Example", "url": "https://example.com",
+                    "urls": [{ "label": "", "url": "https://example.com" }, { "label": "", "url": "https://login.example.com" }]
+                }
+            }"#,
+        );
+
+        let result = import_1pux(&payload).unwrap();
+
+        assert_eq!(result.folders.len(), 1);
+        assert_eq!(result.folders[0].name, "Personal");
+        assert_eq!(result.ciphers.len(), 1);
+
+        let cipher = &result.ciphers[0];
+        assert_eq!(cipher.name, "Example");
+        assert_eq!(cipher.folder_id, Some(result.folders[0].id));
+        assert_eq!(cipher.notes.as_deref(), Some("Some notes"));
+        assert!(cipher.favorite);
+        assert_eq!(cipher.creation_date.timestamp(), 1700000000);
+        assert_eq!(cipher.revision_date.timestamp(), 1700001000);
+        assert_eq!(cipher.deleted_date, None);
+
+        match &cipher.r#type {
+            CipherType::Login(login) => {
+                assert_eq!(login.username.as_deref(), Some("user@example.com"));
+                assert_eq!(login.password.as_deref(), Some("password123"));
+                assert_eq!(
+                    login.totp.as_deref(),
+                    Some("otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP")
+                );
+                assert_eq!(login.login_uris.len(), 2);
+            }
+            _ => panic!("Expected login"),
+        }
+
+        assert_eq!(cipher.fields.len(), 2);
+        assert_eq!(cipher.fields[0].name.as_deref(), Some("PIN"));
+        assert_eq!(cipher.fields[0].r#type, 1);
+        assert_eq!(cipher.fields[1].value.as_deref(), Some("other@example.com"));
+    }
+
+    #[test]
+    fn test_import_card() {
+        let payload = export(
+            r#"{
+                "uuid": "2", "createdAt": 1700000000, "updatedAt": 1700000000,
+                "state": "archived", "categoryUuid": "002",
+                "details": {
+                    "sections": [{
+                        "title": "", "name": "",
+                        "fields": [
+                            { "title": "cardholder name", "id": "cardholder", "value": { "string": "John Doe" } },
+                            { "title": "type", "id": "type", "value": { "creditCardType": "visa" } },
+                            { "title": "number", "id": "ccnum", "value": { "creditCardNumber": "4111111111111111" } },
+                            { "title": "verification number", "id": "cvv", "value": { "concealed": "123" } },
+                            { "title": "expiry date", "id": "expiry", "value": { "monthYear": 203006 } },
+                            { "title": "valid from", "id": "validFrom", "value": { "monthYear": 202406 } }
+                        ]
+                    }]
+                },
+                "overview": { "title": "Visa" }
+            }"#,
+        );
+
+        let result = import_1pux(&payload).unwrap();
+        let cipher = &result.ciphers[0];
+
+        assert!(!cipher.favorite);
+        assert!(cipher.deleted_date.is_some());
+        match &cipher.r#type {
+            CipherType::Card(card) => {
+                assert_eq!(card.cardholder_name.as_deref(), Some("John Doe"));
+                assert_eq!(card.brand.as_deref(), Some("visa"));
+                assert_eq!(card.number.as_deref(), Some("4111111111111111"));
+                assert_eq!(card.code.as_deref(), Some("123"));
+                assert_eq!(card.exp_month.as_deref(), Some("6"));
+                assert_eq!(card.exp_year.as_deref(), Some("2030"));
+            }
+            _ => panic!("Expected card"),
+        }
+
+        assert_eq!(cipher.fields.len(), 1);
+        assert_eq!(cipher.fields[0].value.as_deref(), Some("06/2024"));
+    }
+
+    #[test]
+    fn test_import_identity_documents() {
+        let payload = export(
+            r#"{
+                "uuid": "3", "createdAt": 1700000000, "updatedAt": 1700000000, "categoryUuid": "103",
+                "details": {
+                    "sections": [{
+                        "fields": [
+                            { "title": "full name", "id": "fullname", "value": { "string": "John Michael Doe" } },
+                            { "title": "address", "id": "address", "value": { "address": { "street": "Main Street 1", "city": "Springfield", "country": "us", "zip": "12345", "state": "IL" } } },
+                            { "title": "number", "id": "number", "value": { "string": "D1234567" } },
+                            { "title": "date of birth", "id": "birthdate", "value": { "date": 946684800 } }
+                        ]
+                    }]
+                },
+                "overview": { "title": "Driver License" }
+            }, {
+                "uuid": "4", "createdAt": 1700000000, "updatedAt": 1700000000, "categoryUuid": "006",
+                "details": {},
+                "overview": { "title": "Scan.pdf" }
+            }"#,
+        );
+
+        let result = import_1pux(&payload).unwrap();
+
+        assert_eq!(result.ciphers.len(), 1);
+        let cipher = &result.ciphers[0];
+        match &cipher.r#type {
+            CipherType::Identity(identity) => {
+                assert_eq!(identity.first_name.as_deref(), Some("John"));
+                assert_eq!(identity.middle_name.as_deref(), Some("Michael"));
+                assert_eq!(identity.last_name.as_deref(), Some("Doe"));
+                assert_eq!(identity.license_number.as_deref(), Some("D1234567"));
+                assert_eq!(identity.address1.as_deref(), Some("Main Street 1"));
+                assert_eq!(identity.state.as_deref(), Some("IL"));
+            }
+            _ => panic!("Expected identity"),
+        }
+        assert_eq!(cipher.fields[0].value.as_deref(), Some("2000-01-01"));
+
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].name, "Scan.pdf");
+        assert_eq!(result.skipped[0].reason, "Documents are not supported");
+    }
+}
@@ -1,15 +1,23 @@
+use bitwarden_core::require;
 use chrono::{DateTime, Utc};
 use thiserror::Error;
 use uuid::Uuid;
 
 use crate::{
-    Card, Cipher, CipherType, Field, Folder, Identity, Login, LoginUri, SecureNote, SshKey,
+    Card, Cipher, CipherType, Collection, Field, Folder, Identity, ImportingCipher, Login,
+    LoginUri, SecureNote, SecureNoteType, SshKey,
 };
 
 #[derive(Error, Debug)]
 pub enum JsonError {
     #[error("JSON error: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error(transparent)]
+    MissingField(#[from] bitwarden_core::MissingFieldError),
+    #[error("Unknown cipher type: {0}")]
+    UnknownCipherType(u8),
+    #[error("Encrypted exports can't be imported, export the vault unencrypted")]
+    Encrypted,
 }
 
 pub(crate) fn export_json(folders: Vec<Folder>, ciphers: Vec<Cipher>) -> Result<String, JsonError> {
@@ -22,20 +30,69 @@ pub(crate) fn export_json(folders: Vec<Folder>, ciphers: Vec<Cipher>) -> Result<
     Ok(serde_json::to_string_pretty(&export)?)
 }
 
+pub(crate) fn export_organization_json(
+    collections: Vec<Collection>,
+    ciphers: Vec<Cipher>,
+) -> Result<String, JsonError> {
+    let export = JsonOrganizationExport {
+        encrypted: false,
+        collections: collections.into_iter().map(|c| c.into()).collect(),
+        items: ciphers.into_iter().map(|c| c.into()).collect(),
+    };
+
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+/// Parse a decrypted Bitwarden JSON export, either of an individual or an organization vault.
+/// Collections are not imported.
+pub(crate) fn import_json(payload: &str) -> Result<(Vec<Folder>, Vec<ImportingCipher>), JsonError> {
+    // Password protected exports have no items, so check this before parsing the whole export
+    let header: JsonImportHeader = serde_json::from_str(payload)?;
+    if header.encrypted {
+        return Err(JsonError::Encrypted);
+    }
+
+    let import: JsonExport = serde_json::from_str(payload)?;
+
+    let folders = import.folders.into_iter().map(|f| f.into()).collect();
+    let ciphers = import
+        .items
+        .into_iter()
+        .map(|c| c.try_into())
+        .collect::<Result<_, _>>()?;
+
+    Ok((folders, ciphers))
+}
+
 /// JSON export format. These are intentionally decoupled from the internal data structures to
 /// ensure internal changes are not reflected in the public exports.
 ///
 /// Be careful about changing these structs to maintain compatibility with old exporters/importers.
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonExport {
     encrypted: bool,
+    // Organization exports contain collections instead of folders
+    #[serde(default)]
     folders: Vec<JsonFolder>,
     items: Vec<JsonCipher>,
 }
 
+#[derive(serde::Deserialize)]
+struct JsonImportHeader {
+    encrypted: bool,
+}
+
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
+struct JsonOrganizationExport {
+    encrypted: bool,
+    collections: Vec<JsonCollection>,
+    items: Vec<JsonCipher>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct JsonFolder {
     id: Uuid,
     name: String,
@@ -50,8 +107,37 @@ impl From<Folder> for JsonFolder {
     }
 }
 
+impl From<JsonFolder> for Folder {
+    fn from(folder: JsonFolder) -> Self {
+        Folder {
+            id: folder.id,
+            name: folder.name,
+        }
+    }
+}
+
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
+struct JsonCollection {
+    id: Uuid,
+    organization_id: Uuid,
+    name: String,
+    external_id: Option<String>,
+}
+
+impl From<Collection> for JsonCollection {
+    fn from(collection: Collection) -> Self {
+        JsonCollection {
+            id: collection.id,
+            organization_id: collection.organization_id,
+            name: collection.name,
+            external_id: collection.external_id,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct JsonCipher {
     id: Uuid,
     folder_id: Option<Uuid>,
@@ -77,8 +163,9 @@ struct JsonCipher {
     favorite: bool,
     reprompt: u8,
 
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     fields: Vec<JsonField>,
+    #[serde(skip_deserializing)]
     password_history: Option<Vec<String>>,
 
     revision_date: DateTime<Utc>,
@@ -86,13 +173,15 @@ struct JsonCipher {
     deleted_date: Option<DateTime<Utc>>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonLogin {
     username: Option<String>,
     password: Option<String>,
+    #[serde(default)]
     uris: Vec<JsonLoginUri>,
     totp: Option<String>,
+    #[serde(default, skip_deserializing)]
     fido2_credentials: Vec<String>,
 }
 
@@ -108,7 +197,19 @@ impl From<Login> for JsonLogin {
     }
 }
 
-#[derive(serde::Serialize)]
+impl From<JsonLogin> for Login {
+    fn from(login: JsonLogin) -> Self {
+        Login {
+            username: login.username,
+            password: login.password,
+            login_uris: login.uris.into_iter().map(|u| u.into()).collect(),
+            totp: login.totp,
+            fido2_credentials: None,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonLoginUri {
     uri: Option<String>,
@@ -124,7 +225,16 @@ impl From<LoginUri> for JsonLoginUri {
     }
 }
 
-#[derive(serde::Serialize)]
+impl From<JsonLoginUri> for LoginUri {
+    fn from(login_uri: JsonLoginUri) -> Self {
+        LoginUri {
+            uri: login_uri.uri,
+            r#match: login_uri.r#match,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonSecureNote {
     r#type: u8,
@@ -138,7 +248,15 @@ impl From<SecureNote> for JsonSecureNote {
     }
 }
 
-#[derive(serde::Serialize)]
+impl From<JsonSecureNote> for SecureNote {
+    fn from(_note: JsonSecureNote) -> Self {
+        SecureNote {
+            r#type: SecureNoteType::Generic,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonCard {
     cardholder_name: Option<String>,
@@ -162,7 +280,20 @@ impl From<Card> for JsonCard {
     }
 }
 
-#[derive(serde::Serialize)]
+impl From<JsonCard> for Card {
+    fn from(card: JsonCard) -> Self {
+        Card {
+            cardholder_name: card.cardholder_name,
+            exp_month: card.exp_month,
+            exp_year: card.exp_year,
+            code: card.code,
+            brand: card.brand,
+            number: card.number,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonIdentity {
     title: Option<String>,
@@ -210,7 +341,32 @@ impl From<Identity> for JsonIdentity {
     }
 }
 
-#[derive(serde::Serialize)]
+impl From<JsonIdentity> for Identity {
+    fn from(identity: JsonIdentity) -> Self {
+        Identity {
+            title: identity.title,
+            first_name: identity.first_name,
+            middle_name: identity.middle_name,
+            last_name: identity.last_name,
+            address1: identity.address1,
+            address2: identity.address2,
+            address3: identity.address3,
+            city: identity.city,
+            state: identity.state,
+            postal_code: identity.postal_code,
+            country: identity.country,
+            company: identity.company,
+            email: identity.email,
+            phone: identity.phone,
+            ssn: identity.ssn,
+            username: identity.username,
+            passport_number: identity.passport_number,
+            license_number: identity.license_number,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonSshKey {
     private_key: String,
@@ -228,7 +384,17 @@ impl From<SshKey> for JsonSshKey {
     }
 }
 
-#[derive(serde::Serialize)]
+impl From<JsonSshKey> for SshKey {
+    fn from(ssh_key: JsonSshKey) -> Self {
+        SshKey {
+            private_key: ssh_key.private_key,
+            public_key: ssh_key.public_key,
+            fingerprint: ssh_key.key_fingerprint,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonField {
     name: Option<String>,
@@ -248,6 +414,17 @@ impl From<Field> for JsonField {
     }
 }
 
+impl From<JsonField> for Field {
+    fn from(field: JsonField) -> Self {
+        Field {
+            name: field.name,
+            value: field.value,
+            r#type: field.r#type,
+            linked_id: field.linked_id,
+        }
+    }
+}
+
 impl From<Cipher> for JsonCipher {
     fn from(cipher: Cipher) -> Self {
         let r#type = match cipher.r#type {
@@ -269,8 +446,8 @@ impl From<Cipher> for JsonCipher {
         JsonCipher {
             id: cipher.id,
             folder_id: cipher.folder_id,
-            organization_id: None,
-            collection_ids: None,
+            organization_id: cipher.organization_id,
+            collection_ids: cipher.organization_id.map(|_| cipher.collection_ids),
             name: cipher.name,
             notes: cipher.notes,
             r#type,
@@ -290,6 +467,34 @@ impl From<Cipher> for JsonCipher {
     }
 }
 
+impl TryFrom<JsonCipher> for ImportingCipher {
+    type Error = JsonError;
+
+    fn try_from(cipher: JsonCipher) -> Result<Self, Self::Error> {
+        let r#type = match cipher.r#type {
+            1 => CipherType::Login(Box::new(require!(cipher.login).into())),
+            2 => CipherType::SecureNote(Box::new(require!(cipher.secure_note).into())),
+            3 => CipherType::Card(Box::new(require!(cipher.card).into())),
+            4 => CipherType::Identity(Box::new(require!(cipher.identity).into())),
+            5 => CipherType::SshKey(Box::new(require!(cipher.ssh_key).into())),
+            t => return Err(JsonError::UnknownCipherType(t)),
+        };
+
+        Ok(ImportingCipher {
+            folder_id: cipher.folder_id,
+            name: cipher.name,
+            notes: cipher.notes,
+            r#type,
+            favorite: cipher.favorite,
+            reprompt: cipher.reprompt,
+            fields: cipher.fields.into_iter().map(|f| f.into()).collect(),
+            revision_date: cipher.revision_date,
+            creation_date: cipher.creation_date,
+            deleted_date: cipher.deleted_date,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, io::Read, path::PathBuf};
@@ -302,6 +507,8 @@ mod tests {
         let cipher = Cipher {
             id: "25c8c414-b446-48e9-a1bd-b10700bbd740".parse().unwrap(),
             folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+            organization_id: None,
+            collection_ids: vec![],
 
             name: "Bitwarden".to_string(),
             notes: Some("My note".to_string()),
@@ -431,6 +638,8 @@ mod tests {
         let cipher = Cipher {
             id: "23f0f877-42b1-4820-a850-b10700bc41eb".parse().unwrap(),
             folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
 
             name: "My secure note".to_string(),
             notes: Some("Very secure!".to_string()),
@@ -476,11 +685,85 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_export_organization() {
+        let json = export_organization_json(
+            vec![Collection {
+                id: "e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5".parse().unwrap(),
+                organization_id: "1bc9ac1e-f5aa-45f2-94bf-b181009709b8".parse().unwrap(),
+                name: "Engineering".to_string(),
+                external_id: None,
+            }],
+            vec![Cipher {
+                id: "23f0f877-42b1-4820-a850-b10700bc41eb".parse().unwrap(),
+                folder_id: None,
+                organization_id: Some("1bc9ac1e-f5aa-45f2-94bf-b181009709b8".parse().unwrap()),
+                collection_ids: vec!["e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5".parse().unwrap()],
+
+                name: "My secure note".to_string(),
+                notes: Some("Very secure!".to_string()),
+
+                r#type: CipherType::SecureNote(Box::new(SecureNote {
+                    r#type: SecureNoteType::Generic,
+                })),
+
+                favorite: false,
+                reprompt: 0,
+
+                fields: vec![],
+
+                revision_date: "2024-01-30T11:25:25.466Z".parse().unwrap(),
+                creation_date: "2024-01-30T11:25:25.466Z".parse().unwrap(),
+                deleted_date: None,
+            }],
+        )
+        .unwrap();
+
+        let expected = r#"{
+            "encrypted": false,
+            "collections": [
+              {
+                "id": "e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5",
+                "organizationId": "1bc9ac1e-f5aa-45f2-94bf-b181009709b8",
+                "name": "Engineering",
+                "externalId": null
+              }
+            ],
+            "items": [
+              {
+                "passwordHistory": null,
+                "revisionDate": "2024-01-30T11:25:25.466Z",
+                "creationDate": "2024-01-30T11:25:25.466Z",
+                "deletedDate": null,
+                "id": "23f0f877-42b1-4820-a850-b10700bc41eb",
+                "organizationId": "1bc9ac1e-f5aa-45f2-94bf-b181009709b8",
+                "folderId": null,
+                "type": 2,
+                "reprompt": 0,
+                "name": "My secure note",
+                "notes": "Very secure!",
+                "favorite": false,
+                "secureNote": {
+                  "type": 0
+                },
+                "collectionIds": ["e0ad2ea0-c1bd-4bd4-9c63-b18301109bc5"]
+              }
+            ]
+        }"#;
+
+        assert_eq!(
+            json.parse::<serde_json::Value>().unwrap(),
+            expected.parse::<serde_json::Value>().unwrap()
+        )
+    }
+
     #[test]
     fn test_convert_card() {
         let cipher = Cipher {
             id: "3ed8de45-48ee-4e26-a2dc-b10701276c53".parse().unwrap(),
             folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
 
             name: "My card".to_string(),
             notes: None,
@@ -541,6 +824,8 @@ mod tests {
         let cipher = Cipher {
             id: "41cc3bc1-c3d9-4637-876c-b10701273712".parse().unwrap(),
             folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+            organization_id: None,
+            collection_ids: vec![],
 
             name: "My identity".to_string(),
             notes: None,
@@ -625,6 +910,8 @@ mod tests {
         let cipher = Cipher {
             id: "23f0f877-42b1-4820-a850-b10700bc41eb".parse().unwrap(),
             folder_id: None,
+            organization_id: None,
+            collection_ids: vec![],
 
             name: "My ssh key".to_string(),
             notes: None,
@@ -694,6 +981,8 @@ mod tests {
                 Cipher {
                     id: "25c8c414-b446-48e9-a1bd-b10700bbd740".parse().unwrap(),
                     folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "Bitwarden".to_string(),
                     notes: Some("My note".to_string()),
@@ -752,6 +1041,8 @@ mod tests {
                 Cipher {
                     id: "23f0f877-42b1-4820-a850-b10700bc41eb".parse().unwrap(),
                     folder_id: None,
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "My secure note".to_string(),
                     notes: Some("Very secure!".to_string()),
@@ -772,6 +1063,8 @@ mod tests {
                 Cipher {
                     id: "3ed8de45-48ee-4e26-a2dc-b10701276c53".parse().unwrap(),
                     folder_id: None,
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "My card".to_string(),
                     notes: None,
@@ -797,6 +1090,8 @@ mod tests {
                 Cipher {
                     id: "41cc3bc1-c3d9-4637-876c-b10701273712".parse().unwrap(),
                     folder_id: Some("942e2984-1b9a-453b-b039-b107012713b9".parse().unwrap()),
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "My identity".to_string(),
                     notes: None,
@@ -834,6 +1129,8 @@ mod tests {
                 Cipher {
                     id: "646594a9-a9cb-4082-9d57-0024c3fbcaa9".parse().unwrap(),
                     folder_id: None,
+                    organization_id: None,
+                    collection_ids: vec![],
 
                     name: "My ssh key".to_string(),
                     notes: None,
@@ -862,4 +1159,102 @@ mod tests {
             expected.parse::<serde_json::Value>().unwrap()
         )
     }
+
+    #[test]
+    pub fn test_import() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources");
+        d.push("json_export.json");
+
+        let mut file = fs::File::open(d).unwrap();
+
+        let mut payload = String::new();
+        file.read_to_string(&mut payload).unwrap();
+
+        let (folders, ciphers) = import_json(&payload).unwrap();
+
+        assert_eq!(folders.len(), 1);
+        assert_eq!(folders[0].name, "Important");
+
+        let types: Vec<String> = ciphers.iter().map(|c| c.r#type.to_string()).collect();
+        assert_eq!(types, ["login", "note", "card", "identity", "ssh_key"]);
+
+        let login = &ciphers[0];
+        assert_eq!(login.folder_id, Some(folders[0].id));
+        assert_eq!(login.name, "Bitwarden");
+        assert_eq!(login.notes.as_deref(), Some("My note"));
+        assert!(login.favorite);
+        assert_eq!(login.fields.len(), 5);
+        assert_eq!(login.fields[4].linked_id, Some(101));
+        let CipherType::Login(l) = &login.r#type else {
+            panic!("Expected login");
+        };
+        assert_eq!(l.username.as_deref(), Some("test@bitwarden.com"));
+        assert_eq!(
+            l.login_uris[0].uri.as_deref(),
+            Some("https://vault.bitwarden.com")
+        );
+
+        let CipherType::SshKey(ssh_key) = &ciphers[4].r#type else {
+            panic!("Expected ssh key");
+        };
+        assert_eq!(
+            ssh_key.fingerprint,
+            "SHA256:1JjFjvPRkj1Gbf2qRP1dgHiIzEuNAEvp+92x99jw3K0"
+        );
+    }
+
+    #[test]
+    fn test_import_unknown_type() {
+        let payload = r#"{
+            "encrypted": false,
+            "folders": [],
+            "items": [
+                {
+                    "id": "25c8c414-b446-48e9-a1bd-b10700bbd740",
+                    "folderId": null,
+                    "organizationId": null,
+                    "collectionIds": null,
+                    "name": "Unknown",
+                    "notes": null,
+                    "type": 42,
+                    "favorite": false,
+                    "reprompt": 0,
+                    "revisionDate": "2024-01-30T14:09:33.753Z",
+                    "creationDate": "2024-01-30T11:23:54.416Z",
+                    "deletedDate": null
+                }
+            ]
+        }"#;
+
+        let result = import_json(payload);
+        assert!(matches!(result, Err(JsonError::UnknownCipherType(42))));
+    }
+
+    #[test]
+    fn test_import_encrypted() {
+        let account_encrypted = r#"{
+            "encrypted": true,
+            "folders": [],
+            "items": []
+        }"#;
+        let password_protected = r#"{
+            "encrypted": true,
+            "passwordProtected": true,
+            "salt": "c2FsdA==",
+            "kdfType": 0,
+            "kdfIterations": 600000,
+            "encKeyValidation_DO_NOT_EDIT": "2.ZXhhbXBsZQ==|ZXhhbXBsZQ==|ZXhhbXBsZQ==",
+            "data": "2.ZXhhbXBsZQ==|ZXhhbXBsZQ==|ZXhhbXBsZQ=="
+        }"#;
+
+        assert!(matches!(
+            import_json(account_encrypted),
+            Err(JsonError::Encrypted)
+        ));
+        assert!(matches!(
+            import_json(password_protected),
+            Err(JsonError::Encrypted)
+        ));
+    }
 }
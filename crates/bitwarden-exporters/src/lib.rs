@@ -1,7 +1,8 @@
 use std::fmt;
 
 use bitwarden_vault::{
-    CipherRepromptType, CipherView, Fido2CredentialFullView, LoginUriView, UriMatchType,
+    CardView, CipherRepromptType, CipherView, Fido2CredentialFullView, FieldType, FieldView,
+    IdentityView, LoginUriView, LoginView, SecureNoteView, SshKeyView, UriMatchType,
 };
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
@@ -17,6 +18,7 @@ mod cxf;
 pub use cxf::Account;
 mod encrypted_json;
 mod exporter_client;
+mod importers;
 mod json;
 mod models;
 pub use exporter_client::{ExporterClient, ExporterClientExt};
@@ -32,6 +34,52 @@ pub enum ExportFormat {
     EncryptedJson { password: String },
 }
 
+#[derive(JsonSchema)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ImportFormat {
+    Csv,
+    Json,
+    EncryptedJson {
+        password: String,
+    },
+    /// The `export.data` file contained in a 1Password `.1pux` archive.
+    OnePassword1Pux,
+    /// An unencrypted KeePass 2.x XML export.
+    KeePassXml,
+    LastPassCsv,
+    /// A password export from Chrome or another Chromium based browser.
+    ChromeCsv,
+    FirefoxCsv,
+}
+
+/// The result of importing a vault export, encrypted with the user key.
+///
+/// The folders keep the ids referenced by the ciphers, which need to be remapped to the ids
+/// assigned by the server when uploading.
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct ImportedVault {
+    pub folders: Vec<bitwarden_vault::Folder>,
+    pub ciphers: Vec<bitwarden_vault::Cipher>,
+    /// Entries of the export which could not be imported.
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// The result of a Credential Exchange Format (CXF) export.
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct CxfExport {
+    pub payload: String,
+    /// Ciphers which could not be exported, like SSH keys of an unsupported type.
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// An entry of an export which was not imported, along with the reason why.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct SkippedItem {
+    pub name: String,
+    pub reason: String,
+}
+
 /// Export representation of a Bitwarden folder.
 ///
 /// These are mostly duplicated from the `bitwarden` vault models to facilitate a stable export API
@@ -41,6 +89,17 @@ pub struct Folder {
     pub name: String,
 }
 
+/// Export representation of a Bitwarden collection.
+///
+/// These are mostly duplicated from the `bitwarden` vault models to facilitate a stable export API
+/// that is not tied to the internal vault models. We may revisit this in the future.
+pub struct Collection {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub external_id: Option<String>,
+}
+
 /// Export representation of a Bitwarden cipher.
 ///
 /// These are mostly duplicated from the `bitwarden` vault models to facilitate a stable export API
@@ -49,6 +108,8 @@ pub struct Folder {
 pub struct Cipher {
     pub id: Uuid,
     pub folder_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub collection_ids: Vec<Uuid>,
 
     pub name: String,
     pub notes: Option<String>,
@@ -90,27 +151,51 @@ pub struct ImportingCipher {
 
 impl From<ImportingCipher> for CipherView {
     fn from(value: ImportingCipher) -> Self {
-        let login = match value.r#type {
-            CipherType::Login(login) => {
-                let l: Vec<LoginUriView> = login
-                    .login_uris
-                    .into_iter()
-                    .map(LoginUriView::from)
-                    .collect();
-
-                Some(bitwarden_vault::LoginView {
-                    username: login.username,
-                    password: login.password,
-                    password_revision_date: None,
-                    uris: if l.is_empty() { None } else { Some(l) },
-                    totp: login.totp,
-                    autofill_on_page_load: None,
-                    fido2_credentials: None,
-                })
-            }
-            _ => None,
+        let (r#type, login, secure_note, card, identity, ssh_key) = match value.r#type {
+            CipherType::Login(login) => (
+                bitwarden_vault::CipherType::Login,
+                Some((*login).into()),
+                None,
+                None,
+                None,
+                None,
+            ),
+            CipherType::SecureNote(note) => (
+                bitwarden_vault::CipherType::SecureNote,
+                None,
+                Some((*note).into()),
+                None,
+                None,
+                None,
+            ),
+            CipherType::Card(card) => (
+                bitwarden_vault::CipherType::Card,
+                None,
+                None,
+                Some((*card).into()),
+                None,
+                None,
+            ),
+            CipherType::Identity(identity) => (
+                bitwarden_vault::CipherType::Identity,
+                None,
+                None,
+                None,
+                Some((*identity).into()),
+                None,
+            ),
+            CipherType::SshKey(ssh_key) => (
+                bitwarden_vault::CipherType::SshKey,
+                None,
+                None,
+                None,
+                None,
+                Some((*ssh_key).into()),
+            ),
         };
 
+        let fields: Vec<FieldView> = value.fields.into_iter().map(FieldView::from).collect();
+
         Self {
             id: None,
             organization_id: None,
@@ -118,29 +203,57 @@ impl From<ImportingCipher> for CipherView {
             collection_ids: vec![],
             key: None,
             name: value.name,
-            notes: None,
-            r#type: bitwarden_vault::CipherType::Login,
+            notes: value.notes,
+            r#type,
             login,
-            identity: None,
-            card: None,
-            secure_note: None,
-            ssh_key: None,
+            identity,
+            card,
+            secure_note,
+            ssh_key,
             favorite: value.favorite,
-            reprompt: CipherRepromptType::None,
+            reprompt: match value.reprompt {
+                1 => CipherRepromptType::Password,
+                _ => CipherRepromptType::None,
+            },
             organization_use_totp: true,
             edit: true,
             view_password: true,
             local_data: None,
             attachments: None,
-            fields: None,
+            fields: if fields.is_empty() {
+                None
+            } else {
+                Some(fields)
+            },
             password_history: None,
             creation_date: value.creation_date,
-            deleted_date: None,
+            deleted_date: value.deleted_date,
             revision_date: value.revision_date,
         }
     }
 }
 
+/// Passkeys are not part of the view, they are encrypted separately when importing.
+impl From<Login> for LoginView {
+    fn from(value: Login) -> Self {
+        let uris: Vec<LoginUriView> = value
+            .login_uris
+            .into_iter()
+            .map(LoginUriView::from)
+            .collect();
+
+        Self {
+            username: value.username,
+            password: value.password,
+            password_revision_date: None,
+            uris: if uris.is_empty() { None } else { Some(uris) },
+            totp: value.totp,
+            autofill_on_page_load: None,
+            fido2_credentials: None,
+        }
+    }
+}
+
 impl From<LoginUri> for bitwarden_vault::LoginUriView {
     fn from(value: LoginUri) -> Self {
         Self {
@@ -167,6 +280,22 @@ pub struct Field {
     pub linked_id: Option<u32>,
 }
 
+impl From<Field> for FieldView {
+    fn from(value: Field) -> Self {
+        Self {
+            name: value.name,
+            value: value.value,
+            r#type: match value.r#type {
+                1 => FieldType::Hidden,
+                2 => FieldType::Boolean,
+                3 => FieldType::Linked,
+                _ => FieldType::Text,
+            },
+            linked_id: value.linked_id.and_then(|id| id.try_into().ok()),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum CipherType {
     Login(Box<Login>),
@@ -251,11 +380,34 @@ pub struct Card {
     pub number: Option<String>,
 }
 
+impl From<Card> for CardView {
+    fn from(value: Card) -> Self {
+        Self {
+            cardholder_name: value.cardholder_name,
+            exp_month: value.exp_month,
+            exp_year: value.exp_year,
+            code: value.code,
+            brand: value.brand,
+            number: value.number,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SecureNote {
     pub r#type: SecureNoteType,
 }
 
+impl From<SecureNote> for SecureNoteView {
+    fn from(value: SecureNote) -> Self {
+        Self {
+            r#type: match value.r#type {
+                SecureNoteType::Generic => bitwarden_vault::SecureNoteType::Generic,
+            },
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum SecureNoteType {
     Generic = 0,
@@ -283,6 +435,31 @@ pub struct Identity {
     pub license_number: Option<String>,
 }
 
+impl From<Identity> for IdentityView {
+    fn from(value: Identity) -> Self {
+        Self {
+            title: value.title,
+            first_name: value.first_name,
+            middle_name: value.middle_name,
+            last_name: value.last_name,
+            address1: value.address1,
+            address2: value.address2,
+            address3: value.address3,
+            city: value.city,
+            state: value.state,
+            postal_code: value.postal_code,
+            country: value.country,
+            company: value.company,
+            email: value.email,
+            phone: value.phone,
+            ssn: value.ssn,
+            username: value.username,
+            passport_number: value.passport_number,
+            license_number: value.license_number,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SshKey {
     /// [OpenSSH private key](https://github.com/openssh/openssh-portable/blob/master/PROTOCOL.key), in PEM encoding.
@@ -292,3 +469,156 @@ pub struct SshKey {
     /// SSH fingerprint using SHA256 in the format: `SHA256:BASE64_ENCODED_FINGERPRINT`
     pub fingerprint: String,
 }
+
+impl From<SshKey> for SshKeyView {
+    fn from(value: SshKey) -> Self {
+        Self {
+            private_key: value.private_key,
+            public_key: value.public_key,
+            fingerprint: value.fingerprint,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn importing_cipher(r#type: CipherType) -> ImportingCipher {
+        ImportingCipher {
+            folder_id: None,
+            name: "Test".to_string(),
+            notes: Some("My note".to_string()),
+            r#type,
+            favorite: true,
+            reprompt: 1,
+            fields: vec![
+                Field {
+                    name: Some("Hidden".to_string()),
+                    value: Some("secret".to_string()),
+                    r#type: 1,
+                    linked_id: None,
+                },
+                Field {
+                    name: Some("Linked".to_string()),
+                    value: None,
+                    r#type: 3,
+                    linked_id: Some(101),
+                },
+            ],
+            revision_date: "2024-01-30T17:55:36.150Z".parse().unwrap(),
+            creation_date: "2024-01-30T17:55:36.150Z".parse().unwrap(),
+            deleted_date: Some("2024-02-01T10:00:00.000Z".parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_importing_cipher_common_fields() {
+        let view: CipherView = importing_cipher(CipherType::SecureNote(Box::new(SecureNote {
+            r#type: SecureNoteType::Generic,
+        })))
+        .into();
+
+        assert_eq!(view.r#type, bitwarden_vault::CipherType::SecureNote);
+        assert!(view.secure_note.is_some());
+        assert!(view.login.is_none());
+        assert_eq!(view.notes.as_deref(), Some("My note"));
+        assert!(view.favorite);
+        assert_eq!(view.reprompt, CipherRepromptType::Password);
+        assert_eq!(
+            view.deleted_date,
+            Some("2024-02-01T10:00:00.000Z".parse().unwrap())
+        );
+
+        let fields = view.fields.unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].value.as_deref(), Some("secret"));
+        assert!(matches!(fields[0].r#type, FieldType::Hidden));
+        assert!(matches!(fields[1].r#type, FieldType::Linked));
+        assert_eq!(fields[1].linked_id.map(u32::from), Some(101));
+    }
+
+    #[test]
+    fn test_importing_cipher_card() {
+        let view: CipherView = importing_cipher(CipherType::Card(Box::new(Card {
+            cardholder_name: Some("John Doe".to_string()),
+            exp_month: Some("1".to_string()),
+            exp_year: Some("2032".to_string()),
+            code: Some("123".to_string()),
+            brand: Some("Visa".to_string()),
+            number: Some("4111111111111111".to_string()),
+        })))
+        .into();
+
+        assert_eq!(view.r#type, bitwarden_vault::CipherType::Card);
+        let card = view.card.unwrap();
+        assert_eq!(card.cardholder_name.as_deref(), Some("John Doe"));
+        assert_eq!(card.number.as_deref(), Some("4111111111111111"));
+    }
+
+    #[test]
+    fn test_importing_cipher_identity() {
+        let view: CipherView = importing_cipher(CipherType::Identity(Box::new(Identity {
+            title: Some("Mr".to_string()),
+            first_name: Some("John".to_string()),
+            middle_name: None,
+            last_name: Some("Doe".to_string()),
+            address1: None,
+            address2: None,
+            address3: None,
+            city: None,
+            state: None,
+            postal_code: None,
+            country: None,
+            company: Some("Bitwarden".to_string()),
+            email: None,
+            phone: None,
+            ssn: None,
+            username: Some("JDoe".to_string()),
+            passport_number: None,
+            license_number: None,
+        })))
+        .into();
+
+        assert_eq!(view.r#type, bitwarden_vault::CipherType::Identity);
+        let identity = view.identity.unwrap();
+        assert_eq!(identity.first_name.as_deref(), Some("John"));
+        assert_eq!(identity.username.as_deref(), Some("JDoe"));
+    }
+
+    #[test]
+    fn test_importing_cipher_ssh_key() {
+        let view: CipherView = importing_cipher(CipherType::SshKey(Box::new(SshKey {
+            private_key: "private".to_string(),
+            public_key: "public".to_string(),
+            fingerprint: "SHA256:fingerprint".to_string(),
+        })))
+        .into();
+
+        assert_eq!(view.r#type, bitwarden_vault::CipherType::SshKey);
+        let ssh_key = view.ssh_key.unwrap();
+        assert_eq!(ssh_key.public_key, "public");
+        assert_eq!(ssh_key.fingerprint, "SHA256:fingerprint");
+    }
+
+    #[test]
+    fn test_importing_cipher_login() {
+        let view: CipherView = importing_cipher(CipherType::Login(Box::new(Login {
+            username: Some("test@bitwarden.com".to_string()),
+            password: Some("asdfasdfasdf".to_string()),
+            login_uris: vec![LoginUri {
+                uri: Some("https://vault.bitwarden.com".to_string()),
+                r#match: Some(3),
+            }],
+            totp: None,
+            fido2_credentials: None,
+        })))
+        .into();
+
+        assert_eq!(view.r#type, bitwarden_vault::CipherType::Login);
+        let login = view.login.unwrap();
+        assert_eq!(login.username.as_deref(), Some("test@bitwarden.com"));
+        let uris = login.uris.unwrap();
+        assert_eq!(uris[0].r#match, Some(UriMatchType::Exact));
+    }
+}
@@ -1,8 +1,8 @@
-use bitwarden_core::{require, MissingFieldError};
-use bitwarden_crypto::{KeyContainer, KeyDecryptable, LocateKey};
+use bitwarden_core::{key_management::KeyIds, require, MissingFieldError};
+use bitwarden_crypto::KeyStore;
 use bitwarden_vault::{
-    CardView, Cipher, CipherType, CipherView, Fido2CredentialFullView, FieldView, FolderView,
-    IdentityView, LoginUriView, SecureNoteType, SecureNoteView, SshKeyView,
+    CardView, Cipher, CipherType, CipherView, CollectionView, Fido2CredentialFullView, FieldView,
+    FolderView, IdentityView, LoginUriView, SecureNoteType, SecureNoteView, SshKeyView,
 };
 
 impl TryFrom<FolderView> for crate::Folder {
@@ -16,16 +16,28 @@ impl TryFrom<FolderView> for crate::Folder {
     }
 }
 
+impl TryFrom<CollectionView> for crate::Collection {
+    type Error = MissingFieldError;
+
+    fn try_from(value: CollectionView) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: require!(value.id),
+            organization_id: value.organization_id,
+            name: value.name,
+            external_id: value.external_id,
+        })
+    }
+}
+
 impl crate::Cipher {
     pub(crate) fn from_cipher(
-        enc: &dyn KeyContainer,
+        key_store: &KeyStore<KeyIds>,
         cipher: Cipher,
     ) -> Result<Self, crate::error::ExportError> {
-        let key = cipher.locate_key(enc, &None)?;
-        let view: CipherView = cipher.decrypt_with_key(key)?;
+        let view: CipherView = key_store.decrypt(&cipher)?;
 
         let r = match view.r#type {
-            CipherType::Login => crate::CipherType::Login(Box::new(from_login(&view, enc)?)),
+            CipherType::Login => crate::CipherType::Login(Box::new(from_login(&view, key_store)?)),
             CipherType::SecureNote => {
                 let s = require!(view.secure_note);
                 crate::CipherType::SecureNote(Box::new(s.into()))
@@ -47,6 +59,8 @@ impl crate::Cipher {
         Ok(Self {
             id: require!(view.id),
             folder_id: view.folder_id,
+            organization_id: view.organization_id,
+            collection_ids: view.collection_ids,
             name: view.name,
             notes: view.notes,
             r#type: r,
@@ -68,7 +82,7 @@ impl crate::Cipher {
 /// Convert a `LoginView` into a `crate::Login`.
 fn from_login(
     view: &CipherView,
-    enc: &dyn KeyContainer,
+    key_store: &KeyStore<KeyIds>,
 ) -> Result<crate::Login, MissingFieldError> {
     let l = require!(view.login.clone());
 
@@ -83,7 +97,7 @@ fn from_login(
             .collect(),
         totp: l.totp,
         fido2_credentials: l.fido2_credentials.as_ref().and_then(|_| {
-            let credentials = view.get_fido2_credentials(enc).ok()?;
+            let credentials = view.get_fido2_credentials(&mut key_store.context()).ok()?;
             if credentials.is_empty() {
                 None
             } else {
@@ -199,10 +213,10 @@ impl From<SecureNoteType> for crate::SecureNoteType {
 
 #[cfg(test)]
 mod tests {
-    use bitwarden_crypto::{CryptoError, KeyContainer, KeyEncryptable, SymmetricCryptoKey};
+    use bitwarden_core::key_management::SymmetricKeyId;
+    use bitwarden_crypto::SymmetricCryptoKey;
     use bitwarden_vault::{CipherRepromptType, LoginView};
     use chrono::{DateTime, Utc};
-    use uuid::Uuid;
 
     use super::*;
 
@@ -221,16 +235,44 @@ mod tests {
         assert_eq!(f.name, "test_name".to_string());
     }
 
-    struct MockKeyContainer(SymmetricCryptoKey);
-    impl KeyContainer for MockKeyContainer {
-        fn get_key<'a>(&'a self, _: &Option<Uuid>) -> Result<&'a SymmetricCryptoKey, CryptoError> {
-            Ok(&self.0)
-        }
+    #[test]
+    fn test_try_from_collection_view() {
+        let test_id: uuid::Uuid = "fd411a1a-fec8-4070-985d-0e6560860e69".parse().unwrap();
+        let org_id: uuid::Uuid = "1bc9ac1e-f5aa-45f2-94bf-b181009709b8".parse().unwrap();
+        let view = CollectionView {
+            id: Some(test_id),
+            organization_id: org_id,
+            name: "Default collection".to_string(),
+            external_id: None,
+            hide_passwords: false,
+            read_only: false,
+            manage: true,
+        };
+
+        let c: crate::Collection = view.try_into().unwrap();
+
+        assert_eq!(c.id, test_id);
+        assert_eq!(c.organization_id, org_id);
+        assert_eq!(c.name, "Default collection".to_string());
+        assert_eq!(c.external_id, None);
+    }
+
+    fn create_key_store() -> KeyStore<KeyIds> {
+        let key_store = KeyStore::<KeyIds>::default();
+        #[allow(deprecated)]
+        key_store
+            .context_mut()
+            .set_symmetric_key(
+                SymmetricKeyId::User,
+                SymmetricCryptoKey::generate(rand::thread_rng()),
+            )
+            .unwrap();
+        key_store
     }
 
     #[test]
     fn test_from_login() {
-        let enc = MockKeyContainer(SymmetricCryptoKey::generate(rand::thread_rng()));
+        let key_store = create_key_store();
 
         let test_id: uuid::Uuid = "fd411a1a-fec8-4070-985d-0e6560860e69".parse().unwrap();
         let view = CipherView {
@@ -269,7 +311,7 @@ mod tests {
             revision_date: "2024-01-30T17:55:36.150Z".parse().unwrap(),
         };
 
-        let login = from_login(&view, &enc).unwrap();
+        let login = from_login(&view, &key_store).unwrap();
 
         assert_eq!(login.username, Some("test_username".to_string()));
         assert_eq!(login.password, Some("test_password".to_string()));
@@ -279,7 +321,7 @@ mod tests {
 
     #[test]
     fn test_from_cipher_login() {
-        let enc = MockKeyContainer(SymmetricCryptoKey::generate(rand::thread_rng()));
+        let key_store = create_key_store();
 
         let test_id: uuid::Uuid = "fd411a1a-fec8-4070-985d-0e6560860e69".parse().unwrap();
         let cipher_view = CipherView {
@@ -317,11 +359,9 @@ mod tests {
             deleted_date: None,
             revision_date: "2024-01-30T17:55:36.150Z".parse().unwrap(),
         };
-        let encrypted = cipher_view
-            .encrypt_with_key(enc.get_key(&None).unwrap())
-            .unwrap();
+        let encrypted = key_store.encrypt(cipher_view).unwrap();
 
-        let cipher: crate::Cipher = crate::Cipher::from_cipher(&enc, encrypted).unwrap();
+        let cipher: crate::Cipher = crate::Cipher::from_cipher(&key_store, encrypted).unwrap();
 
         assert_eq!(cipher.id, test_id);
         assert_eq!(cipher.folder_id, None);
@@ -8,4 +8,4 @@ pub use error::SendParseError;
 mod send_client;
 pub use send_client::{SendClient, SendClientExt};
 mod send;
-pub use send::{Send, SendListView, SendView};
+pub use send::{Send, SendListView, SendTextView, SendType, SendView};
@@ -2,11 +2,16 @@ use base64::{
     engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
     Engine,
 };
-use bitwarden_api_api::models::{SendFileModel, SendResponseModel, SendTextModel};
-use bitwarden_core::require;
+use bitwarden_api_api::models::{
+    SendFileModel, SendResponseModel, SendTextModel, SendWithIdRequestModel,
+};
+use bitwarden_core::{
+    key_management::{KeyIds, SymmetricKeyId},
+    require, MissingFieldError,
+};
 use bitwarden_crypto::{
-    derive_shareable_key, generate_random_bytes, CryptoError, EncString, KeyDecryptable,
-    KeyEncryptable, SymmetricCryptoKey,
+    generate_random_bytes, CryptoError, Decryptable, EncString, Encryptable, IdentifyKey,
+    KeyStoreContext,
 };
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
@@ -141,82 +146,121 @@ pub struct SendListView {
     pub expiration_date: Option<DateTime<Utc>>,
 }
 
+const SEND_KEY: SymmetricKeyId = SymmetricKeyId::Local("send_key");
+
 impl Send {
+    /// Decrypt the send key with `enc_key` and stretch it into the context, returning its id.
     pub fn get_key(
+        ctx: &mut KeyStoreContext<KeyIds>,
         send_key: &EncString,
-        enc_key: &SymmetricCryptoKey,
-    ) -> Result<SymmetricCryptoKey, CryptoError> {
-        let key: Vec<u8> = send_key.decrypt_with_key(enc_key)?;
-        Self::derive_shareable_key(&key)
+        enc_key: SymmetricKeyId,
+    ) -> Result<SymmetricKeyId, CryptoError> {
+        let key: Vec<u8> = send_key.decrypt(ctx, enc_key)?;
+        Self::derive_shareable_key(ctx, &key)
     }
 
-    fn derive_shareable_key(key: &[u8]) -> Result<SymmetricCryptoKey, CryptoError> {
+    fn derive_shareable_key(
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: &[u8],
+    ) -> Result<SymmetricKeyId, CryptoError> {
         let key = Zeroizing::new(key.try_into().map_err(|_| CryptoError::InvalidKeyLen)?);
-        Ok(derive_shareable_key(key, "send", Some("send")))
+        ctx.derive_shareable_key(SEND_KEY, key, "send", Some("send"))
+    }
+}
+
+impl IdentifyKey<SymmetricKeyId> for Send {
+    fn key_identifier(&self) -> SymmetricKeyId {
+        SymmetricKeyId::User
+    }
+}
+
+impl IdentifyKey<SymmetricKeyId> for SendView {
+    fn key_identifier(&self) -> SymmetricKeyId {
+        SymmetricKeyId::User
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, SendTextView> for SendText {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<SendTextView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, SendTextView> for SendText {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SendTextView, CryptoError> {
         Ok(SendTextView {
-            text: self.text.decrypt_with_key(key)?,
+            text: self.text.decrypt(ctx, key)?,
             hidden: self.hidden,
         })
     }
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, SendText> for SendTextView {
-    fn encrypt_with_key(self, key: &SymmetricCryptoKey) -> Result<SendText, CryptoError> {
+impl Encryptable<KeyIds, SymmetricKeyId, SendText> for SendTextView {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SendText, CryptoError> {
         Ok(SendText {
-            text: self.text.encrypt_with_key(key)?,
+            text: self.text.encrypt(ctx, key)?,
             hidden: self.hidden,
         })
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, SendFileView> for SendFile {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<SendFileView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, SendFileView> for SendFile {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SendFileView, CryptoError> {
         Ok(SendFileView {
             id: self.id.clone(),
-            file_name: self.file_name.decrypt_with_key(key)?,
+            file_name: self.file_name.decrypt(ctx, key)?,
             size: self.size.clone(),
             size_name: self.size_name.clone(),
         })
     }
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, SendFile> for SendFileView {
-    fn encrypt_with_key(self, key: &SymmetricCryptoKey) -> Result<SendFile, CryptoError> {
+impl Encryptable<KeyIds, SymmetricKeyId, SendFile> for SendFileView {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SendFile, CryptoError> {
         Ok(SendFile {
             id: self.id.clone(),
-            file_name: self.file_name.encrypt_with_key(key)?,
+            file_name: self.file_name.encrypt(ctx, key)?,
             size: self.size.clone(),
             size_name: self.size_name.clone(),
         })
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, SendView> for Send {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<SendView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, SendView> for Send {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SendView, CryptoError> {
         // For sends, we first decrypt the send key with the user key, and stretch it to it's full
         // size For the rest of the fields, we ignore the provided SymmetricCryptoKey and
         // the stretched key
-        let k: Vec<u8> = self.key.decrypt_with_key(key)?;
-        let key = Send::derive_shareable_key(&k)?;
+        let k: Vec<u8> = self.key.decrypt(ctx, key)?;
+        let key = Send::derive_shareable_key(ctx, &k)?;
 
         Ok(SendView {
             id: self.id,
             access_id: self.access_id.clone(),
 
-            name: self.name.decrypt_with_key(&key).ok().unwrap_or_default(),
-            notes: self.notes.decrypt_with_key(&key).ok().flatten(),
+            name: self.name.decrypt(ctx, key).ok().unwrap_or_default(),
+            notes: self.notes.decrypt(ctx, key).ok().flatten(),
             key: Some(URL_SAFE_NO_PAD.encode(k)),
             new_password: None,
             has_password: self.password.is_some(),
 
             r#type: self.r#type,
-            file: self.file.decrypt_with_key(&key).ok().flatten(),
-            text: self.text.decrypt_with_key(&key).ok().flatten(),
+            file: self.file.decrypt(ctx, key).ok().flatten(),
+            text: self.text.decrypt(ctx, key).ok().flatten(),
 
             max_access_count: self.max_access_count,
             access_count: self.access_count,
@@ -230,18 +274,22 @@ impl KeyDecryptable<SymmetricCryptoKey, SendView> for Send {
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, SendListView> for Send {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<SendListView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, SendListView> for Send {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SendListView, CryptoError> {
         // For sends, we first decrypt the send key with the user key, and stretch it to it's full
         // size For the rest of the fields, we ignore the provided SymmetricCryptoKey and
         // the stretched key
-        let key = Send::get_key(&self.key, key)?;
+        let key = Send::get_key(ctx, &self.key, key)?;
 
         Ok(SendListView {
             id: self.id,
             access_id: self.access_id.clone(),
 
-            name: self.name.decrypt_with_key(&key)?,
+            name: self.name.decrypt(ctx, key)?,
             r#type: self.r#type,
 
             disabled: self.disabled,
@@ -253,12 +301,16 @@ impl KeyDecryptable<SymmetricCryptoKey, SendListView> for Send {
     }
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, Send> for SendView {
-    fn encrypt_with_key(self, key: &SymmetricCryptoKey) -> Result<Send, CryptoError> {
+impl Encryptable<KeyIds, SymmetricKeyId, Send> for SendView {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<Send, CryptoError> {
         // For sends, we first decrypt the send key with the user key, and stretch it to it's full
         // size For the rest of the fields, we ignore the provided SymmetricCryptoKey and
         // the stretched key
-        let k = match (self.key, self.id) {
+        let k = match (&self.key, self.id) {
             // Existing send, decrypt key
             (Some(k), _) => URL_SAFE_NO_PAD
                 .decode(k)
@@ -271,23 +323,23 @@ impl KeyEncryptable<SymmetricCryptoKey, Send> for SendView {
             // Existing send without key
             _ => return Err(CryptoError::InvalidKey),
         };
-        let send_key = Send::derive_shareable_key(&k)?;
+        let send_key = Send::derive_shareable_key(ctx, &k)?;
 
         Ok(Send {
             id: self.id,
-            access_id: self.access_id,
+            access_id: self.access_id.clone(),
 
-            name: self.name.encrypt_with_key(&send_key)?,
-            notes: self.notes.encrypt_with_key(&send_key)?,
-            key: k.encrypt_with_key(key)?,
-            password: self.new_password.map(|password| {
+            name: self.name.encrypt(ctx, send_key)?,
+            notes: self.notes.encrypt(ctx, send_key)?,
+            key: k.encrypt(ctx, key)?,
+            password: self.new_password.as_ref().map(|password| {
                 let password = bitwarden_crypto::pbkdf2(password.as_bytes(), &k, SEND_ITERATIONS);
                 STANDARD.encode(password)
             }),
 
             r#type: self.r#type,
-            file: self.file.encrypt_with_key(&send_key)?,
-            text: self.text.encrypt_with_key(&send_key)?,
+            file: self.file.encrypt(ctx, send_key)?,
+            text: self.text.encrypt(ctx, send_key)?,
 
             max_access_count: self.max_access_count,
             access_count: self.access_count,
@@ -335,6 +387,50 @@ impl From<bitwarden_api_api::models::SendType> for SendType {
     }
 }
 
+impl From<SendType> for bitwarden_api_api::models::SendType {
+    fn from(t: SendType) -> Self {
+        match t {
+            SendType::Text => bitwarden_api_api::models::SendType::Text,
+            SendType::File => bitwarden_api_api::models::SendType::File,
+        }
+    }
+}
+
+impl TryFrom<Send> for SendWithIdRequestModel {
+    type Error = MissingFieldError;
+
+    fn try_from(send: Send) -> Result<Self, Self::Error> {
+        Ok(SendWithIdRequestModel {
+            r#type: Some(send.r#type.into()),
+            file_length: None,
+            name: Some(send.name.to_string()),
+            notes: send.notes.map(|n| n.to_string()),
+            key: send.key.to_string(),
+            max_access_count: send.max_access_count.map(|c| c as i32),
+            expiration_date: send.expiration_date.map(|d| d.to_rfc3339()),
+            deletion_date: send.deletion_date.to_rfc3339(),
+            file: send.file.map(|f| {
+                Box::new(SendFileModel {
+                    id: f.id,
+                    file_name: Some(f.file_name.to_string()),
+                    size: f.size.and_then(|s| s.parse().ok()),
+                    size_name: f.size_name,
+                })
+            }),
+            text: send.text.map(|t| {
+                Box::new(SendTextModel {
+                    text: t.text.map(|t| t.to_string()),
+                    hidden: Some(t.hidden),
+                })
+            }),
+            password: send.password,
+            disabled: send.disabled,
+            hide_email: Some(send.hide_email),
+            id: require!(send.id),
+        })
+    }
+}
+
 impl TryFrom<SendFileModel> for SendFile {
     type Error = SendParseError;
 
@@ -361,28 +457,20 @@ impl TryFrom<SendTextModel> for SendText {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
-    use bitwarden_crypto::{Kdf, KeyContainer, KeyDecryptable, KeyEncryptable, MasterKey};
+    use bitwarden_crypto::{Kdf, KeyStore, MasterKey};
 
     use super::*;
 
-    struct MockKeyContainer(HashMap<Option<Uuid>, SymmetricCryptoKey>);
-    impl MockKeyContainer {
-        fn new(master_key: MasterKey, user_key: EncString) -> Result<Self, CryptoError> {
-            let user_key = master_key.decrypt_user_key(user_key)?;
-            Ok(Self(HashMap::from([(None, user_key)])))
-        }
-    }
-    impl KeyContainer for MockKeyContainer {
-        fn get_key<'a>(
-            &'a self,
-            org_id: &Option<Uuid>,
-        ) -> Result<&'a SymmetricCryptoKey, CryptoError> {
-            self.0
-                .get(org_id)
-                .ok_or(CryptoError::MissingKey(org_id.unwrap_or_default()))
-        }
+    fn build_key_store(master_key: MasterKey, user_key: EncString) -> KeyStore<KeyIds> {
+        let user_key = master_key.decrypt_user_key(user_key).unwrap();
+
+        let store = KeyStore::default();
+        #[allow(deprecated)]
+        store
+            .context_mut()
+            .set_symmetric_key(SymmetricKeyId::User, user_key)
+            .unwrap();
+        store
     }
 
     #[test]
@@ -396,24 +484,25 @@ mod tests {
             },
         )
         .unwrap();
-        let enc = MockKeyContainer::new(
+        let key_store = build_key_store(
             master_key,
             "2.majkL1/hNz9yptLqNAUSnw==|RiOzMTTJMG948qu8O3Zm1EQUO2E8BuTwFKnO9LWQjMzxMWJM5GbyOq2/A+tumPbTERt4JWur/FKfgHb+gXuYiEYlXPMuVBvT7nv4LPytJuM=|IVqMxHJeR1ZXY0sGngTC0x+WqbG8p6V+BTrdgBbQXjM=".parse().unwrap(),
-        ).unwrap();
-
-        let k = enc.get_key(&None).unwrap();
+        );
+        let mut ctx = key_store.context();
 
         let send_key = "2.+1KUfOX8A83Xkwk1bumo/w==|Nczvv+DTkeP466cP/wMDnGK6W9zEIg5iHLhcuQG6s+M=|SZGsfuIAIaGZ7/kzygaVUau3LeOvJUlolENBOU+LX7g="
             .parse()
             .unwrap();
 
         // Get the send key
-        let send_key = Send::get_key(&send_key, k).unwrap();
+        let send_key = Send::get_key(&mut ctx, &send_key, SymmetricKeyId::User).unwrap();
+        #[allow(deprecated)]
+        let send_key = ctx.dangerous_get_symmetric_key(send_key).unwrap();
         let send_key_b64 = send_key.to_base64();
         assert_eq!(send_key_b64, "IR9ImHGm6rRuIjiN7csj94bcZR5WYTJj5GtNfx33zm6tJCHUl+QZlpNPba8g2yn70KnOHsAODLcR0um6E3MAlg==");
     }
 
-    fn build_encryption_settings() -> MockKeyContainer {
+    fn build_test_key_store() -> KeyStore<KeyIds> {
         let master_key = MasterKey::derive(
             "asdfasdfasdf",
             "test@bitwarden.com",
@@ -423,16 +512,15 @@ mod tests {
         )
         .unwrap();
 
-        MockKeyContainer::new(
+        build_key_store(
             master_key,
             "2.Q/2PhzcC7GdeiMHhWguYAQ==|GpqzVdr0go0ug5cZh1n+uixeBC3oC90CIe0hd/HWA/pTRDZ8ane4fmsEIcuc8eMKUt55Y2q/fbNzsYu41YTZzzsJUSeqVjT8/iTQtgnNdpo=|dwI+uyvZ1h/iZ03VQ+/wrGEFYVewBUUl/syYgjsNMbE=".parse().unwrap(),
-        ).unwrap()
+        )
     }
 
     #[test]
     pub fn test_decrypt() {
-        let enc = build_encryption_settings();
-        let key = enc.get_key(&None).unwrap();
+        let key_store = build_test_key_store();
 
         let send = Send {
             id: "3d80dd72-2d14-4f26-812c-b0f0018aa144".parse().ok(),
@@ -457,7 +545,7 @@ mod tests {
             hide_email: false,
         };
 
-        let view: SendView = send.decrypt_with_key(key).unwrap();
+        let view: SendView = key_store.decrypt(&send).unwrap();
 
         let expected = SendView {
             id: "3d80dd72-2d14-4f26-812c-b0f0018aa144".parse().ok(),
@@ -487,8 +575,7 @@ mod tests {
 
     #[test]
     pub fn test_encrypt() {
-        let enc = build_encryption_settings();
-        let key = enc.get_key(&None).unwrap();
+        let key_store = build_test_key_store();
 
         let view = SendView {
             id: "3d80dd72-2d14-4f26-812c-b0f0018aa144".parse().ok(),
@@ -514,19 +601,15 @@ mod tests {
         };
 
         // Re-encrypt and decrypt again to ensure encrypt works
-        let v: SendView = view
-            .clone()
-            .encrypt_with_key(key)
-            .unwrap()
-            .decrypt_with_key(key)
+        let v: SendView = key_store
+            .decrypt(&key_store.encrypt(view.clone()).unwrap())
             .unwrap();
         assert_eq!(v, view);
     }
 
     #[test]
     pub fn test_create() {
-        let enc = build_encryption_settings();
-        let key = enc.get_key(&None).unwrap();
+        let key_store = build_test_key_store();
 
         let view = SendView {
             id: None,
@@ -552,11 +635,8 @@ mod tests {
         };
 
         // Re-encrypt and decrypt again to ensure encrypt works
-        let v: SendView = view
-            .clone()
-            .encrypt_with_key(key)
-            .unwrap()
-            .decrypt_with_key(key)
+        let v: SendView = key_store
+            .decrypt(&key_store.encrypt(view.clone()).unwrap())
             .unwrap();
 
         // Ignore key when comparing
@@ -566,8 +646,7 @@ mod tests {
 
     #[test]
     pub fn test_create_password() {
-        let enc = build_encryption_settings();
-        let key = enc.get_key(&None).unwrap();
+        let key_store = build_test_key_store();
 
         let view = SendView {
             id: None,
@@ -592,14 +671,14 @@ mod tests {
             expiration_date: None,
         };
 
-        let send: Send = view.encrypt_with_key(key).unwrap();
+        let send: Send = key_store.encrypt(view).unwrap();
 
         assert_eq!(
             send.password,
             Some("vTIDfdj3FTDbejmMf+mJWpYdMXsxfeSd1Sma3sjCtiQ=".to_owned())
         );
 
-        let v: SendView = send.decrypt_with_key(key).unwrap();
+        let v: SendView = key_store.decrypt(&send).unwrap();
         assert_eq!(v.new_password, None);
         assert!(v.has_password);
     }
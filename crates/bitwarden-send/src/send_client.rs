@@ -1,7 +1,10 @@
-use std::path::Path;
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
 
 use bitwarden_core::{Client, Error};
-use bitwarden_crypto::{EncString, KeyDecryptable, KeyEncryptable};
+use bitwarden_crypto::{Decryptable, EncString, Encryptable, IdentifyKey};
 
 use crate::{Send, SendListView, SendView};
 
@@ -15,19 +18,15 @@ impl<'a> SendClient<'a> {
     }
 
     pub fn decrypt(&self, send: Send) -> Result<SendView, Error> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let send_view = send.decrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let send_view = key_store.decrypt(&send)?;
 
         Ok(send_view)
     }
 
     pub fn decrypt_list(&self, sends: Vec<Send>) -> Result<Vec<SendListView>, Error> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let send_views = sends.decrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let send_views = key_store.decrypt_list(&sends)?;
 
         Ok(send_views)
     }
@@ -38,48 +37,104 @@ impl<'a> SendClient<'a> {
         encrypted_file_path: &Path,
         decrypted_file_path: &Path,
     ) -> Result<(), Error> {
-        let data = std::fs::read(encrypted_file_path)?;
-        let decrypted = self.decrypt_buffer(send, &data)?;
-        std::fs::write(decrypted_file_path, decrypted)?;
+        let reader = std::io::BufReader::new(std::fs::File::open(encrypted_file_path)?);
+        let mut writer = std::fs::File::create(decrypted_file_path)?;
+
+        let result = self.decrypt_stream(send, reader, &mut writer);
+        if result.is_err() {
+            // Don't leave partially decrypted contents behind
+            drop(writer);
+            let _ = std::fs::remove_file(decrypted_file_path);
+        }
+        result
+    }
+
+    /// Decrypts the file contents read from `reader` into `writer`, without holding the whole
+    /// file in memory. Both the chunked streaming format and the legacy format are supported.
+    ///
+    /// If this fails, any data already written to `writer` must be discarded.
+    pub fn decrypt_stream(
+        &self,
+        send: Send,
+        reader: impl Read,
+        writer: &mut impl Write,
+    ) -> Result<(), Error> {
+        let key_store = self.client.internal.get_key_store();
+        let mut ctx = key_store.context();
+        let key = Send::get_key(&mut ctx, &send.key, send.key_identifier())?;
+
+        let mut decryptor = ctx.decrypt_stream(key, reader)?;
+        std::io::copy(&mut decryptor, writer)?;
         Ok(())
     }
 
     pub fn decrypt_buffer(&self, send: Send, encrypted_buffer: &[u8]) -> Result<Vec<u8>, Error> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-        let key = Send::get_key(&send.key, key)?;
+        let key_store = self.client.internal.get_key_store();
+        let mut ctx = key_store.context();
+        let key = Send::get_key(&mut ctx, &send.key, send.key_identifier())?;
 
         let buf = EncString::from_buffer(encrypted_buffer)?;
-        Ok(buf.decrypt_with_key(&key)?)
+        Ok(buf.decrypt(&mut ctx, key)?)
     }
 
     pub fn encrypt(&self, send_view: SendView) -> Result<Send, Error> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let send = send_view.encrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let send = key_store.encrypt(send_view)?;
 
         Ok(send)
     }
 
+    /// Encrypts the file, see [SendClient::encrypt_stream] for the format used.
     pub fn encrypt_file(
         &self,
         send: Send,
         decrypted_file_path: &Path,
         encrypted_file_path: &Path,
     ) -> Result<(), Error> {
-        let data = std::fs::read(decrypted_file_path)?;
-        let encrypted = self.encrypt_buffer(send, &data)?;
-        std::fs::write(encrypted_file_path, encrypted)?;
+        let mut reader = std::io::BufReader::new(std::fs::File::open(decrypted_file_path)?);
+        let writer = std::io::BufWriter::new(std::fs::File::create(encrypted_file_path)?);
+
+        let result = self.encrypt_stream(send, &mut reader, writer);
+        if result.is_err() {
+            // Don't leave a partially encrypted file behind
+            let _ = std::fs::remove_file(encrypted_file_path);
+        }
+        result
+    }
+
+    /// Encrypts the file contents read from `reader` into `writer`. When the
+    /// `enableStreamEncryption` flag is set, the chunked streaming format is used without holding
+    /// the whole file in memory. Otherwise the contents are encrypted as a single [EncString].
+    pub fn encrypt_stream(
+        &self,
+        send: Send,
+        reader: &mut impl Read,
+        mut writer: impl Write,
+    ) -> Result<(), Error> {
+        if !self.client.internal.get_flags().enable_stream_encryption {
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer)?;
+            writer.write_all(&self.encrypt_buffer(send, &buffer)?)?;
+            writer.flush()?;
+            return Ok(());
+        }
+
+        let key_store = self.client.internal.get_key_store();
+        let mut ctx = key_store.context();
+        let key = Send::get_key(&mut ctx, &send.key, send.key_identifier())?;
+
+        let mut encryptor = ctx.encrypt_stream(key, writer)?;
+        std::io::copy(reader, &mut encryptor)?;
+        encryptor.finish()?;
         Ok(())
     }
 
     pub fn encrypt_buffer(&self, send: Send, buffer: &[u8]) -> Result<Vec<u8>, Error> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-        let key = Send::get_key(&send.key, key)?;
+        let key_store = self.client.internal.get_key_store();
+        let mut ctx = key_store.context();
+        let key = Send::get_key(&mut ctx, &send.key, send.key_identifier())?;
 
-        let encrypted = buffer.encrypt_with_key(&key)?;
+        let encrypted = buffer.encrypt(&mut ctx, key)?;
         Ok(encrypted.to_buffer()?)
     }
 }
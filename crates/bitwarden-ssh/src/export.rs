@@ -0,0 +1,88 @@
+use pkcs8::EncodePrivateKey;
+use rsa::BigUint;
+use ssh_key::{
+    private::{KeypairData, RsaKeypair},
+    Mpint,
+};
+
+use crate::error::SshKeyExportError;
+
+/// Convert an unencrypted OpenSSH private key into a DER encoded PKCS8 private key, as used by the
+/// Credential Exchange Format.
+/// # Returns
+/// - [SshKeyExportError::KeyConversionError] if the key is encrypted, malformed or of an
+///   unsupported type
+pub fn export_pkcs8_der_key(private_key: &str) -> Result<Vec<u8>, SshKeyExportError> {
+    let private_key = ssh_key::PrivateKey::from_openssh(private_key)
+        .map_err(|_| SshKeyExportError::KeyConversionError)?;
+
+    let doc = match private_key.key_data() {
+        KeypairData::Ed25519(keypair) => ed25519::KeypairBytes {
+            secret_key: keypair.private.to_bytes(),
+            public_key: None,
+        }
+        .to_pkcs8_der(),
+        KeypairData::Rsa(keypair) => rsa_private_key(keypair)?.to_pkcs8_der(),
+        _ => return Err(SshKeyExportError::KeyConversionError),
+    }
+    .map_err(|_| SshKeyExportError::KeyConversionError)?;
+
+    Ok(doc.as_bytes().to_vec())
+}
+
+/// Build the RSA key from its components, as the conversion provided by `ssh-key` fails to
+/// validate some keys generated by OpenSSH.
+fn rsa_private_key(keypair: &RsaKeypair) -> Result<rsa::RsaPrivateKey, SshKeyExportError> {
+    fn to_biguint(value: &Mpint) -> Result<BigUint, SshKeyExportError> {
+        value
+            .as_positive_bytes()
+            .map(BigUint::from_bytes_be)
+            .ok_or(SshKeyExportError::KeyConversionError)
+    }
+
+    rsa::RsaPrivateKey::from_components(
+        to_biguint(&keypair.public.n)?,
+        to_biguint(&keypair.public.e)?,
+        to_biguint(&keypair.private.d)?,
+        vec![
+            to_biguint(&keypair.private.p)?,
+            to_biguint(&keypair.private.q)?,
+        ],
+    )
+    .map_err(|_| SshKeyExportError::KeyConversionError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::import_pkcs8_der_key;
+
+    #[test]
+    fn export_ed25519_roundtrip() {
+        let private_key = include_str!("../resources/import/ed25519_openssh_unencrypted");
+        let public_key = include_str!("../resources/import/ed25519_openssh_unencrypted.pub")
+            .replace("testkey", "");
+
+        let der = export_pkcs8_der_key(private_key).unwrap();
+        let result = import_pkcs8_der_key(&der).unwrap();
+        assert_eq!(result.public_key, public_key.trim());
+    }
+
+    #[test]
+    fn export_rsa_roundtrip() {
+        let private_key = include_str!("../resources/import/rsa_openssh_unencrypted");
+        let public_key =
+            include_str!("../resources/import/rsa_openssh_unencrypted.pub").replace("testkey", "");
+
+        let der = export_pkcs8_der_key(private_key).unwrap();
+        let result = import_pkcs8_der_key(&der).unwrap();
+        assert_eq!(result.public_key, public_key.trim());
+    }
+
+    #[test]
+    fn export_encrypted_key_error() {
+        let private_key = include_str!("../resources/import/ed25519_openssh_encrypted");
+        let result = export_pkcs8_der_key(private_key);
+        assert_eq!(result.unwrap_err(), SshKeyExportError::KeyConversionError);
+    }
+}
@@ -48,8 +48,13 @@ fn import_pkcs8_key(
         SecretDocument::from_pkcs8_pem(&encoded_key).map_err(|_| SshKeyImportError::ParsingError)?
     };
 
+    import_pkcs8_der_key(doc.as_bytes())
+}
+
+/// Import an unencrypted DER encoded PKCS8 private key, as used by the Credential Exchange Format.
+pub fn import_pkcs8_der_key(encoded_key: &[u8]) -> Result<SshKey, SshKeyImportError> {
     let private_key_info =
-        PrivateKeyInfo::from_der(doc.as_bytes()).map_err(|_| SshKeyImportError::ParsingError)?;
+        PrivateKeyInfo::from_der(encoded_key).map_err(|_| SshKeyImportError::ParsingError)?;
 
     let private_key = match private_key_info.algorithm.oid {
         ed25519::pkcs8::ALGORITHM_OID => {
@@ -169,6 +174,16 @@ mod tests {
         assert_eq!(result.public_key, public_key);
     }
 
+    #[test]
+    fn import_pkcs8_der_key_ed25519() {
+        let private_key = include_str!("../resources/import/ed25519_pkcs8_unencrypted");
+        let public_key = include_str!("../resources/import/ed25519_pkcs8_unencrypted.pub")
+            .replace("testkey", "");
+        let (_, doc) = SecretDocument::from_pem(private_key).unwrap();
+        let result = import_pkcs8_der_key(doc.as_bytes()).unwrap();
+        assert_eq!(result.public_key, public_key.trim());
+    }
+
     #[test]
     fn import_key_ed25519_openssh_encrypted_wrong_password() {
         let private_key = include_str!("../resources/import/ed25519_openssh_encrypted");
@@ -1,4 +1,5 @@
 pub mod error;
+pub mod export;
 pub mod generator;
 pub mod import;
 
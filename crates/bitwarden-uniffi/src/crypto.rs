@@ -3,11 +3,11 @@ use std::sync::Arc;
 use bitwarden_core::{
     mobile::crypto::{
         DeriveKeyConnectorRequest, DerivePinKeyResponse, InitOrgCryptoRequest,
-        InitUserCryptoRequest, UpdatePasswordResponse,
+        InitUserCryptoRequest, UpdateKdfResponse, UpdatePasswordResponse,
     },
     Error,
 };
-use bitwarden_crypto::{AsymmetricEncString, EncString};
+use bitwarden_crypto::{AsymmetricEncString, EncString, Kdf};
 
 use crate::{error::Result, Client};
 
@@ -52,6 +52,13 @@ impl CryptoClient {
         Ok(self.0 .0.crypto().update_password(new_password)?)
     }
 
+    /// Change the KDF of the user's master password, which will re-encrypt the user's encryption
+    /// key with the master key derived with the new KDF. This returns the new encrypted user key
+    /// and the password hashes needed to update the KDF on the server.
+    pub fn make_kdf_update(&self, password: String, kdf: Kdf) -> Result<UpdateKdfResponse> {
+        Ok(self.0 .0.crypto().make_kdf_update(password, kdf)?)
+    }
+
     /// Generates a PIN protected user key from the provided PIN. The result can be stored and later
     /// used to initialize another client instance by using the PIN and the PIN key with
     /// `initialize_user_crypto`.
@@ -45,6 +45,8 @@ pub type Result<T, E = BitwardenError> = std::result::Result<T, E>;
 pub enum Error {
     #[error(transparent)]
     Core(#[from] bitwarden_core::Error),
+    #[error(transparent)]
+    PublicKey(#[from] bitwarden_core::platform::PublicKeyError),
 
     // Generators
     #[error(transparent)]
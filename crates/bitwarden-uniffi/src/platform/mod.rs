@@ -1,8 +1,13 @@
 use std::sync::Arc;
 
-use bitwarden_core::platform::FingerprintRequest;
+use bitwarden_core::platform::{
+    FingerprintRequest, PublicKeyOwner, PublicKeyResponse, TrustedPublicKey,
+};
 
-use crate::{error::Result, Client};
+use crate::{
+    error::{Error, Result},
+    Client,
+};
 
 mod fido2;
 
@@ -25,6 +30,47 @@ impl PlatformClient {
             .user_fingerprint(fingerprint_material)?)
     }
 
+    /// Fetch the public key of a user or organization, verifying it against the trusted keys
+    pub async fn get_public_key(&self, owner: PublicKeyOwner) -> Result<PublicKeyResponse> {
+        Ok(self
+            .0
+             .0
+            .platform()
+            .get_public_key(owner)
+            .await
+            .map_err(Error::PublicKey)?)
+    }
+
+    /// Accept a new public key for `owner`, after the user has confirmed its fingerprint
+    pub fn trust_public_key(&self, owner: PublicKeyOwner, public_key: String) -> Result<()> {
+        Ok(self
+            .0
+             .0
+            .platform()
+            .trust_public_key(owner, public_key)
+            .map_err(Error::PublicKey)?)
+    }
+
+    /// Forget the trusted public key of `owner`
+    pub fn forget_public_key(&self, owner: PublicKeyOwner) {
+        self.0 .0.platform().forget_public_key(owner)
+    }
+
+    /// Load previously trusted public keys
+    pub fn load_trusted_public_keys(&self, keys: Vec<TrustedPublicKey>) -> Result<()> {
+        Ok(self
+            .0
+             .0
+            .platform()
+            .load_trusted_public_keys(keys)
+            .map_err(Error::PublicKey)?)
+    }
+
+    /// The trusted public keys, to persist between sessions
+    pub fn get_trusted_public_keys(&self) -> Vec<TrustedPublicKey> {
+        self.0 .0.platform().get_trusted_public_keys()
+    }
+
     /// Load feature flags into the client
     pub fn load_flags(&self, flags: std::collections::HashMap<String, bool>) -> Result<()> {
         self.0 .0.internal.load_flags(flags);
@@ -1,11 +1,14 @@
 use std::sync::Arc;
 
-use bitwarden_exporters::{Account, ExportFormat, ExporterClientExt};
+use bitwarden_exporters::{
+    Account, CxfExport, ExportFormat, ExporterClientExt, ImportFormat, ImportedVault,
+};
 use bitwarden_generators::{
     GeneratorClientsExt, PassphraseGeneratorRequest, PasswordGeneratorRequest,
     UsernameGeneratorRequest,
 };
 use bitwarden_vault::{Cipher, Collection, Folder};
+use uuid::Uuid;
 
 use crate::{
     error::{Error, Result},
@@ -78,6 +81,7 @@ impl ExporterClient {
     /// Export organization vault
     pub fn export_organization_vault(
         &self,
+        organization_id: Uuid,
         collections: Vec<Collection>,
         ciphers: Vec<Cipher>,
         format: ExportFormat,
@@ -86,7 +90,17 @@ impl ExporterClient {
             .0
              .0
             .exporters()
-            .export_organization_vault(collections, ciphers, format)
+            .export_organization_vault(organization_id, collections, ciphers, format)
+            .map_err(Error::Export)?)
+    }
+
+    /// Import a Bitwarden export
+    pub fn import(&self, payload: String, format: ImportFormat) -> Result<ImportedVault> {
+        Ok(self
+            .0
+             .0
+            .exporters()
+            .import(payload, format)
             .map_err(Error::Export)?)
     }
 
@@ -95,13 +109,19 @@ impl ExporterClient {
     /// *Warning:* Expect this API to be unstable, and it will change in the future.
     ///
     /// For use with Apple using [ASCredentialExportManager](https://developer.apple.com/documentation/authenticationservices/ascredentialexportmanager).
-    /// Ideally the output should be immediately deserialized to [ASImportableAccount](https://developer.apple.com/documentation/authenticationservices/asimportableaccount).
-    pub fn export_cxf(&self, account: Account, ciphers: Vec<Cipher>) -> Result<String> {
+    /// Ideally the output payload should be immediately deserialized to [ASImportableAccount](https://developer.apple.com/documentation/authenticationservices/asimportableaccount).
+    /// Ciphers which can't be exported are listed in [CxfExport::skipped].
+    pub fn export_cxf(
+        &self,
+        account: Account,
+        folders: Vec<Folder>,
+        ciphers: Vec<Cipher>,
+    ) -> Result<CxfExport> {
         Ok(self
             .0
              .0
             .exporters()
-            .export_cxf(account, ciphers)
+            .export_cxf(account, folders, ciphers)
             .map_err(Error::Export)?)
     }
 
@@ -111,7 +131,7 @@ impl ExporterClient {
     ///
     /// For use with Apple using [ASCredentialExportManager](https://developer.apple.com/documentation/authenticationservices/ascredentialexportmanager).
     /// Ideally the input should be immediately serialized from [ASImportableAccount](https://developer.apple.com/documentation/authenticationservices/asimportableaccount).
-    pub fn import_cxf(&self, payload: String) -> Result<Vec<Cipher>> {
+    pub fn import_cxf(&self, payload: String) -> Result<ImportedVault> {
         Ok(self
             .0
              .0
@@ -0,0 +1,120 @@
+//! Compares the parallel list decryption used after a sync against decrypting the items one at a
+//! time, for ciphers with and without individual cipher keys.
+//!
+//! Run with `cargo bench -p bitwarden-vault --features test-utils`.
+use bitwarden_core::{
+    client::test_accounts::test_bitwarden_com_account, key_management::SymmetricKeyId, Client,
+};
+use bitwarden_vault::{
+    test_utils::cipher_view, Cipher, CipherListView, CipherView, Folder, FolderView, VaultClientExt,
+};
+use chrono::Utc;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+const ITEMS: usize = 1000;
+
+fn ciphers(client: &Client, with_cipher_keys: bool) -> Vec<Cipher> {
+    let key_store = client.internal.get_key_store();
+
+    (0..ITEMS)
+        .map(|i| {
+            let mut view = CipherView {
+                name: format!("Login {i}"),
+                ..cipher_view()
+            };
+            if with_cipher_keys {
+                view.generate_cipher_key(&mut key_store.context(), SymmetricKeyId::User)
+                    .expect("user key is set");
+            }
+            key_store.encrypt(view).expect("cipher encrypts")
+        })
+        .collect()
+}
+
+fn folders(client: &Client) -> Vec<Folder> {
+    let key_store = client.internal.get_key_store();
+
+    (0..ITEMS)
+        .map(|i| {
+            key_store
+                .encrypt(FolderView {
+                    id: None,
+                    name: format!("Folder {i}"),
+                    revision_date: Utc::now(),
+                })
+                .expect("folder encrypts")
+        })
+        .collect()
+}
+
+fn decrypt_list(c: &mut Criterion) {
+    let client = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("runtime builds")
+        .block_on(Client::init_test_account(test_bitwarden_com_account()));
+    let key_store = client.internal.get_key_store();
+
+    let mut group = c.benchmark_group("decrypt_ciphers");
+    for (name, with_cipher_keys) in [("user_key", false), ("cipher_keys", true)] {
+        let ciphers = ciphers(&client, with_cipher_keys);
+
+        group.bench_with_input(
+            BenchmarkId::new("parallel", name),
+            &ciphers,
+            |b, ciphers| {
+                b.iter_batched(
+                    || ciphers.clone(),
+                    |ciphers| {
+                        client
+                            .vault()
+                            .ciphers()
+                            .decrypt_list(ciphers)
+                            .expect("ciphers decrypt")
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("sequential", name),
+            &ciphers,
+            |b, ciphers| {
+                b.iter(|| {
+                    ciphers
+                        .iter()
+                        .map(|c| key_store.decrypt(c).expect("cipher decrypts"))
+                        .collect::<Vec<CipherListView>>()
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let folders = folders(&client);
+    let mut group = c.benchmark_group("decrypt_folders");
+    group.bench_function("parallel", |b| {
+        b.iter_batched(
+            || folders.clone(),
+            |folders| {
+                client
+                    .vault()
+                    .folders()
+                    .decrypt_list(folders)
+                    .expect("folders decrypt")
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            folders
+                .iter()
+                .map(|f| key_store.decrypt(f).expect("folder decrypts"))
+                .collect::<Vec<FolderView>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decrypt_list);
+criterion_main!(benches);
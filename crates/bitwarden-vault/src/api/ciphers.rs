@@ -0,0 +1,334 @@
+use bitwarden_api_api::{
+    apis::ciphers_api,
+    models::{CipherCreateRequestModel, CipherResponseModel, CipherShareRequestModel},
+};
+use bitwarden_core::{require, Client};
+use uuid::Uuid;
+
+use super::VaultApiError;
+use crate::{mobile::cipher_client::ClientCiphers, Cipher, CipherView};
+
+/// Create a new cipher. Organization ciphers are created in the provided collections.
+pub(crate) async fn create_cipher(
+    client: &Client,
+    cipher_view: CipherView,
+) -> Result<CipherView, VaultApiError> {
+    let ciphers = ClientCiphers { client };
+    let cipher = ciphers.encrypt(cipher_view)?;
+    let collection_ids = cipher.collection_ids.clone();
+
+    let config = client.internal.get_api_configurations().await;
+    let response = if cipher.organization_id.is_some() && !collection_ids.is_empty() {
+        ciphers_api::ciphers_create_post(
+            &config.api,
+            Some(CipherCreateRequestModel {
+                collection_ids: Some(collection_ids.clone()),
+                cipher: Box::new(cipher.into()),
+            }),
+        )
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?
+    } else {
+        ciphers_api::ciphers_post(&config.api, Some(cipher.into()))
+            .await
+            .map_err(|e| VaultApiError::Api(e.into()))?
+    };
+
+    process_response(&ciphers, response, collection_ids)
+}
+
+/// Update an existing cipher, which must have an id.
+pub(crate) async fn update_cipher(
+    client: &Client,
+    cipher_view: CipherView,
+) -> Result<CipherView, VaultApiError> {
+    let id = require!(cipher_view.id);
+
+    let ciphers = ClientCiphers { client };
+    let cipher = ciphers.encrypt(cipher_view)?;
+    let collection_ids = cipher.collection_ids.clone();
+
+    let config = client.internal.get_api_configurations().await;
+    let response = ciphers_api::ciphers_id_put(&config.api, id, Some(cipher.into()))
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    process_response(&ciphers, response, collection_ids)
+}
+
+/// Permanently delete a cipher.
+pub(crate) async fn delete_cipher(client: &Client, id: Uuid) -> Result<(), VaultApiError> {
+    let config = client.internal.get_api_configurations().await;
+    ciphers_api::ciphers_id_delete(&config.api, id)
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    Ok(())
+}
+
+/// Move a cipher to the trash, from where it can be restored.
+pub(crate) async fn soft_delete_cipher(client: &Client, id: Uuid) -> Result<(), VaultApiError> {
+    let config = client.internal.get_api_configurations().await;
+    ciphers_api::ciphers_id_delete_put(&config.api, id)
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    Ok(())
+}
+
+/// Restore a cipher from the trash.
+pub(crate) async fn restore_cipher(client: &Client, id: Uuid) -> Result<CipherView, VaultApiError> {
+    let config = client.internal.get_api_configurations().await;
+    ciphers_api::ciphers_id_restore_put(&config.api, id)
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    // The restore response doesn't include the collections, but the details do
+    let response = ciphers_api::ciphers_id_details_get(&config.api, id)
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+    let cipher: Cipher = response.try_into()?;
+
+    Ok(ClientCiphers { client }.decrypt(cipher)?)
+}
+
+/// Share a personal cipher with an organization, re-encrypting it with the organization key.
+pub(crate) async fn share_cipher(
+    client: &Client,
+    cipher_view: CipherView,
+    organization_id: Uuid,
+    collection_ids: Vec<Uuid>,
+) -> Result<CipherView, VaultApiError> {
+    let id = require!(cipher_view.id);
+
+    let ciphers = ClientCiphers { client };
+    let mut cipher_view = ciphers.move_to_organization(cipher_view, organization_id)?;
+    cipher_view.collection_ids = collection_ids.clone();
+    let cipher = ciphers.encrypt(cipher_view)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response = ciphers_api::ciphers_id_share_put(
+        &config.api,
+        id,
+        Some(CipherShareRequestModel {
+            collection_ids: collection_ids.iter().map(|id| id.to_string()).collect(),
+            cipher: Box::new(cipher.into()),
+        }),
+    )
+    .await
+    .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    process_response(&ciphers, response, collection_ids)
+}
+
+fn process_response(
+    ciphers: &ClientCiphers,
+    response: CipherResponseModel,
+    collection_ids: Vec<Uuid>,
+) -> Result<CipherView, VaultApiError> {
+    let mut cipher: Cipher = response.try_into()?;
+    // The collections are not part of the response
+    cipher.collection_ids = collection_ids;
+
+    Ok(ciphers.decrypt(cipher)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_api_api::models::{
+        CipherDetailsResponseModel, CipherRequestModel, CipherResponseModel,
+    };
+    use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
+    use wiremock::{matchers, Mock, Request, ResponseTemplate};
+
+    use super::*;
+    use crate::{api::start_mock, test_utils::cipher_view, VaultClientExt};
+
+    const CIPHER_ID: &str = "5faa9684-c793-4a2d-8a12-b1e60a7c1c7d";
+    const ORGANIZATION_ID: &str = "1bc9ac1e-f5aa-45f2-94bf-b181009709b8";
+    const COLLECTION_ID: &str = "66c5ca57-0868-4c7e-902f-b181009709c0";
+
+    /// Respond with the cipher that was sent, like the server would after storing it.
+    fn echo_cipher(request: &Request) -> ResponseTemplate {
+        let body: serde_json::Value = request.body_json().unwrap();
+        // Create and share requests wrap the cipher
+        let cipher = body.get("cipher").cloned().unwrap_or(body);
+        let cipher: CipherRequestModel = serde_json::from_value(cipher).unwrap();
+
+        let response = CipherResponseModel {
+            id: Some(CIPHER_ID.parse().unwrap()),
+            organization_id: cipher.organization_id.map(|id| id.parse().unwrap()),
+            r#type: cipher.r#type,
+            name: Some(cipher.name),
+            notes: cipher.notes,
+            login: cipher.login,
+            key: cipher.key,
+            reprompt: cipher.reprompt,
+            favorite: cipher.favorite,
+            creation_date: Some("2024-05-31T09:35:55.12Z".to_owned()),
+            revision_date: Some("2024-05-31T09:35:55.12Z".to_owned()),
+            ..CipherResponseModel::new()
+        };
+
+        ResponseTemplate::new(200).set_body_json(response)
+    }
+
+    #[tokio::test]
+    async fn test_create_cipher() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("POST"))
+                .and(matchers::path("/api/ciphers"))
+                .respond_with(echo_cipher)],
+        )
+        .await;
+
+        let view = client.vault().create_cipher(cipher_view()).await.unwrap();
+
+        assert_eq!(view.id, Some(CIPHER_ID.parse().unwrap()));
+        assert_eq!(view.name, "Test login");
+        assert_eq!(
+            view.login.unwrap().username.as_deref(),
+            Some("test@bitwarden.com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_organization_cipher() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("POST"))
+                .and(matchers::path("/api/ciphers/create"))
+                .respond_with(echo_cipher)],
+        )
+        .await;
+
+        let view = client
+            .vault()
+            .create_cipher(CipherView {
+                organization_id: Some(ORGANIZATION_ID.parse().unwrap()),
+                collection_ids: vec![COLLECTION_ID.parse().unwrap()],
+                ..cipher_view()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(view.organization_id, Some(ORGANIZATION_ID.parse().unwrap()));
+        assert_eq!(
+            view.collection_ids,
+            vec![COLLECTION_ID.parse::<Uuid>().unwrap()]
+        );
+        assert_eq!(view.name, "Test login");
+    }
+
+    #[tokio::test]
+    async fn test_update_cipher_requires_id() {
+        let client = Client::init_test_account(test_bitwarden_com_account()).await;
+
+        let result = client.vault().update_cipher(cipher_view()).await;
+
+        assert!(matches!(result, Err(VaultApiError::MissingField(_))));
+    }
+
+    #[tokio::test]
+    async fn test_share_cipher() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("PUT"))
+                .and(matchers::path(format!("/api/ciphers/{CIPHER_ID}/share")))
+                .respond_with(echo_cipher)],
+        )
+        .await;
+
+        let view = client
+            .vault()
+            .share_cipher(
+                CipherView {
+                    id: Some(CIPHER_ID.parse().unwrap()),
+                    ..cipher_view()
+                },
+                ORGANIZATION_ID.parse().unwrap(),
+                vec![COLLECTION_ID.parse().unwrap()],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(view.organization_id, Some(ORGANIZATION_ID.parse().unwrap()));
+        assert_eq!(
+            view.collection_ids,
+            vec![COLLECTION_ID.parse::<Uuid>().unwrap()]
+        );
+        assert_eq!(view.name, "Test login");
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_cipher() {
+        let (server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("PUT"))
+                .and(matchers::path(format!("/api/ciphers/{CIPHER_ID}/delete")))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)],
+        )
+        .await;
+
+        client
+            .vault()
+            .soft_delete_cipher(CIPHER_ID.parse().unwrap())
+            .await
+            .unwrap();
+
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_restore_cipher_keeps_collections() {
+        let encrypting_client = Client::init_test_account(test_bitwarden_com_account()).await;
+        let cipher = encrypting_client
+            .vault()
+            .ciphers()
+            .encrypt(CipherView {
+                organization_id: Some(ORGANIZATION_ID.parse().unwrap()),
+                ..cipher_view()
+            })
+            .unwrap();
+        let cipher: CipherRequestModel = cipher.into();
+        let details = CipherDetailsResponseModel {
+            id: Some(CIPHER_ID.parse().unwrap()),
+            organization_id: Some(ORGANIZATION_ID.parse().unwrap()),
+            r#type: cipher.r#type,
+            name: Some(cipher.name),
+            login: cipher.login,
+            key: cipher.key,
+            collection_ids: Some(vec![COLLECTION_ID.parse().unwrap()]),
+            creation_date: Some("2024-05-31T09:35:55.12Z".to_owned()),
+            revision_date: Some("2024-05-31T09:35:55.12Z".to_owned()),
+            ..CipherDetailsResponseModel::new()
+        };
+
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![
+                Mock::given(matchers::method("PUT"))
+                    .and(matchers::path(format!("/api/ciphers/{CIPHER_ID}/restore")))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(&details)),
+                Mock::given(matchers::method("GET"))
+                    .and(matchers::path(format!("/api/ciphers/{CIPHER_ID}/details")))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(&details)),
+            ],
+        )
+        .await;
+
+        let view = client
+            .vault()
+            .restore_cipher(CIPHER_ID.parse().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            view.collection_ids,
+            vec![COLLECTION_ID.parse::<Uuid>().unwrap()]
+        );
+        assert_eq!(view.name, "Test login");
+    }
+}
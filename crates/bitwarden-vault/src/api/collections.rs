@@ -0,0 +1,207 @@
+use bitwarden_api_api::{apis::collections_api, models::CollectionResponseModel};
+use bitwarden_core::{require, Client};
+use uuid::Uuid;
+
+use super::VaultApiError;
+use crate::{mobile::collection_client::ClientCollections, Collection, CollectionView};
+
+/// Create a collection in the organization of the view, encrypted with the organization key.
+pub(crate) async fn create_collection(
+    client: &Client,
+    collection_view: CollectionView,
+) -> Result<CollectionView, VaultApiError> {
+    let organization_id = collection_view.organization_id;
+    let permissions = Permissions::from(&collection_view);
+
+    let collections = ClientCollections { client };
+    let request = collections.encrypt(collection_view)?.into();
+
+    let config = client.internal.get_api_configurations().await;
+    let response = collections_api::organizations_org_id_collections_post(
+        &config.api,
+        organization_id,
+        Some(request),
+    )
+    .await
+    .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    process_response(&collections, response, permissions)
+}
+
+/// Update the name or external id of an existing collection, which must have an id.
+pub(crate) async fn update_collection(
+    client: &Client,
+    collection_view: CollectionView,
+) -> Result<CollectionView, VaultApiError> {
+    let id = require!(collection_view.id);
+    let organization_id = collection_view.organization_id;
+    let permissions = Permissions::from(&collection_view);
+
+    let collections = ClientCollections { client };
+    let request = collections.encrypt(collection_view)?.into();
+
+    let config = client.internal.get_api_configurations().await;
+    let response = collections_api::organizations_org_id_collections_id_put(
+        &config.api,
+        organization_id,
+        id,
+        Some(request),
+    )
+    .await
+    .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    process_response(&collections, response, permissions)
+}
+
+/// Delete a collection. The ciphers in the collection are kept.
+pub(crate) async fn delete_collection(
+    client: &Client,
+    organization_id: Uuid,
+    id: Uuid,
+) -> Result<(), VaultApiError> {
+    let config = client.internal.get_api_configurations().await;
+    collections_api::organizations_org_id_collections_id_delete(&config.api, organization_id, id)
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    Ok(())
+}
+
+/// The permissions of the user on the collection, which are not part of the response.
+struct Permissions {
+    hide_passwords: bool,
+    read_only: bool,
+    manage: bool,
+}
+
+impl From<&CollectionView> for Permissions {
+    fn from(view: &CollectionView) -> Self {
+        Self {
+            hide_passwords: view.hide_passwords,
+            read_only: view.read_only,
+            manage: view.manage,
+        }
+    }
+}
+
+fn process_response(
+    collections: &ClientCollections,
+    response: CollectionResponseModel,
+    permissions: Permissions,
+) -> Result<CollectionView, VaultApiError> {
+    let collection: Collection = response.try_into()?;
+
+    Ok(CollectionView {
+        hide_passwords: permissions.hide_passwords,
+        read_only: permissions.read_only,
+        manage: permissions.manage,
+        ..collections.decrypt(collection)?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_api_api::models::{CollectionRequestModel, CollectionResponseModel};
+    use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
+    use wiremock::{matchers, Mock, Request, ResponseTemplate};
+
+    use super::*;
+    use crate::{api::start_mock, VaultClientExt};
+
+    const ORGANIZATION_ID: &str = "1bc9ac1e-f5aa-45f2-94bf-b181009709b8";
+    const COLLECTION_ID: &str = "66c5ca57-0868-4c7e-902f-b181009709c0";
+
+    fn echo_collection(request: &Request) -> ResponseTemplate {
+        let collection: CollectionRequestModel = request.body_json().unwrap();
+
+        ResponseTemplate::new(200).set_body_json(CollectionResponseModel {
+            object: Some("collection".to_owned()),
+            id: Some(COLLECTION_ID.parse().unwrap()),
+            organization_id: Some(ORGANIZATION_ID.parse().unwrap()),
+            name: Some(collection.name),
+            external_id: collection.external_id,
+        })
+    }
+
+    fn collection_view(id: Option<Uuid>) -> CollectionView {
+        CollectionView {
+            id,
+            organization_id: ORGANIZATION_ID.parse().unwrap(),
+            name: "Engineering".to_owned(),
+            external_id: Some("engineering".to_owned()),
+            hide_passwords: false,
+            read_only: false,
+            manage: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_collection() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("POST"))
+                .and(matchers::path(format!(
+                    "/api/organizations/{ORGANIZATION_ID}/collections"
+                )))
+                .respond_with(echo_collection)],
+        )
+        .await;
+
+        let collection = client
+            .vault()
+            .create_collection(collection_view(None))
+            .await
+            .unwrap();
+
+        assert_eq!(collection.id, Some(COLLECTION_ID.parse().unwrap()));
+        assert_eq!(collection.name, "Engineering");
+        assert_eq!(collection.external_id.as_deref(), Some("engineering"));
+        assert!(collection.manage);
+    }
+
+    #[tokio::test]
+    async fn test_update_collection() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("PUT"))
+                .and(matchers::path(format!(
+                    "/api/organizations/{ORGANIZATION_ID}/collections/{COLLECTION_ID}"
+                )))
+                .respond_with(echo_collection)],
+        )
+        .await;
+
+        let collection = client
+            .vault()
+            .update_collection(collection_view(Some(COLLECTION_ID.parse().unwrap())))
+            .await
+            .unwrap();
+
+        assert_eq!(collection.name, "Engineering");
+    }
+
+    #[tokio::test]
+    async fn test_delete_collection() {
+        let (server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("DELETE"))
+                .and(matchers::path(format!(
+                    "/api/organizations/{ORGANIZATION_ID}/collections/{COLLECTION_ID}"
+                )))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)],
+        )
+        .await;
+
+        client
+            .vault()
+            .delete_collection(
+                ORGANIZATION_ID.parse().unwrap(),
+                COLLECTION_ID.parse().unwrap(),
+            )
+            .await
+            .unwrap();
+
+        server.verify().await;
+    }
+}
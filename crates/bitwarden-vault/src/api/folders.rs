@@ -0,0 +1,149 @@
+use bitwarden_api_api::{apis::folders_api, models::FolderResponseModel};
+use bitwarden_core::{require, Client};
+use uuid::Uuid;
+
+use super::VaultApiError;
+use crate::{mobile::folder_client::ClientFolders, Folder, FolderView};
+
+pub(crate) async fn create_folder(
+    client: &Client,
+    folder_view: FolderView,
+) -> Result<FolderView, VaultApiError> {
+    let folders = ClientFolders { client };
+    let request = folders.encrypt(folder_view)?.into();
+
+    let config = client.internal.get_api_configurations().await;
+    let response = folders_api::folders_post(&config.api, Some(request))
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    process_response(&folders, response)
+}
+
+/// Rename an existing folder, which must have an id.
+pub(crate) async fn update_folder(
+    client: &Client,
+    folder_view: FolderView,
+) -> Result<FolderView, VaultApiError> {
+    let id = require!(folder_view.id);
+
+    let folders = ClientFolders { client };
+    let request = folders.encrypt(folder_view)?.into();
+
+    let config = client.internal.get_api_configurations().await;
+    let response = folders_api::folders_id_put(&config.api, &id.to_string(), Some(request))
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    process_response(&folders, response)
+}
+
+/// Delete a folder. The ciphers in the folder are kept, without a folder.
+pub(crate) async fn delete_folder(client: &Client, id: Uuid) -> Result<(), VaultApiError> {
+    let config = client.internal.get_api_configurations().await;
+    folders_api::folders_id_delete(&config.api, &id.to_string())
+        .await
+        .map_err(|e| VaultApiError::Api(e.into()))?;
+
+    Ok(())
+}
+
+fn process_response(
+    folders: &ClientFolders,
+    response: FolderResponseModel,
+) -> Result<FolderView, VaultApiError> {
+    let folder: Folder = response.try_into()?;
+
+    Ok(folders.decrypt(folder)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_api_api::models::{FolderRequestModel, FolderResponseModel};
+    use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
+    use chrono::Utc;
+    use wiremock::{matchers, Mock, Request, ResponseTemplate};
+
+    use super::*;
+    use crate::{api::start_mock, VaultClientExt};
+
+    const FOLDER_ID: &str = "9b2f4e6a-3c1d-4e5f-8a7b-6c5d4e3f2a1b";
+
+    fn echo_folder(request: &Request) -> ResponseTemplate {
+        let folder: FolderRequestModel = request.body_json().unwrap();
+
+        ResponseTemplate::new(200).set_body_json(FolderResponseModel {
+            object: Some("folder".to_owned()),
+            id: Some(FOLDER_ID.parse().unwrap()),
+            name: Some(folder.name),
+            revision_date: Some("2024-05-31T09:35:55.12Z".to_owned()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_folder() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("POST"))
+                .and(matchers::path("/api/folders"))
+                .respond_with(echo_folder)],
+        )
+        .await;
+
+        let folder = client
+            .vault()
+            .create_folder(FolderView {
+                id: None,
+                name: "Work".to_owned(),
+                revision_date: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(folder.id, Some(FOLDER_ID.parse().unwrap()));
+        assert_eq!(folder.name, "Work");
+    }
+
+    #[tokio::test]
+    async fn test_update_folder() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("PUT"))
+                .and(matchers::path(format!("/api/folders/{FOLDER_ID}")))
+                .respond_with(echo_folder)],
+        )
+        .await;
+
+        let folder = client
+            .vault()
+            .update_folder(FolderView {
+                id: Some(FOLDER_ID.parse().unwrap()),
+                name: "Personal".to_owned(),
+                revision_date: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(folder.name, "Personal");
+    }
+
+    #[tokio::test]
+    async fn test_delete_folder() {
+        let (server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("DELETE"))
+                .and(matchers::path(format!("/api/folders/{FOLDER_ID}")))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)],
+        )
+        .await;
+
+        client
+            .vault()
+            .delete_folder(FOLDER_ID.parse().unwrap())
+            .await
+            .unwrap();
+
+        server.verify().await;
+    }
+}
@@ -0,0 +1,63 @@
+//! Create, update and delete vault items using the Bitwarden API.
+//!
+//! The items are encrypted with the appropriate key before being sent to the server, and the
+//! response is decrypted before being returned.
+use bitwarden_core::{MissingFieldError, VaultLocked};
+use bitwarden_crypto::CryptoError;
+use thiserror::Error;
+
+use crate::{CipherError, DecryptError, EncryptError, VaultParseError};
+
+mod ciphers;
+pub(crate) use ciphers::{
+    create_cipher, delete_cipher, restore_cipher, share_cipher, soft_delete_cipher, update_cipher,
+};
+mod collections;
+pub(crate) use collections::{create_collection, delete_collection, update_collection};
+mod folders;
+pub(crate) use folders::{create_folder, delete_folder, update_folder};
+
+#[derive(Debug, Error)]
+pub enum VaultApiError {
+    #[error(transparent)]
+    Api(#[from] bitwarden_core::ApiError),
+    #[error(transparent)]
+    MissingField(#[from] MissingFieldError),
+    #[error(transparent)]
+    VaultLocked(#[from] VaultLocked),
+    #[error(transparent)]
+    VaultParse(#[from] VaultParseError),
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+    #[error(transparent)]
+    Encrypt(#[from] EncryptError),
+    #[error(transparent)]
+    Decrypt(#[from] DecryptError),
+    #[error(transparent)]
+    Cipher(#[from] CipherError),
+}
+
+/// Start a mock server and a client for the test account which uses it.
+#[cfg(test)]
+pub(crate) async fn start_mock(
+    account: bitwarden_core::client::test_accounts::TestAccount,
+    mocks: Vec<wiremock::Mock>,
+) -> (wiremock::MockServer, bitwarden_core::Client) {
+    let server = wiremock::MockServer::start().await;
+
+    for mock in mocks {
+        server.register(mock).await;
+    }
+
+    let settings = bitwarden_core::ClientSettings {
+        identity_url: format!("http://{}/identity", server.address()),
+        api_url: format!("http://{}/api", server.address()),
+        user_agent: "Bitwarden Rust-SDK [TEST]".into(),
+        device_type: bitwarden_core::DeviceType::SDK,
+    };
+
+    let client =
+        bitwarden_core::Client::init_test_account_with_settings(account, Some(settings)).await;
+
+    (server, client)
+}
@@ -1,11 +1,14 @@
+use std::io::{Read, Write};
+
+use bitwarden_core::key_management::{KeyIds, SymmetricKeyId};
 use bitwarden_crypto::{
-    CryptoError, EncString, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey,
+    CryptoError, Decryptable, EncString, Encryptable, IdentifyKey, KeyStoreContext,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use super::Cipher;
-use crate::VaultParseError;
+use crate::{DecryptFileError, EncryptFileError, VaultParseError};
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -57,21 +60,35 @@ pub struct AttachmentFileView<'a> {
     pub contents: &'a [u8],
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, AttachmentEncryptResult> for AttachmentFileView<'_> {
-    fn encrypt_with_key(
-        self,
-        key: &SymmetricCryptoKey,
+const ATTACHMENT_KEY: SymmetricKeyId = SymmetricKeyId::Local("attachment_key");
+
+impl IdentifyKey<SymmetricKeyId> for AttachmentFileView<'_> {
+    fn key_identifier(&self) -> SymmetricKeyId {
+        self.cipher.key_identifier()
+    }
+}
+impl IdentifyKey<SymmetricKeyId> for AttachmentFile {
+    fn key_identifier(&self) -> SymmetricKeyId {
+        self.cipher.key_identifier()
+    }
+}
+
+impl Encryptable<KeyIds, SymmetricKeyId, AttachmentEncryptResult> for AttachmentFileView<'_> {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
     ) -> Result<AttachmentEncryptResult, CryptoError> {
-        let ciphers_key = Cipher::get_cipher_key(key, &self.cipher.key)?;
-        let ciphers_key = ciphers_key.as_ref().unwrap_or(key);
+        let ciphers_key = Cipher::decrypt_cipher_key(ctx, key, &self.cipher.key)?;
 
-        let mut attachment = self.attachment;
+        let mut attachment = self.attachment.clone();
 
         // Because this is a new attachment, we have to generate a key for it, encrypt the contents
         // with it, and then encrypt the key with the cipher key
-        let attachment_key = SymmetricCryptoKey::generate(rand::thread_rng());
-        let encrypted_contents = self.contents.encrypt_with_key(&attachment_key)?;
-        attachment.key = Some(attachment_key.to_vec().encrypt_with_key(ciphers_key)?);
+        let attachment_key = ctx.generate_symmetric_key(ATTACHMENT_KEY)?;
+        let encrypted_contents = self.contents.encrypt(ctx, attachment_key)?;
+        attachment.key =
+            Some(ctx.encrypt_symmetric_key_with_symmetric_key(ciphers_key, attachment_key)?);
 
         let contents = encrypted_contents.to_buffer()?;
 
@@ -80,12 +97,99 @@ impl KeyEncryptable<SymmetricCryptoKey, AttachmentEncryptResult> for AttachmentF
         attachment.size_name = Some(size_name(contents.len()));
 
         Ok(AttachmentEncryptResult {
-            attachment: attachment.encrypt_with_key(ciphers_key)?,
+            attachment: attachment.encrypt(ctx, ciphers_key)?,
             contents,
         })
     }
 }
 
+impl AttachmentView {
+    /// Encrypts the attachment contents read from `reader` into `writer` using the chunked
+    /// streaming format, and returns the encrypted attachment with its key and size set.
+    pub(crate) fn encrypt_stream(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        cipher: &Cipher,
+        reader: &mut impl Read,
+        writer: impl Write,
+    ) -> Result<Attachment, EncryptFileError> {
+        let ciphers_key = Cipher::decrypt_cipher_key(ctx, cipher.key_identifier(), &cipher.key)?;
+
+        let mut attachment = self.clone();
+
+        let attachment_key = ctx.generate_symmetric_key(ATTACHMENT_KEY)?;
+        let mut writer = CountingWriter {
+            inner: writer,
+            len: 0,
+        };
+        let mut encryptor = ctx.encrypt_stream(attachment_key, &mut writer)?;
+        std::io::copy(reader, &mut encryptor)?;
+        encryptor.finish()?;
+
+        attachment.key =
+            Some(ctx.encrypt_symmetric_key_with_symmetric_key(ciphers_key, attachment_key)?);
+        attachment.size = Some(writer.len.to_string());
+        attachment.size_name = Some(size_name(writer.len));
+
+        Ok(attachment.encrypt(ctx, ciphers_key)?)
+    }
+}
+
+impl Attachment {
+    /// Decrypts the attachment contents read from `reader` into `writer`. Both the chunked
+    /// streaming format and the legacy single [EncString] format are supported.
+    ///
+    /// If this fails, any data already written to `writer` must be discarded.
+    pub(crate) fn decrypt_stream(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        cipher: &Cipher,
+        reader: impl Read,
+        writer: &mut impl Write,
+    ) -> Result<(), DecryptFileError> {
+        let content_key = content_key(ctx, cipher.key_identifier(), cipher, self)?;
+        let mut decryptor = ctx.decrypt_stream(content_key, reader)?;
+        std::io::copy(&mut decryptor, writer)?;
+        Ok(())
+    }
+}
+
+/// Counts the bytes written to the inner writer, used to calculate the attachment size.
+struct CountingWriter<W: Write> {
+    inner: W,
+    len: usize,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.len += len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns the key used to encrypt the contents of the attachment.
+fn content_key(
+    ctx: &mut KeyStoreContext<KeyIds>,
+    key: SymmetricKeyId,
+    cipher: &Cipher,
+    attachment: &Attachment,
+) -> Result<SymmetricKeyId, CryptoError> {
+    let ciphers_key = Cipher::decrypt_cipher_key(ctx, key, &cipher.key)?;
+
+    // Version 2 or 3, `AttachmentKey` or `CipherKey(AttachmentKey)`
+    if let Some(attachment_key) = &attachment.key {
+        ctx.decrypt_symmetric_key_with_symmetric_key(ciphers_key, ATTACHMENT_KEY, attachment_key)
+    } else {
+        // Legacy attachment version 1, use user/org key
+        Ok(key)
+    }
+}
+
 fn size_name(size: usize) -> String {
     let units = ["Bytes", "KB", "MB", "GB", "TB"];
     let size = size as f64;
@@ -96,45 +200,46 @@ fn size_name(size: usize) -> String {
     format!("{} {}", size_round, units[unit])
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, Vec<u8>> for AttachmentFile {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<Vec<u8>, CryptoError> {
-        let ciphers_key = Cipher::get_cipher_key(key, &self.cipher.key)?;
-        let ciphers_key = ciphers_key.as_ref().unwrap_or(key);
-
-        // Version 2 or 3, `AttachmentKey` or `CipherKey(AttachmentKey)`
-        if let Some(attachment_key) = &self.attachment.key {
-            let mut content_key: Vec<u8> = attachment_key.decrypt_with_key(ciphers_key)?;
-            let content_key = SymmetricCryptoKey::try_from(content_key.as_mut_slice())?;
-
-            self.contents.decrypt_with_key(&content_key)
-        } else {
-            // Legacy attachment version 1, use user/org key
-            self.contents.decrypt_with_key(key)
-        }
+impl Decryptable<KeyIds, SymmetricKeyId, Vec<u8>> for AttachmentFile {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<Vec<u8>, CryptoError> {
+        let content_key = content_key(ctx, key, &self.cipher, &self.attachment)?;
+        self.contents.decrypt(ctx, content_key)
     }
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, Attachment> for AttachmentView {
-    fn encrypt_with_key(self, key: &SymmetricCryptoKey) -> Result<Attachment, CryptoError> {
+impl Encryptable<KeyIds, SymmetricKeyId, Attachment> for AttachmentView {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<Attachment, CryptoError> {
         Ok(Attachment {
-            id: self.id,
-            url: self.url,
-            size: self.size,
-            size_name: self.size_name,
-            file_name: self.file_name.encrypt_with_key(key)?,
-            key: self.key,
+            id: self.id.clone(),
+            url: self.url.clone(),
+            size: self.size.clone(),
+            size_name: self.size_name.clone(),
+            file_name: self.file_name.encrypt(ctx, key)?,
+            key: self.key.clone(),
         })
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, AttachmentView> for Attachment {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<AttachmentView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, AttachmentView> for Attachment {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<AttachmentView, CryptoError> {
         Ok(AttachmentView {
             id: self.id.clone(),
             url: self.url.clone(),
             size: self.size.clone(),
             size_name: self.size_name.clone(),
-            file_name: self.file_name.decrypt_with_key(key)?,
+            file_name: self.file_name.decrypt(ctx, key)?,
             key: self.key.clone(),
         })
     }
@@ -157,10 +262,20 @@ impl TryFrom<bitwarden_api_api::models::AttachmentResponseModel> for Attachment
     }
 }
 
+impl From<Attachment> for bitwarden_api_api::models::CipherAttachmentModel {
+    fn from(attachment: Attachment) -> Self {
+        Self {
+            file_name: attachment.file_name.map(|f| f.to_string()),
+            key: attachment.key.map(|k| k.to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use base64::{engine::general_purpose::STANDARD, Engine};
-    use bitwarden_crypto::{EncString, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey};
+    use bitwarden_core::key_management::{KeyIds, SymmetricKeyId};
+    use bitwarden_crypto::{EncString, KeyStore, SymmetricCryptoKey};
 
     use crate::{
         cipher::cipher::{CipherRepromptType, CipherType},
@@ -182,6 +297,12 @@ mod tests {
     #[test]
     fn test_encrypt_attachment() {
         let user_key: SymmetricCryptoKey = "w2LO+nwV4oxwswVYCxlOfRUseXfvU03VzvKQHrqeklPgiMZrspUe6sOBToCnDn9Ay0tuCBn8ykVVRb7PWhub2Q==".to_string().try_into().unwrap();
+        let key_store = KeyStore::<KeyIds>::default();
+        #[allow(deprecated)]
+        key_store
+            .context_mut()
+            .set_symmetric_key(SymmetricKeyId::User, user_key)
+            .unwrap();
 
         let attachment = AttachmentView {
             id: None,
@@ -226,7 +347,7 @@ mod tests {
             contents: contents.as_slice(),
         };
 
-        let result = attachment_file.encrypt_with_key(&user_key).unwrap();
+        let result = key_store.encrypt(attachment_file).unwrap();
 
         assert_eq!(result.contents.len(), 161);
         assert_eq!(result.attachment.size, Some("161".into()));
@@ -236,6 +357,12 @@ mod tests {
     #[test]
     fn test_attachment_key() {
         let user_key: SymmetricCryptoKey = "w2LO+nwV4oxwswVYCxlOfRUseXfvU03VzvKQHrqeklPgiMZrspUe6sOBToCnDn9Ay0tuCBn8ykVVRb7PWhub2Q==".to_string().try_into().unwrap();
+        let key_store = KeyStore::<KeyIds>::default();
+        #[allow(deprecated)]
+        key_store
+            .context_mut()
+            .set_symmetric_key(SymmetricKeyId::User, user_key)
+            .unwrap();
 
         let attachment = Attachment {
             id: None,
@@ -277,13 +404,13 @@ mod tests {
         let enc_file = STANDARD.decode(b"Ao00qr1xLsV+ZNQpYZ/UwEwOWo3hheKwCYcOGIbsorZ6JIG2vLWfWEXCVqP0hDuzRvmx8otApNZr8pJYLNwCe1aQ+ySHQYGkdubFjoMojulMbQ959Y4SJ6Its/EnVvpbDnxpXTDpbutDxyhxfq1P3lstL2G9rObJRrxiwdGlRGu1h94UA1fCCkIUQux5LcqUee6W4MyQmRnsUziH8gGzmtI=").unwrap();
         let original = STANDARD.decode(b"rMweTemxOL9D0iWWfRxiY3enxiZ5IrwWD6ef2apGO6MvgdGhy2fpwmATmn7BpSj9lRumddLLXm7u8zSp6hnXt1hS71YDNh78LjGKGhGL4sbg8uNnpa/I6GK/83jzqGYN7+ESbg==").unwrap();
 
-        let dec = AttachmentFile {
-            cipher,
-            attachment,
-            contents: EncString::from_buffer(&enc_file).unwrap(),
-        }
-        .decrypt_with_key(&user_key)
-        .unwrap();
+        let dec = key_store
+            .decrypt(&AttachmentFile {
+                cipher,
+                attachment,
+                contents: EncString::from_buffer(&enc_file).unwrap(),
+            })
+            .unwrap();
 
         assert_eq!(dec, original);
     }
@@ -291,6 +418,12 @@ mod tests {
     #[test]
     fn test_attachment_without_key() {
         let user_key: SymmetricCryptoKey = "w2LO+nwV4oxwswVYCxlOfRUseXfvU03VzvKQHrqeklPgiMZrspUe6sOBToCnDn9Ay0tuCBn8ykVVRb7PWhub2Q==".to_string().try_into().unwrap();
+        let key_store = KeyStore::<KeyIds>::default();
+        #[allow(deprecated)]
+        key_store
+            .context_mut()
+            .set_symmetric_key(SymmetricKeyId::User, user_key)
+            .unwrap();
 
         let attachment = Attachment {
             id: None,
@@ -332,14 +465,114 @@ mod tests {
         let enc_file = STANDARD.decode(b"AsQLXOBHrJ8porroTUlPxeJOm9XID7LL9D2+KwYATXEpR1EFjLBpcCvMmnqcnYLXIEefe9TCeY4Us50ux43kRSpvdB7YkjxDKV0O1/y6tB7qC4vvv9J9+O/uDEnMx/9yXuEhAW/LA/TsU/WAgxkOM0uTvm8JdD9LUR1z9Ql7zOWycMVzkvGsk2KBNcqAdrotS5FlDftZOXyU8pWecNeyA/w=").unwrap();
         let original = STANDARD.decode(b"rMweTemxOL9D0iWWfRxiY3enxiZ5IrwWD6ef2apGO6MvgdGhy2fpwmATmn7BpSj9lRumddLLXm7u8zSp6hnXt1hS71YDNh78LjGKGhGL4sbg8uNnpa/I6GK/83jzqGYN7+ESbg==").unwrap();
 
-        let dec = AttachmentFile {
-            cipher,
-            attachment,
-            contents: EncString::from_buffer(&enc_file).unwrap(),
-        }
-        .decrypt_with_key(&user_key)
-        .unwrap();
+        let dec = key_store
+            .decrypt(&AttachmentFile {
+                cipher,
+                attachment,
+                contents: EncString::from_buffer(&enc_file).unwrap(),
+            })
+            .unwrap();
 
         assert_eq!(dec, original);
     }
+
+    #[test]
+    fn test_attachment_stream_roundtrip() {
+        let user_key: SymmetricCryptoKey = "w2LO+nwV4oxwswVYCxlOfRUseXfvU03VzvKQHrqeklPgiMZrspUe6sOBToCnDn9Ay0tuCBn8ykVVRb7PWhub2Q==".to_string().try_into().unwrap();
+        let key_store = KeyStore::<KeyIds>::default();
+        #[allow(deprecated)]
+        key_store
+            .context_mut()
+            .set_symmetric_key(SymmetricKeyId::User, user_key)
+            .unwrap();
+
+        let cipher  = Cipher {
+            id: None,
+            organization_id: None,
+            folder_id: None,
+            collection_ids: Vec::new(),
+            key: Some("2.Gg8yCM4IIgykCZyq0O4+cA==|GJLBtfvSJTDJh/F7X4cJPkzI6ccnzJm5DYl3yxOW2iUn7DgkkmzoOe61sUhC5dgVdV0kFqsZPcQ0yehlN1DDsFIFtrb4x7LwzJNIkMgxNyg=|1rGkGJ8zcM5o5D0aIIwAyLsjMLrPsP3EWm3CctBO3Fw=".parse().unwrap()),
+            name: "2.d24xECyEdMZ3MG9s6SrGNw==|XvJlTeu5KJ22M3jKosy6iw==|8xGiQty4X61cDMx6PVqkJfSQ0ZTdA/5L9TpG7QfovoM=".parse().unwrap(),
+            notes: None,
+            r#type: CipherType::Login,
+            login: None,
+            identity: None,
+            card: None,
+            secure_note: None,
+            ssh_key: None,
+            favorite: false,
+            reprompt: CipherRepromptType::None,
+            organization_use_totp: false,
+            edit: true,
+            view_password: true,
+            local_data: None,
+            attachments: None,
+            fields: None,
+            password_history: None,
+            creation_date: "2023-07-24T12:05:09.466666700Z".parse().unwrap(),
+            deleted_date: None,
+            revision_date: "2023-07-27T19:28:05.240Z".parse().unwrap(),
+        };
+
+        let attachment = AttachmentView {
+            id: None,
+            url: None,
+            size: None,
+            size_name: None,
+            file_name: Some("Test.bin".into()),
+            key: None,
+        };
+
+        let contents: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+
+        let mut encrypted = Vec::new();
+        let attachment = attachment
+            .encrypt_stream(
+                &mut key_store.context(),
+                &cipher,
+                &mut contents.as_slice(),
+                &mut encrypted,
+            )
+            .unwrap();
+        assert_eq!(attachment.size, Some(encrypted.len().to_string()));
+
+        let mut decrypted = Vec::new();
+        attachment
+            .decrypt_stream(
+                &mut key_store.context(),
+                &cipher,
+                encrypted.as_slice(),
+                &mut decrypted,
+            )
+            .unwrap();
+        assert_eq!(decrypted, contents);
+
+        // Attachments encrypted as a single EncString can also be decrypted as a stream
+        let legacy = key_store
+            .encrypt(AttachmentFileView {
+                cipher: cipher.clone(),
+                attachment: AttachmentView {
+                    id: None,
+                    url: None,
+                    size: None,
+                    size_name: None,
+                    file_name: Some("Test.bin".into()),
+                    key: None,
+                },
+                contents: &contents,
+            })
+            .unwrap();
+
+        let mut decrypted = Vec::new();
+        legacy
+            .attachment
+            .decrypt_stream(
+                &mut key_store.context(),
+                &cipher,
+                legacy.contents.as_slice(),
+                &mut decrypted,
+            )
+            .unwrap();
+        assert_eq!(decrypted, contents);
+    }
 }
@@ -1,8 +1,9 @@
 use bitwarden_api_api::models::CipherFieldModel;
-use bitwarden_core::require;
-use bitwarden_crypto::{
-    CryptoError, EncString, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey,
+use bitwarden_core::{
+    key_management::{KeyIds, SymmetricKeyId},
+    require,
 };
+use bitwarden_crypto::{CryptoError, Decryptable, EncString, Encryptable, KeyStoreContext};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -42,22 +43,30 @@ pub struct FieldView {
     pub linked_id: Option<LinkedIdType>,
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, Field> for FieldView {
-    fn encrypt_with_key(self, key: &SymmetricCryptoKey) -> Result<Field, CryptoError> {
+impl Encryptable<KeyIds, SymmetricKeyId, Field> for FieldView {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<Field, CryptoError> {
         Ok(Field {
-            name: self.name.encrypt_with_key(key)?,
-            value: self.value.encrypt_with_key(key)?,
+            name: self.name.encrypt(ctx, key)?,
+            value: self.value.encrypt(ctx, key)?,
             r#type: self.r#type,
             linked_id: self.linked_id,
         })
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, FieldView> for Field {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<FieldView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, FieldView> for Field {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<FieldView, CryptoError> {
         Ok(FieldView {
-            name: self.name.decrypt_with_key(key).ok().flatten(),
-            value: self.value.decrypt_with_key(key).ok().flatten(),
+            name: self.name.decrypt(ctx, key).ok().flatten(),
+            value: self.value.decrypt(ctx, key).ok().flatten(),
             r#type: self.r#type,
             linked_id: self.linked_id,
         })
@@ -90,3 +99,25 @@ impl From<bitwarden_api_api::models::FieldType> for FieldType {
         }
     }
 }
+
+impl From<Field> for CipherFieldModel {
+    fn from(field: Field) -> Self {
+        Self {
+            r#type: Some(field.r#type.into()),
+            name: field.name.map(|n| n.to_string()),
+            value: field.value.map(|v| v.to_string()),
+            linked_id: field.linked_id.map(|id| u32::from(id) as i32),
+        }
+    }
+}
+
+impl From<FieldType> for bitwarden_api_api::models::FieldType {
+    fn from(model: FieldType) -> Self {
+        match model {
+            FieldType::Text => bitwarden_api_api::models::FieldType::Text,
+            FieldType::Hidden => bitwarden_api_api::models::FieldType::Hidden,
+            FieldType::Boolean => bitwarden_api_api::models::FieldType::Boolean,
+            FieldType::Linked => bitwarden_api_api::models::FieldType::Linked,
+        }
+    }
+}
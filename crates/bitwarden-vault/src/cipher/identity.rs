@@ -1,7 +1,6 @@
 use bitwarden_api_api::models::CipherIdentityModel;
-use bitwarden_crypto::{
-    CryptoError, EncString, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey,
-};
+use bitwarden_core::key_management::{KeyIds, SymmetricKeyId};
+use bitwarden_crypto::{CryptoError, Decryptable, EncString, Encryptable, KeyStoreContext};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -55,52 +54,60 @@ pub struct IdentityView {
     pub license_number: Option<String>,
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, Identity> for IdentityView {
-    fn encrypt_with_key(self, key: &SymmetricCryptoKey) -> Result<Identity, CryptoError> {
+impl Encryptable<KeyIds, SymmetricKeyId, Identity> for IdentityView {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<Identity, CryptoError> {
         Ok(Identity {
-            title: self.title.encrypt_with_key(key)?,
-            first_name: self.first_name.encrypt_with_key(key)?,
-            middle_name: self.middle_name.encrypt_with_key(key)?,
-            last_name: self.last_name.encrypt_with_key(key)?,
-            address1: self.address1.encrypt_with_key(key)?,
-            address2: self.address2.encrypt_with_key(key)?,
-            address3: self.address3.encrypt_with_key(key)?,
-            city: self.city.encrypt_with_key(key)?,
-            state: self.state.encrypt_with_key(key)?,
-            postal_code: self.postal_code.encrypt_with_key(key)?,
-            country: self.country.encrypt_with_key(key)?,
-            company: self.company.encrypt_with_key(key)?,
-            email: self.email.encrypt_with_key(key)?,
-            phone: self.phone.encrypt_with_key(key)?,
-            ssn: self.ssn.encrypt_with_key(key)?,
-            username: self.username.encrypt_with_key(key)?,
-            passport_number: self.passport_number.encrypt_with_key(key)?,
-            license_number: self.license_number.encrypt_with_key(key)?,
+            title: self.title.encrypt(ctx, key)?,
+            first_name: self.first_name.encrypt(ctx, key)?,
+            middle_name: self.middle_name.encrypt(ctx, key)?,
+            last_name: self.last_name.encrypt(ctx, key)?,
+            address1: self.address1.encrypt(ctx, key)?,
+            address2: self.address2.encrypt(ctx, key)?,
+            address3: self.address3.encrypt(ctx, key)?,
+            city: self.city.encrypt(ctx, key)?,
+            state: self.state.encrypt(ctx, key)?,
+            postal_code: self.postal_code.encrypt(ctx, key)?,
+            country: self.country.encrypt(ctx, key)?,
+            company: self.company.encrypt(ctx, key)?,
+            email: self.email.encrypt(ctx, key)?,
+            phone: self.phone.encrypt(ctx, key)?,
+            ssn: self.ssn.encrypt(ctx, key)?,
+            username: self.username.encrypt(ctx, key)?,
+            passport_number: self.passport_number.encrypt(ctx, key)?,
+            license_number: self.license_number.encrypt(ctx, key)?,
         })
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, IdentityView> for Identity {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<IdentityView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, IdentityView> for Identity {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<IdentityView, CryptoError> {
         Ok(IdentityView {
-            title: self.title.decrypt_with_key(key).ok().flatten(),
-            first_name: self.first_name.decrypt_with_key(key).ok().flatten(),
-            middle_name: self.middle_name.decrypt_with_key(key).ok().flatten(),
-            last_name: self.last_name.decrypt_with_key(key).ok().flatten(),
-            address1: self.address1.decrypt_with_key(key).ok().flatten(),
-            address2: self.address2.decrypt_with_key(key).ok().flatten(),
-            address3: self.address3.decrypt_with_key(key).ok().flatten(),
-            city: self.city.decrypt_with_key(key).ok().flatten(),
-            state: self.state.decrypt_with_key(key).ok().flatten(),
-            postal_code: self.postal_code.decrypt_with_key(key).ok().flatten(),
-            country: self.country.decrypt_with_key(key).ok().flatten(),
-            company: self.company.decrypt_with_key(key).ok().flatten(),
-            email: self.email.decrypt_with_key(key).ok().flatten(),
-            phone: self.phone.decrypt_with_key(key).ok().flatten(),
-            ssn: self.ssn.decrypt_with_key(key).ok().flatten(),
-            username: self.username.decrypt_with_key(key).ok().flatten(),
-            passport_number: self.passport_number.decrypt_with_key(key).ok().flatten(),
-            license_number: self.license_number.decrypt_with_key(key).ok().flatten(),
+            title: self.title.decrypt(ctx, key).ok().flatten(),
+            first_name: self.first_name.decrypt(ctx, key).ok().flatten(),
+            middle_name: self.middle_name.decrypt(ctx, key).ok().flatten(),
+            last_name: self.last_name.decrypt(ctx, key).ok().flatten(),
+            address1: self.address1.decrypt(ctx, key).ok().flatten(),
+            address2: self.address2.decrypt(ctx, key).ok().flatten(),
+            address3: self.address3.decrypt(ctx, key).ok().flatten(),
+            city: self.city.decrypt(ctx, key).ok().flatten(),
+            state: self.state.decrypt(ctx, key).ok().flatten(),
+            postal_code: self.postal_code.decrypt(ctx, key).ok().flatten(),
+            country: self.country.decrypt(ctx, key).ok().flatten(),
+            company: self.company.decrypt(ctx, key).ok().flatten(),
+            email: self.email.decrypt(ctx, key).ok().flatten(),
+            phone: self.phone.decrypt(ctx, key).ok().flatten(),
+            ssn: self.ssn.decrypt(ctx, key).ok().flatten(),
+            username: self.username.decrypt(ctx, key).ok().flatten(),
+            passport_number: self.passport_number.decrypt(ctx, key).ok().flatten(),
+            license_number: self.license_number.decrypt(ctx, key).ok().flatten(),
         })
     }
 }
@@ -131,3 +138,28 @@ impl TryFrom<CipherIdentityModel> for Identity {
         })
     }
 }
+
+impl From<Identity> for CipherIdentityModel {
+    fn from(identity: Identity) -> Self {
+        Self {
+            title: identity.title.map(|v| v.to_string()),
+            first_name: identity.first_name.map(|v| v.to_string()),
+            middle_name: identity.middle_name.map(|v| v.to_string()),
+            last_name: identity.last_name.map(|v| v.to_string()),
+            address1: identity.address1.map(|v| v.to_string()),
+            address2: identity.address2.map(|v| v.to_string()),
+            address3: identity.address3.map(|v| v.to_string()),
+            city: identity.city.map(|v| v.to_string()),
+            state: identity.state.map(|v| v.to_string()),
+            postal_code: identity.postal_code.map(|v| v.to_string()),
+            country: identity.country.map(|v| v.to_string()),
+            company: identity.company.map(|v| v.to_string()),
+            email: identity.email.map(|v| v.to_string()),
+            phone: identity.phone.map(|v| v.to_string()),
+            ssn: identity.ssn.map(|v| v.to_string()),
+            username: identity.username.map(|v| v.to_string()),
+            passport_number: identity.passport_number.map(|v| v.to_string()),
+            license_number: identity.license_number.map(|v| v.to_string()),
+        }
+    }
+}
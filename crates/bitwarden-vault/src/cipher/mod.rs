@@ -15,7 +15,7 @@ pub use attachment::{
 };
 pub use card::{CardBrand, CardView};
 pub use cipher::{Cipher, CipherError, CipherListView, CipherRepromptType, CipherType, CipherView};
-pub use field::FieldView;
+pub use field::{FieldType, FieldView};
 pub use identity::IdentityView;
 pub use login::{
     Fido2Credential, Fido2CredentialFullView, Fido2CredentialNewView, Fido2CredentialView, Login,
@@ -1,9 +1,14 @@
-use bitwarden_crypto::{
-    CryptoError, EncString, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey,
+use bitwarden_api_api::models::CipherSshKeyModel;
+use bitwarden_core::{
+    key_management::{KeyIds, SymmetricKeyId},
+    require,
 };
+use bitwarden_crypto::{CryptoError, Decryptable, EncString, Encryptable, KeyStoreContext};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::VaultParseError;
+
 #[derive(Serialize, Deserialize, Debug, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
@@ -28,22 +33,52 @@ pub struct SshKeyView {
     pub fingerprint: String,
 }
 
-impl KeyEncryptable<SymmetricCryptoKey, SshKey> for SshKeyView {
-    fn encrypt_with_key(self, key: &SymmetricCryptoKey) -> Result<SshKey, CryptoError> {
+impl Encryptable<KeyIds, SymmetricKeyId, SshKey> for SshKeyView {
+    fn encrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SshKey, CryptoError> {
         Ok(SshKey {
-            private_key: self.private_key.encrypt_with_key(key)?,
-            public_key: self.public_key.encrypt_with_key(key)?,
-            fingerprint: self.fingerprint.encrypt_with_key(key)?,
+            private_key: self.private_key.encrypt(ctx, key)?,
+            public_key: self.public_key.encrypt(ctx, key)?,
+            fingerprint: self.fingerprint.encrypt(ctx, key)?,
         })
     }
 }
 
-impl KeyDecryptable<SymmetricCryptoKey, SshKeyView> for SshKey {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<SshKeyView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, SshKeyView> for SshKey {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<SshKeyView, CryptoError> {
         Ok(SshKeyView {
-            private_key: self.private_key.decrypt_with_key(key)?,
-            public_key: self.public_key.decrypt_with_key(key)?,
-            fingerprint: self.fingerprint.decrypt_with_key(key)?,
+            private_key: self.private_key.decrypt(ctx, key)?,
+            public_key: self.public_key.decrypt(ctx, key)?,
+            fingerprint: self.fingerprint.decrypt(ctx, key)?,
         })
     }
 }
+
+impl TryFrom<CipherSshKeyModel> for SshKey {
+    type Error = VaultParseError;
+
+    fn try_from(ssh_key: CipherSshKeyModel) -> Result<Self, Self::Error> {
+        Ok(Self {
+            private_key: require!(ssh_key.private_key).parse()?,
+            public_key: require!(ssh_key.public_key).parse()?,
+            fingerprint: require!(ssh_key.key_fingerprint).parse()?,
+        })
+    }
+}
+
+impl From<SshKey> for CipherSshKeyModel {
+    fn from(ssh_key: SshKey) -> Self {
+        Self {
+            private_key: Some(ssh_key.private_key.to_string()),
+            public_key: Some(ssh_key.public_key.to_string()),
+            key_fingerprint: Some(ssh_key.fingerprint.to_string()),
+        }
+    }
+}
@@ -1,7 +1,12 @@
-use bitwarden_api_api::models::CollectionDetailsResponseModel;
-use bitwarden_core::require;
+use bitwarden_api_api::models::{
+    CollectionDetailsResponseModel, CollectionRequestModel, CollectionResponseModel,
+};
+use bitwarden_core::{
+    key_management::{KeyIds, SymmetricKeyId},
+    require,
+};
 use bitwarden_crypto::{
-    CryptoError, EncString, KeyContainer, KeyDecryptable, LocateKey, SymmetricCryptoKey,
+    CryptoError, Decryptable, EncString, Encryptable, IdentifyKey, KeyStoreContext,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -9,7 +14,7 @@ use uuid::Uuid;
 
 use crate::VaultParseError;
 
-#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[derive(Serialize, Deserialize, Debug, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 #[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct Collection {
@@ -39,22 +44,46 @@ pub struct CollectionView {
     pub manage: bool,
 }
 
-impl LocateKey for Collection {
-    fn locate_key<'a>(
+impl IdentifyKey<SymmetricKeyId> for Collection {
+    fn key_identifier(&self) -> SymmetricKeyId {
+        SymmetricKeyId::Organization(self.organization_id)
+    }
+}
+impl IdentifyKey<SymmetricKeyId> for CollectionView {
+    fn key_identifier(&self) -> SymmetricKeyId {
+        SymmetricKeyId::Organization(self.organization_id)
+    }
+}
+impl Encryptable<KeyIds, SymmetricKeyId, Collection> for CollectionView {
+    fn encrypt(
         &self,
-        enc: &'a dyn KeyContainer,
-        _: &Option<Uuid>,
-    ) -> Result<&'a SymmetricCryptoKey, CryptoError> {
-        enc.get_key(&Some(self.organization_id))
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<Collection, CryptoError> {
+        Ok(Collection {
+            id: self.id,
+            organization_id: self.organization_id,
+
+            name: self.name.encrypt(ctx, key)?,
+
+            external_id: self.external_id.clone(),
+            hide_passwords: self.hide_passwords,
+            read_only: self.read_only,
+            manage: self.manage,
+        })
     }
 }
-impl KeyDecryptable<SymmetricCryptoKey, CollectionView> for Collection {
-    fn decrypt_with_key(&self, key: &SymmetricCryptoKey) -> Result<CollectionView, CryptoError> {
+impl Decryptable<KeyIds, SymmetricKeyId, CollectionView> for Collection {
+    fn decrypt(
+        &self,
+        ctx: &mut KeyStoreContext<KeyIds>,
+        key: SymmetricKeyId,
+    ) -> Result<CollectionView, CryptoError> {
         Ok(CollectionView {
             id: self.id,
             organization_id: self.organization_id,
 
-            name: self.name.decrypt_with_key(key).ok().unwrap_or_default(),
+            name: self.name.decrypt(ctx, key).ok().unwrap_or_default(),
 
             external_id: self.external_id.clone(),
             hide_passwords: self.hide_passwords,
@@ -79,3 +108,32 @@ impl TryFrom<CollectionDetailsResponseModel> for Collection {
         })
     }
 }
+
+impl TryFrom<CollectionResponseModel> for Collection {
+    type Error = VaultParseError;
+
+    fn try_from(collection: CollectionResponseModel) -> Result<Self, Self::Error> {
+        Ok(Collection {
+            id: collection.id,
+            organization_id: require!(collection.organization_id),
+            name: require!(collection.name).parse()?,
+            external_id: collection.external_id,
+            // The permissions of the user are not part of the response
+            hide_passwords: false,
+            read_only: false,
+            manage: false,
+        })
+    }
+}
+
+impl From<Collection> for CollectionRequestModel {
+    fn from(collection: Collection) -> Self {
+        Self {
+            name: collection.name.to_string(),
+            external_id: collection.external_id,
+            // Leave the access of groups and users unchanged
+            groups: None,
+            users: None,
+        }
+    }
+}
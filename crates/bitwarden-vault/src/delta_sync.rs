@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+
+use bitwarden_api_api::apis::accounts_api;
+use bitwarden_core::Client;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    sync::{sync, SyncError},
+    Cipher, Collection, Folder, SyncRequest, SyncResponse,
+};
+
+/// The result of an earlier sync, which is kept by the client between syncs.
+pub struct PreviousSync<'a> {
+    /// The [DeltaSyncResponse::revision_date] of the earlier sync
+    pub revision_date: DateTime<Utc>,
+    pub vault: &'a SyncResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DeltaSyncResponse {
+    /// Revision date of the account, to provide with the next sync
+    pub revision_date: DateTime<Utc>,
+    /// `None` when nothing changed since the previous sync, in which case the cached data is
+    /// still current
+    pub changes: Option<SyncChanges>,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SyncChanges {
+    /// The complete vault data, which replaces the cached data
+    pub vault: SyncResponse,
+    pub ciphers: ItemDiff<Cipher>,
+    pub folders: ItemDiff<Folder>,
+    pub collections: ItemDiff<Collection>,
+}
+
+/// The items which differ between the previous and the current sync. Only these need to be
+/// decrypted again.
+#[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ItemDiff<T> {
+    pub added: Vec<T>,
+    pub changed: Vec<T>,
+    /// Ids of the items which are no longer accessible
+    pub removed: Vec<Uuid>,
+}
+
+/// Sync the vault, unless the account has not been modified since the previous sync.
+pub(crate) async fn delta_sync(
+    client: &Client,
+    input: &SyncRequest,
+    previous: Option<PreviousSync<'_>>,
+) -> Result<DeltaSyncResponse, SyncError> {
+    // Retrieve the revision date before syncing, so that changes made during the sync are picked
+    // up by the next one.
+    let config = client.internal.get_api_configurations().await;
+    let revision_date = accounts_api::accounts_revision_date_get(&config.api)
+        .await
+        .map_err(|e| SyncError::Api(e.into()))?;
+    let revision_date = DateTime::from_timestamp_millis(revision_date)
+        .ok_or(SyncError::InvalidRevisionDate(revision_date))?;
+
+    if previous
+        .as_ref()
+        .is_some_and(|p| p.revision_date >= revision_date)
+    {
+        return Ok(DeltaSyncResponse {
+            revision_date,
+            changes: None,
+        });
+    }
+
+    let vault = sync(client, input).await?;
+
+    let (ciphers, folders, collections) = match previous {
+        Some(previous) => (
+            diff(&previous.vault.ciphers, &vault.ciphers),
+            diff(&previous.vault.folders, &vault.folders),
+            diff(&previous.vault.collections, &vault.collections),
+        ),
+        None => (
+            diff(&[], &vault.ciphers),
+            diff(&[], &vault.folders),
+            diff(&[], &vault.collections),
+        ),
+    };
+
+    Ok(DeltaSyncResponse {
+        revision_date,
+        changes: Some(SyncChanges {
+            vault,
+            ciphers,
+            folders,
+            collections,
+        }),
+    })
+}
+
+/// An item of the vault which can be compared between syncs.
+trait SyncItem: Clone {
+    fn id(&self) -> Option<Uuid>;
+
+    /// Whether the item was modified since the `previous` version.
+    fn is_changed(&self, previous: &Self) -> bool;
+}
+
+impl SyncItem for Cipher {
+    fn id(&self) -> Option<Uuid> {
+        self.id
+    }
+
+    fn is_changed(&self, previous: &Self) -> bool {
+        // The collections, folder, favorite status and permissions are specific to the user and
+        // changing them does not update the revision date of the cipher
+        self.revision_date != previous.revision_date
+            || self.collection_ids != previous.collection_ids
+            || self.folder_id != previous.folder_id
+            || self.favorite != previous.favorite
+            || self.edit != previous.edit
+            || self.view_password != previous.view_password
+            || self.organization_use_totp != previous.organization_use_totp
+    }
+}
+
+impl SyncItem for Folder {
+    fn id(&self) -> Option<Uuid> {
+        self.id
+    }
+
+    fn is_changed(&self, previous: &Self) -> bool {
+        self.revision_date != previous.revision_date
+    }
+}
+
+impl SyncItem for Collection {
+    fn id(&self) -> Option<Uuid> {
+        self.id
+    }
+
+    /// Collections have no revision date, so compare all the fields.
+    fn is_changed(&self, previous: &Self) -> bool {
+        self != previous
+    }
+}
+
+fn diff<T: SyncItem>(previous: &[T], current: &[T]) -> ItemDiff<T> {
+    let mut previous: HashMap<_, _> = previous
+        .iter()
+        .filter_map(|item| item.id().map(|id| (id, item)))
+        .collect();
+
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+
+    for item in current {
+        match item.id().and_then(|id| previous.remove(&id)) {
+            Some(prev) if item.is_changed(prev) => changed.push(item.clone()),
+            Some(_) => {}
+            None => added.push(item.clone()),
+        }
+    }
+
+    ItemDiff {
+        added,
+        changed,
+        removed: previous.into_keys().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
+    use serde_json::json;
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::{api::start_mock, VaultClientExt};
+
+    const ORGANIZATION_ID: &str = "1bc9ac1e-f5aa-45f2-94bf-b181009709b8";
+    const NAME: &str = "2.Q/2PhzcC7GdeiMHhWguYAQ==|GpqzVdr0go0ug5cZh1n+uixeBC3oC90CIe0hd/HWA/pTRDZ8ane4fmsEIcuc8eMKUt55Y2q/fbNzsYu41YTZzzsJUSeqVjT8/iTQtgnNdpo=|dwI+uyvZ1h/iZ03VQ+/wrGEFYVewBUUl/syYgjsNMbE=";
+
+    fn folder(id: &str, revision_date: &str) -> serde_json::Value {
+        json!({ "id": id, "name": NAME, "revisionDate": revision_date })
+    }
+
+    fn collection(id: &str, external_id: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "organizationId": ORGANIZATION_ID,
+            "name": NAME,
+            "externalId": external_id,
+            "hidePasswords": false,
+            "readOnly": false,
+            "manage": true,
+        })
+    }
+
+    fn sync_response(
+        folders: Vec<serde_json::Value>,
+        collections: Vec<serde_json::Value>,
+    ) -> serde_json::Value {
+        json!({
+            "object": "sync",
+            "profile": {
+                "id": "b1fd4bf2-9643-4787-87f3-b0f00189c33b",
+                "name": "Test",
+                "email": "test@bitwarden.com",
+                "organizations": [],
+            },
+            "folders": folders,
+            "collections": collections,
+            "ciphers": [],
+            "policies": [],
+            "sends": [],
+        })
+    }
+
+    async fn mock_delta_sync(
+        revision_date: i64,
+        response: serde_json::Value,
+        previous: Option<PreviousSync<'_>>,
+    ) -> DeltaSyncResponse {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![
+                Mock::given(matchers::method("GET"))
+                    .and(matchers::path("/api/accounts/revision-date"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(revision_date)),
+                Mock::given(matchers::method("GET"))
+                    .and(matchers::path("/api/sync"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(response)),
+            ],
+        )
+        .await;
+
+        client
+            .vault()
+            .delta_sync(
+                &SyncRequest {
+                    exclude_subdomains: None,
+                },
+                previous,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cipher_favorite_changed() {
+        let previous: Cipher = serde_json::from_value(json!({
+            "id": "5d9ef6a4-0a8f-4b5e-9a1a-b18100973b47",
+            "collectionIds": [],
+            "name": NAME,
+            "type": 2,
+            "favorite": false,
+            "reprompt": 0,
+            "organizationUseTotp": false,
+            "edit": true,
+            "viewPassword": true,
+            "creationDate": "2024-05-31T09:35:55.12Z",
+            "revisionDate": "2024-05-31T09:35:55.12Z",
+        }))
+        .unwrap();
+
+        assert!(!previous.is_changed(&previous));
+
+        let current = Cipher {
+            favorite: true,
+            ..previous.clone()
+        };
+
+        let changes = diff(&[previous], &[current]);
+        assert!(changes.added.is_empty());
+        assert_eq!(changes.changed.len(), 1);
+        assert!(changes.changed[0].favorite);
+    }
+
+    #[tokio::test]
+    async fn test_delta_sync_initial() {
+        let response = mock_delta_sync(
+            1717148155120,
+            sync_response(
+                vec![folder(
+                    "9b2f4e6a-3c1d-4e5f-8a7b-6c5d4e3f2a1b",
+                    "2024-05-31T09:35:55.12Z",
+                )],
+                vec![],
+            ),
+            None,
+        )
+        .await;
+
+        assert_eq!(response.revision_date.timestamp_millis(), 1717148155120);
+        let changes = response.changes.unwrap();
+        assert_eq!(changes.folders.added.len(), 1);
+        assert!(changes.folders.changed.is_empty());
+        assert!(changes.folders.removed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delta_sync_unchanged() {
+        let previous: SyncResponse = serde_json::from_value(json!({
+            "profile": {
+                "id": "b1fd4bf2-9643-4787-87f3-b0f00189c33b",
+                "name": "Test",
+                "email": "test@bitwarden.com",
+                "key": null,
+                "privateKey": null,
+                "organizations": [],
+            },
+            "folders": [],
+            "collections": [],
+            "ciphers": [],
+            "domains": null,
+            "policies": [],
+            "sends": [],
+        }))
+        .unwrap();
+
+        let response = mock_delta_sync(
+            1717148155120,
+            json!({}),
+            Some(PreviousSync {
+                revision_date: DateTime::from_timestamp_millis(1717148155120).unwrap(),
+                vault: &previous,
+            }),
+        )
+        .await;
+
+        assert!(response.changes.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delta_sync_diff() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("GET"))
+                .and(matchers::path("/api/sync"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(sync_response(
+                    vec![
+                        folder(
+                            "9b2f4e6a-3c1d-4e5f-8a7b-6c5d4e3f2a1b",
+                            "2024-05-31T09:35:55.12Z",
+                        ),
+                        folder(
+                            "c3a1ff5e-0d8b-4c37-9f27-b18100973b44",
+                            "2024-05-31T09:35:55.12Z",
+                        ),
+                    ],
+                    vec![
+                        collection("66c5ca57-0868-4c7e-902f-b181009709c0", "engineering"),
+                        collection("a4ea1a5e-2f7d-4a0d-a3a4-b18100973b45", "sales"),
+                    ],
+                )))],
+        )
+        .await;
+        let previous = client
+            .vault()
+            .sync(&SyncRequest {
+                exclude_subdomains: None,
+            })
+            .await
+            .unwrap();
+
+        let response = mock_delta_sync(
+            1717148255120,
+            sync_response(
+                vec![
+                    // Renamed
+                    folder(
+                        "9b2f4e6a-3c1d-4e5f-8a7b-6c5d4e3f2a1b",
+                        "2024-05-31T10:00:00Z",
+                    ),
+                    folder(
+                        "c3a1ff5e-0d8b-4c37-9f27-b18100973b44",
+                        "2024-05-31T09:35:55.12Z",
+                    ),
+                    // Created
+                    folder(
+                        "e6f3c5d1-5a77-4a84-9d6b-b18100973b46",
+                        "2024-05-31T10:00:00Z",
+                    ),
+                ],
+                vec![collection(
+                    "66c5ca57-0868-4c7e-902f-b181009709c0",
+                    "platform",
+                )],
+            ),
+            Some(PreviousSync {
+                revision_date: DateTime::from_timestamp_millis(1717148155120).unwrap(),
+                vault: &previous,
+            }),
+        )
+        .await;
+
+        let changes = response.changes.unwrap();
+
+        assert_eq!(changes.vault.folders.len(), 3);
+        assert_eq!(
+            changes.folders.added[0].id,
+            Some("e6f3c5d1-5a77-4a84-9d6b-b18100973b46".parse().unwrap())
+        );
+        assert_eq!(
+            changes.folders.changed[0].id,
+            Some("9b2f4e6a-3c1d-4e5f-8a7b-6c5d4e3f2a1b".parse().unwrap())
+        );
+        assert!(changes.folders.removed.is_empty());
+
+        assert!(changes.collections.added.is_empty());
+        assert_eq!(
+            changes.collections.changed[0].external_id.as_deref(),
+            Some("platform")
+        );
+        assert_eq!(
+            changes.collections.removed,
+            vec!["a4ea1a5e-2f7d-4a0d-a3a4-b18100973b45"
+                .parse::<Uuid>()
+                .unwrap()]
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Rotate the user key, re-encrypting everything it protects.
+//!
+//! Rotation generates a new user key and re-encrypts the personal ciphers, folders and sends, the
+//! private key, the emergency access grants and the admin password reset enrollments with it. The
+//! server logs out every session after a rotation, so the client has to log in again afterwards.
+//!
+//! The public keys of the organizations and emergency access grantees are verified against the
+//! trusted keys of the client, so the server can't substitute its own keys to obtain the new user
+//! key.
+use std::collections::HashMap;
+
+use bitwarden_api_api::{
+    apis::{emergency_access_api, sync_api},
+    models::{
+        EmergencyAccessStatusType, EmergencyAccessType, EmergencyAccessWithIdRequestModel,
+        ResetPasswordWithOrgIdRequestModel, UpdateKeyRequestModel,
+    },
+};
+use bitwarden_core::{
+    key_management::{KeyIds, SymmetricKeyId},
+    mobile::crypto::{make_user_key_rotation, UserKeyRotationRequest},
+    platform::{PublicKeyError, PublicKeyOwner},
+    require, Client, MissingFieldError,
+};
+use bitwarden_crypto::{CryptoError, Decryptable, Encryptable, KeyStoreContext};
+use bitwarden_send::{Send, SendParseError};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{Cipher, CipherError, CipherView, Folder, FolderView, VaultParseError};
+
+#[derive(Debug, Error)]
+pub enum KeyRotationError {
+    #[error(transparent)]
+    Core(#[from] bitwarden_core::Error),
+    #[error(transparent)]
+    Api(#[from] bitwarden_core::ApiError),
+    #[error(transparent)]
+    MissingField(#[from] MissingFieldError),
+    #[error(transparent)]
+    VaultParse(#[from] VaultParseError),
+    #[error(transparent)]
+    SendParse(#[from] SendParseError),
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+    #[error(transparent)]
+    Cipher(#[from] CipherError),
+    #[error(transparent)]
+    PublicKey(#[from] PublicKeyError),
+}
+
+/// An emergency access grantee which has been given the user key.
+pub(crate) struct EmergencyAccessGrantee {
+    pub(crate) id: Uuid,
+    pub(crate) r#type: EmergencyAccessType,
+    pub(crate) wait_time_days: i32,
+    /// Base64 encoded public key of the grantee
+    pub(crate) public_key: String,
+}
+
+/// The data protected by the current user key.
+pub(crate) struct UserKeyRotationData {
+    pub(crate) ciphers: Vec<Cipher>,
+    pub(crate) folders: Vec<Folder>,
+    pub(crate) sends: Vec<Send>,
+    pub(crate) emergency_access: Vec<EmergencyAccessGrantee>,
+    /// Base64 encoded public keys of the organizations the user is enrolled in admin password
+    /// reset for, by organization id
+    pub(crate) reset_password_public_keys: HashMap<Uuid, String>,
+}
+
+pub(crate) async fn get_user_key_rotation_data(
+    client: &Client,
+) -> Result<UserKeyRotationData, KeyRotationError> {
+    let config = client.internal.get_api_configurations().await;
+
+    let sync = sync_api::sync_get(&config.api, Some(true))
+        .await
+        .map_err(|e| KeyRotationError::Api(e.into()))?;
+
+    let ciphers = require!(sync.ciphers)
+        .into_iter()
+        .map(Cipher::try_from)
+        .collect::<Result<_, _>>()?;
+    let folders = require!(sync.folders)
+        .into_iter()
+        .map(Folder::try_from)
+        .collect::<Result<_, _>>()?;
+    let sends = require!(sync.sends)
+        .into_iter()
+        .map(Send::try_from)
+        .collect::<Result<_, _>>()?;
+
+    let mut reset_password_public_keys = HashMap::new();
+    for organization in require!(sync.profile).organizations.unwrap_or_default() {
+        if organization.reset_password_enrolled != Some(true) {
+            continue;
+        }
+        let id = require!(organization.id);
+        let response = client
+            .platform()
+            .get_public_key(PublicKeyOwner::Organization(id))
+            .await?;
+        reset_password_public_keys.insert(id, response.public_key);
+    }
+
+    let grantees = emergency_access_api::emergency_access_trusted_get(&config.api)
+        .await
+        .map_err(|e| KeyRotationError::Api(e.into()))?;
+
+    let mut emergency_access = Vec::new();
+    for grantee in grantees.data.unwrap_or_default() {
+        // Grantees only receive the user key once they have been confirmed
+        if !matches!(
+            grantee.status,
+            Some(
+                EmergencyAccessStatusType::Confirmed
+                    | EmergencyAccessStatusType::RecoveryInitiated
+                    | EmergencyAccessStatusType::RecoveryApproved
+            )
+        ) {
+            continue;
+        }
+        let grantee_id = require!(grantee.grantee_id);
+        let response = client
+            .platform()
+            .get_public_key(PublicKeyOwner::User(grantee_id))
+            .await?;
+
+        emergency_access.push(EmergencyAccessGrantee {
+            id: require!(grantee.id),
+            r#type: require!(grantee.r#type),
+            wait_time_days: require!(grantee.wait_time_days),
+            public_key: response.public_key,
+        });
+    }
+
+    Ok(UserKeyRotationData {
+        ciphers,
+        folders,
+        sends,
+        emergency_access,
+        reset_password_public_keys,
+    })
+}
+
+/// Build the key rotation request, re-encrypting `data` with a newly generated user key.
+///
+/// Passkeys used for login with encryption are left untouched, and will have to be enrolled again
+/// to unlock the vault after the rotation.
+pub(crate) fn make_update_key_request(
+    client: &Client,
+    password: String,
+    data: UserKeyRotationData,
+) -> Result<UpdateKeyRequestModel, KeyRotationError> {
+    const NEW_USER_KEY: SymmetricKeyId = SymmetricKeyId::Local("new_user_key");
+
+    let key_store = client.internal.get_key_store();
+    let mut ctx = key_store.context();
+
+    let request = UserKeyRotationRequest {
+        password,
+        emergency_access_public_keys: data
+            .emergency_access
+            .iter()
+            .map(|g| (g.id, g.public_key.clone()))
+            .collect(),
+        reset_password_public_keys: data.reset_password_public_keys,
+    };
+    let mut response = make_user_key_rotation(client, &mut ctx, NEW_USER_KEY, &request)?;
+
+    let ciphers = data
+        .ciphers
+        .into_iter()
+        // Organization ciphers are protected by the organization key
+        .filter(|c| c.organization_id.is_none())
+        .map(|c| Ok(rotate_cipher(&mut ctx, c, NEW_USER_KEY)?.try_into()?))
+        .collect::<Result<_, KeyRotationError>>()?;
+
+    let folders = data
+        .folders
+        .into_iter()
+        .map(|f| {
+            let folder: FolderView = f.decrypt(&mut ctx, SymmetricKeyId::User)?;
+            Ok(folder.encrypt(&mut ctx, NEW_USER_KEY)?.into())
+        })
+        .collect::<Result<_, KeyRotationError>>()?;
+
+    // The contents of a send are protected by its own key, so only that key is re-encrypted
+    let sends = data
+        .sends
+        .into_iter()
+        .map(|mut send| {
+            let send_key: Vec<u8> = send.key.decrypt(&mut ctx, SymmetricKeyId::User)?;
+            send.key = send_key.encrypt(&mut ctx, NEW_USER_KEY)?;
+            Ok(send.try_into()?)
+        })
+        .collect::<Result<_, KeyRotationError>>()?;
+
+    let emergency_access_keys = data
+        .emergency_access
+        .into_iter()
+        .map(|g| EmergencyAccessWithIdRequestModel {
+            r#type: g.r#type,
+            wait_time_days: g.wait_time_days,
+            key_encrypted: response
+                .emergency_access_keys
+                .remove(&g.id)
+                .map(|k| k.to_string()),
+            id: g.id,
+        })
+        .collect();
+
+    let reset_password_keys = response
+        .reset_password_keys
+        .into_iter()
+        .map(
+            |(organization_id, key)| ResetPasswordWithOrgIdRequestModel {
+                reset_password_key: Some(key.to_string()),
+                master_password_hash: Some(response.master_password_hash.clone()),
+                organization_id,
+            },
+        )
+        .collect();
+
+    Ok(UpdateKeyRequestModel {
+        master_password_hash: response.master_password_hash,
+        key: response.user_key.to_string(),
+        private_key: response.private_key.to_string(),
+        ciphers: Some(ciphers),
+        folders: Some(folders),
+        sends: Some(sends),
+        emergency_access_keys: Some(emergency_access_keys),
+        reset_password_keys: Some(reset_password_keys),
+        web_authn_keys: None,
+    })
+}
+
+/// Re-encrypt a personal cipher with `new_user_key`. Ciphers with their own key only need that
+/// key to be re-encrypted, older ciphers have all their fields re-encrypted.
+fn rotate_cipher(
+    ctx: &mut KeyStoreContext<KeyIds>,
+    mut cipher: Cipher,
+    new_user_key: SymmetricKeyId,
+) -> Result<Cipher, CipherError> {
+    let old_user_key = SymmetricKeyId::User;
+
+    if let Some(cipher_key) = &mut cipher.key {
+        let dec_cipher_key = ctx.decrypt_symmetric_key_with_symmetric_key(
+            old_user_key,
+            SymmetricKeyId::Local("cipher_key"),
+            cipher_key,
+        )?;
+        *cipher_key = ctx.encrypt_symmetric_key_with_symmetric_key(new_user_key, dec_cipher_key)?;
+        return Ok(cipher);
+    }
+
+    let mut view: CipherView = cipher.decrypt(ctx, old_user_key)?;
+
+    // Attachments without keys are encrypted directly with the user key
+    if view.attachments.iter().flatten().any(|a| a.key.is_none()) {
+        return Err(CipherError::AttachmentsWithoutKeys);
+    }
+    view.reencrypt_attachment_keys(ctx, old_user_key, new_user_key)?;
+    view.reencrypt_fido2_credentials(ctx, old_user_key, new_user_key)?;
+
+    Ok(view.encrypt(ctx, new_user_key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
+    use bitwarden_crypto::{EncString, Kdf, KeyDecryptable, MasterKey};
+    use bitwarden_send::{SendTextView, SendType, SendView};
+    use chrono::Utc;
+
+    use super::*;
+    use crate::test_utils::cipher_view;
+
+    fn existing_cipher_view() -> CipherView {
+        CipherView {
+            id: Some(Uuid::new_v4()),
+            ..cipher_view()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_make_update_key_request() {
+        let client = Client::init_test_account(test_bitwarden_com_account()).await;
+        let key_store = client.internal.get_key_store();
+
+        let mut keyed_cipher = existing_cipher_view();
+        let mut ctx = key_store.context();
+        keyed_cipher
+            .generate_cipher_key(&mut ctx, SymmetricKeyId::User)
+            .unwrap();
+
+        let send_view = SendView {
+            id: None,
+            access_id: None,
+            name: "Test send".to_owned(),
+            notes: None,
+            key: None,
+            new_password: None,
+            has_password: false,
+            r#type: SendType::Text,
+            file: None,
+            text: Some(SendTextView {
+                text: Some("Hello, world!".to_owned()),
+                hidden: false,
+            }),
+            max_access_count: None,
+            access_count: 0,
+            disabled: false,
+            hide_email: false,
+            revision_date: Utc::now(),
+            deletion_date: Utc::now(),
+            expiration_date: None,
+        };
+        let send = Send {
+            id: Some(Uuid::new_v4()),
+            ..send_view.encrypt(&mut ctx, SymmetricKeyId::User).unwrap()
+        };
+        let send_key: Vec<u8> = send.key.decrypt(&mut ctx, SymmetricKeyId::User).unwrap();
+        drop(ctx);
+
+        let data = UserKeyRotationData {
+            ciphers: vec![
+                key_store.encrypt(existing_cipher_view()).unwrap(),
+                key_store.encrypt(keyed_cipher).unwrap(),
+            ],
+            folders: vec![key_store
+                .encrypt(FolderView {
+                    id: Some(Uuid::new_v4()),
+                    name: "Test folder".to_owned(),
+                    revision_date: Utc::now(),
+                })
+                .unwrap()],
+            sends: vec![send],
+            emergency_access: vec![],
+            reset_password_public_keys: HashMap::new(),
+        };
+
+        let request = make_update_key_request(&client, "asdfasdfasdf".to_owned(), data).unwrap();
+
+        let master_key = MasterKey::derive(
+            "asdfasdfasdf",
+            "test@bitwarden.com",
+            &Kdf::PBKDF2 {
+                iterations: 600_000.try_into().unwrap(),
+            },
+        )
+        .unwrap();
+        let new_user_key = master_key
+            .decrypt_user_key(request.key.parse().unwrap())
+            .unwrap();
+
+        let ciphers = request.ciphers.unwrap();
+        assert_eq!(ciphers.len(), 2);
+        // The cipher without a key has its fields encrypted with the new user key
+        let name: EncString = ciphers[0].name.parse().unwrap();
+        let name: String = name.decrypt_with_key(&new_user_key).unwrap();
+        assert_eq!(name, "Test login");
+        // The cipher with a key only has its key re-encrypted
+        let cipher_key: EncString = ciphers[1].key.as_ref().unwrap().parse().unwrap();
+        let cipher_key: Vec<u8> = cipher_key.decrypt_with_key(&new_user_key).unwrap();
+        assert_eq!(cipher_key.len(), 64);
+
+        let folders = request.folders.unwrap();
+        let name: EncString = folders[0].name.parse().unwrap();
+        let name: String = name.decrypt_with_key(&new_user_key).unwrap();
+        assert_eq!(name, "Test folder");
+
+        let sends = request.sends.unwrap();
+        let key: EncString = sends[0].key.parse().unwrap();
+        let key: Vec<u8> = key.decrypt_with_key(&new_user_key).unwrap();
+        assert_eq!(key, send_key);
+    }
+}
@@ -3,6 +3,8 @@ uniffi::setup_scaffolding!();
 #[cfg(feature = "uniffi")]
 mod uniffi_support;
 
+mod api;
+pub use api::VaultApiError;
 mod cipher;
 pub use cipher::*;
 mod collection;
@@ -19,10 +21,23 @@ pub use totp::{
 };
 mod error;
 pub use error::{DecryptError, EncryptError, VaultParseError};
+mod key_rotation;
+pub use key_rotation::KeyRotationError;
 mod vault_client;
 pub use vault_client::{VaultClient, VaultClientExt};
 mod mobile;
 pub use mobile::attachment_client::{DecryptFileError, EncryptFileError};
 mod sync;
 mod totp_client;
+mod two_factor;
+pub use two_factor::{
+    AuthenticatorSetup, TwoFactorError, TwoFactorProviderView, WebAuthnAttestation,
+    WebAuthnKeyView, WebAuthnView, YubiKeyView,
+};
+mod two_factor_client;
 pub use sync::{SyncRequest, SyncResponse};
+pub use two_factor_client::TwoFactorClient;
+mod delta_sync;
+pub use delta_sync::{DeltaSyncResponse, ItemDiff, PreviousSync, SyncChanges};
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
@@ -1,7 +1,10 @@
-use std::path::Path;
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
 
 use bitwarden_core::Client;
-use bitwarden_crypto::{EncString, KeyDecryptable, KeyEncryptable, LocateKey};
+use bitwarden_crypto::{CryptoError, EncString};
 use bitwarden_error::bitwarden_error;
 use thiserror::Error;
 
@@ -24,6 +27,12 @@ pub enum EncryptFileError {
     Io(#[from] std::io::Error),
 }
 
+impl From<CryptoError> for EncryptFileError {
+    fn from(e: CryptoError) -> Self {
+        Self::Encrypt(e.into())
+    }
+}
+
 /// Generic error type for decryption errors
 #[bitwarden_error(flat)]
 #[derive(Debug, Error)]
@@ -34,6 +43,12 @@ pub enum DecryptFileError {
     Io(#[from] std::io::Error),
 }
 
+impl From<CryptoError> for DecryptFileError {
+    fn from(e: CryptoError) -> Self {
+        Self::Decrypt(e.into())
+    }
+}
+
 impl ClientAttachments<'_> {
     pub fn encrypt_buffer(
         &self,
@@ -41,16 +56,15 @@ impl ClientAttachments<'_> {
         attachment: AttachmentView,
         buffer: &[u8],
     ) -> Result<AttachmentEncryptResult, EncryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = cipher.locate_key(&enc, &None)?;
+        let key_store = self.client.internal.get_key_store();
 
-        Ok(AttachmentFileView {
+        Ok(key_store.encrypt(AttachmentFileView {
             cipher,
             attachment,
             contents: buffer,
-        }
-        .encrypt_with_key(key)?)
+        })?)
     }
+    /// Encrypts the attachment file, see [ClientAttachments::encrypt_stream] for the format used.
     pub fn encrypt_file(
         &self,
         cipher: Cipher,
@@ -58,13 +72,41 @@ impl ClientAttachments<'_> {
         decrypted_file_path: &Path,
         encrypted_file_path: &Path,
     ) -> Result<Attachment, EncryptFileError> {
-        let data = std::fs::read(decrypted_file_path)?;
-        let AttachmentEncryptResult {
-            attachment,
-            contents,
-        } = self.encrypt_buffer(cipher, attachment, &data)?;
-        std::fs::write(encrypted_file_path, contents)?;
-        Ok(attachment)
+        let mut reader = std::io::BufReader::new(std::fs::File::open(decrypted_file_path)?);
+        let writer = std::io::BufWriter::new(std::fs::File::create(encrypted_file_path)?);
+
+        let result = self.encrypt_stream(cipher, attachment, &mut reader, writer);
+        if result.is_err() {
+            // Don't leave a partially encrypted file behind
+            let _ = std::fs::remove_file(encrypted_file_path);
+        }
+        result
+    }
+
+    /// Encrypts the attachment contents read from `reader` into `writer`. When the
+    /// `enableStreamEncryption` flag is set, the chunked streaming format is used without holding
+    /// the whole file in memory. Otherwise the contents are encrypted as a single [EncString].
+    pub fn encrypt_stream(
+        &self,
+        cipher: Cipher,
+        attachment: AttachmentView,
+        reader: &mut impl Read,
+        mut writer: impl Write,
+    ) -> Result<Attachment, EncryptFileError> {
+        if !self.client.internal.get_flags().enable_stream_encryption {
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer)?;
+            let AttachmentEncryptResult {
+                attachment,
+                contents,
+            } = self.encrypt_buffer(cipher, attachment, &buffer)?;
+            writer.write_all(&contents)?;
+            writer.flush()?;
+            return Ok(attachment);
+        }
+
+        let key_store = self.client.internal.get_key_store();
+        attachment.encrypt_stream(&mut key_store.context(), &cipher, reader, writer)
     }
 
     pub fn decrypt_buffer(
@@ -73,15 +115,13 @@ impl ClientAttachments<'_> {
         attachment: Attachment,
         encrypted_buffer: &[u8],
     ) -> Result<Vec<u8>, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = cipher.locate_key(&enc, &None)?;
+        let key_store = self.client.internal.get_key_store();
 
-        Ok(AttachmentFile {
+        Ok(key_store.decrypt(&AttachmentFile {
             cipher,
             attachment,
             contents: EncString::from_buffer(encrypted_buffer)?,
-        }
-        .decrypt_with_key(key)?)
+        })?)
     }
     pub fn decrypt_file(
         &self,
@@ -90,10 +130,32 @@ impl ClientAttachments<'_> {
         encrypted_file_path: &Path,
         decrypted_file_path: &Path,
     ) -> Result<(), DecryptFileError> {
-        let data = std::fs::read(encrypted_file_path)?;
-        let decrypted = self.decrypt_buffer(cipher, attachment, &data)?;
-        std::fs::write(decrypted_file_path, decrypted)?;
-        Ok(())
+        let reader = std::io::BufReader::new(std::fs::File::open(encrypted_file_path)?);
+        let mut writer = std::fs::File::create(decrypted_file_path)?;
+
+        let result = self.decrypt_stream(cipher, attachment, reader, &mut writer);
+        if result.is_err() {
+            // Don't leave partially decrypted contents behind
+            drop(writer);
+            let _ = std::fs::remove_file(decrypted_file_path);
+        }
+        result
+    }
+
+    /// Decrypts the attachment contents read from `reader` into `writer`, without holding the
+    /// whole file in memory. Both the chunked streaming format and the legacy format are
+    /// supported.
+    ///
+    /// If this fails, any data already written to `writer` must be discarded.
+    pub fn decrypt_stream(
+        &self,
+        cipher: Cipher,
+        attachment: Attachment,
+        reader: impl Read,
+        writer: &mut impl Write,
+    ) -> Result<(), DecryptFileError> {
+        let key_store = self.client.internal.get_key_store();
+        attachment.decrypt_stream(&mut key_store.context(), &cipher, reader, writer)
     }
 }
 
@@ -1,5 +1,5 @@
 use bitwarden_core::Client;
-use bitwarden_crypto::{KeyDecryptable, KeyEncryptable, LocateKey};
+use bitwarden_crypto::IdentifyKey;
 use uuid::Uuid;
 
 use crate::{
@@ -12,10 +12,10 @@ pub struct ClientCiphers<'a> {
 
 impl ClientCiphers<'_> {
     pub fn encrypt(&self, mut cipher_view: CipherView) -> Result<Cipher, EncryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
+        let key_store = self.client.internal.get_key_store();
 
         // TODO: Once this flag is removed, the key generation logic should
-        // be moved directly into the KeyEncryptable implementation
+        // be moved directly into the Encryptable implementation
         if cipher_view.key.is_none()
             && self
                 .client
@@ -23,46 +23,35 @@ impl ClientCiphers<'_> {
                 .get_flags()
                 .enable_cipher_key_encryption
         {
-            let key = cipher_view.locate_key(&enc, &None)?;
-            cipher_view.generate_cipher_key(key)?;
+            let key = cipher_view.key_identifier();
+            cipher_view.generate_cipher_key(&mut key_store.context(), key)?;
         }
 
-        let key = cipher_view.locate_key(&enc, &None)?;
-        let cipher = cipher_view.encrypt_with_key(key)?;
+        let cipher = key_store.encrypt(cipher_view)?;
 
         Ok(cipher)
     }
 
     pub fn decrypt(&self, cipher: Cipher) -> Result<CipherView, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = cipher.locate_key(&enc, &None)?;
-
-        let cipher_view = cipher.decrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let cipher_view = key_store.decrypt(&cipher)?;
 
         Ok(cipher_view)
     }
 
     pub fn decrypt_list(&self, ciphers: Vec<Cipher>) -> Result<Vec<CipherListView>, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-
-        let cipher_views: Result<Vec<CipherListView>, _> = ciphers
-            .iter()
-            .map(|c| -> Result<CipherListView, _> {
-                let key = c.locate_key(&enc, &None)?;
-                Ok(c.decrypt_with_key(key)?)
-            })
-            .collect();
+        let key_store = self.client.internal.get_key_store();
+        let cipher_views = key_store.decrypt_list(&ciphers)?;
 
-        cipher_views
+        Ok(cipher_views)
     }
 
     pub fn decrypt_fido2_credentials(
         &self,
         cipher_view: CipherView,
     ) -> Result<Vec<crate::Fido2CredentialView>, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-
-        let credentials = cipher_view.decrypt_fido2_credentials(&enc)?;
+        let key_store = self.client.internal.get_key_store();
+        let credentials = cipher_view.decrypt_fido2_credentials(&mut key_store.context())?;
 
         Ok(credentials)
     }
@@ -72,8 +61,8 @@ impl ClientCiphers<'_> {
         mut cipher_view: CipherView,
         organization_id: Uuid,
     ) -> Result<CipherView, CipherError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        cipher_view.move_to_organization(&enc, organization_id)?;
+        let key_store = self.client.internal.get_key_store();
+        cipher_view.move_to_organization(&mut key_store.context(), organization_id)?;
         Ok(cipher_view)
     }
 }
@@ -92,7 +81,9 @@ mod tests {
     use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
 
     use super::*;
-    use crate::{Attachment, CipherRepromptType, CipherType, Login, VaultClientExt};
+    use crate::{
+        Attachment, AttachmentView, CipherRepromptType, CipherType, Login, VaultClientExt,
+    };
 
     #[tokio::test]
     async fn test_decrypt_list() {
@@ -215,6 +206,30 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[tokio::test]
+    async fn test_encrypt_cipher_with_aead_encryption() {
+        let client = Client::init_test_account(test_bitwarden_com_account()).await;
+        client.internal.load_flags(std::collections::HashMap::from([
+            ("enableCipherKeyEncryption".to_owned(), true),
+            ("enableAeadEncryption".to_owned(), true),
+        ]));
+
+        let view = client.vault().ciphers().decrypt(test_cipher()).unwrap();
+        let new_cipher = client.vault().ciphers().encrypt(view).unwrap();
+
+        assert!(matches!(
+            new_cipher.name,
+            bitwarden_crypto::EncString::Aes256Gcm_B64 { .. }
+        ));
+        assert!(matches!(
+            new_cipher.key,
+            Some(bitwarden_crypto::EncString::Aes256Gcm_B64 { .. })
+        ));
+
+        let view = client.vault().ciphers().decrypt(new_cipher).unwrap();
+        assert_eq!(view.name, "234234");
+    }
+
     #[tokio::test]
     async fn test_encrypt_cipher_with_legacy_attachment_without_key() {
         let client = Client::init_test_account(test_bitwarden_com_account()).await;
@@ -331,4 +346,70 @@ mod tests {
 
         assert_eq!(content, b"Hello");
     }
+
+    #[tokio::test]
+    async fn test_encrypt_attachment_stream_format_behind_flag() {
+        let client = Client::init_test_account(test_bitwarden_com_account()).await;
+        let cipher = test_cipher();
+        let attachment = AttachmentView {
+            id: None,
+            url: None,
+            size: None,
+            size_name: None,
+            file_name: Some("h.txt".to_string()),
+            key: None,
+        };
+
+        // The legacy single EncString format is used by default
+        let mut encrypted = Vec::new();
+        let encrypted_attachment = client
+            .vault()
+            .attachments()
+            .encrypt_stream(
+                cipher.clone(),
+                attachment.clone(),
+                &mut b"Hello".as_slice(),
+                &mut encrypted,
+            )
+            .unwrap();
+        assert_ne!(encrypted[0], bitwarden_crypto::STREAM_ENC_TYPE);
+        let content = client
+            .vault()
+            .attachments()
+            .decrypt_buffer(cipher.clone(), encrypted_attachment, &encrypted)
+            .unwrap();
+        assert_eq!(content, b"Hello");
+
+        client
+            .internal
+            .load_flags(std::collections::HashMap::from([(
+                "enableStreamEncryption".to_owned(),
+                true,
+            )]));
+
+        let mut encrypted = Vec::new();
+        let encrypted_attachment = client
+            .vault()
+            .attachments()
+            .encrypt_stream(
+                cipher.clone(),
+                attachment,
+                &mut b"Hello".as_slice(),
+                &mut encrypted,
+            )
+            .unwrap();
+        assert_eq!(encrypted[0], bitwarden_crypto::STREAM_ENC_TYPE);
+        let mut content = Vec::new();
+        client
+            .vault()
+            .attachments()
+            .decrypt_stream(
+                cipher,
+                encrypted_attachment,
+                encrypted.as_slice(),
+                &mut content,
+            )
+            .unwrap();
+        assert_eq!(content, b"Hello");
+    }
 }
@@ -1,18 +1,25 @@
 use bitwarden_core::Client;
-use bitwarden_crypto::{KeyDecryptable, LocateKey};
 
-use crate::{error::DecryptError, Collection, CollectionView, VaultClient};
+use crate::{
+    error::{DecryptError, EncryptError},
+    Collection, CollectionView, VaultClient,
+};
 
 pub struct ClientCollections<'a> {
     pub(crate) client: &'a Client,
 }
 
 impl ClientCollections<'_> {
-    pub fn decrypt(&self, collection: Collection) -> Result<CollectionView, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = collection.locate_key(&enc, &None)?;
+    pub fn encrypt(&self, collection_view: CollectionView) -> Result<Collection, EncryptError> {
+        let key_store = self.client.internal.get_key_store();
+        let collection = key_store.encrypt(collection_view)?;
 
-        let view = collection.decrypt_with_key(key)?;
+        Ok(collection)
+    }
+
+    pub fn decrypt(&self, collection: Collection) -> Result<CollectionView, DecryptError> {
+        let key_store = self.client.internal.get_key_store();
+        let view = key_store.decrypt(&collection)?;
 
         Ok(view)
     }
@@ -21,17 +28,10 @@ impl ClientCollections<'_> {
         &self,
         collections: Vec<Collection>,
     ) -> Result<Vec<CollectionView>, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-
-        let views: Result<Vec<CollectionView>, _> = collections
-            .iter()
-            .map(|c| -> Result<CollectionView, _> {
-                let key = c.locate_key(&enc, &None)?;
-                Ok(c.decrypt_with_key(key)?)
-            })
-            .collect();
+        let key_store = self.client.internal.get_key_store();
+        let views = key_store.decrypt_list(&collections)?;
 
-        views
+        Ok(views)
     }
 }
 
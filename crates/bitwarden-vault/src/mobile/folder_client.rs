@@ -1,5 +1,4 @@
 use bitwarden_core::Client;
-use bitwarden_crypto::{KeyDecryptable, KeyEncryptable};
 
 use crate::{
     error::{DecryptError, EncryptError},
@@ -12,28 +11,22 @@ pub struct ClientFolders<'a> {
 
 impl ClientFolders<'_> {
     pub fn encrypt(&self, folder_view: FolderView) -> Result<Folder, EncryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let folder = folder_view.encrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let folder = key_store.encrypt(folder_view)?;
 
         Ok(folder)
     }
 
     pub fn decrypt(&self, folder: Folder) -> Result<FolderView, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let folder_view = folder.decrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let folder_view = key_store.decrypt(&folder)?;
 
         Ok(folder_view)
     }
 
     pub fn decrypt_list(&self, folders: Vec<Folder>) -> Result<Vec<FolderView>, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let views = folders.decrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let views = key_store.decrypt_list(&folders)?;
 
         Ok(views)
     }
@@ -1,5 +1,4 @@
 use bitwarden_core::Client;
-use bitwarden_crypto::{KeyDecryptable, KeyEncryptable};
 
 use crate::{DecryptError, EncryptError, PasswordHistory, PasswordHistoryView, VaultClient};
 
@@ -12,10 +11,8 @@ impl ClientPasswordHistory<'_> {
         &self,
         history_view: PasswordHistoryView,
     ) -> Result<PasswordHistory, EncryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let history = history_view.encrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let history = key_store.encrypt(history_view)?;
 
         Ok(history)
     }
@@ -24,10 +21,8 @@ impl ClientPasswordHistory<'_> {
         &self,
         history: Vec<PasswordHistory>,
     ) -> Result<Vec<PasswordHistoryView>, DecryptError> {
-        let enc = self.client.internal.get_encryption_settings()?;
-        let key = enc.get_key(&None)?;
-
-        let history_view = history.decrypt_with_key(key)?;
+        let key_store = self.client.internal.get_key_store();
+        let history_view = key_store.decrypt_list(&history)?;
 
         Ok(history_view)
     }
@@ -2,9 +2,12 @@ use bitwarden_api_api::models::{
     DomainsResponseModel, ProfileOrganizationResponseModel, ProfileResponseModel, SyncResponseModel,
 };
 use bitwarden_core::{
-    client::encryption_settings::{EncryptionSettings, EncryptionSettingsError},
-    require, Client, MissingFieldError,
+    admin_console::Policy, client::encryption_settings::EncryptionSettingsError, require, Client,
+    MissingFieldError,
 };
+use bitwarden_crypto::{CryptoError, EncString};
+use bitwarden_send::{Send, SendParseError};
+use rayon::prelude::*;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -22,6 +25,12 @@ pub enum SyncError {
     VaultParse(#[from] VaultParseError),
     #[error(transparent)]
     EncryptionSettings(#[from] EncryptionSettingsError),
+    #[error(transparent)]
+    SendParse(#[from] SendParseError),
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+    #[error("Invalid account revision date: {0}")]
+    InvalidRevisionDate(i64),
 }
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -45,9 +54,9 @@ pub(crate) async fn sync(client: &Client, input: &SyncRequest) -> Result<SyncRes
         .filter_map(|o| o.id.zip(o.key.as_deref().and_then(|k| k.parse().ok())))
         .collect();
 
-    let enc = client.internal.initialize_org_crypto(org_keys)?;
+    client.internal.initialize_org_crypto(org_keys)?;
 
-    SyncResponse::process_response(sync, &enc)
+    SyncResponse::process_response(sync)
 }
 
 #[derive(Serialize, Deserialize, Debug, JsonSchema)]
@@ -57,8 +66,10 @@ pub struct ProfileResponse {
     pub name: String,
     pub email: String,
 
-    //key: String,
-    //private_key: String,
+    /// User key, protected by the master key. Not present for accounts without a master password
+    pub key: Option<EncString>,
+    /// Private key, protected by the user key
+    pub private_key: Option<EncString>,
     pub organizations: Vec<ProfileOrganizationResponse>,
 }
 
@@ -86,15 +97,13 @@ pub struct SyncResponse {
     /// List of ciphers accessible by the user
     pub ciphers: Vec<Cipher>,
     pub domains: Option<DomainResponse>,
-    //pub policies: Vec<Policy>,
-    //pub sends: Vec<Send>,
+    /// Policies of the organizations the user is a member of
+    pub policies: Vec<Policy>,
+    pub sends: Vec<Send>,
 }
 
 impl SyncResponse {
-    pub(crate) fn process_response(
-        response: SyncResponseModel,
-        enc: &EncryptionSettings,
-    ) -> Result<SyncResponse, SyncError> {
+    pub(crate) fn process_response(response: SyncResponseModel) -> Result<SyncResponse, SyncError> {
         let profile = require!(response.profile);
         let ciphers = require!(response.ciphers);
 
@@ -108,13 +117,18 @@ impl SyncResponse {
         }
 
         Ok(SyncResponse {
-            profile: ProfileResponse::process_response(*profile, enc)?,
+            profile: ProfileResponse::process_response(*profile)?,
             folders: try_into_iter(require!(response.folders))?,
             collections: try_into_iter(require!(response.collections))?,
-            ciphers: try_into_iter(ciphers)?,
+            // Parsing every encrypted field of a large vault is noticeable, so spread it over the
+            // available cores like the decryption in `KeyStore::decrypt_list`
+            ciphers: ciphers
+                .into_par_iter()
+                .map(|c| c.try_into())
+                .collect::<Result<_, _>>()?,
             domains: response.domains.map(|d| (*d).try_into()).transpose()?,
-            //policies: try_into_iter(require!(response.policies))?,
-            //sends: try_into_iter(require!(response.sends))?,
+            policies: try_into_iter(response.policies.unwrap_or_default())?,
+            sends: try_into_iter(response.sends.unwrap_or_default())?,
         })
     }
 }
@@ -130,16 +144,13 @@ impl ProfileOrganizationResponse {
 }
 
 impl ProfileResponse {
-    fn process_response(
-        response: ProfileResponseModel,
-        _enc: &EncryptionSettings,
-    ) -> Result<ProfileResponse, MissingFieldError> {
+    fn process_response(response: ProfileResponseModel) -> Result<ProfileResponse, SyncError> {
         Ok(ProfileResponse {
             id: require!(response.id),
             name: require!(response.name),
             email: require!(response.email),
-            //key: response.key,
-            //private_key: response.private_key,
+            key: response.key.map(|k| k.parse()).transpose()?,
+            private_key: response.private_key.map(|k| k.parse()).transpose()?,
             organizations: response
                 .organizations
                 .unwrap_or_default()
@@ -165,3 +176,114 @@ impl TryFrom<DomainsResponseModel> for DomainResponse {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_core::{
+        admin_console::PolicyType, client::test_accounts::test_bitwarden_com_account,
+    };
+    use serde_json::json;
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::{api::start_mock, VaultClientExt};
+
+    const USER_KEY: &str = "2.Q/2PhzcC7GdeiMHhWguYAQ==|GpqzVdr0go0ug5cZh1n+uixeBC3oC90CIe0hd/HWA/pTRDZ8ane4fmsEIcuc8eMKUt55Y2q/fbNzsYu41YTZzzsJUSeqVjT8/iTQtgnNdpo=|dwI+uyvZ1h/iZ03VQ+/wrGEFYVewBUUl/syYgjsNMbE=";
+
+    #[tokio::test]
+    async fn test_sync_policies_and_sends() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("GET"))
+                .and(matchers::path("/api/sync"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "object": "sync",
+                    "profile": {
+                        "id": "b1fd4bf2-9643-4787-87f3-b0f00189c33b",
+                        "name": "Test",
+                        "email": "test@bitwarden.com",
+                        "key": USER_KEY,
+                        "organizations": [],
+                    },
+                    "folders": [],
+                    "collections": [],
+                    "ciphers": [],
+                    "policies": [{
+                        "id": "0d1a4fd7-9d2d-4a4b-9f0a-b18100970a01",
+                        "organizationId": "1bc9ac1e-f5aa-45f2-94bf-b181009709b8",
+                        "type": 1,
+                        "data": { "minLength": 12 },
+                        "enabled": true,
+                    }],
+                    "sends": [{
+                        "id": "3d80dd72-2d14-4f26-812c-b0f0018aa144",
+                        "accessId": "ct2APV2EFS6cEbDwAYqhRA",
+                        "type": 0,
+                        "name": USER_KEY,
+                        "key": USER_KEY,
+                        "accessCount": 0,
+                        "disabled": false,
+                        "hideEmail": false,
+                        "revisionDate": "2024-01-07T23:56:48.207363Z",
+                        "deletionDate": "2024-01-14T23:56:48Z",
+                    }],
+                })))],
+        )
+        .await;
+
+        let sync = client
+            .vault()
+            .sync(&SyncRequest {
+                exclude_subdomains: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(sync.profile.key, Some(USER_KEY.parse().unwrap()));
+        assert_eq!(sync.profile.private_key, None);
+
+        assert_eq!(sync.policies.len(), 1);
+        assert_eq!(sync.policies[0].r#type, PolicyType::MasterPassword);
+        assert!(sync.policies[0].enabled);
+
+        assert_eq!(sync.sends.len(), 1);
+        assert_eq!(
+            sync.sends[0].access_id.as_deref(),
+            Some("ct2APV2EFS6cEbDwAYqhRA")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_without_policies_and_sends() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("GET"))
+                .and(matchers::path("/api/sync"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "object": "sync",
+                    "profile": {
+                        "id": "b1fd4bf2-9643-4787-87f3-b0f00189c33b",
+                        "name": "Test",
+                        "email": "test@bitwarden.com",
+                        "key": USER_KEY,
+                        "organizations": [],
+                    },
+                    "folders": [],
+                    "collections": [],
+                    "ciphers": [],
+                })))],
+        )
+        .await;
+
+        let sync = client
+            .vault()
+            .sync(&SyncRequest {
+                exclude_subdomains: None,
+            })
+            .await
+            .unwrap();
+
+        assert!(sync.policies.is_empty());
+        assert!(sync.sends.is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+//! Fixtures shared by the unit tests and benchmarks of this crate.
+use chrono::Utc;
+
+use crate::{CipherRepromptType, CipherType, CipherView, LoginView};
+
+/// A personal login without an id, as it would be before being created on the server.
+pub fn cipher_view() -> CipherView {
+    CipherView {
+        id: None,
+        organization_id: None,
+        folder_id: None,
+        collection_ids: vec![],
+        key: None,
+        name: "Test login".to_owned(),
+        notes: None,
+        r#type: CipherType::Login,
+        login: Some(LoginView {
+            username: Some("test@bitwarden.com".to_owned()),
+            password: Some("password".to_owned()),
+            password_revision_date: None,
+            uris: None,
+            totp: None,
+            autofill_on_page_load: None,
+            fido2_credentials: None,
+        }),
+        identity: None,
+        card: None,
+        secure_note: None,
+        ssh_key: None,
+        favorite: false,
+        reprompt: CipherRepromptType::None,
+        organization_use_totp: true,
+        edit: true,
+        view_password: true,
+        local_data: None,
+        attachments: None,
+        fields: None,
+        password_history: None,
+        creation_date: Utc::now(),
+        deleted_date: None,
+        revision_date: Utc::now(),
+    }
+}
@@ -0,0 +1,660 @@
+//! Set up and manage the two-step login providers of the logged in user.
+//!
+//! Every change needs the user to verify themselves again with a [SecretVerificationRequest].
+use bitwarden_api_api::{
+    apis::two_factor_api,
+    models::{
+        AuthenticatorAttestationRawResponse, CredentialCreateOptions, PublicKeyCredentialType,
+        ResponseData, SecretVerificationRequestModel, TwoFactorEmailRequestModel,
+        TwoFactorProviderRequestModel, TwoFactorProviderType, TwoFactorRecoveryRequestModel,
+        TwoFactorWebAuthnDeleteRequestModel, TwoFactorWebAuthnRequestModel,
+        TwoFactorWebAuthnResponseModel, TwoFactorYubiKeyResponseModel,
+        UpdateTwoFactorAuthenticatorRequestModel, UpdateTwoFactorEmailRequestModel,
+        UpdateTwoFactorYubicoOtpRequestModel,
+    },
+};
+use bitwarden_core::{
+    auth::login::TwoFactorProvider, platform::SecretVerificationRequest, require, ApiError, Client,
+    MissingFieldError,
+};
+use bitwarden_crypto::{CryptoError, HashPurpose, MasterKey};
+use chrono::{Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{generate_totp, TotpError};
+
+/// The number of YubiKeys the server stores
+const MAX_YUBIKEYS: usize = 5;
+
+#[derive(Debug, Error)]
+pub enum TwoFactorError {
+    #[error(transparent)]
+    Core(#[from] bitwarden_core::Error),
+    #[error(transparent)]
+    Api(#[from] ApiError),
+    #[error(transparent)]
+    MissingField(#[from] MissingFieldError),
+    #[error(transparent)]
+    Crypto(#[from] CryptoError),
+    #[error(transparent)]
+    Totp(#[from] TotpError),
+
+    #[error("The verification code is invalid")]
+    InvalidCode,
+    #[error("At most {MAX_YUBIKEYS} YubiKeys can be registered")]
+    TooManyYubiKeys,
+    #[error("{0:?} can't be managed as a two-step login provider")]
+    UnsupportedProvider(TwoFactorProvider),
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TwoFactorProviderView {
+    pub provider: TwoFactorProvider,
+    pub enabled: bool,
+}
+
+/// A new authenticator secret, to be added to the user's authenticator app before it's enabled.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AuthenticatorSetup {
+    /// Base32 encoded secret
+    pub key: String,
+    /// `otpauth://` URI of the secret, usually shown as a QR code
+    pub uri: String,
+    /// Proves to the server that the secret was issued to the verified user
+    pub user_verification_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct YubiKeyView {
+    pub enabled: bool,
+    /// The public ids of the registered YubiKeys
+    pub keys: Vec<String>,
+    /// Whether any of the YubiKeys supports NFC
+    pub nfc: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebAuthnView {
+    pub enabled: bool,
+    pub keys: Vec<WebAuthnKeyView>,
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebAuthnKeyView {
+    /// The slot of the key, used to replace or delete it
+    pub id: i32,
+    pub name: Option<String>,
+}
+
+/// The credential a WebAuthn authenticator created for the challenge from
+/// [TwoFactorClient::get_webauthn_challenge](crate::TwoFactorClient::get_webauthn_challenge).
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebAuthnAttestation {
+    pub id: Vec<u8>,
+    pub raw_id: Vec<u8>,
+    pub attestation_object: Vec<u8>,
+    pub client_data_json: Vec<u8>,
+}
+
+pub(crate) async fn list_providers(
+    client: &Client,
+) -> Result<Vec<TwoFactorProviderView>, TwoFactorError> {
+    let config = client.internal.get_api_configurations().await;
+    let response = two_factor_api::two_factor_get(&config.api)
+        .await
+        .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    response
+        .data
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| {
+            Ok(TwoFactorProviderView {
+                provider: provider_from_api(require!(p.r#type)),
+                enabled: p.enabled.unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// Turn off a provider. Turning off the last one turns off two-step login.
+pub(crate) async fn disable_provider(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    provider: TwoFactorProvider,
+) -> Result<(), TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    two_factor_api::two_factor_disable_put(
+        &config.api,
+        Some(TwoFactorProviderRequestModel {
+            master_password_hash: verification.master_password_hash,
+            otp: verification.otp,
+            auth_request_access_code: None,
+            secret: None,
+            r#type: provider_to_api(provider)?,
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(())
+}
+
+/// Get a new authenticator secret from the server, labelled with `account_name` in the
+/// authenticator app.
+pub(crate) async fn setup_authenticator(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    account_name: &str,
+) -> Result<AuthenticatorSetup, TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response =
+        two_factor_api::two_factor_get_authenticator_post(&config.api, Some(verification))
+            .await
+            .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    let key = require!(response.key);
+    let mut uri = reqwest::Url::parse("otpauth://totp/").expect("otpauth URI is valid");
+    uri.path_segments_mut()
+        .expect("otpauth URI has a path")
+        .pop()
+        .push(&format!("Bitwarden:{account_name}"));
+    uri.query_pairs_mut()
+        .append_pair("secret", &key)
+        .append_pair("issuer", "Bitwarden");
+
+    Ok(AuthenticatorSetup {
+        key,
+        uri: uri.to_string(),
+        user_verification_token: response.user_verification_token,
+    })
+}
+
+/// Enable the authenticator once the user entered the first code from it. The code is checked
+/// locally first, allowing one period of clock drift, so typos don't need a round trip.
+pub(crate) async fn enable_authenticator(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    setup: &AuthenticatorSetup,
+    code: &str,
+) -> Result<(), TwoFactorError> {
+    let now = Utc::now();
+    let mut valid = false;
+    for drift in [-1, 0, 1] {
+        let totp = generate_totp(setup.key.clone(), Some(now + Duration::seconds(30 * drift)))?;
+        valid |= totp.code == code;
+    }
+    if !valid {
+        return Err(TwoFactorError::InvalidCode);
+    }
+
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    two_factor_api::two_factor_authenticator_put(
+        &config.api,
+        Some(UpdateTwoFactorAuthenticatorRequestModel {
+            master_password_hash: verification.master_password_hash,
+            otp: verification.otp,
+            auth_request_access_code: None,
+            secret: None,
+            token: code.to_owned(),
+            key: setup.key.clone(),
+            user_verification_token: setup.user_verification_token.clone(),
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(())
+}
+
+/// Send a code to `email`, to be confirmed with [enable_email].
+pub(crate) async fn send_email_setup_code(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    email: &str,
+) -> Result<(), TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    two_factor_api::two_factor_send_email_post(
+        &config.api,
+        Some(TwoFactorEmailRequestModel {
+            master_password_hash: verification.master_password_hash,
+            otp: verification.otp,
+            auth_request_access_code: None,
+            secret: None,
+            email: email.to_owned(),
+            auth_request_id: None,
+            sso_email2_fa_session_token: None,
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(())
+}
+
+pub(crate) async fn enable_email(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    email: &str,
+    code: &str,
+) -> Result<(), TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    two_factor_api::two_factor_email_put(
+        &config.api,
+        Some(UpdateTwoFactorEmailRequestModel {
+            master_password_hash: verification.master_password_hash,
+            otp: verification.otp,
+            auth_request_access_code: None,
+            secret: None,
+            email: email.to_owned(),
+            auth_request_id: None,
+            sso_email2_fa_session_token: None,
+            token: code.to_owned(),
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(())
+}
+
+pub(crate) async fn get_yubikey(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+) -> Result<YubiKeyView, TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response = two_factor_api::two_factor_get_yubikey_post(&config.api, Some(verification))
+        .await
+        .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(yubikey_view(response))
+}
+
+/// Replace the registered YubiKeys. `keys` are OTPs from each YubiKey, or the public ids of keys
+/// that are already registered.
+pub(crate) async fn update_yubikey(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    keys: Vec<String>,
+    nfc: bool,
+) -> Result<YubiKeyView, TwoFactorError> {
+    if keys.len() > MAX_YUBIKEYS {
+        return Err(TwoFactorError::TooManyYubiKeys);
+    }
+    let mut keys = keys.into_iter();
+
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response = two_factor_api::two_factor_yubikey_put(
+        &config.api,
+        Some(UpdateTwoFactorYubicoOtpRequestModel {
+            master_password_hash: verification.master_password_hash,
+            otp: verification.otp,
+            auth_request_access_code: None,
+            secret: None,
+            key1: keys.next(),
+            key2: keys.next(),
+            key3: keys.next(),
+            key4: keys.next(),
+            key5: keys.next(),
+            nfc,
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(yubikey_view(response))
+}
+
+pub(crate) async fn get_webauthn(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+) -> Result<WebAuthnView, TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response = two_factor_api::two_factor_get_webauthn_post(&config.api, Some(verification))
+        .await
+        .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    webauthn_view(response)
+}
+
+/// Get the options to create a new WebAuthn credential with, to be registered with
+/// [register_webauthn].
+pub(crate) async fn get_webauthn_challenge(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+) -> Result<CredentialCreateOptions, TwoFactorError> {
+    let verification: SecretVerificationRequestModel = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+
+    // The generated API doesn't include this endpoint
+    let mut request = config
+        .api
+        .client
+        .post(format!(
+            "{}/two-factor/get-webauthn-challenge",
+            config.api.base_path
+        ))
+        .json(&verification);
+    if let Some(token) = &config.api.oauth_access_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await.map_err(ApiError::from)?;
+    let status = response.status();
+    let content = response.text().await.map_err(ApiError::from)?;
+    if !status.is_success() {
+        return Err(ApiError::ResponseContent {
+            status,
+            message: content,
+        })?;
+    }
+
+    Ok(serde_json::from_str(&content).map_err(ApiError::from)?)
+}
+
+/// Register a WebAuthn credential in slot `id`, replacing any key already in it.
+pub(crate) async fn register_webauthn(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    id: i32,
+    name: Option<String>,
+    attestation: WebAuthnAttestation,
+) -> Result<WebAuthnView, TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response = two_factor_api::two_factor_webauthn_put(
+        &config.api,
+        Some(TwoFactorWebAuthnRequestModel {
+            master_password_hash: verification.master_password_hash,
+            otp: verification.otp,
+            auth_request_access_code: None,
+            secret: None,
+            id,
+            device_response: Box::new(AuthenticatorAttestationRawResponse {
+                id: Some(attestation.id),
+                raw_id: Some(attestation.raw_id),
+                r#type: Some(PublicKeyCredentialType::PublicKey),
+                response: Some(Box::new(ResponseData {
+                    attestation_object: Some(attestation.attestation_object),
+                    client_data_json: Some(attestation.client_data_json),
+                })),
+                extensions: None,
+            }),
+            name,
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    webauthn_view(response)
+}
+
+pub(crate) async fn delete_webauthn(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+    id: i32,
+) -> Result<WebAuthnView, TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response = two_factor_api::two_factor_webauthn_delete(
+        &config.api,
+        Some(TwoFactorWebAuthnDeleteRequestModel {
+            master_password_hash: verification.master_password_hash,
+            otp: verification.otp,
+            auth_request_access_code: None,
+            secret: None,
+            id,
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    webauthn_view(response)
+}
+
+pub(crate) async fn get_recovery_code(
+    client: &Client,
+    verification: &SecretVerificationRequest,
+) -> Result<String, TwoFactorError> {
+    let verification = verification.to_request_model(client)?;
+
+    let config = client.internal.get_api_configurations().await;
+    let response = two_factor_api::two_factor_get_recover_post(&config.api, Some(verification))
+        .await
+        .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(require!(response.code))
+}
+
+/// Turn off two-step login with the recovery code, for a user who lost access to their providers.
+/// The server issues a new recovery code afterwards. Doesn't need the user to be logged in.
+pub(crate) async fn recover(
+    client: &Client,
+    email: &str,
+    master_password: &str,
+    recovery_code: &str,
+) -> Result<(), TwoFactorError> {
+    let kdf = client.auth().prelogin(email.to_owned()).await?;
+    let master_key = MasterKey::derive(master_password, email, &kdf)?;
+    let master_password_hash = master_key
+        .derive_master_key_hash(master_password.as_bytes(), HashPurpose::ServerAuthorization)?;
+
+    let config = client.internal.get_api_configurations().await;
+    two_factor_api::two_factor_recover_post(
+        &config.api,
+        Some(TwoFactorRecoveryRequestModel {
+            master_password_hash: Some(master_password_hash),
+            otp: None,
+            auth_request_access_code: None,
+            secret: None,
+            email: email.to_owned(),
+            auth_request_id: None,
+            sso_email2_fa_session_token: None,
+            recovery_code: recovery_code.to_owned(),
+        }),
+    )
+    .await
+    .map_err(|e| TwoFactorError::Api(e.into()))?;
+
+    Ok(())
+}
+
+fn yubikey_view(response: TwoFactorYubiKeyResponseModel) -> YubiKeyView {
+    YubiKeyView {
+        enabled: response.enabled.unwrap_or(false),
+        keys: [
+            response.key1,
+            response.key2,
+            response.key3,
+            response.key4,
+            response.key5,
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        nfc: response.nfc.unwrap_or(false),
+    }
+}
+
+fn webauthn_view(response: TwoFactorWebAuthnResponseModel) -> Result<WebAuthnView, TwoFactorError> {
+    Ok(WebAuthnView {
+        enabled: response.enabled.unwrap_or(false),
+        keys: response
+            .keys
+            .unwrap_or_default()
+            .into_iter()
+            .map(|k| {
+                Ok(WebAuthnKeyView {
+                    id: require!(k.id),
+                    name: k.name,
+                })
+            })
+            .collect::<Result<_, TwoFactorError>>()?,
+    })
+}
+
+fn provider_from_api(provider: TwoFactorProviderType) -> TwoFactorProvider {
+    match provider {
+        TwoFactorProviderType::Authenticator => TwoFactorProvider::Authenticator,
+        TwoFactorProviderType::Email => TwoFactorProvider::Email,
+        TwoFactorProviderType::Duo => TwoFactorProvider::Duo,
+        TwoFactorProviderType::YubiKey => TwoFactorProvider::Yubikey,
+        TwoFactorProviderType::U2f => TwoFactorProvider::U2f,
+        TwoFactorProviderType::Remember => TwoFactorProvider::Remember,
+        TwoFactorProviderType::OrganizationDuo => TwoFactorProvider::OrganizationDuo,
+        TwoFactorProviderType::WebAuthn => TwoFactorProvider::WebAuthn,
+    }
+}
+
+fn provider_to_api(provider: TwoFactorProvider) -> Result<TwoFactorProviderType, TwoFactorError> {
+    Ok(match provider {
+        TwoFactorProvider::Authenticator => TwoFactorProviderType::Authenticator,
+        TwoFactorProvider::Email => TwoFactorProviderType::Email,
+        TwoFactorProvider::Duo => TwoFactorProviderType::Duo,
+        TwoFactorProvider::Yubikey => TwoFactorProviderType::YubiKey,
+        TwoFactorProvider::WebAuthn => TwoFactorProviderType::WebAuthn,
+        // Remember isn't a provider, and the others are managed by an organization or deprecated
+        TwoFactorProvider::U2f
+        | TwoFactorProvider::Remember
+        | TwoFactorProvider::OrganizationDuo => {
+            return Err(TwoFactorError::UnsupportedProvider(provider))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::api::start_mock;
+
+    const KEY: &str = "JBSWY3DPEHPK3PXP";
+
+    fn verification() -> SecretVerificationRequest {
+        SecretVerificationRequest {
+            master_password: Some("asdfasdfasdf".to_owned()),
+            otp: None,
+        }
+    }
+
+    fn setup() -> AuthenticatorSetup {
+        AuthenticatorSetup {
+            key: KEY.to_owned(),
+            uri: String::new(),
+            user_verification_token: Some("verification-token".to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_setup_authenticator() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("POST"))
+                .and(matchers::path("/api/two-factor/get-authenticator"))
+                .and(matchers::body_string_contains("masterPasswordHash"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "object": "twoFactorAuthenticator",
+                    "enabled": false,
+                    "key": KEY,
+                    "userVerificationToken": "verification-token",
+                })))],
+        )
+        .await;
+
+        let setup = setup_authenticator(&client, &verification(), "test@bitwarden.com")
+            .await
+            .unwrap();
+
+        assert_eq!(setup.key, KEY);
+        assert_eq!(
+            setup.uri,
+            "otpauth://totp/Bitwarden:test@bitwarden.com?secret=JBSWY3DPEHPK3PXP&issuer=Bitwarden"
+        );
+        // The URI is a valid TOTP key
+        assert_eq!(
+            generate_totp(setup.uri, None).unwrap().code,
+            generate_totp(KEY.to_owned(), None).unwrap().code
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enable_authenticator() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::method("PUT"))
+                .and(matchers::path("/api/two-factor/authenticator"))
+                .and(matchers::body_partial_json(serde_json::json!({
+                    "key": KEY,
+                    "userVerificationToken": "verification-token",
+                })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "object": "twoFactorAuthenticator",
+                    "enabled": true,
+                })))
+                .expect(1)],
+        )
+        .await;
+
+        let code = generate_totp(KEY.to_owned(), None).unwrap().code;
+        enable_authenticator(&client, &verification(), &setup(), &code)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_enable_authenticator_invalid_code() {
+        let (_server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![Mock::given(matchers::path("/api/two-factor/authenticator"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)],
+        )
+        .await;
+
+        let code = generate_totp(KEY.to_owned(), Some(Utc::now() - Duration::minutes(10)))
+            .unwrap()
+            .code;
+        let result = enable_authenticator(&client, &verification(), &setup(), &code).await;
+
+        assert!(matches!(result, Err(TwoFactorError::InvalidCode)));
+    }
+
+    #[tokio::test]
+    async fn test_update_yubikey_too_many_keys() {
+        let (_server, client) = start_mock(test_bitwarden_com_account(), vec![]).await;
+
+        let result =
+            update_yubikey(&client, &verification(), vec!["key".to_owned(); 6], false).await;
+
+        assert!(matches!(result, Err(TwoFactorError::TooManyYubiKeys)));
+    }
+}
@@ -0,0 +1,141 @@
+use bitwarden_api_api::models::CredentialCreateOptions;
+use bitwarden_core::{auth::login::TwoFactorProvider, platform::SecretVerificationRequest, Client};
+
+use crate::{
+    two_factor::{
+        delete_webauthn, disable_provider, enable_authenticator, enable_email, get_recovery_code,
+        get_webauthn, get_webauthn_challenge, get_yubikey, list_providers, recover,
+        register_webauthn, send_email_setup_code, setup_authenticator, update_yubikey,
+        AuthenticatorSetup, TwoFactorError, TwoFactorProviderView, WebAuthnAttestation,
+        WebAuthnView, YubiKeyView,
+    },
+    VaultClient,
+};
+
+pub struct TwoFactorClient<'a> {
+    pub(crate) client: &'a Client,
+}
+
+impl TwoFactorClient<'_> {
+    /// List the two-step login providers of the user and whether they're enabled.
+    pub async fn list(&self) -> Result<Vec<TwoFactorProviderView>, TwoFactorError> {
+        list_providers(self.client).await
+    }
+
+    pub async fn disable(
+        &self,
+        verification: &SecretVerificationRequest,
+        provider: TwoFactorProvider,
+    ) -> Result<(), TwoFactorError> {
+        disable_provider(self.client, verification, provider).await
+    }
+
+    /// Generate the secret and otpauth URI to set up an authenticator app with.
+    pub async fn setup_authenticator(
+        &self,
+        verification: &SecretVerificationRequest,
+        account_name: &str,
+    ) -> Result<AuthenticatorSetup, TwoFactorError> {
+        setup_authenticator(self.client, verification, account_name).await
+    }
+
+    /// Enable the authenticator app with the first code it generated.
+    pub async fn enable_authenticator(
+        &self,
+        verification: &SecretVerificationRequest,
+        setup: &AuthenticatorSetup,
+        code: &str,
+    ) -> Result<(), TwoFactorError> {
+        enable_authenticator(self.client, verification, setup, code).await
+    }
+
+    pub async fn send_email_setup_code(
+        &self,
+        verification: &SecretVerificationRequest,
+        email: &str,
+    ) -> Result<(), TwoFactorError> {
+        send_email_setup_code(self.client, verification, email).await
+    }
+
+    pub async fn enable_email(
+        &self,
+        verification: &SecretVerificationRequest,
+        email: &str,
+        code: &str,
+    ) -> Result<(), TwoFactorError> {
+        enable_email(self.client, verification, email, code).await
+    }
+
+    pub async fn get_yubikey(
+        &self,
+        verification: &SecretVerificationRequest,
+    ) -> Result<YubiKeyView, TwoFactorError> {
+        get_yubikey(self.client, verification).await
+    }
+
+    pub async fn update_yubikey(
+        &self,
+        verification: &SecretVerificationRequest,
+        keys: Vec<String>,
+        nfc: bool,
+    ) -> Result<YubiKeyView, TwoFactorError> {
+        update_yubikey(self.client, verification, keys, nfc).await
+    }
+
+    pub async fn get_webauthn(
+        &self,
+        verification: &SecretVerificationRequest,
+    ) -> Result<WebAuthnView, TwoFactorError> {
+        get_webauthn(self.client, verification).await
+    }
+
+    pub async fn get_webauthn_challenge(
+        &self,
+        verification: &SecretVerificationRequest,
+    ) -> Result<CredentialCreateOptions, TwoFactorError> {
+        get_webauthn_challenge(self.client, verification).await
+    }
+
+    pub async fn register_webauthn(
+        &self,
+        verification: &SecretVerificationRequest,
+        id: i32,
+        name: Option<String>,
+        attestation: WebAuthnAttestation,
+    ) -> Result<WebAuthnView, TwoFactorError> {
+        register_webauthn(self.client, verification, id, name, attestation).await
+    }
+
+    pub async fn delete_webauthn(
+        &self,
+        verification: &SecretVerificationRequest,
+        id: i32,
+    ) -> Result<WebAuthnView, TwoFactorError> {
+        delete_webauthn(self.client, verification, id).await
+    }
+
+    pub async fn get_recovery_code(
+        &self,
+        verification: &SecretVerificationRequest,
+    ) -> Result<String, TwoFactorError> {
+        get_recovery_code(self.client, verification).await
+    }
+
+    /// Turn off two-step login with the recovery code, which also issues a new recovery code.
+    pub async fn recover(
+        &self,
+        email: &str,
+        master_password: &str,
+        recovery_code: &str,
+    ) -> Result<(), TwoFactorError> {
+        recover(self.client, email, master_password, recovery_code).await
+    }
+}
+
+impl<'a> VaultClient<'a> {
+    pub fn two_factor(&'a self) -> TwoFactorClient<'a> {
+        TwoFactorClient {
+            client: self.client,
+        }
+    }
+}
@@ -1,8 +1,14 @@
+use bitwarden_api_api::models::UpdateKeyRequestModel;
 use bitwarden_core::Client;
+use uuid::Uuid;
 
 use crate::{
+    api::{self, VaultApiError},
+    delta_sync::delta_sync,
+    key_rotation::{get_user_key_rotation_data, make_update_key_request, KeyRotationError},
     sync::{sync, SyncError},
-    SyncRequest, SyncResponse,
+    CipherView, CollectionView, DeltaSyncResponse, FolderView, PreviousSync, SyncRequest,
+    SyncResponse,
 };
 
 pub struct VaultClient<'a> {
@@ -17,6 +23,132 @@ impl<'a> VaultClient<'a> {
     pub async fn sync(&self, input: &SyncRequest) -> Result<SyncResponse, SyncError> {
         sync(self.client, input).await
     }
+
+    /// Sync the vault, skipping the download when nothing changed since the `previous` sync.
+    /// Returns the ciphers, folders and collections which were added, changed or removed, so that
+    /// only those need to be decrypted again.
+    pub async fn delta_sync(
+        &self,
+        input: &SyncRequest,
+        previous: Option<PreviousSync<'_>>,
+    ) -> Result<DeltaSyncResponse, SyncError> {
+        delta_sync(self.client, input, previous).await
+    }
+
+    /// Create a cipher. Organization ciphers are created in the collections of the view.
+    pub async fn create_cipher(&self, cipher: CipherView) -> Result<CipherView, VaultApiError> {
+        api::create_cipher(self.client, cipher).await
+    }
+
+    pub async fn update_cipher(&self, cipher: CipherView) -> Result<CipherView, VaultApiError> {
+        api::update_cipher(self.client, cipher).await
+    }
+
+    /// Permanently delete a cipher, see [VaultClient::soft_delete_cipher] to move it to the trash
+    /// instead.
+    pub async fn delete_cipher(&self, id: Uuid) -> Result<(), VaultApiError> {
+        api::delete_cipher(self.client, id).await
+    }
+
+    pub async fn soft_delete_cipher(&self, id: Uuid) -> Result<(), VaultApiError> {
+        api::soft_delete_cipher(self.client, id).await
+    }
+
+    pub async fn restore_cipher(&self, id: Uuid) -> Result<CipherView, VaultApiError> {
+        api::restore_cipher(self.client, id).await
+    }
+
+    /// Share a personal cipher with an organization, adding it to the provided collections.
+    pub async fn share_cipher(
+        &self,
+        cipher: CipherView,
+        organization_id: Uuid,
+        collection_ids: Vec<Uuid>,
+    ) -> Result<CipherView, VaultApiError> {
+        api::share_cipher(self.client, cipher, organization_id, collection_ids).await
+    }
+
+    pub async fn create_folder(&self, folder: FolderView) -> Result<FolderView, VaultApiError> {
+        api::create_folder(self.client, folder).await
+    }
+
+    pub async fn update_folder(&self, folder: FolderView) -> Result<FolderView, VaultApiError> {
+        api::update_folder(self.client, folder).await
+    }
+
+    pub async fn delete_folder(&self, id: Uuid) -> Result<(), VaultApiError> {
+        api::delete_folder(self.client, id).await
+    }
+
+    pub async fn create_collection(
+        &self,
+        collection: CollectionView,
+    ) -> Result<CollectionView, VaultApiError> {
+        api::create_collection(self.client, collection).await
+    }
+
+    pub async fn update_collection(
+        &self,
+        collection: CollectionView,
+    ) -> Result<CollectionView, VaultApiError> {
+        api::update_collection(self.client, collection).await
+    }
+
+    pub async fn delete_collection(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+    ) -> Result<(), VaultApiError> {
+        api::delete_collection(self.client, organization_id, id).await
+    }
+
+    /// Build the request to rotate the user key. Everything protected by the current user key is
+    /// fetched from the server and re-encrypted with a newly generated one.
+    pub async fn make_user_key_rotation(
+        &self,
+        password: String,
+    ) -> Result<UpdateKeyRequestModel, KeyRotationError> {
+        let data = get_user_key_rotation_data(self.client).await?;
+        make_update_key_request(self.client, password, data)
+    }
+
+    /// Rotate the user key. The server logs out every session afterwards, including this one.
+    ///
+    /// When this device is trusted, pass its identifier to re-encrypt the device keys of this and
+    /// the other trusted devices for the new user key, so they stay trusted. The rotation request
+    /// has no room for the device keys and the session can't update them once it's revoked, so
+    /// they are updated first, and restored when posting the rotation fails.
+    pub async fn rotate_user_key(
+        &self,
+        password: String,
+        device_identifier: Option<String>,
+    ) -> Result<(), KeyRotationError> {
+        let request = self.make_user_key_rotation(password.clone()).await?;
+
+        let backup = match device_identifier {
+            Some(device_identifier) => Some(
+                self.client
+                    .devices()
+                    .rotate_keys(&device_identifier, &password, request.key.parse()?)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let config = self.client.internal.get_api_configurations().await;
+        if let Err(e) =
+            bitwarden_api_api::apis::accounts_api::accounts_key_post(&config.api, Some(request))
+                .await
+        {
+            // The server kept the current user key, so the devices need their previous keys back
+            if let Some(backup) = backup {
+                self.client.devices().restore_keys(backup).await?;
+            }
+            return Err(KeyRotationError::Api(e.into()));
+        }
+
+        Ok(())
+    }
 }
 
 pub trait VaultClientExt<'a> {
@@ -28,3 +160,104 @@ impl<'a> VaultClientExt<'a> for Client {
         VaultClient::new(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bitwarden_core::client::test_accounts::test_bitwarden_com_account;
+    use serde_json::json;
+    use wiremock::{matchers, Mock, ResponseTemplate};
+
+    use super::*;
+    use crate::api::start_mock;
+
+    const DEVICE_IDENTIFIER: &str = "b86dd6ab-4265-4ddf-a7f1-eb28d5677f33";
+
+    #[tokio::test]
+    async fn test_rotate_user_key_restores_device_keys_on_failure() {
+        let (server, client) = start_mock(
+            test_bitwarden_com_account(),
+            vec![
+                Mock::given(matchers::method("GET"))
+                    .and(matchers::path("/api/sync"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "object": "sync",
+                        "profile": {
+                            "id": "b1fd4bf2-9643-4787-87f3-b0f00189c33b",
+                            "organizations": [],
+                        },
+                        "folders": [],
+                        "ciphers": [],
+                        "sends": [],
+                    }))),
+                Mock::given(matchers::method("GET"))
+                    .and(matchers::path("/api/emergency-access/trusted"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "object": "list",
+                        "data": [],
+                    }))),
+                Mock::given(matchers::method("GET"))
+                    .and(matchers::path("/api/devices"))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                        "object": "list",
+                        "data": [{
+                            "object": "device",
+                            "id": "c9e6a7e4-1b06-4b2a-9d85-b2a500d2a5b6",
+                            "name": "firefox",
+                            "type": 10,
+                            "identifier": DEVICE_IDENTIFIER,
+                            "creationDate": "2024-05-01T12:00:00.000Z",
+                            "isTrusted": true,
+                        }],
+                    }))),
+                Mock::given(matchers::method("POST"))
+                    .and(matchers::path("/api/accounts/key"))
+                    .respond_with(ResponseTemplate::new(400))
+                    .expect(1),
+                Mock::given(matchers::method("POST"))
+                    .and(matchers::path("/api/devices/update-trust"))
+                    .respond_with(ResponseTemplate::new(200))
+                    .expect(2),
+            ],
+        )
+        .await;
+
+        let device = client.auth().trust_device().unwrap();
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path(format!(
+                "/api/devices/{DEVICE_IDENTIFIER}/retrieve-keys"
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "object": "protectedDevice",
+                "identifier": DEVICE_IDENTIFIER,
+                "encryptedUserKey": device.protected_user_key.to_string(),
+                "encryptedPublicKey": device.protected_device_public_key.to_string(),
+            })))
+            .mount(&server)
+            .await;
+
+        let result = client
+            .vault()
+            .rotate_user_key(
+                "asdfasdfasdf".to_owned(),
+                Some(DEVICE_IDENTIFIER.to_owned()),
+            )
+            .await;
+        assert!(matches!(result, Err(KeyRotationError::Api(_))));
+
+        // The device keys for the current user key are put back
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = requests.last().unwrap().body_json().unwrap();
+        assert_eq!(
+            requests.last().unwrap().url.path(),
+            "/api/devices/update-trust"
+        );
+        assert_eq!(
+            body["currentDevice"]["encryptedUserKey"],
+            device.protected_user_key.to_string()
+        );
+        assert_eq!(
+            body["currentDevice"]["encryptedPublicKey"],
+            device.protected_device_public_key.to_string()
+        );
+    }
+}
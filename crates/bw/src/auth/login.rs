@@ -1,32 +1,18 @@
 use bitwarden_cli::text_prompt_when_none;
-use bitwarden_core::{
-    auth::login::{
-        ApiKeyLoginRequest, PasswordLoginRequest, TwoFactorEmailRequest, TwoFactorProvider,
-        TwoFactorRequest,
-    },
-    Client,
-};
-use bitwarden_vault::{SyncRequest, VaultClientExt};
+use bitwarden_core::{auth::login::ApiKeyLoginRequest, Client};
 use color_eyre::eyre::{bail, Result};
 use inquire::{Password, Text};
 use log::{debug, error, info};
 
-pub(crate) async fn login_password(client: Client, email: Option<String>) -> Result<()> {
+pub(crate) async fn login_password(client: &Client, email: Option<String>) -> Result<()> {
     let email = text_prompt_when_none("Email", email)?;
 
     let password = Password::new("Password").without_confirmation().prompt()?;
 
     let kdf = client.auth().prelogin(email.clone()).await?;
 
-    let result = client
-        .auth()
-        .login_password(&PasswordLoginRequest {
-            email: email.clone(),
-            password: password.clone(),
-            two_factor: None,
-            kdf: kdf.clone(),
-        })
-        .await?;
+    let mut session = client.auth().password_login_session(email, password, kdf);
+    let result = session.login(None).await?;
 
     if result.captcha.is_some() {
         // TODO: We should build a web captcha solution
@@ -34,66 +20,37 @@ pub(crate) async fn login_password(client: Client, email: Option<String>) -> Res
     } else if let Some(two_factor) = result.two_factor {
         error!("{:?}", two_factor);
 
-        let two_factor = if let Some(tf) = two_factor.authenticator {
+        let result = if let Some(tf) = two_factor.authenticator {
             debug!("{:?}", tf);
 
             let token = Text::new("Authenticator code").prompt()?;
-
-            Some(TwoFactorRequest {
-                token,
-                provider: TwoFactorProvider::Authenticator,
-                remember: false,
-            })
+            session.submit_totp(&token, false).await?
         } else if let Some(tf) = two_factor.email {
             // Send token
-            client
-                .auth()
-                .send_two_factor_email(&TwoFactorEmailRequest {
-                    email: email.clone(),
-                    password: password.clone(),
-                })
-                .await?;
+            session.send_email_code().await?;
 
             info!("Two factor code sent to {:?}", tf);
             let token = Text::new("Two factor code").prompt()?;
+            session.submit_email_code(&token, false).await?
+        } else if let Some(tf) = two_factor.yubi_key {
+            debug!("{:?}", tf);
 
-            Some(TwoFactorRequest {
-                token,
-                provider: TwoFactorProvider::Email,
-                remember: false,
-            })
+            let token = Text::new("YubiKey OTP").prompt()?;
+            session.submit_yubikey_otp(&token, false).await?
         } else {
             bail!("Not supported: {:?}", two_factor);
         };
 
-        let result = client
-            .auth()
-            .login_password(&PasswordLoginRequest {
-                email,
-                password,
-                two_factor,
-                kdf,
-            })
-            .await?;
-
         debug!("{:?}", result);
     } else {
         debug!("{:?}", result);
     }
 
-    let res = client
-        .vault()
-        .sync(&SyncRequest {
-            exclude_subdomains: Some(true),
-        })
-        .await?;
-    info!("{:#?}", res);
-
     Ok(())
 }
 
 pub(crate) async fn login_api_key(
-    client: Client,
+    client: &Client,
     client_id: Option<String>,
     client_secret: Option<String>,
 ) -> Result<()> {
@@ -117,7 +74,7 @@ pub(crate) async fn login_api_key(
 }
 
 pub(crate) async fn login_device(
-    client: Client,
+    client: &Client,
     email: Option<String>,
     device_identifier: Option<String>,
 ) -> Result<()> {
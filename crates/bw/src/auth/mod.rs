@@ -1,2 +1,4 @@
 mod login;
+mod unlock;
 pub(crate) use login::{login_api_key, login_device, login_password};
+pub(crate) use unlock::{lock, print_session_key, unlock};
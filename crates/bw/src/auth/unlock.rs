@@ -0,0 +1,30 @@
+use bitwarden_crypto::SymmetricCryptoKey;
+use color_eyre::eyre::Result;
+use inquire::Password;
+
+use crate::state::{self, SESSION_ENV};
+
+pub(crate) fn unlock() -> Result<()> {
+    let password = Password::new("Master password")
+        .without_confirmation()
+        .prompt()?;
+
+    let session_key = state::unlock(&password)?;
+    print_session_key(&session_key);
+
+    Ok(())
+}
+
+pub(crate) fn lock() -> Result<()> {
+    state::lock()?;
+    println!("Your vault is locked.");
+
+    Ok(())
+}
+
+pub(crate) fn print_session_key(session_key: &SymmetricCryptoKey) {
+    println!("Your vault is now unlocked!");
+    println!();
+    println!("To use it, set your session key to the `{SESSION_ENV}` environment variable:");
+    println!("$ export {SESSION_ENV}=\"{}\"", session_key.to_base64());
+}
@@ -1,5 +1,5 @@
 use bitwarden_cli::{install_color_eyre, text_prompt_when_none, Color};
-use bitwarden_core::{auth::RegisterRequest, ClientSettings};
+use bitwarden_core::auth::RegisterRequest;
 use bitwarden_generators::{
     GeneratorClientsExt, PassphraseGeneratorRequest, PasswordGeneratorRequest,
 };
@@ -7,9 +7,13 @@ use clap::{command, Args, CommandFactory, Parser, Subcommand};
 use color_eyre::eyre::Result;
 use inquire::Password;
 use render::Output;
+use state::Session;
+use uuid::Uuid;
 
 mod auth;
 mod render;
+mod state;
+mod vault;
 
 #[derive(Parser, Clone)]
 #[command(name = "Bitwarden CLI", version, about = "Bitwarden CLI", long_about = None)]
@@ -42,6 +46,12 @@ enum Commands {
         server: Option<String>,
     },
 
+    #[command(long_about = "Unlock the vault and start a new session")]
+    Unlock {},
+
+    #[command(long_about = "Lock the vault, invalidating the current session")]
+    Lock {},
+
     #[command(long_about = "Manage vault items")]
     Item {
         #[command(subcommand)]
@@ -86,8 +96,38 @@ enum LoginCommands {
 
 #[derive(Subcommand, Clone)]
 enum ItemCommands {
-    Get { id: String },
-    Create {},
+    #[command(long_about = "List the items in the vault")]
+    List {
+        #[arg(
+            long,
+            help = "Only show items whose name or subtitle contain this text"
+        )]
+        search: Option<String>,
+
+        #[arg(long, action, help = "List the items in the trash")]
+        trash: bool,
+    },
+    #[command(long_about = "Get a single item")]
+    Get { id: Uuid },
+    #[command(long_about = "Create an item from its JSON representation")]
+    Create {
+        #[arg(help = "Item JSON, read from stdin when omitted")]
+        json: Option<String>,
+    },
+    #[command(long_about = "Replace an item with the provided JSON representation")]
+    Edit {
+        id: Uuid,
+
+        #[arg(help = "Item JSON, read from stdin when omitted")]
+        json: Option<String>,
+    },
+    #[command(long_about = "Move an item to the trash, or delete it permanently")]
+    Delete {
+        id: Uuid,
+
+        #[arg(short = 'p', long, action, help = "Permanently delete the item")]
+        permanent: bool,
+    },
 }
 
 #[derive(Subcommand, Clone)]
@@ -151,29 +191,30 @@ async fn process_commands() -> Result<()> {
 
     match command.clone() {
         Commands::Login(args) => {
-            let settings = args.server.map(|server| ClientSettings {
-                api_url: format!("{}/api", server),
-                identity_url: format!("{}/identity", server),
-                ..Default::default()
-            });
-            let client = bitwarden_core::Client::new(settings);
+            let client = bitwarden_core::Client::new(state::client_settings(args.server.clone()));
 
             match args.command {
                 // FIXME: Rust CLI will not support password login!
                 LoginCommands::Password { email } => {
-                    auth::login_password(client, email).await?;
+                    auth::login_password(&client, email).await?;
                 }
                 LoginCommands::ApiKey {
                     client_id,
                     client_secret,
-                } => auth::login_api_key(client, client_id, client_secret).await?,
+                } => auth::login_api_key(&client, client_id, client_secret).await?,
                 LoginCommands::Device {
                     email,
                     device_identifier,
                 } => {
-                    auth::login_device(client, email, device_identifier).await?;
+                    auth::login_device(&client, email, device_identifier).await?;
                 }
             }
+
+            let (mut session, session_key) = Session::login(client, args.server).await?;
+            vault::sync(&mut session).await?;
+            session.save()?;
+
+            auth::print_session_key(&session_key);
             return Ok(());
         }
         Commands::Register {
@@ -182,12 +223,7 @@ async fn process_commands() -> Result<()> {
             password_hint,
             server,
         } => {
-            let settings = server.map(|server| ClientSettings {
-                api_url: format!("{}/api", server),
-                identity_url: format!("{}/identity", server),
-                ..Default::default()
-            });
-            let client = bitwarden_core::Client::new(settings);
+            let client = bitwarden_core::Client::new(state::client_settings(server));
 
             let email = text_prompt_when_none("Email", email)?;
             let password = Password::new("Password").prompt()?;
@@ -201,46 +237,72 @@ async fn process_commands() -> Result<()> {
                     password_hint,
                 })
                 .await?;
+            return Ok(());
+        }
+        Commands::Unlock {} => return auth::unlock(),
+        Commands::Lock {} => return auth::lock(),
+        Commands::Generate { command } => {
+            // The generators don't need an unlocked vault
+            let client = bitwarden_core::Client::new(None);
+
+            match command {
+                GeneratorCommands::Password(args) => {
+                    let password = client.generator().password(PasswordGeneratorRequest {
+                        lowercase: args.lowercase,
+                        uppercase: args.uppercase,
+                        numbers: args.numbers,
+                        special: args.special,
+                        length: args.length,
+                        ..Default::default()
+                    })?;
+
+                    println!("{}", password);
+                }
+                GeneratorCommands::Passphrase(args) => {
+                    let passphrase = client.generator().passphrase(PassphraseGeneratorRequest {
+                        num_words: args.words,
+                        word_separator: args.separator.to_string(),
+                        capitalize: args.capitalize,
+                        include_number: args.include_number,
+                    })?;
+
+                    println!("{}", passphrase);
+                }
+            }
+            return Ok(());
         }
         _ => {}
     }
 
-    // Not login, assuming we have a config
-    let client = bitwarden_core::Client::new(None);
+    // Not login, restore the session persisted by `bw login` or `bw unlock`
+    let mut session = Session::open().await?;
 
     // And finally we process all the commands which require authentication
     match command {
-        Commands::Login(_) => unreachable!(),
-        Commands::Register { .. } => unreachable!(),
-        Commands::Item { command: _ } => todo!(),
-        Commands::Sync {} => todo!(),
-        Commands::Generate { command } => match command {
-            GeneratorCommands::Password(args) => {
-                let password = client.generator().password(PasswordGeneratorRequest {
-                    lowercase: args.lowercase,
-                    uppercase: args.uppercase,
-                    numbers: args.numbers,
-                    special: args.special,
-                    length: args.length,
-                    ..Default::default()
-                })?;
-
-                println!("{}", password);
+        Commands::Login(_)
+        | Commands::Register { .. }
+        | Commands::Unlock {}
+        | Commands::Lock {}
+        | Commands::Generate { .. } => unreachable!(),
+        Commands::Item { command } => match command {
+            ItemCommands::List { search, trash } => {
+                vault::list_items(&session, search, trash, cli.output)?
+            }
+            ItemCommands::Get { id } => vault::get_item(&session, id, cli.output)?,
+            ItemCommands::Create { json } => {
+                vault::create_item(&mut session, json, cli.output).await?
+            }
+            ItemCommands::Edit { id, json } => {
+                vault::edit_item(&mut session, id, json, cli.output).await?
             }
-            GeneratorCommands::Passphrase(args) => {
-                let passphrase = client.generator().passphrase(PassphraseGeneratorRequest {
-                    num_words: args.words,
-                    word_separator: args.separator.to_string(),
-                    capitalize: args.capitalize,
-                    include_number: args.include_number,
-                })?;
-
-                println!("{}", passphrase);
+            ItemCommands::Delete { id, permanent } => {
+                vault::delete_item(&mut session, id, permanent).await?
             }
         },
+        Commands::Sync {} => vault::sync(&mut session).await?,
     };
 
-    Ok(())
+    session.save()
 }
 
 #[cfg(test)]
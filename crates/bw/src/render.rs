@@ -1,4 +1,6 @@
 use clap::ValueEnum;
+use comfy_table::Table;
+use serde::Serialize;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 #[allow(clippy::upper_case_acronyms)]
@@ -9,3 +11,60 @@ pub(crate) enum Output {
     TSV,
     None,
 }
+
+const ASCII_HEADER_ONLY: &str = "     --            ";
+
+pub(crate) fn serialize_response<T: Serialize + TableSerialize<N>, const N: usize>(
+    data: T,
+    output: Output,
+) {
+    match output {
+        Output::JSON => {
+            let text = serde_json::to_string_pretty(&data).expect("Serialize should be infallible");
+            println!("{}", text);
+        }
+        Output::YAML => {
+            let text = serde_yaml::to_string(&data).expect("Serialize should be infallible");
+            print!("{}", text);
+        }
+        Output::Table => {
+            let mut table = Table::new();
+            table
+                .load_preset(ASCII_HEADER_ONLY)
+                .set_header(T::get_headers())
+                .add_rows(data.get_values());
+
+            println!("{table}");
+        }
+        Output::TSV => {
+            println!("{}", T::get_headers().join("\t"));
+
+            let rows: Vec<String> = data
+                .get_values()
+                .into_iter()
+                .map(|row| row.join("\t"))
+                .collect();
+            println!("{}", rows.join("\n"));
+        }
+        Output::None => {}
+    }
+}
+
+/// Types that can be rendered as rows in the table and TSV output formats
+pub(crate) trait TableSerialize<const N: usize>: Sized {
+    fn get_headers() -> [&'static str; N];
+    fn get_values(&self) -> Vec<[String; N]>;
+}
+
+impl<T, const N: usize> TableSerialize<N> for Vec<T>
+where
+    T: TableSerialize<N>,
+{
+    fn get_headers() -> [&'static str; N] {
+        T::get_headers()
+    }
+
+    fn get_values(&self) -> Vec<[String; N]> {
+        self.iter().flat_map(|t| t.get_values()).collect()
+    }
+}
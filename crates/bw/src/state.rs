@@ -0,0 +1,328 @@
+use std::{collections::HashMap, io::Write, path::PathBuf};
+
+use bitwarden_api_api::apis::accounts_api;
+use bitwarden_core::{
+    client::UserSession,
+    mobile::crypto::{InitOrgCryptoRequest, InitUserCryptoMethod, InitUserCryptoRequest},
+    ApiError, Client, ClientSettings,
+};
+use bitwarden_crypto::{
+    AsymmetricEncString, EncString, Kdf, KeyDecryptable, KeyEncryptable, MasterKey,
+    SymmetricCryptoKey,
+};
+use bitwarden_vault::{PreviousSync, SyncResponse};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{bail, eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const STATE_VERSION: u32 = 1;
+const STATE_FILE: &str = "data.json";
+const DATA_DIR_ENV: &str = "BW_DATA_DIR";
+pub(crate) const SESSION_ENV: &str = "BW_SESSION";
+
+/// State persisted between invocations of the CLI.
+///
+/// Only what is needed to unlock the vault is stored in plain text, everything else is kept in
+/// [StateData] encrypted with the user key.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    version: u32,
+    server: Option<String>,
+    email: String,
+    kdf: Kdf,
+    /// User key, protected by the master key
+    user_key: EncString,
+    /// User key, protected by the session key handed out by the last unlock. Removed on lock.
+    session_user_key: Option<EncString>,
+    /// [StateData], encrypted with the user key
+    data: EncString,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct StateData {
+    session: UserSession,
+    /// Private key, protected by the user key
+    private_key: EncString,
+    organization_keys: HashMap<Uuid, AsymmetricEncString>,
+    /// Vault data from the last `bw sync`
+    vault: Option<SyncResponse>,
+    /// Account revision date at the last `bw sync`
+    revision_date: Option<DateTime<Utc>>,
+}
+
+/// Keys of the account, as returned by the server
+struct AccountKeys {
+    user_key: EncString,
+    private_key: EncString,
+    organization_keys: HashMap<Uuid, AsymmetricEncString>,
+}
+
+/// An unlocked session, holding a client restored from the persisted state.
+pub(crate) struct Session {
+    pub(crate) client: Client,
+    state: State,
+    user_key: SymmetricCryptoKey,
+    data: StateData,
+}
+
+impl Session {
+    /// Persist the state of a client that just logged in. Returns the session together with the
+    /// key that unlocks it.
+    pub(crate) async fn login(
+        client: Client,
+        server: Option<String>,
+    ) -> Result<(Self, SymmetricCryptoKey)> {
+        let session = client.internal.get_user_session()?;
+        let user_key =
+            SymmetricCryptoKey::try_from(client.crypto().get_user_encryption_key().await?)?;
+        let keys = fetch_account_keys(&client).await?;
+
+        let data = StateData {
+            session,
+            private_key: keys.private_key,
+            organization_keys: keys.organization_keys,
+            vault: None,
+            revision_date: None,
+        };
+
+        let session_key = SymmetricCryptoKey::generate(rand::thread_rng());
+        let state = State {
+            version: STATE_VERSION,
+            server,
+            email: data.session.email.clone(),
+            kdf: data.session.kdf.clone(),
+            user_key: keys.user_key,
+            session_user_key: Some(user_key.to_base64().encrypt_with_key(&session_key)?),
+            data: encrypt_data(&data, &user_key)?,
+        };
+
+        Ok((
+            Session {
+                client,
+                state,
+                user_key,
+                data,
+            },
+            session_key,
+        ))
+    }
+
+    /// Open the session unlocked by the key in the `BW_SESSION` environment variable.
+    pub(crate) async fn open() -> Result<Self> {
+        let state = State::load()?;
+
+        let (Some(session_user_key), Ok(session_key)) =
+            (&state.session_user_key, std::env::var(SESSION_ENV))
+        else {
+            bail!("Vault is locked, run `bw unlock` and set {SESSION_ENV}");
+        };
+
+        let session_key = SymmetricCryptoKey::try_from(session_key)
+            .map_err(|_| eyre!("Invalid session key in {SESSION_ENV}"))?;
+        let user_key: String = session_user_key
+            .decrypt_with_key(&session_key)
+            .map_err(|_| eyre!("Invalid session key in {SESSION_ENV}"))?;
+        let user_key = SymmetricCryptoKey::try_from(user_key)?;
+
+        let data: String = state.data.decrypt_with_key(&user_key)?;
+        let data: StateData = serde_json::from_str(&data)?;
+
+        let client = Client::new(client_settings(state.server.clone()));
+        client
+            .crypto()
+            .initialize_user_crypto(InitUserCryptoRequest {
+                kdf_params: state.kdf.clone(),
+                email: state.email.clone(),
+                private_key: data.private_key.to_string(),
+                method: InitUserCryptoMethod::DecryptedKey {
+                    decrypted_user_key: user_key.to_base64(),
+                },
+            })
+            .await?;
+        client
+            .crypto()
+            .initialize_org_crypto(InitOrgCryptoRequest {
+                organization_keys: data.organization_keys.clone(),
+            })
+            .await?;
+        client.internal.set_user_session(data.session.clone());
+
+        Ok(Session {
+            client,
+            state,
+            user_key,
+            data,
+        })
+    }
+
+    /// Vault data from the last `bw sync`.
+    pub(crate) fn vault(&self) -> Result<&SyncResponse> {
+        self.data
+            .vault
+            .as_ref()
+            .ok_or_else(|| eyre!("No vault data found, run `bw sync` first"))
+    }
+
+    pub(crate) fn vault_mut(&mut self) -> Option<&mut SyncResponse> {
+        self.data.vault.as_mut()
+    }
+
+    /// The last `bw sync`, if the vault data is known to be up to date as of that sync.
+    pub(crate) fn previous_sync(&self) -> Option<PreviousSync<'_>> {
+        Some(PreviousSync {
+            revision_date: self.data.revision_date?,
+            vault: self.data.vault.as_ref()?,
+        })
+    }
+
+    /// Replace the cached vault data, refreshing the account keys at the same time.
+    pub(crate) async fn set_vault(
+        &mut self,
+        vault: SyncResponse,
+        revision_date: DateTime<Utc>,
+    ) -> Result<()> {
+        let keys = fetch_account_keys(&self.client).await?;
+
+        self.state.user_key = keys.user_key;
+        self.data.private_key = keys.private_key;
+        self.data.organization_keys = keys.organization_keys;
+        self.data.vault = Some(vault);
+        self.data.revision_date = Some(revision_date);
+
+        Ok(())
+    }
+
+    /// Write the session back to disk, including any renewed tokens.
+    pub(crate) fn save(mut self) -> Result<()> {
+        if let Ok(session) = self.client.internal.get_user_session() {
+            self.data.session = session;
+        }
+
+        self.state.data = encrypt_data(&self.data, &self.user_key)?;
+        self.state.save()
+    }
+}
+
+/// Unlock the vault using the master password. Returns the key of the new session.
+pub(crate) fn unlock(password: &str) -> Result<SymmetricCryptoKey> {
+    let mut state = State::load()?;
+
+    let master_key = MasterKey::derive(password, &state.email, &state.kdf)?;
+    let user_key = master_key
+        .decrypt_user_key(state.user_key.clone())
+        .map_err(|_| eyre!("Invalid master password"))?;
+
+    let session_key = SymmetricCryptoKey::generate(rand::thread_rng());
+    state.session_user_key = Some(user_key.to_base64().encrypt_with_key(&session_key)?);
+    state.save()?;
+
+    Ok(session_key)
+}
+
+/// Lock the vault, invalidating the key of the current session.
+pub(crate) fn lock() -> Result<()> {
+    let mut state = State::load()?;
+    state.session_user_key = None;
+    state.save()
+}
+
+pub(crate) fn client_settings(server: Option<String>) -> Option<ClientSettings> {
+    server.map(|server| ClientSettings {
+        api_url: format!("{}/api", server),
+        identity_url: format!("{}/identity", server),
+        ..Default::default()
+    })
+}
+
+impl State {
+    fn load() -> Result<Self> {
+        let content = std::fs::read_to_string(data_dir()?.join(STATE_FILE))
+            .map_err(|_| eyre!("You are not logged in, run `bw login` first"))?;
+        let state: State = serde_json::from_str(&content)?;
+
+        if state.version != STATE_VERSION {
+            bail!("Unsupported state version, run `bw login` again");
+        }
+
+        Ok(state)
+    }
+
+    fn save(&self) -> Result<()> {
+        let dir = data_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(STATE_FILE);
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // The state contains the encrypted vault and tokens, only the user should be able to read it
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // The mode only applies to new files, so restrict files created by older versions too
+            if path.exists() {
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            }
+        }
+
+        let mut file = options.open(path)?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+fn encrypt_data(data: &StateData, user_key: &SymmetricCryptoKey) -> Result<EncString> {
+    Ok(serde_json::to_string(data)?.encrypt_with_key(user_key)?)
+}
+
+async fn fetch_account_keys(client: &Client) -> Result<AccountKeys> {
+    let config = client.internal.get_api_configurations().await;
+    let profile = accounts_api::accounts_profile_get(&config.api)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(AccountKeys {
+        user_key: profile
+            .key
+            .ok_or_else(|| eyre!("Accounts without a master password are not supported"))?
+            .parse()?,
+        private_key: profile
+            .private_key
+            .ok_or_else(|| eyre!("Account is missing a private key"))?
+            .parse()?,
+        organization_keys: profile
+            .organizations
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|o| o.id.zip(o.key))
+            .map(|(id, key)| {
+                let key = key
+                    .parse::<AsymmetricEncString>()
+                    .wrap_err_with(|| format!("Invalid key for organization {id}"))?;
+                Ok((id, key))
+            })
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// Directory where the CLI keeps its local data.
+///
+/// Can be overridden with the `BW_DATA_DIR` environment variable, otherwise defaults to a `bw`
+/// folder in the platform configuration directory.
+fn data_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV) {
+        return Ok(PathBuf::from(dir));
+    }
+
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok_or_else(|| eyre!("Unable to determine the data directory, set {DATA_DIR_ENV}"))?;
+
+    Ok(config_dir.join("bw"))
+}
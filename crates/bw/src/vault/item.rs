@@ -0,0 +1,162 @@
+use std::io::Read;
+
+use bitwarden_vault::{Cipher, CipherListView, CipherView, VaultClientExt};
+use chrono::Utc;
+use color_eyre::eyre::{eyre, Result};
+use uuid::Uuid;
+
+use crate::{
+    render::{serialize_response, Output, TableSerialize},
+    state::Session,
+};
+
+pub(crate) fn list_items(
+    session: &Session,
+    search: Option<String>,
+    trash: bool,
+    output: Output,
+) -> Result<()> {
+    let ciphers: Vec<Cipher> = session
+        .vault()?
+        .ciphers
+        .iter()
+        .filter(|c| c.deleted_date.is_some() == trash)
+        .cloned()
+        .collect();
+
+    let mut items = session.client.vault().ciphers().decrypt_list(ciphers)?;
+
+    if let Some(search) = search {
+        let search = search.to_lowercase();
+        items.retain(|i| {
+            i.name.to_lowercase().contains(&search) || i.subtitle.to_lowercase().contains(&search)
+        });
+    }
+
+    serialize_response(items, output);
+
+    Ok(())
+}
+
+pub(crate) fn get_item(session: &Session, id: Uuid, output: Output) -> Result<()> {
+    let cipher = session
+        .vault()?
+        .ciphers
+        .iter()
+        .find(|c| c.id == Some(id))
+        .cloned()
+        .ok_or_else(|| eyre!("Item {id} not found"))?;
+
+    let view = session.client.vault().ciphers().decrypt(cipher)?;
+
+    serialize_response(view, output);
+
+    Ok(())
+}
+
+pub(crate) async fn create_item(
+    session: &mut Session,
+    json: Option<String>,
+    output: Output,
+) -> Result<()> {
+    let view: CipherView = serde_json::from_str(&read_input(json)?)?;
+
+    let view = session.client.vault().create_cipher(view).await?;
+    update_cache(session, &view)?;
+
+    serialize_response(view, output);
+
+    Ok(())
+}
+
+pub(crate) async fn edit_item(
+    session: &mut Session,
+    id: Uuid,
+    json: Option<String>,
+    output: Output,
+) -> Result<()> {
+    let mut view: CipherView = serde_json::from_str(&read_input(json)?)?;
+    view.id = Some(id);
+
+    let view = session.client.vault().update_cipher(view).await?;
+    update_cache(session, &view)?;
+
+    serialize_response(view, output);
+
+    Ok(())
+}
+
+pub(crate) async fn delete_item(session: &mut Session, id: Uuid, permanent: bool) -> Result<()> {
+    if permanent {
+        session.client.vault().delete_cipher(id).await?;
+    } else {
+        session.client.vault().soft_delete_cipher(id).await?;
+    }
+
+    if let Some(data) = session.vault_mut() {
+        if permanent {
+            data.ciphers.retain(|c| c.id != Some(id));
+        } else if let Some(cipher) = data.ciphers.iter_mut().find(|c| c.id == Some(id)) {
+            cipher.deleted_date = Some(Utc::now());
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the JSON item from the argument, or from stdin when not provided.
+fn read_input(json: Option<String>) -> Result<String> {
+    match json {
+        Some(json) => Ok(json),
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            Ok(buf)
+        }
+    }
+}
+
+/// Insert or replace the cipher in the locally stored vault data, if any.
+fn update_cache(session: &mut Session, view: &CipherView) -> Result<()> {
+    let cipher = session.client.vault().ciphers().encrypt(view.clone())?;
+
+    if let Some(data) = session.vault_mut() {
+        data.ciphers.retain(|c| c.id != cipher.id);
+        data.ciphers.push(cipher);
+    }
+
+    Ok(())
+}
+
+impl TableSerialize<4> for CipherListView {
+    fn get_headers() -> [&'static str; 4] {
+        ["ID", "Name", "Subtitle", "Revision Date"]
+    }
+
+    fn get_values(&self) -> Vec<[String; 4]> {
+        vec![[
+            self.id.map(|id| id.to_string()).unwrap_or_default(),
+            self.name.clone(),
+            self.subtitle.clone(),
+            self.revision_date.to_rfc3339(),
+        ]]
+    }
+}
+
+impl TableSerialize<4> for CipherView {
+    fn get_headers() -> [&'static str; 4] {
+        ["ID", "Name", "Username", "Revision Date"]
+    }
+
+    fn get_values(&self) -> Vec<[String; 4]> {
+        vec![[
+            self.id.map(|id| id.to_string()).unwrap_or_default(),
+            self.name.clone(),
+            self.login
+                .as_ref()
+                .and_then(|l| l.username.clone())
+                .unwrap_or_default(),
+            self.revision_date.to_rfc3339(),
+        ]]
+    }
+}
@@ -0,0 +1,5 @@
+mod item;
+mod sync;
+
+pub(crate) use item::{create_item, delete_item, edit_item, get_item, list_items};
+pub(crate) use sync::sync;
@@ -0,0 +1,40 @@
+use bitwarden_vault::{SyncRequest, VaultClientExt};
+use color_eyre::eyre::Result;
+use log::info;
+
+use crate::state::Session;
+
+pub(crate) async fn sync(session: &mut Session) -> Result<()> {
+    let res = session
+        .client
+        .vault()
+        .delta_sync(
+            &SyncRequest {
+                exclude_subdomains: Some(true),
+            },
+            session.previous_sync(),
+        )
+        .await?;
+
+    let Some(changes) = res.changes else {
+        info!("Vault is up to date");
+        return Ok(());
+    };
+
+    info!(
+        "Synced {} items, {} folders and {} collections",
+        changes.vault.ciphers.len(),
+        changes.vault.folders.len(),
+        changes.vault.collections.len()
+    );
+    info!(
+        "{} items added, {} changed and {} removed",
+        changes.ciphers.added.len(),
+        changes.ciphers.changed.len(),
+        changes.ciphers.removed.len()
+    );
+
+    session.set_vault(changes.vault, res.revision_date).await?;
+
+    Ok(())
+}
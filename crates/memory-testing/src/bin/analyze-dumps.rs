@@ -68,7 +68,10 @@ fn main() -> io::Result<()> {
             let final_pos = find_subarrays(&value, &final_core);
 
             let name = format!("{idx}: {} / {}", case.name, lookup.name);
-            let ok_cond = final_pos.len() <= lookup.allowed_count.unwrap_or(0);
+            let ok_cond = final_pos.len() <= lookup.allowed_count.unwrap_or(0)
+                && lookup
+                    .allowed_initial_count
+                    .map_or(true, |allowed| initial_pos.len() <= allowed);
 
             table.add_row([
                 name.as_str(),
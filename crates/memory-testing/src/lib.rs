@@ -1,10 +1,24 @@
 use std::path::Path;
 
-use bitwarden_crypto::Kdf;
+use bitwarden_crypto::{key_ids, Kdf};
 use zeroize::{Zeroize, Zeroizing};
 
 pub const TEST_STRING: &str = "THIS IS USED TO CHECK THAT THE MEMORY IS DUMPED CORRECTLY";
 
+key_ids! {
+    #[symmetric]
+    pub enum TestSymmKey {
+        Key(usize),
+    }
+
+    #[asymmetric]
+    pub enum TestAsymmKey {
+        Key(usize),
+    }
+
+    pub TestIds => TestSymmKey, TestAsymmKey;
+}
+
 pub fn load_cases(base_dir: &Path) -> Cases {
     let mut json_str = std::fs::read_to_string(base_dir.join("cases.json")).unwrap();
     let cases: Cases = serde_json::from_str(&json_str).unwrap();
@@ -35,6 +49,11 @@ pub enum CaseCommand {
     SymmetricKey {
         key: String,
     },
+    KeyStore {
+        symmetric_key: String,
+        #[serde(default)]
+        private_key: Option<String>,
+    },
     AsymmetricKey {
         private_key: String,
     },
@@ -57,6 +76,11 @@ pub struct MemoryLookup {
 
     #[serde(default)]
     pub allowed_count: Option<usize>,
+
+    /// When set, the value also needs to be absent from the initial dump, taken while it's still
+    /// in use. Used for values that are expected to live only in memory excluded from dumps.
+    #[serde(default)]
+    pub allowed_initial_count: Option<usize>,
 }
 
 // We don't actually want these values to be caught by the memory testing,
@@ -1,6 +1,7 @@
 use std::{env, io::Read, path::Path, process};
 
-use bitwarden_crypto::{MasterKey, SymmetricCryptoKey};
+use bitwarden_crypto::{AsymmetricCryptoKey, KeyStore, MasterKey, SymmetricCryptoKey};
+use memory_testing::{TestAsymmKey, TestIds, TestSymmKey};
 
 fn wait_for_dump() {
     println!("Waiting for dump...");
@@ -20,6 +21,7 @@ fn main() {
     let cases = memory_testing::load_cases(base_dir);
 
     let mut symmetric_keys = Vec::new();
+    let mut key_stores = Vec::new();
     let mut asymmetric_keys = Vec::new();
     let mut master_keys = Vec::new();
     let mut strings = Vec::new();
@@ -30,6 +32,27 @@ fn main() {
                 let key = SymmetricCryptoKey::try_from(key).unwrap();
                 symmetric_keys.push((key.to_vec(), key));
             }
+            memory_testing::CaseCommand::KeyStore {
+                symmetric_key,
+                private_key,
+            } => {
+                let key = SymmetricCryptoKey::try_from(symmetric_key).unwrap();
+                let store = KeyStore::<TestIds>::default();
+                #[allow(deprecated)]
+                store
+                    .context_mut()
+                    .set_symmetric_key(TestSymmKey::Key(key_stores.len()), key)
+                    .unwrap();
+                if let Some(private_key) = private_key {
+                    let key = AsymmetricCryptoKey::from_pem(&private_key).unwrap();
+                    #[allow(deprecated)]
+                    store
+                        .context_mut()
+                        .set_asymmetric_key(TestAsymmKey::Key(key_stores.len()), key)
+                        .unwrap();
+                }
+                key_stores.push(store);
+            }
             memory_testing::CaseCommand::AsymmetricKey { private_key } => {
                 let key = bitwarden_crypto::AsymmetricCryptoKey::from_pem(&private_key).unwrap();
                 asymmetric_keys.push(key);
@@ -64,6 +87,7 @@ fn main() {
     let _ = std::hint::black_box((
         test_string,
         symmetric_keys,
+        key_stores,
         asymmetric_keys,
         master_keys,
         strings,